@@ -1,7 +1,10 @@
 //! Easing functions for smooth animations
 
+use serde::Deserialize;
+
 /// Easing function types
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Easing {
     /// Linear interpolation
     Linear,
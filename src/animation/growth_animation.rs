@@ -6,6 +6,7 @@
 //! - Organic easing curves
 
 use std::collections::HashMap;
+use serde::Deserialize;
 use super::easing::{Easing, ease};
 use crate::growth::BranchNode;
 
@@ -26,12 +27,43 @@ pub struct GrowthAnimation {
     pub easing: Easing,
     /// Per-generation delay (stagger effect)
     pub generation_delay: f32,
+    /// Timing overrides indexed by generation
+    pub generation_timing: Vec<GenerationTiming>,
     /// Maximum generation in tree
     pub max_generation: usize,
     /// Per-branch animation states
     branch_states: HashMap<String, BranchAnimState>,
 }
 
+/// Timing override for one generation (fractions of the whole animation)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct GenerationTiming {
+    /// Delay after the previous generation starts (defaults to `generation_delay`)
+    pub delay: Option<f32>,
+    /// How long this generation takes to grow
+    pub duration: Option<f32>,
+    /// Easing for this generation (defaults to the overall easing)
+    pub easing: Option<Easing>,
+}
+
+/// Growth timing configuration, e.g. from `set_growth_timing`
+///
+/// ```yaml
+/// generation_delay: 0.1
+/// generations:
+///   - { duration: 0.4, easing: ease_in_out }   # linger on the founders
+///   - { delay: 0.3 }
+///   - { delay: 0.05, duration: 0.15 }          # then accelerate
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct GrowthTiming {
+    pub generation_delay: Option<f32>,
+    pub easing: Option<Easing>,
+    pub generations: Vec<GenerationTiming>,
+}
+
 /// Animation state for a single branch
 #[derive(Debug, Clone, Copy)]
 pub struct BranchAnimState {
@@ -86,6 +118,7 @@ impl Default for GrowthAnimation {
             complete: false,
             easing: Easing::Organic,
             generation_delay: 0.15, // 15% delay between generations
+            generation_timing: Vec::new(),
             max_generation: 0,
             branch_states: HashMap::new(),
         }
@@ -110,6 +143,47 @@ impl GrowthAnimation {
         }
     }
 
+    /// Apply timing overrides (unset values keep their current settings)
+    pub fn set_timing(&mut self, timing: &GrowthTiming) {
+        if let Some(delay) = timing.generation_delay {
+            self.generation_delay = delay.max(0.0);
+        }
+        if let Some(easing) = timing.easing {
+            self.easing = easing;
+        }
+        self.generation_timing = timing.generations.clone();
+        self.update_branch_states();
+    }
+
+    /// Start/end progress and easing of every generation
+    ///
+    /// Without overrides generation `g` starts at `g * generation_delay` and all
+    /// generations end together at 1.0. Overridden schedules that run past 1.0
+    /// are compressed to fit the animation's duration.
+    fn generation_windows(&self) -> Vec<(f32, f32, Easing)> {
+        let default_duration = (1.0 - self.generation_delay * self.max_generation as f32).max(0.01);
+
+        let mut windows = Vec::with_capacity(self.max_generation + 1);
+        let mut start = 0.0;
+        for generation in 0..=self.max_generation {
+            let timing = self.generation_timing.get(generation).copied().unwrap_or_default();
+            if generation > 0 {
+                start += timing.delay.unwrap_or(self.generation_delay).max(0.0);
+            }
+            let duration = timing.duration.unwrap_or(default_duration).max(0.001);
+            windows.push((start, start + duration, timing.easing.unwrap_or(self.easing)));
+        }
+
+        let end = windows.iter().map(|w| w.1).fold(0.0, f32::max);
+        if end > 1.0 {
+            for window in &mut windows {
+                window.0 /= end;
+                window.1 /= end;
+            }
+        }
+        windows
+    }
+
     /// Initialize branch states from tree structure
     pub fn init_from_tree(&mut self, root: &BranchNode) {
         self.branch_states.clear();
@@ -128,7 +202,9 @@ impl GrowthAnimation {
             },
         );
 
-        for child in &node.children {
+        // A partner's strand grows together with the branch it twines around
+        let branches = node.children.iter().chain(&node.roots).chain(&node.grove);
+        for child in node.partner.as_deref().into_iter().chain(branches) {
             self.collect_branches(child);
         }
     }
@@ -190,14 +266,12 @@ impl GrowthAnimation {
     }
 
     fn update_branch_states(&mut self) {
-        let total_gens = self.max_generation + 1;
-        let _gen_window = 1.0 / total_gens as f32;
+        let windows = self.generation_windows();
 
         for state in self.branch_states.values_mut() {
             // Calculate when this branch should start growing
             // Earlier generations start earlier
-            let gen_start = state.generation as f32 * self.generation_delay;
-            let gen_end = gen_start + (1.0 - self.generation_delay * self.max_generation as f32);
+            let (gen_start, gen_end, easing) = windows[state.generation.min(windows.len() - 1)];
 
             // Calculate local progress for this branch
             let local_t = if self.progress <= gen_start {
@@ -209,7 +283,7 @@ impl GrowthAnimation {
             };
 
             // Apply easing
-            let eased = ease(local_t, self.easing);
+            let eased = ease(local_t, easing);
             state.local_progress = eased;
 
             // Different aspects animate at slightly different rates for organic feel
@@ -297,6 +371,7 @@ mod tests {
             start_radius: 0.3,
             end_radius: 0.2,
             generation: 0,
+            event_positions: vec![],
             children: vec![
                 BranchNode {
                     person_id: "child1".to_string(),
@@ -308,7 +383,13 @@ mod tests {
                     start_radius: 0.2,
                     end_radius: 0.15,
                     generation: 1,
+                    event_positions: vec![],
                     children: vec![],
+                    roots: vec![],
+                    hidden_descendants: 0,
+                    partner: None,
+                    waypoints: Vec::new(),
+                    grove: Vec::new(),
                 },
                 BranchNode {
                     person_id: "child2".to_string(),
@@ -320,9 +401,20 @@ mod tests {
                     start_radius: 0.2,
                     end_radius: 0.15,
                     generation: 1,
+                    event_positions: vec![],
                     children: vec![],
+                    roots: vec![],
+                    hidden_descendants: 0,
+                    partner: None,
+                    waypoints: Vec::new(),
+                    grove: Vec::new(),
                 },
             ],
+            roots: vec![],
+            hidden_descendants: 0,
+            partner: None,
+            waypoints: Vec::new(),
+            grove: Vec::new(),
         }
     }
 
@@ -445,4 +537,54 @@ mod tests {
         let state = anim.get_branch_state("root");
         assert!(state.visibility > 0.5);
     }
+
+    #[test]
+    fn test_default_windows_match_stagger() {
+        let mut anim = GrowthAnimation::new(5.0);
+        anim.init_from_tree(&create_test_tree());
+
+        let windows = anim.generation_windows();
+        assert_eq!(windows[0].0, 0.0);
+        assert!((windows[1].0 - anim.generation_delay).abs() < 1e-6);
+        assert!((windows[1].1 - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_generation_timing_overrides() {
+        let mut anim = GrowthAnimation::new(5.0);
+        anim.init_from_tree(&create_test_tree());
+
+        let timing: GrowthTiming = serde_yaml::from_str(
+            "generations:\n  - { duration: 0.6, easing: linear }\n  - { delay: 0.6, duration: 0.2 }\n",
+        )
+        .unwrap();
+        anim.set_timing(&timing);
+
+        let windows = anim.generation_windows();
+        assert_eq!(windows[0], (0.0, 0.6, Easing::Linear));
+        assert!((windows[1].0 - 0.6).abs() < 1e-6);
+        assert!((windows[1].1 - 0.8).abs() < 1e-6);
+
+        // Children wait until the founders are done
+        anim.set_progress(0.5);
+        assert_eq!(anim.get_branch_state("child1").visibility, 0.0);
+        assert!((anim.get_branch_state("root").local_progress - 0.5 / 0.6).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_long_schedule_compressed() {
+        let mut anim = GrowthAnimation::new(5.0);
+        anim.init_from_tree(&create_test_tree());
+        anim.set_timing(&GrowthTiming {
+            generations: vec![
+                GenerationTiming { duration: Some(1.0), ..Default::default() },
+                GenerationTiming { delay: Some(1.0), duration: Some(1.0), ..Default::default() },
+            ],
+            ..Default::default()
+        });
+
+        let windows = anim.generation_windows();
+        assert!((windows[1].1 - 1.0).abs() < 1e-6);
+        assert!((windows[1].0 - 0.5).abs() < 1e-6);
+    }
 }
@@ -6,5 +6,5 @@
 mod growth_animation;
 mod easing;
 
-pub use growth_animation::{GrowthAnimation, BranchAnimState};
+pub use growth_animation::{GrowthAnimation, BranchAnimState, GenerationTiming, GrowthTiming};
 pub use easing::{Easing, ease};
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+use super::date::DateValue;
+use super::family_tree::FamilyTree;
+use super::limits::{self, InputLimits};
+use super::person::Person;
+
+/// Assembles a family person by person, for apps with their own data model
+///
+/// The first person added is the root unless [`FamilyTreeBuilder::set_root`]
+/// picks another. Text passes through the same limits, sanitization and
+/// checks as uploaded YAML.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default)]
+pub struct FamilyTreeBuilder {
+    name: String,
+    root_id: Option<String>,
+    people: HashMap<String, Person>,
+    /// Ids in insertion order, so limit errors are reported deterministically
+    order: Vec<String>,
+    limits: InputLimits,
+}
+
+/// A validated family produced by [`FamilyTreeBuilder::build`]
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct BuiltFamily {
+    tree: FamilyTree,
+}
+
+#[wasm_bindgen]
+impl FamilyTreeBuilder {
+    #[wasm_bindgen(constructor)]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn add_person(&mut self, id: &str, name: &str) -> Result<(), String> {
+        if self.people.contains_key(id) {
+            return Err(format!("Duplicate person '{}'", id));
+        }
+        self.people.insert(id.to_string(), Person::new(id, name));
+        self.order.push(id.to_string());
+        if self.root_id.is_none() {
+            self.root_id = Some(id.to_string());
+        }
+        Ok(())
+    }
+
+    /// Make `child_id` the last child of `parent_id`; each person has one parent
+    pub fn add_child(&mut self, parent_id: &str, child_id: &str) -> Result<(), String> {
+        if parent_id == child_id {
+            return Err(format!("'{}' cannot be their own child", parent_id));
+        }
+        if !self.people.contains_key(child_id) {
+            return Err(format!("Person '{}' not found", child_id));
+        }
+        if let Some(parent) = self.people.values().find(|p| p.children.iter().any(|c| c == child_id)) {
+            return Err(format!("'{}' is already a child of '{}'", child_id, parent.id));
+        }
+        self.person_mut(parent_id)?.children.push(child_id.to_string());
+        Ok(())
+    }
+
+    pub fn set_biography(&mut self, id: &str, biography: &str) -> Result<(), String> {
+        self.person_mut(id)?.biography = biography.to_string();
+        Ok(())
+    }
+
+    /// Set birth and death years (either may be left undefined)
+    pub fn set_lifespan(&mut self, id: &str, birth_year: Option<i32>, death_year: Option<i32>) -> Result<(), String> {
+        let person = self.person_mut(id)?;
+        person.birth_year = birth_year.map(DateValue::year);
+        person.death_year = death_year.map(DateValue::year);
+        Ok(())
+    }
+
+    pub fn set_root(&mut self, id: &str) -> Result<(), String> {
+        self.person_mut(id)?;
+        self.root_id = Some(id.to_string());
+        Ok(())
+    }
+
+    /// Limit the family like uploaded documents, from a YAML/JSON mapping
+    /// (see the engine's `set_input_limits`)
+    pub fn set_input_limits(&mut self, config: &str) -> Result<(), String> {
+        self.limits = serde_yaml::from_str(config).map_err(|e| format!("Invalid input limits: {}", e))?;
+        Ok(())
+    }
+
+    /// Validate the family; the builder can keep being edited afterwards
+    pub fn build(&self) -> Result<BuiltFamily, String> {
+        let root_id = self.root_id.clone().ok_or("Family has no people")?;
+        let mut people: Vec<Person> = self.order.iter().map(|id| self.people[id].clone()).collect();
+        self.limits.check_people(&people)?;
+        for person in &mut people {
+            limits::sanitize_person(person);
+        }
+
+        let people = people.into_iter().map(|p| (p.id.clone(), p)).collect();
+        let tree = FamilyTree::from_parts(self.name.clone(), root_id, people, Vec::new())?;
+        Ok(BuiltFamily { tree })
+    }
+}
+
+impl FamilyTreeBuilder {
+    fn person_mut(&mut self, id: &str) -> Result<&mut Person, String> {
+        self.people.get_mut(id).ok_or_else(|| format!("Person '{}' not found", id))
+    }
+}
+
+#[wasm_bindgen]
+impl BuiltFamily {
+    /// Number of people in the family
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.tree.len()
+    }
+}
+
+impl BuiltFamily {
+    pub fn tree(&self) -> &FamilyTree {
+        &self.tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_family() {
+        let mut builder = FamilyTreeBuilder::new("Built");
+        builder.add_person("root", "Root").unwrap();
+        builder.add_person("a", "A").unwrap();
+        builder.add_person("b", "B").unwrap();
+        builder.add_child("root", "a").unwrap();
+        builder.add_child("a", "b").unwrap();
+        builder.set_biography("b", "Kept\u{0} the records.").unwrap();
+        builder.set_lifespan("b", Some(1950), None).unwrap();
+
+        let family = builder.build().unwrap();
+        let tree = family.tree();
+        assert_eq!(tree.root_id, "root");
+        assert_eq!(family.size(), 3);
+        assert_eq!(tree.children_of("a")[0].id, "b");
+        assert_eq!(tree.get("b").unwrap().biography, "Kept the records.");
+    }
+
+    #[test]
+    fn test_builder_rejects_bad_edges() {
+        let mut builder = FamilyTreeBuilder::new("Bad");
+        assert!(builder.build().is_err());
+
+        builder.add_person("root", "Root").unwrap();
+        builder.add_person("a", "A").unwrap();
+        assert!(builder.add_person("a", "Again").is_err());
+        assert!(builder.add_child("root", "ghost").is_err());
+        assert!(builder.add_child("root", "root").is_err());
+
+        builder.add_child("root", "a").unwrap();
+        assert!(builder.add_child("a", "a").is_err());
+        builder.add_person("b", "B").unwrap();
+        assert!(builder.add_child("b", "a").is_err());
+
+        // Each edge is fine alone, but together they loop
+        builder.add_child("a", "b").unwrap();
+        builder.add_child("b", "root").unwrap();
+        assert_eq!(builder.build().unwrap_err(), "'root' is listed as their own descendant");
+    }
+
+    #[test]
+    fn test_builder_limits() {
+        let mut builder = FamilyTreeBuilder::new("Small");
+        builder.add_person("root", "Root").unwrap();
+        builder.add_person("a", "A").unwrap();
+        assert!(builder.build().is_ok());
+
+        builder.set_input_limits("max_people: 1").unwrap();
+        assert!(builder.build().unwrap_err().contains("limit of 1"));
+        assert!(builder.set_input_limits("max_people: lots").is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use super::date::DateValue;
+use super::family_tree::FamilyTree;
+use super::person::Person;
+
+/// Build a family from CSV rows
+///
+/// Expects a header row naming the columns `id`, `name`, `parent_id`, `birth`,
+/// `death` and `biography` (any order; only `id` and `name` are required).
+/// The single row without a parent becomes the root, and children keep the
+/// order of their rows.
+pub fn family_from_csv(text: &str) -> Result<FamilyTree, String> {
+    let mut records = parse_records(text)?.into_iter();
+    let header = records.next().ok_or("CSV is empty")?;
+
+    let column = |names: &[&str]| {
+        header.iter().position(|h| {
+            let h = h.trim().to_ascii_lowercase().replace(['-', ' '], "_");
+            names.contains(&h.as_str())
+        })
+    };
+    let id_col = column(&["id"]).ok_or("CSV is missing an 'id' column")?;
+    let name_col = column(&["name"]).ok_or("CSV is missing a 'name' column")?;
+    let parent_col = column(&["parent_id", "parent"]);
+    let birth_col = column(&["birth", "birth_year", "born"]);
+    let death_col = column(&["death", "death_year", "died"]);
+    let bio_col = column(&["biography", "bio"]);
+
+    let mut people: HashMap<String, Person> = HashMap::new();
+    let mut order: Vec<(String, Option<String>)> = Vec::new();
+
+    for (index, record) in records.enumerate() {
+        let line = index + 2;
+        let field = |col: Option<usize>| {
+            col.and_then(|c| record.get(c))
+                .map(|v| v.trim())
+                .filter(|v| !v.is_empty())
+        };
+        let date = |col: Option<usize>| {
+            field(col)
+                .map(DateValue::parse)
+                .transpose()
+                .map_err(|e| format!("Line {}: {}", line, e))
+        };
+
+        if record.iter().all(|v| v.trim().is_empty()) {
+            continue;
+        }
+        let id = field(Some(id_col)).ok_or_else(|| format!("Line {}: missing id", line))?;
+        let name = field(Some(name_col)).unwrap_or(id);
+        if people.contains_key(id) {
+            return Err(format!("Line {}: duplicate id '{}'", line, id));
+        }
+
+        let person = Person::new(id, name)
+            .with_biography(field(bio_col).unwrap_or(""))
+            .with_dates(date(birth_col)?, date(death_col)?);
+        people.insert(id.to_string(), person);
+        order.push((id.to_string(), field(parent_col).map(str::to_string)));
+    }
+
+    let mut root = None;
+    for (id, parent) in &order {
+        match parent {
+            Some(parent_id) => people
+                .get_mut(parent_id)
+                .ok_or_else(|| format!("Parent '{}' of '{}' not found", parent_id, id))?
+                .children
+                .push(id.clone()),
+            None if root.is_none() => root = Some(id.clone()),
+            None => return Err(format!("Multiple people without a parent ('{}')", id)),
+        }
+    }
+
+    let root_id = root.ok_or("No root person (a row without parent_id)")?;
+    let name = people[&root_id].name.clone();
+    FamilyTree::from_parts(name, root_id, people, Vec::new())
+}
+
+/// Split CSV text into records, honouring quoted fields (`"a, ""b"""`)
+fn parse_records(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err("Unterminated quoted field in CSV".to_string());
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::InputLimits;
+
+    const CSV: &str = "\
+id,name,parent_id,birth,death,biography
+root,Ada Root,,1850,~1920,\"Founder, \"\"the elder\"\"\"
+a,Ann,root,1875,,
+b,Ben,root,1880s,,\"Two
+lines\"
+c,Cleo,a,1900,,
+";
+
+    #[test]
+    fn test_from_csv() {
+        let tree = family_from_csv(CSV).unwrap();
+        assert_eq!(tree.len(), 4);
+        assert_eq!(tree.root_id, "root");
+        assert_eq!(tree.name, "Ada Root");
+
+        let root = tree.get("root").unwrap();
+        assert_eq!(root.children, vec!["a", "b"]);
+        assert_eq!(root.biography, "Founder, \"the elder\"");
+        assert!(root.death_year.unwrap().approximate);
+        assert_eq!(tree.get("b").unwrap().biography, "Two\nlines");
+        assert_eq!(tree.children_of("a")[0].id, "c");
+    }
+
+    #[test]
+    fn test_column_order_and_aliases() {
+        let csv = "Name,ID,Parent\nRoot,r,\nKid,k,r\n";
+        let tree = family_from_csv(csv).unwrap();
+        assert_eq!(tree.get("k").unwrap().name, "Kid");
+        assert_eq!(tree.children_of("r").len(), 1);
+    }
+
+    #[test]
+    fn test_csv_errors() {
+        assert!(family_from_csv("").is_err());
+        assert!(family_from_csv("name\nA\n").is_err());
+        assert!(family_from_csv("id,name,parent_id\na,A,\nb,B,\n").is_err());
+        assert!(family_from_csv("id,name,parent_id\na,A,ghost\n").is_err());
+        assert!(family_from_csv("id,name\na,\"A\n").is_err());
+    }
+
+    #[test]
+    fn test_csv_limits() {
+        let limits = InputLimits { max_children: 1, ..Default::default() };
+        let csv = "id,name,parent_id\na,A,\nb,B,a\nc,C,a\n";
+        assert!(FamilyTree::from_csv_with_limits(csv, &limits).is_err());
+        assert!(FamilyTree::from_csv(csv).is_ok());
+    }
+}
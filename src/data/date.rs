@@ -0,0 +1,255 @@
+use std::fmt;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// How precisely a date is known
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatePrecision {
+    /// Full calendar date (YYYY-MM-DD)
+    Day,
+    /// Year and month (YYYY-MM)
+    Month,
+    /// Year only
+    Year,
+    /// Decade only ("1850s")
+    Decade,
+}
+
+/// A possibly approximate or partial date
+///
+/// Accepts plain years (`1850`), approximate years (`"~1850"`, `"c. 1850"`,
+/// `"abt 1850"`), decades (`"1850s"`) and ISO-style dates (`"1850-03"`,
+/// `"1850-03-14"`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateValue {
+    pub year: i32,
+    pub month: Option<u8>,
+    pub day: Option<u8>,
+    pub precision: DatePrecision,
+    /// Marked as approximate in the source data
+    pub approximate: bool,
+}
+
+impl DateValue {
+    /// An exactly known year
+    pub fn year(year: i32) -> Self {
+        Self {
+            year,
+            month: None,
+            day: None,
+            precision: DatePrecision::Year,
+            approximate: false,
+        }
+    }
+
+    /// An approximately known year ("~1850")
+    pub fn approximate(year: i32) -> Self {
+        Self {
+            approximate: true,
+            ..Self::year(year)
+        }
+    }
+
+    /// A decade, stored as its first year ("1850s")
+    pub fn decade(year: i32) -> Self {
+        Self {
+            year: year - year.rem_euclid(10),
+            precision: DatePrecision::Decade,
+            ..Self::year(year)
+        }
+    }
+
+    /// Parse from the textual forms accepted in YAML
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let s = input.trim();
+        let err = || format!("Invalid date '{}'", input);
+
+        // At most one approximation marker, then a plain date
+        let lower = s.to_ascii_lowercase();
+        let (body, approximate) = match ["~", "circa", "c.", "ca.", "abt", "about"]
+            .iter()
+            .find_map(|prefix| lower.strip_prefix(prefix))
+        {
+            Some(rest) => (rest.trim(), true),
+            None => (lower.as_str(), false),
+        };
+
+        if let Some(decade) = body.strip_suffix('s') {
+            let year: i32 = decade.parse().map_err(|_| err())?;
+            return Ok(Self { approximate, ..Self::decade(year) });
+        }
+
+        let mut parts = body.splitn(3, '-');
+        let year: i32 = parts.next().unwrap_or("").parse().map_err(|_| err())?;
+        let month = parts.next().map(|m| m.parse::<u8>()).transpose().map_err(|_| err())?;
+        let day = parts.next().map(|d| d.parse::<u8>()).transpose().map_err(|_| err())?;
+
+        if month.is_some_and(|m| !(1..=12).contains(&m)) || day.is_some_and(|d| !(1..=31).contains(&d)) {
+            return Err(err());
+        }
+
+        let precision = match (month, day) {
+            (Some(_), Some(_)) => DatePrecision::Day,
+            (Some(_), None) => DatePrecision::Month,
+            _ => DatePrecision::Year,
+        };
+
+        Ok(Self {
+            year,
+            month,
+            day,
+            precision,
+            approximate,
+        })
+    }
+
+    /// Uncertainty of the date (0.0 = exact, 1.0 = very vague)
+    pub fn uncertainty(&self) -> f32 {
+        let base = match self.precision {
+            DatePrecision::Day | DatePrecision::Month | DatePrecision::Year => 0.0,
+            DatePrecision::Decade => 0.6,
+        };
+        let approx: f32 = if self.approximate { 0.5 } else { 0.0 };
+        (base + approx).min(1.0)
+    }
+
+    /// Whether the date is anything less than an exact year
+    pub fn is_uncertain(&self) -> bool {
+        self.uncertainty() > 0.0
+    }
+
+    /// Chronological ordering key; missing months and days sort first
+    pub fn sort_key(&self) -> (i32, u8, u8) {
+        (self.year, self.month.unwrap_or(0), self.day.unwrap_or(0))
+    }
+}
+
+/// This calendar year, from the browser's clock (or the system's off the web)
+#[cfg(target_arch = "wasm32")]
+pub fn current_year() -> i32 {
+    js_sys::Date::new_0().get_full_year() as i32
+}
+
+/// This calendar year, from the browser's clock (or the system's off the web)
+#[cfg(not(target_arch = "wasm32"))]
+pub fn current_year() -> i32 {
+    const SECONDS_PER_YEAR: u64 = 31_556_952; // 365.2425 days
+    let since_epoch = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    1970 + (since_epoch / SECONDS_PER_YEAR) as i32
+}
+
+impl From<i32> for DateValue {
+    fn from(year: i32) -> Self {
+        Self::year(year)
+    }
+}
+
+impl fmt::Display for DateValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.approximate {
+            write!(f, "~")?;
+        }
+        match (self.precision, self.month, self.day) {
+            (DatePrecision::Day, Some(m), Some(d)) => write!(f, "{}-{:02}-{:02}", self.year, m, d),
+            (DatePrecision::Month, Some(m), _) => write!(f, "{}-{:02}", self.year, m),
+            (DatePrecision::Decade, _, _) => write!(f, "{}s", self.year),
+            _ => write!(f, "{}", self.year),
+        }
+    }
+}
+
+impl Serialize for DateValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.precision == DatePrecision::Year && !self.approximate {
+            serializer.serialize_i32(self.year)
+        } else {
+            serializer.collect_str(self)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for DateValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Year(i32),
+            Text(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Year(year) => Ok(Self::year(year)),
+            Raw::Text(text) => Self::parse(&text).map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plain_year() {
+        let date = DateValue::parse("1850").unwrap();
+        assert_eq!(date, DateValue::year(1850));
+        assert!(!date.is_uncertain());
+    }
+
+    #[test]
+    fn test_parse_approximate() {
+        for text in ["~1850", "c. 1850", "circa 1850", "abt 1850"] {
+            let date = DateValue::parse(text).unwrap();
+            assert_eq!(date.year, 1850, "{}", text);
+            assert!(date.approximate, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_parse_decade() {
+        let date = DateValue::parse("1850s").unwrap();
+        assert_eq!(date.year, 1850);
+        assert_eq!(date.precision, DatePrecision::Decade);
+        assert!(date.is_uncertain());
+
+        let vague = DateValue::parse("~1850s").unwrap();
+        assert!(vague.uncertainty() > date.uncertainty());
+    }
+
+    #[test]
+    fn test_parse_full_date() {
+        let date = DateValue::parse("1850-03-14").unwrap();
+        assert_eq!(date.month, Some(3));
+        assert_eq!(date.day, Some(14));
+        assert_eq!(date.precision, DatePrecision::Day);
+        assert_eq!(date.to_string(), "1850-03-14");
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(DateValue::parse("sometime").is_err());
+        assert!(DateValue::parse("1850-13").is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_stacked_prefixes() {
+        for text in ["~~1850", "~ circa 1850", "abt c. 1850", &"~".repeat(10_000)] {
+            assert!(DateValue::parse(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for text in ["1850", "~1850", "1850s", "1850-03"] {
+            assert_eq!(DateValue::parse(text).unwrap().to_string(), text);
+        }
+    }
+
+    #[test]
+    fn test_deserialize_int_and_string() {
+        let dates: Vec<DateValue> = serde_yaml::from_str("[1850, \"~1900\", \"1920s\"]").unwrap();
+        assert_eq!(dates[0], DateValue::year(1850));
+        assert!(dates[1].approximate);
+        assert_eq!(dates[2].precision, DatePrecision::Decade);
+    }
+}
@@ -0,0 +1,158 @@
+use super::family_tree::FamilyTree;
+use super::person::Person;
+
+/// A person present in both trees whose data differs
+#[derive(Debug, Clone, PartialEq)]
+pub struct PersonChange {
+    pub id: String,
+    /// Names of the fields that differ (`name`, `children`, ...)
+    pub fields: Vec<&'static str>,
+}
+
+/// Structural differences between two versions of a family
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FamilyDiff {
+    /// Ids only in the newer tree
+    pub added: Vec<String>,
+    /// Ids only in the older tree
+    pub removed: Vec<String>,
+    /// People in both trees with different data
+    pub changed: Vec<PersonChange>,
+}
+
+impl FamilyDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Serialize to a JSON object
+    pub fn to_json(&self) -> String {
+        let quote = |ids: &[String]| {
+            ids.iter().map(|id| format!("\"{}\"", crate::escape_json(id))).collect::<Vec<_>>().join(",")
+        };
+        let changed: Vec<String> = self
+            .changed
+            .iter()
+            .map(|change| {
+                let fields: Vec<String> = change.fields.iter().map(|f| format!("\"{}\"", f)).collect();
+                format!(
+                    r#"{{"id":"{}","fields":[{}]}}"#,
+                    crate::escape_json(&change.id),
+                    fields.join(",")
+                )
+            })
+            .collect();
+
+        format!(
+            r#"{{"added":[{}],"removed":[{}],"changed":[{}]}}"#,
+            quote(&self.added),
+            quote(&self.removed),
+            changed.join(",")
+        )
+    }
+}
+
+/// Compare `old` against `new`; every list is sorted by id
+pub fn diff(old: &FamilyTree, new: &FamilyTree) -> FamilyDiff {
+    let mut result = FamilyDiff::default();
+
+    for (id, person) in &new.people {
+        match old.people.get(id) {
+            None => result.added.push(id.clone()),
+            Some(before) => {
+                let fields = changed_fields(before, person);
+                if !fields.is_empty() {
+                    result.changed.push(PersonChange { id: id.clone(), fields });
+                }
+            }
+        }
+    }
+    result.removed = old.people.keys().filter(|id| !new.people.contains_key(*id)).cloned().collect();
+
+    result.added.sort();
+    result.removed.sort();
+    result.changed.sort_by(|a, b| a.id.cmp(&b.id));
+    result
+}
+
+fn changed_fields(a: &Person, b: &Person) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    let mut check = |name, differs: bool| {
+        if differs {
+            fields.push(name);
+        }
+    };
+
+    check("name", a.name != b.name);
+    check("surname", a.surname != b.surname);
+    check("name_localized", a.name_localized != b.name_localized);
+    check("biography", a.biography != b.biography);
+    check("birth_year", a.birth_year != b.birth_year);
+    check("death_year", a.death_year != b.death_year);
+    check("children", a.children != b.children);
+    check("events", a.events != b.events);
+    check("gender", a.gender != b.gender);
+    check("meta", a.meta != b.meta);
+    check("placeholder", a.placeholder != b.placeholder);
+    check("order", a.order != b.order);
+    check("audio_id", a.audio_id != b.audio_id);
+    check("living", a.living != b.living);
+    check("sources", a.sources != b.sources);
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BEFORE: &str = r#"
+family:
+  name: "Diff"
+  root: "gran"
+people:
+  - id: "gran"
+    name: "Gran"
+    children: ["mum", "uncle"]
+  - id: "mum"
+    name: "Mum"
+  - id: "uncle"
+    name: "Uncle"
+"#;
+
+    const AFTER: &str = r#"
+family:
+  name: "Diff"
+  root: "gran"
+people:
+  - id: "gran"
+    name: "Gran"
+    children: ["mum"]
+  - id: "mum"
+    name: "Mum"
+    biography: "Found in the parish records."
+    children: ["me"]
+  - id: "me"
+    name: "Me"
+"#;
+
+    #[test]
+    fn test_diff_added_removed_changed() {
+        let before = FamilyTree::from_yaml(BEFORE).unwrap();
+        let after = FamilyTree::from_yaml(AFTER).unwrap();
+        let d = diff(&before, &after);
+
+        assert_eq!(d.added, vec!["me"]);
+        assert_eq!(d.removed, vec!["uncle"]);
+        assert_eq!(d.changed.len(), 2);
+        assert_eq!(d.changed[0], PersonChange { id: "gran".into(), fields: vec!["children"] });
+        assert_eq!(d.changed[1].fields, vec!["biography", "children"]);
+    }
+
+    #[test]
+    fn test_identical_trees() {
+        let tree = FamilyTree::from_yaml(BEFORE).unwrap();
+        let d = diff(&tree, &tree.clone());
+        assert!(d.is_empty());
+        assert_eq!(d.to_json(), r#"{"added":[],"removed":[],"changed":[]}"#);
+    }
+}
@@ -0,0 +1,255 @@
+use super::family_tree::{FamilyTree, PersonLink};
+use super::person::Person;
+
+/// Assumed maximum lifespan when only one end of it is known
+const MAX_LIFESPAN: i32 = 100;
+
+/// A non-fatal problem found when checking a family
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationWarning {
+    /// Two people share a name and their lifetimes overlap
+    LikelyDuplicate { first: String, second: String },
+}
+
+impl ValidationWarning {
+    pub fn message(&self) -> String {
+        match self {
+            Self::LikelyDuplicate { first, second } => {
+                format!("'{}' and '{}' may be the same person", first, second)
+            }
+        }
+    }
+
+    /// Serialize to a JSON object
+    pub fn to_json(&self) -> String {
+        match self {
+            Self::LikelyDuplicate { first, second } => format!(
+                r#"{{"kind":"likely_duplicate","ids":["{}","{}"],"message":"{}"}}"#,
+                crate::escape_json(first),
+                crate::escape_json(second),
+                crate::escape_json(&self.message())
+            ),
+        }
+    }
+}
+
+/// Name used for duplicate matching: lowercase, single-spaced
+fn normalized_name(person: &Person) -> String {
+    person.name.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Years the person could have been alive, if any date is known
+fn lifetime(person: &Person) -> Option<(i32, i32)> {
+    match (person.birth_year, person.death_year) {
+        (Some(b), Some(d)) => Some((b.year, d.year)),
+        (Some(b), None) => Some((b.year, b.year + MAX_LIFESPAN)),
+        (None, Some(d)) => Some((d.year - MAX_LIFESPAN, d.year)),
+        (None, None) => None,
+    }
+}
+
+/// Pairs of people with the same name and overlapping lifetimes, sorted by id
+pub fn find_duplicates(tree: &FamilyTree) -> Vec<ValidationWarning> {
+    let mut people: Vec<&Person> = tree.people.values().collect();
+    people.sort_by(|a, b| normalized_name(a).cmp(&normalized_name(b)).then_with(|| a.id.cmp(&b.id)));
+
+    let mut warnings = Vec::new();
+    for (i, a) in people.iter().enumerate() {
+        let name = normalized_name(a);
+        for b in people[i + 1..].iter().take_while(|b| normalized_name(b) == name) {
+            let overlap = match (lifetime(a), lifetime(b)) {
+                (Some((a0, a1)), Some((b0, b1))) => a0 <= b1 && b0 <= a1,
+                _ => false,
+            };
+            if overlap {
+                warnings.push(ValidationWarning::LikelyDuplicate {
+                    first: a.id.clone(),
+                    second: b.id.clone(),
+                });
+            }
+        }
+    }
+    warnings
+}
+
+/// Fold `remove_id` into `keep_id`
+///
+/// The kept person's data wins; gaps are filled from the duplicate, children,
+/// events and sources are combined, and every parent and link pointing at the
+/// duplicate is redirected.
+pub fn merge_people(tree: &mut FamilyTree, keep_id: &str, remove_id: &str) -> Result<(), String> {
+    if keep_id == remove_id {
+        return Err("Cannot merge a person with themselves".to_string());
+    }
+    for id in [keep_id, remove_id] {
+        if tree.get(id).is_none() {
+            return Err(format!("Person '{}' not found", id));
+        }
+    }
+    let related = tree.ancestors_of(keep_id).iter().any(|p| p.id == remove_id)
+        || tree.ancestors_of(remove_id).iter().any(|p| p.id == keep_id);
+    if related {
+        return Err(format!("'{}' and '{}' are ancestor and descendant", keep_id, remove_id));
+    }
+
+    let removed = tree.people.remove(remove_id).expect("checked above");
+    let keep = tree.people.get_mut(keep_id).expect("checked above");
+
+    if keep.biography.trim().is_empty() {
+        keep.biography = removed.biography;
+    }
+    keep.surname = keep.surname.take().or(removed.surname);
+    keep.birth_year = keep.birth_year.or(removed.birth_year);
+    keep.death_year = keep.death_year.or(removed.death_year);
+    keep.gender = keep.gender.or(removed.gender);
+    keep.living = keep.living.or(removed.living);
+    keep.audio_id = keep.audio_id.take().or(removed.audio_id);
+    keep.order = keep.order.or(removed.order);
+    keep.partner = keep.partner.take().or(removed.partner).filter(|p| p != keep_id);
+    keep.placeholder &= removed.placeholder;
+    for (tag, name) in removed.name_localized {
+        keep.name_localized.entry(tag).or_insert(name);
+    }
+    for (key, value) in removed.meta {
+        if !keep.meta.contains_key(&key) {
+            keep.meta.insert(key, value);
+        }
+    }
+    for event in removed.events {
+        if !keep.events.contains(&event) {
+            keep.events.push(event);
+        }
+    }
+    for source in removed.sources {
+        if !keep.sources.contains(&source) {
+            keep.sources.push(source);
+        }
+    }
+    for child in removed.children {
+        if !keep.children.contains(&child) {
+            keep.children.push(child);
+        }
+    }
+
+    // Parents and partners of the duplicate now point at the kept person, once
+    for parent in tree.people.values_mut() {
+        if parent.partner.as_deref() == Some(remove_id) {
+            parent.partner = (parent.id != keep_id).then(|| keep_id.to_string());
+        }
+        if parent.children.iter().any(|c| c == remove_id) {
+            let has_keep = parent.children.iter().any(|c| c == keep_id);
+            if has_keep {
+                parent.children.retain(|c| c != remove_id);
+            } else {
+                for child in parent.children.iter_mut().filter(|c| *c == remove_id) {
+                    *child = keep_id.to_string();
+                }
+            }
+        }
+    }
+
+    for root in &mut tree.grove_roots {
+        if root == remove_id {
+            *root = keep_id.to_string();
+        }
+    }
+    let mut seen = std::collections::HashSet::new();
+    let root_id = tree.root_id.clone();
+    tree.grove_roots.retain(|root| *root != root_id && seen.insert(root.clone()));
+
+    let mut links: Vec<PersonLink> = Vec::with_capacity(tree.links.len());
+    for mut link in std::mem::take(&mut tree.links) {
+        for end in [&mut link.from, &mut link.to] {
+            if end == remove_id {
+                *end = keep_id.to_string();
+            }
+        }
+        let duplicate = links
+            .iter()
+            .any(|l| l.from == link.from && l.to == link.to && l.label == link.label);
+        if link.from != link.to && !duplicate {
+            links.push(link);
+        }
+    }
+    tree.links = links;
+
+    if tree.root_id == remove_id {
+        tree.root_id = keep_id.to_string();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = r#"
+family:
+  name: "Dupes"
+  root: "root"
+people:
+  - id: "root"
+    name: "Root"
+    children: ["anna", "tom"]
+  - id: "anna"
+    name: "Anna Berg"
+    birth_year: 1900
+    children: ["kid"]
+  - id: "tom"
+    name: "Tom Berg"
+    children: ["anna-2"]
+  - id: "anna-2"
+    name: "anna  berg"
+    birth_year: 1902
+    death_year: 1970
+    biography: "Recorded in the parish register."
+    children: ["kid2"]
+  - id: "anna-3"
+    name: "Anna Berg"
+    birth_year: 1780
+    death_year: 1840
+  - id: "kid"
+    name: "Kid"
+  - id: "kid2"
+    name: "Kid Two"
+links:
+  - from: "kid2"
+    to: "anna-2"
+    label: "named after"
+"#;
+
+    #[test]
+    fn test_find_duplicates() {
+        let tree = FamilyTree::from_yaml(YAML).unwrap();
+        let warnings = find_duplicates(&tree);
+
+        assert_eq!(
+            warnings,
+            vec![ValidationWarning::LikelyDuplicate { first: "anna".into(), second: "anna-2".into() }]
+        );
+        assert!(warnings[0].to_json().contains(r#""ids":["anna","anna-2"]"#));
+    }
+
+    #[test]
+    fn test_merge_people() {
+        let mut tree = FamilyTree::from_yaml(YAML).unwrap();
+        merge_people(&mut tree, "anna", "anna-2").unwrap();
+
+        let anna = tree.get("anna").unwrap();
+        assert_eq!(anna.birth_year.unwrap().year, 1900);
+        assert_eq!(anna.death_year.unwrap().year, 1970);
+        assert_eq!(anna.biography, "Recorded in the parish register.");
+        assert_eq!(anna.children, vec!["kid", "kid2"]);
+        assert!(tree.get("anna-2").is_none());
+        assert_eq!(tree.get("tom").unwrap().children, vec!["anna"]);
+        assert_eq!(tree.links[0].to, "anna");
+        assert!(find_duplicates(&tree).is_empty());
+    }
+
+    #[test]
+    fn test_merge_rejects_ancestor() {
+        let mut tree = FamilyTree::from_yaml(YAML).unwrap();
+        assert!(merge_people(&mut tree, "tom", "anna-2").is_err());
+        assert!(merge_people(&mut tree, "anna", "anna").is_err());
+    }
+}
@@ -1,18 +1,118 @@
-use std::collections::HashMap;
-use serde::Deserialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use serde::{Deserialize, Serialize};
+use super::diff::{self, FamilyDiff};
+use super::duplicates::{self, ValidationWarning};
+use super::limits::{self, InputLimits};
+use super::merge::{self, MergeReport, MergeStrategy};
 use super::person::Person;
+use super::schema;
+
+/// How siblings without an explicit `order` are arranged
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChildOrder {
+    /// As listed in the parent's `children`
+    Listed,
+    /// By birth date, oldest first
+    #[default]
+    Chronological,
+}
+
+impl ChildOrder {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "listed" => Ok(Self::Listed),
+            "chronological" => Ok(Self::Chronological),
+            other => Err(format!("Unknown child order '{}'", other)),
+        }
+    }
+}
 
 /// YAML input format for a family
+///
+/// Older documents are upgraded by [`schema::parse_family_input`] before
+/// reaching this struct.
 #[derive(Debug, Deserialize)]
 pub struct FamilyInput {
+    /// Format version (see [`schema::SCHEMA_VERSION`])
+    #[serde(default = "default_version")]
+    pub version: u32,
     pub family: FamilyMeta,
     pub people: Vec<Person>,
+    #[serde(default)]
+    pub links: Vec<PersonLink>,
+    /// Per-file growth tuning, applied when the family is shown
+    #[serde(default)]
+    pub growth: Option<serde_yaml::Value>,
+}
+
+/// A non-genealogical relationship between two people ("named after", "business partners")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonLink {
+    pub from: String,
+    pub to: String,
+    #[serde(default)]
+    pub label: String,
+}
+
+fn default_version() -> u32 {
+    schema::SCHEMA_VERSION
 }
 
 #[derive(Debug, Deserialize)]
 pub struct FamilyMeta {
     pub name: String,
     pub root: String,
+    /// Further founding ancestors, each grown as its own trunk beside `root`
+    #[serde(default)]
+    pub roots: Vec<String>,
+}
+
+/// Summary numbers for one generation
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenerationStats {
+    /// Depth from the root (0 = root)
+    pub generation: usize,
+    /// Number of people in this generation
+    pub people: usize,
+    /// Average lifespan in years of people with known birth and death
+    pub average_lifespan: Option<f32>,
+}
+
+/// Summary statistics over a whole family tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct FamilyStatistics {
+    pub generations: Vec<GenerationStats>,
+    /// Average number of children among people who have any
+    pub branching_factor: f32,
+    pub max_depth: usize,
+    pub total_people: usize,
+}
+
+impl FamilyStatistics {
+    /// Serialize to a JSON object
+    pub fn to_json(&self) -> String {
+        let generations: Vec<String> = self
+            .generations
+            .iter()
+            .map(|g| {
+                format!(
+                    r#"{{"generation":{},"people":{},"average_lifespan":{}}}"#,
+                    g.generation,
+                    g.people,
+                    g.average_lifespan.map(|l| format!("{:.1}", l)).unwrap_or_else(|| "null".to_string())
+                )
+            })
+            .collect();
+
+        format!(
+            r#"{{"generations":[{}],"branching_factor":{:.2},"max_depth":{},"total_people":{}}}"#,
+            generations.join(","),
+            self.branching_factor,
+            self.max_depth,
+            self.total_people
+        )
+    }
 }
 
 /// Parsed and indexed family tree
@@ -21,22 +121,75 @@ pub struct FamilyTree {
     pub name: String,
     pub root_id: String,
     pub people: HashMap<String, Person>,
+    /// Explicit links between people, drawn as memory threads
+    pub links: Vec<PersonLink>,
+    /// Growth parameters from the file's `growth:` section, if any
+    pub growth: Option<serde_yaml::Value>,
+    /// Other founding ancestors, grown as a grove of trunks around the root
+    pub grove_roots: Vec<String>,
 }
 
 impl FamilyTree {
-    /// Parse from YAML string
+    /// Parse from YAML string with the default [`InputLimits`]
     pub fn from_yaml(yaml: &str) -> Result<Self, String> {
-        let input: FamilyInput = serde_yaml::from_str(yaml)
-            .map_err(|e| format!("YAML parse error: {}", e))?;
+        Self::from_yaml_with_limits(yaml, &InputLimits::default())
+    }
+
+    /// Parse from YAML string, rejecting documents over `limits` and
+    /// stripping control characters from names and biographies
+    pub fn from_yaml_with_limits(yaml: &str, limits: &InputLimits) -> Result<Self, String> {
+        limits.check_document(yaml)?;
+        let input = schema::parse_family_input(yaml)?;
+        limits.check_people(&input.people)?;
 
         let mut people = HashMap::new();
-        for person in input.people {
+        for mut person in input.people {
+            limits::sanitize_person(&mut person);
             people.insert(person.id.clone(), person);
         }
 
+        let mut tree = Self::from_parts(input.family.name, input.family.root, people, input.links)?;
+        tree.growth = input.growth;
+        for id in input.family.roots {
+            if !tree.people.contains_key(&id) {
+                return Err(format!("Root person '{}' not found in people list", id));
+            }
+            if id != tree.root_id && !tree.grove_roots.contains(&id) {
+                tree.grove_roots.push(id);
+            }
+        }
+        let trunks = tree.trunks();
+        if let Some(nested) = tree.grove_roots.iter().find(|id| !trunks.contains(&id.as_str())) {
+            return Err(format!("Root person '{}' already grows from another trunk", nested));
+        }
+        Ok(tree)
+    }
+
+    /// Parse from CSV with `id`, `name`, `parent_id`, `birth`, `death` and
+    /// `biography` columns (see [`super::csv::family_from_csv`])
+    pub fn from_csv(csv: &str) -> Result<Self, String> {
+        Self::from_csv_with_limits(csv, &InputLimits::default())
+    }
+
+    /// Parse from CSV, applying the same `limits` and sanitizing as YAML
+    pub fn from_csv_with_limits(csv: &str, limits: &InputLimits) -> Result<Self, String> {
+        limits.check_document(csv)?;
+        let mut tree = super::csv::family_from_csv(csv)?;
+        limits.check_family(&tree)?;
+        tree.people.values_mut().for_each(limits::sanitize_person);
+        Ok(tree)
+    }
+
+    /// Assemble and validate a tree from already-indexed people
+    pub fn from_parts(
+        name: String,
+        root_id: String,
+        people: HashMap<String, Person>,
+        links: Vec<PersonLink>,
+    ) -> Result<Self, String> {
         // Validate root exists
-        if !people.contains_key(&input.family.root) {
-            return Err(format!("Root person '{}' not found in people list", input.family.root));
+        if !people.contains_key(&root_id) {
+            return Err(format!("Root person '{}' not found in people list", root_id));
         }
 
         // Validate all children references exist
@@ -51,13 +204,174 @@ impl FamilyTree {
             }
         }
 
+        // Validate partners exist
+        for person in people.values() {
+            if let Some(partner) = &person.partner {
+                if *partner == person.id {
+                    return Err(format!("'{}' is listed as their own partner", person.id));
+                }
+                if !people.contains_key(partner) {
+                    return Err(format!(
+                        "Partner '{}' referenced by '{}' not found",
+                        partner, person.id
+                    ));
+                }
+            }
+        }
+
+        if let Some(id) = find_cycle(&root_id, &people) {
+            return Err(format!("'{}' is listed as their own descendant", id));
+        }
+
+        // Validate link endpoints exist
+        for link in &links {
+            for id in [&link.from, &link.to] {
+                if !people.contains_key(id) {
+                    return Err(format!(
+                        "Person '{}' referenced by link '{}' -> '{}' not found",
+                        id, link.from, link.to
+                    ));
+                }
+            }
+        }
+
         Ok(Self {
-            name: input.family.name,
-            root_id: input.family.root,
+            name,
+            root_id,
             people,
+            links,
+            growth: None,
+            grove_roots: Vec::new(),
         })
     }
 
+    /// Make another person the root, showing the tree from them downward
+    ///
+    /// Everyone stays in the family, so the original root can be restored.
+    pub fn set_root(&mut self, id: &str) -> Result<(), String> {
+        if !self.people.contains_key(id) {
+            return Err(format!("Person '{}' not found", id));
+        }
+        self.root_id = id.to_string();
+        Ok(())
+    }
+
+    /// Merge another family into this one, resolving id collisions by `strategy`
+    ///
+    /// This tree's root is kept; see [`MergeStrategy`] for how shared ids are handled.
+    pub fn merge(&mut self, other: &FamilyTree, strategy: MergeStrategy) -> Result<MergeReport, String> {
+        merge::merge_into(self, other, strategy)
+    }
+
+    /// People added, removed or changed in `newer` relative to this tree
+    pub fn diff(&self, newer: &FamilyTree) -> FamilyDiff {
+        diff::diff(self, newer)
+    }
+
+    /// Non-fatal problems, such as likely duplicate people
+    ///
+    /// Structural errors are rejected when the tree is built; these are left
+    /// for the user to review.
+    pub fn validate(&self) -> Vec<ValidationWarning> {
+        duplicates::find_duplicates(self)
+    }
+
+    /// Fold a duplicate record into another person (see [`ValidationWarning`])
+    pub fn merge_duplicates(&mut self, keep_id: &str, remove_id: &str) -> Result<(), String> {
+        duplicates::merge_people(self, keep_id, remove_id)
+    }
+
+    /// Add a new person as the last child of `parent_id`
+    pub fn add_child(&mut self, parent_id: &str, person: Person) -> Result<(), String> {
+        if self.people.contains_key(&person.id) {
+            return Err(format!("Person '{}' already exists", person.id));
+        }
+        if !self.people.contains_key(parent_id) {
+            return Err(format!("Parent '{}' not found", parent_id));
+        }
+        self.validate_children(&person)?;
+
+        let id = person.id.clone();
+        self.people.insert(id.clone(), person);
+        if let Some(parent) = self.people.get_mut(parent_id) {
+            parent.children.push(id);
+        }
+        Ok(())
+    }
+
+    /// Replace an existing person's data (matched by id)
+    pub fn update_person(&mut self, person: Person) -> Result<(), String> {
+        if !self.people.contains_key(&person.id) {
+            return Err(format!("Person '{}' not found", person.id));
+        }
+        self.validate_children(&person)?;
+        self.people.insert(person.id.clone(), person);
+        Ok(())
+    }
+
+    /// Remove a childless person, detaching them from parents and links
+    pub fn remove_person(&mut self, id: &str) -> Result<Person, String> {
+        if id == self.root_id {
+            return Err("Cannot remove the root person".to_string());
+        }
+        let person = self.people.get(id).ok_or_else(|| format!("Person '{}' not found", id))?;
+        if !person.children.is_empty() {
+            return Err(format!("Person '{}' has children; remove them first", id));
+        }
+
+        let removed = self.people.remove(id).expect("checked above");
+        for other in self.people.values_mut() {
+            other.children.retain(|c| c != id);
+            if other.partner.as_deref() == Some(id) {
+                other.partner = None;
+            }
+        }
+        self.links.retain(|link| link.from != id && link.to != id);
+        self.grove_roots.retain(|root| root != id);
+        Ok(removed)
+    }
+
+    fn validate_children(&self, person: &Person) -> Result<(), String> {
+        for child_id in &person.children {
+            if !self.people.contains_key(child_id) {
+                return Err(format!(
+                    "Child '{}' referenced by '{}' not found",
+                    child_id, person.id
+                ));
+            }
+        }
+        if let Some(partner) = &person.partner {
+            if *partner == person.id {
+                return Err(format!("'{}' is listed as their own partner", person.id));
+            }
+            if !self.people.contains_key(partner) {
+                return Err(format!("Partner '{}' referenced by '{}' not found", partner, person.id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Ids of every trunk to grow: the root, then the rest of the grove
+    ///
+    /// Grove roots whose lines overlap an earlier trunk's (e.g. after
+    /// re-rooting) are skipped so no one is grown twice.
+    pub fn trunks(&self) -> Vec<&str> {
+        let mut grown: HashSet<&str> = HashSet::new();
+        let mut trunks = Vec::new();
+        let candidates = std::iter::once(self.root_id.as_str()).chain(self.grove_roots.iter().map(String::as_str));
+        for id in candidates {
+            let line: Vec<&str> = std::iter::once(id)
+                .chain(self.descendants_of(id).into_iter().map(|p| p.id.as_str()))
+                .collect();
+            if line.iter().any(|member| grown.contains(member)) {
+                continue;
+            }
+            grown.extend(line);
+            trunks.push(id);
+        }
+        trunks
+    }
+
     /// Get the root person
     pub fn root(&self) -> Option<&Person> {
         self.people.get(&self.root_id)
@@ -81,6 +395,23 @@ impl FamilyTree {
             .unwrap_or_default()
     }
 
+    /// Get children of a person arranged left-to-right for display
+    ///
+    /// Siblings with an explicit `order` come first, lowest first. The rest
+    /// keep their listed order, or are sorted by birth date (undated last)
+    /// under [`ChildOrder::Chronological`].
+    pub fn ordered_children_of(&self, id: &str, order: ChildOrder) -> Vec<&Person> {
+        let mut children = self.children_of(id);
+        children.sort_by_key(|child| {
+            let birth = match order {
+                ChildOrder::Chronological => child.birth_year.map(|d| d.sort_key()),
+                ChildOrder::Listed => None,
+            };
+            (child.order.is_none(), child.order, birth.is_none(), birth)
+        });
+        children
+    }
+
     /// Count total people
     pub fn len(&self) -> usize {
         self.people.len()
@@ -107,6 +438,188 @@ impl FamilyTree {
         depth_from(self, &self.root_id)
     }
 
+    /// Get parents of a person (anyone listing them as a child)
+    pub fn parents_of(&self, id: &str) -> Vec<&Person> {
+        let mut parents: Vec<&Person> = self
+            .people
+            .values()
+            .filter(|p| p.children.iter().any(|c| c == id))
+            .collect();
+        parents.sort_by(|a, b| a.id.cmp(&b.id));
+        parents
+    }
+
+    /// Number of generations between the root and this person (root = 0)
+    ///
+    /// `None` if the person is unknown or not descended from the root.
+    pub fn generation_of(&self, id: &str) -> Option<usize> {
+        self.ancestor_distances(id).get(self.root_id.as_str()).copied()
+    }
+
+    /// All ancestors of a person, nearest generation first
+    pub fn ancestors_of(&self, id: &str) -> Vec<&Person> {
+        let mut distances: Vec<(&str, usize)> = self.ancestor_distances(id).into_iter().collect();
+        distances.retain(|&(_, d)| d > 0);
+        distances.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(b.0)));
+        distances.into_iter().filter_map(|(aid, _)| self.people.get(aid)).collect()
+    }
+
+    /// All descendants of a person in pre-order (excluding the person)
+    pub fn descendants_of(&self, id: &str) -> Vec<&Person> {
+        let mut result = Vec::new();
+        let mut seen = HashSet::new();
+        let mut stack: Vec<&str> = self
+            .people
+            .get(id)
+            .map(|p| p.children.iter().rev().map(|c| c.as_str()).collect())
+            .unwrap_or_default();
+
+        while let Some(cid) = stack.pop() {
+            if !seen.insert(cid) {
+                continue;
+            }
+            if let Some(child) = self.people.get(cid) {
+                result.push(child);
+                stack.extend(child.children.iter().rev().map(|c| c.as_str()));
+            }
+        }
+        result
+    }
+
+    /// Describe how `b` is related to `a` (e.g. "grandparent", "second cousin once removed")
+    ///
+    /// Returns `None` if either person is unknown or they share no ancestor.
+    pub fn relationship_between(&self, a: &str, b: &str) -> Option<String> {
+        if !self.people.contains_key(a) || !self.people.contains_key(b) {
+            return None;
+        }
+        if a == b {
+            return Some("self".to_string());
+        }
+
+        let from_a = self.ancestor_distances(a);
+        let from_b = self.ancestor_distances(b);
+
+        // Closest common ancestor by total path length
+        let (da, db) = from_a
+            .iter()
+            .filter_map(|(id, &da)| from_b.get(id).map(|&db| (da, db)))
+            .min_by_key(|&(da, db)| (da + db, da.max(db)))?;
+
+        Some(relationship_name(da, db))
+    }
+
+    /// Generations from `id` up to each of its ancestors (including itself at 0)
+    fn ancestor_distances(&self, id: &str) -> HashMap<&str, usize> {
+        let mut parents: HashMap<&str, Vec<&str>> = HashMap::new();
+        for person in self.people.values() {
+            for child in &person.children {
+                parents.entry(child.as_str()).or_default().push(person.id.as_str());
+            }
+        }
+
+        let mut distances = HashMap::new();
+        let Some((start, _)) = self.people.get_key_value(id) else {
+            return distances;
+        };
+
+        let mut queue = VecDeque::from([(start.as_str(), 0)]);
+        while let Some((pid, dist)) = queue.pop_front() {
+            if distances.contains_key(pid) {
+                continue;
+            }
+            distances.insert(pid, dist);
+            for &parent in parents.get(pid).into_iter().flatten() {
+                queue.push_back((parent, dist + 1));
+            }
+        }
+        distances
+    }
+
+    /// Search people by name, case-insensitively, best matches first
+    ///
+    /// Exact and substring matches rank above fuzzy matches, which tolerate a
+    /// small number of typos per word.
+    pub fn search(&self, query: &str) -> Vec<&Person> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches: Vec<(u32, &Person)> = self
+            .people
+            .values()
+            .filter_map(|p| {
+                p.all_names()
+                    .filter_map(|name| match_score(&query, &name.to_lowercase()))
+                    .min()
+                    .map(|score| (score, p))
+            })
+            .collect();
+
+        matches.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.name.cmp(&b.1.name)));
+        matches.into_iter().map(|(_, p)| p).collect()
+    }
+
+    /// Compute per-generation counts, lifespans and overall branching
+    pub fn statistics(&self) -> FamilyStatistics {
+        // Breadth-first from the root so each person gets their shallowest generation
+        let mut generation_of: HashMap<&str, usize> = HashMap::new();
+        let mut queue = VecDeque::from([(self.root_id.as_str(), 0)]);
+        while let Some((id, generation)) = queue.pop_front() {
+            if generation_of.contains_key(id) {
+                continue;
+            }
+            generation_of.insert(id, generation);
+            for child in self.children_of(id) {
+                queue.push_back((child.id.as_str(), generation + 1));
+            }
+        }
+
+        let depth = generation_of.values().max().map_or(0, |g| g + 1);
+        let mut counts = vec![0usize; depth];
+        let mut lifespans: Vec<Vec<i32>> = vec![Vec::new(); depth];
+        for (id, &generation) in &generation_of {
+            counts[generation] += 1;
+            if let Some(person) = self.people.get(*id) {
+                if let (Some(birth), Some(death)) = (person.birth_year, person.death_year) {
+                    lifespans[generation].push(death.year - birth.year);
+                }
+            }
+        }
+
+        let generations = counts
+            .iter()
+            .zip(&lifespans)
+            .enumerate()
+            .map(|(generation, (&people, spans))| GenerationStats {
+                generation,
+                people,
+                average_lifespan: (!spans.is_empty())
+                    .then(|| spans.iter().sum::<i32>() as f32 / spans.len() as f32),
+            })
+            .collect();
+
+        let parents: Vec<usize> = self
+            .people
+            .values()
+            .map(|p| p.children.len())
+            .filter(|&n| n > 0)
+            .collect();
+        let branching_factor = if parents.is_empty() {
+            0.0
+        } else {
+            parents.iter().sum::<usize>() as f32 / parents.len() as f32
+        };
+
+        FamilyStatistics {
+            generations,
+            branching_factor,
+            max_depth: depth,
+            total_people: self.people.len(),
+        }
+    }
+
     /// Iterate over all people in pre-order (root first)
     pub fn iter_preorder(&self) -> PreorderIter<'_> {
         PreorderIter {
@@ -116,6 +629,146 @@ impl FamilyTree {
     }
 }
 
+/// A person listed among their own descendants, searching from the root
+/// first and then from the other people in id order
+fn find_cycle<'a>(root_id: &'a str, people: &'a HashMap<String, Person>) -> Option<&'a str> {
+    let mut ids: Vec<&str> = people.keys().map(String::as_str).collect();
+    ids.sort_unstable();
+    let mut done = HashSet::new();
+    let mut on_path = HashSet::new();
+    for start in std::iter::once(root_id).chain(ids) {
+        if done.contains(start) {
+            continue;
+        }
+        // Depth first, without recursion so deep lines can't overflow the stack
+        let mut stack = vec![(start, 0)];
+        on_path.insert(start);
+        while let Some(&(id, next)) = stack.last() {
+            let Some(child) = people.get(id).and_then(|p| p.children.get(next)) else {
+                on_path.remove(id);
+                done.insert(id);
+                stack.pop();
+                continue;
+            };
+            let top = stack.len() - 1;
+            stack[top].1 += 1;
+            if on_path.contains(child.as_str()) {
+                return Some(child);
+            }
+            if !done.contains(child.as_str()) {
+                on_path.insert(child);
+                stack.push((child, 0));
+            }
+        }
+    }
+    None
+}
+
+/// Rank how well `query` matches `name` (lower is better), both lowercase
+fn match_score(query: &str, name: &str) -> Option<u32> {
+    if name == query {
+        return Some(0);
+    }
+    if name.starts_with(query) {
+        return Some(1);
+    }
+    if name.contains(query) {
+        return Some(2);
+    }
+
+    // Fuzzy: every query word must be close to some word of the name
+    let mut total = 0;
+    for q in query.split_whitespace() {
+        let tolerance = (q.chars().count() / 4).max(1);
+        let best = name
+            .split_whitespace()
+            .map(|w| {
+                // Compare against the word's prefix so partial words still match
+                let prefix: String = w.chars().take(q.chars().count()).collect();
+                edit_distance(q, &prefix).min(edit_distance(q, w))
+            })
+            .min()?;
+        if best > tolerance {
+            return None;
+        }
+        total += best as u32;
+    }
+    Some(3 + total)
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        curr[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let cost = if ca == cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b.len()]
+}
+
+/// Name the relationship of B to A, given each one's distance to their common ancestor
+fn relationship_name(da: usize, db: usize) -> String {
+    fn greats(n: usize) -> String {
+        "great-".repeat(n)
+    }
+
+    match (da, db) {
+        (0, 0) => "self".to_string(),
+        (0, 1) => "child".to_string(),
+        (0, n) => format!("{}grandchild", greats(n - 2)),
+        (1, 0) => "parent".to_string(),
+        (n, 0) => format!("{}grandparent", greats(n - 2)),
+        (1, 1) => "sibling".to_string(),
+        (1, n) => format!("{}niece/nephew", grand_prefix(n - 2)),
+        (n, 1) => format!("{}aunt/uncle", greats(n - 2)),
+        _ => {
+            let degree = da.min(db) - 1;
+            let removed = da.abs_diff(db);
+            let base = format!("{} cousin", ordinal(degree));
+            match removed {
+                0 => base,
+                1 => format!("{} once removed", base),
+                2 => format!("{} twice removed", base),
+                n => format!("{} {} times removed", base, n),
+            }
+        }
+    }
+}
+
+/// "", "grand-", "great-grand-", ... for descendants of siblings
+fn grand_prefix(n: usize) -> String {
+    match n {
+        0 => String::new(),
+        n => format!("{}grand-", "great-".repeat(n - 1)),
+    }
+}
+
+fn ordinal(n: usize) -> String {
+    const WORDS: [&str; 10] = [
+        "first", "second", "third", "fourth", "fifth",
+        "sixth", "seventh", "eighth", "ninth", "tenth",
+    ];
+    match WORDS.get(n.wrapping_sub(1)) {
+        Some(word) => word.to_string(),
+        None => {
+            let suffix = match (n % 10, n % 100) {
+                (1, r) if r != 11 => "st",
+                (2, r) if r != 12 => "nd",
+                (3, r) if r != 13 => "rd",
+                _ => "th",
+            };
+            format!("{}{}", n, suffix)
+        }
+    }
+}
+
 pub struct PreorderIter<'a> {
     tree: &'a FamilyTree,
     stack: Vec<String>,
@@ -140,6 +793,8 @@ impl<'a> Iterator for PreorderIter<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::data::EventKind;
+    use crate::data::Person;
 
     const SAMPLE_YAML: &str = r#"
 family:
@@ -172,6 +827,28 @@ people:
     biography: "The youngest generation."
 "#;
 
+    #[test]
+    fn test_growth_section() {
+        let yaml = r#"
+family:
+  name: "Tuned"
+  root: "root"
+people:
+  - id: "root"
+    name: "Root"
+growth:
+  base_height: 5.0
+  layout: radial
+"#;
+        let tree = FamilyTree::from_yaml(yaml).unwrap();
+        let growth = tree.growth.unwrap();
+        assert_eq!(growth["base_height"].as_f64(), Some(5.0));
+        assert_eq!(growth["layout"].as_str(), Some("radial"));
+
+        let plain = FamilyTree::from_yaml(SAMPLE_YAML).unwrap();
+        assert!(plain.growth.is_none());
+    }
+
     #[test]
     fn test_parse_yaml() {
         let tree = FamilyTree::from_yaml(SAMPLE_YAML).unwrap();
@@ -207,6 +884,250 @@ people:
         assert_eq!(names.len(), 4);
     }
 
+    const COUSINS_YAML: &str = r#"
+family:
+  name: "Cousins"
+  root: "gg"
+people:
+  - id: "gg"
+    name: "Great Grandparent"
+    children: ["g1", "g2"]
+  - id: "g1"
+    name: "Grandparent One"
+    children: ["p1"]
+  - id: "g2"
+    name: "Grandparent Two"
+    children: ["p2"]
+  - id: "p1"
+    name: "Parent One"
+    children: ["c1"]
+  - id: "p2"
+    name: "Parent Two"
+    children: ["c2"]
+  - id: "c1"
+    name: "Child One"
+  - id: "c2"
+    name: "Child Two"
+"#;
+
+    #[test]
+    fn test_ancestors_and_descendants() {
+        let tree = FamilyTree::from_yaml(SAMPLE_YAML).unwrap();
+
+        let ancestors: Vec<_> = tree.ancestors_of("child1").iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ancestors, vec!["parent1", "grandparent"]);
+
+        let descendants: Vec<_> = tree.descendants_of("grandparent").iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(descendants, vec!["parent1", "child1", "parent2"]);
+
+        assert_eq!(tree.parents_of("parent2")[0].id, "grandparent");
+    }
+
+    #[test]
+    fn test_direct_relationships() {
+        let tree = FamilyTree::from_yaml(SAMPLE_YAML).unwrap();
+        assert_eq!(tree.relationship_between("grandparent", "child1").unwrap(), "grandchild");
+        assert_eq!(tree.relationship_between("child1", "grandparent").unwrap(), "grandparent");
+        assert_eq!(tree.relationship_between("parent1", "parent2").unwrap(), "sibling");
+        assert_eq!(tree.relationship_between("child1", "parent2").unwrap(), "aunt/uncle");
+        assert_eq!(tree.relationship_between("parent2", "child1").unwrap(), "niece/nephew");
+        assert!(tree.relationship_between("child1", "nobody").is_none());
+    }
+
+    #[test]
+    fn test_cousin_relationships() {
+        let tree = FamilyTree::from_yaml(COUSINS_YAML).unwrap();
+        assert_eq!(tree.relationship_between("p1", "p2").unwrap(), "first cousin");
+        assert_eq!(tree.relationship_between("c1", "c2").unwrap(), "second cousin");
+        assert_eq!(tree.relationship_between("c1", "p2").unwrap(), "first cousin once removed");
+        assert_eq!(tree.relationship_between("c1", "gg").unwrap(), "great-grandparent");
+        assert_eq!(tree.relationship_between("g2", "c1").unwrap(), "grand-niece/nephew");
+    }
+
+    #[test]
+    fn test_relationship_names() {
+        assert_eq!(relationship_name(4, 6), "third cousin twice removed");
+        assert_eq!(relationship_name(4, 1), "great-great-aunt/uncle");
+        assert_eq!(relationship_name(13, 13), "12th cousin");
+    }
+
+    #[test]
+    fn test_statistics() {
+        let tree = FamilyTree::from_yaml(SAMPLE_YAML).unwrap();
+        let stats = tree.statistics();
+
+        assert_eq!(stats.max_depth, 3);
+        assert_eq!(stats.total_people, 4);
+        let counts: Vec<_> = stats.generations.iter().map(|g| g.people).collect();
+        assert_eq!(counts, vec![1, 2, 1]);
+        assert_eq!(stats.generations[0].average_lifespan, Some(80.0));
+        assert_eq!(stats.generations[1].average_lifespan, None);
+        // grandparent has 2 children, parent1 has 1
+        assert!((stats.branching_factor - 1.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_statistics_json() {
+        let tree = FamilyTree::from_yaml(SAMPLE_YAML).unwrap();
+        let json = tree.statistics().to_json();
+        assert!(json.contains(r#""max_depth":3"#));
+        assert!(json.contains(r#""average_lifespan":80.0"#));
+        assert!(json.contains(r#""average_lifespan":null"#));
+    }
+
+    #[test]
+    fn test_set_root() {
+        let mut tree = FamilyTree::from_yaml(SAMPLE_YAML).unwrap();
+        tree.set_root("parent1").unwrap();
+
+        assert_eq!(tree.root().unwrap().id, "parent1");
+        assert_eq!(tree.len(), 4);
+        assert!(tree.set_root("nobody").is_err());
+        assert_eq!(tree.root_id, "parent1");
+    }
+
+    #[test]
+    fn test_grove_roots() {
+        let founders = format!("{}\n  - id: \"other\"\n    name: \"Other Founder\"\n", SAMPLE_YAML.trim_end());
+        let yaml = founders.replace("root: \"grandparent\"", "root: \"grandparent\"\n  roots: [\"other\", \"grandparent\"]");
+        let mut tree = FamilyTree::from_yaml(&yaml).unwrap();
+        assert_eq!(tree.trunks(), ["grandparent", "other"]);
+
+        // After re-rooting, a grove root whose line holds the new root is skipped
+        tree.set_root("parent1").unwrap();
+        tree.grove_roots.push("grandparent".to_string());
+        assert_eq!(tree.trunks(), ["parent1", "other"]);
+
+        tree.remove_person("other").unwrap();
+        assert_eq!(tree.trunks(), ["parent1"]);
+
+        let nested = SAMPLE_YAML.replace("root: \"grandparent\"", "root: \"grandparent\"\n  roots: [\"parent2\"]");
+        assert!(FamilyTree::from_yaml(&nested).is_err());
+
+        let missing = SAMPLE_YAML.replace("root: \"grandparent\"", "root: \"grandparent\"\n  roots: [\"ghost\"]");
+        assert!(FamilyTree::from_yaml(&missing).is_err());
+    }
+
+    #[test]
+    fn test_edit_operations() {
+        let mut tree = FamilyTree::from_yaml(SAMPLE_YAML).unwrap();
+
+        tree.add_child("parent2", Person::new("child2", "Child Two")).unwrap();
+        assert_eq!(tree.children_of("parent2").len(), 1);
+        assert!(tree.add_child("parent2", Person::new("child2", "Dup")).is_err());
+        assert!(tree.add_child("nobody", Person::new("x", "X")).is_err());
+
+        let renamed = Person::new("child2", "Renamed");
+        tree.update_person(renamed).unwrap();
+        assert_eq!(tree.get("child2").unwrap().name, "Renamed");
+
+        assert!(tree.remove_person("parent1").is_err());
+        assert!(tree.remove_person("grandparent").is_err());
+        tree.remove_person("child2").unwrap();
+        assert!(tree.children_of("parent2").is_empty());
+        assert_eq!(tree.len(), 4);
+    }
+
+    #[test]
+    fn test_search_substring() {
+        let tree = FamilyTree::from_yaml(SAMPLE_YAML).unwrap();
+        let results = tree.search("PARENT");
+        assert_eq!(results.len(), 3);
+        // Prefix matches rank first
+        assert_eq!(results[0].name, "Parent One");
+    }
+
+    #[test]
+    fn test_search_fuzzy() {
+        let tree = FamilyTree::from_yaml(SAMPLE_YAML).unwrap();
+        let results = tree.search("chld one");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "child1");
+    }
+
+    #[test]
+    fn test_search_localized_names() {
+        let mut tree = FamilyTree::from_yaml(SAMPLE_YAML).unwrap();
+        let mut person = tree.get("child1").unwrap().clone();
+        person.name_localized.insert("el".to_string(), "Παιδί Ένα".to_string());
+        tree.update_person(person).unwrap();
+
+        let results = tree.search("παιδί");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "child1");
+    }
+
+    #[test]
+    fn test_search_no_match() {
+        let tree = FamilyTree::from_yaml(SAMPLE_YAML).unwrap();
+        assert!(tree.search("zebra").is_empty());
+        assert!(tree.search("  ").is_empty());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+
+    #[test]
+    fn test_approximate_dates() {
+        let yaml = r#"
+family:
+  name: "Vague"
+  root: "elder"
+people:
+  - id: "elder"
+    name: "Elder"
+    birth_year: "~1850"
+    death_year: "1910s"
+"#;
+        let tree = FamilyTree::from_yaml(yaml).unwrap();
+        let elder = tree.get("elder").unwrap();
+        assert!(elder.birth_year.unwrap().approximate);
+        assert_eq!(elder.lifespan_string(), "~1850 - 1910s");
+    }
+
+    #[test]
+    fn test_parse_events() {
+        let yaml = r#"
+family:
+  name: "Eventful"
+  root: "elder"
+people:
+  - id: "elder"
+    name: "Elder"
+    birth_year: 1850
+    events:
+      - kind: marriage
+        year: 1875
+        description: "Married in the chapel"
+      - kind: shipwreck
+        year: "~1880"
+"#;
+        let tree = FamilyTree::from_yaml(yaml).unwrap();
+        let events = &tree.get("elder").unwrap().events;
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind, EventKind::Marriage);
+        assert_eq!(events[1].kind, EventKind::Other);
+    }
+
+    #[test]
+    fn test_parse_links() {
+        let yaml = format!("{}\nlinks:\n  - from: child1\n    to: grandparent\n    label: \"named after\"\n", SAMPLE_YAML);
+        let tree = FamilyTree::from_yaml(&yaml).unwrap();
+        assert_eq!(tree.links.len(), 1);
+        assert_eq!(tree.links[0].label, "named after");
+    }
+
+    #[test]
+    fn test_invalid_link_reference() {
+        let yaml = format!("{}\nlinks:\n  - from: child1\n    to: stranger\n", SAMPLE_YAML);
+        let result = FamilyTree::from_yaml(&yaml);
+        assert!(result.unwrap_err().contains("stranger"));
+    }
+
     #[test]
     fn test_invalid_root() {
         let yaml = r#"
@@ -237,4 +1158,54 @@ people:
         let result = FamilyTree::from_yaml(yaml);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_cycle_rejected() {
+        let yaml = r#"
+family:
+  name: "Loop"
+  root: "parent"
+people:
+  - id: "parent"
+    name: "Parent"
+    children: ["child"]
+  - id: "child"
+    name: "Child"
+    children: ["parent"]
+"#;
+        let err = FamilyTree::from_yaml(yaml).unwrap_err();
+        assert_eq!(err, "'parent' is listed as their own descendant");
+    }
+
+    #[test]
+    fn test_ordered_children() {
+        let yaml = r#"
+family:
+  name: "Siblings"
+  root: "parent"
+people:
+  - id: "parent"
+    name: "Parent"
+    children: ["young", "undated", "old", "pinned"]
+  - id: "young"
+    name: "Young"
+    birth_year: "1960-05"
+  - id: "undated"
+    name: "Undated"
+  - id: "old"
+    name: "Old"
+    birth_year: 1955
+  - id: "pinned"
+    name: "Pinned"
+    birth_year: 1970
+    order: 0
+"#;
+        let tree = FamilyTree::from_yaml(yaml).unwrap();
+        let ids = |order| -> Vec<String> {
+            tree.ordered_children_of("parent", order).iter().map(|p| p.id.clone()).collect()
+        };
+
+        assert_eq!(ids(ChildOrder::Chronological), vec!["pinned", "old", "young", "undated"]);
+        assert_eq!(ids(ChildOrder::Listed), vec!["pinned", "young", "undated", "old"]);
+    }
 }
@@ -0,0 +1,140 @@
+use std::collections::VecDeque;
+use super::family_tree::FamilyTree;
+
+/// Bounded snapshot-based undo/redo history of family edits
+#[derive(Debug, Clone)]
+pub struct EditHistory {
+    undo_stack: VecDeque<FamilyTree>,
+    redo_stack: Vec<FamilyTree>,
+    capacity: usize,
+}
+
+impl EditHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            undo_stack: VecDeque::with_capacity(capacity),
+            redo_stack: Vec::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Record the state before an edit (clears the redo stack)
+    pub fn record(&mut self, snapshot: FamilyTree) {
+        if self.undo_stack.len() == self.capacity {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack.push_back(snapshot);
+        self.redo_stack.clear();
+    }
+
+    /// Step back, returning the previous state
+    pub fn undo(&mut self, current: &FamilyTree) -> Option<FamilyTree> {
+        let previous = self.undo_stack.pop_back()?;
+        self.redo_stack.push(current.clone());
+        Some(previous)
+    }
+
+    /// Step forward again, returning the undone state
+    pub fn redo(&mut self, current: &FamilyTree) -> Option<FamilyTree> {
+        let next = self.redo_stack.pop()?;
+        self.undo_stack.push_back(current.clone());
+        Some(next)
+    }
+
+    /// The state `undo` would step back to, leaving the history as is
+    pub fn peek_undo(&self) -> Option<&FamilyTree> {
+        self.undo_stack.back()
+    }
+
+    /// The state `redo` would step forward to, leaving the history as is
+    pub fn peek_redo(&self) -> Option<&FamilyTree> {
+        self.redo_stack.last()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Forget all history (e.g. after loading a new family)
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self::new(50)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Person;
+
+    const YAML: &str = r#"
+family:
+  name: "History"
+  root: "root"
+people:
+  - id: "root"
+    name: "Root"
+"#;
+
+    fn edited(tree: &FamilyTree, id: &str) -> FamilyTree {
+        let mut next = tree.clone();
+        next.add_child("root", Person::new(id, id)).unwrap();
+        next
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut history = EditHistory::default();
+        let v0 = FamilyTree::from_yaml(YAML).unwrap();
+
+        history.record(v0.clone());
+        let v1 = edited(&v0, "a");
+
+        assert_eq!(history.peek_undo().map(FamilyTree::len), Some(1));
+        assert!(!history.can_redo());
+        let undone = history.undo(&v1).unwrap();
+        assert_eq!(undone.len(), 1);
+        assert!(history.can_redo());
+        assert_eq!(history.peek_redo().map(FamilyTree::len), Some(2));
+
+        let redone = history.redo(&undone).unwrap();
+        assert_eq!(redone.len(), 2);
+        assert!(history.can_undo());
+    }
+
+    #[test]
+    fn test_new_edit_clears_redo() {
+        let mut history = EditHistory::default();
+        let v0 = FamilyTree::from_yaml(YAML).unwrap();
+
+        history.record(v0.clone());
+        let v1 = edited(&v0, "a");
+        let back = history.undo(&v1).unwrap();
+
+        history.record(back.clone());
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn test_bounded_capacity() {
+        let mut history = EditHistory::new(2);
+        let tree = FamilyTree::from_yaml(YAML).unwrap();
+        for _ in 0..5 {
+            history.record(tree.clone());
+        }
+
+        assert!(history.undo(&tree).is_some());
+        assert!(history.undo(&tree).is_some());
+        assert!(history.undo(&tree).is_none());
+    }
+}
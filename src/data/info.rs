@@ -0,0 +1,221 @@
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+use super::family_tree::FamilyTree;
+use super::locale::LabelLocale;
+use super::person::{EventKind, Gender, Person, Source, VisualParams};
+
+/// Name shown for living people in privacy mode
+pub const LIVING_LABEL: &str = "Living";
+
+/// Everything the host UI needs to describe one person
+#[derive(Debug, Clone, Serialize)]
+pub struct PersonInfo {
+    pub id: String,
+    /// Name in the active locale
+    pub name: String,
+    pub biography: String,
+    /// Localized lifespan, e.g. "1900 - 1980"
+    pub lifespan: String,
+    pub gender: Option<Gender>,
+    pub placeholder: bool,
+    pub audio_id: Option<String>,
+    pub events: Vec<EventInfo>,
+    pub sources: Vec<Source>,
+    /// Combined confidence of the sources (0.0 to 1.0), `None` without sources
+    pub confidence: Option<f32>,
+    /// Application-defined data with every map key turned into a string
+    pub meta: Value,
+    pub children: Vec<String>,
+    /// First listed parent; `None` for the root and for unattached people
+    pub parent: Option<String>,
+    /// Generations below the root (root = 0)
+    pub generation: Option<usize>,
+    /// Visual parameters derived from the person's data
+    pub visual: VisualParams,
+}
+
+/// A life event as shown to the user
+#[derive(Debug, Clone, Serialize)]
+pub struct EventInfo {
+    pub kind: EventKind,
+    /// Localized date, empty when unknown
+    pub year: String,
+    pub description: String,
+}
+
+impl PersonInfo {
+    pub fn new(tree: &FamilyTree, person: &Person, locale: &LabelLocale) -> Self {
+        let events = person
+            .events
+            .iter()
+            .map(|event| EventInfo {
+                kind: event.kind,
+                year: event.year.map(|y| locale.date(&y)).unwrap_or_default(),
+                description: event.description.clone(),
+            })
+            .collect();
+
+        Self {
+            id: person.id.clone(),
+            name: locale.label(person).into_owned(),
+            biography: person.biography.clone(),
+            lifespan: locale.lifespan(person),
+            gender: person.gender,
+            placeholder: person.placeholder,
+            audio_id: person.audio_id.clone(),
+            events,
+            sources: person.sources.clone(),
+            confidence: person.source_confidence(),
+            meta: string_keys(&Value::Mapping(person.meta.clone())),
+            children: person.children.clone(),
+            parent: tree.parents_of(&person.id).first().map(|p| p.id.clone()),
+            generation: tree.generation_of(&person.id),
+            visual: person.visual_params(),
+        }
+    }
+}
+
+impl PersonInfo {
+    /// Hide the identifying details of a living person for public sharing
+    ///
+    /// Structure (id, children, parent, generation) is kept so the tree still
+    /// makes sense; the branch is dimmed.
+    pub fn redact(&mut self) {
+        self.name = LIVING_LABEL.to_string();
+        self.biography.clear();
+        self.lifespan.clear();
+        self.gender = None;
+        self.audio_id = None;
+        self.events.clear();
+        self.sources.clear();
+        self.confidence = None;
+        self.meta = Value::Mapping(Mapping::new());
+        self.visual.conceal();
+    }
+
+    /// Pick only the named fields, in the order given
+    ///
+    /// Names that are not fields of this struct are passed to `derived`, which
+    /// computes values on request (e.g. descendant counts) or returns `None`
+    /// for unknown names.
+    pub fn select(
+        &self,
+        fields: &[String],
+        mut derived: impl FnMut(&str) -> Option<Value>,
+    ) -> Result<Mapping, String> {
+        let all = serde_yaml::to_value(self).map_err(|e| e.to_string())?;
+        let mut selected = Mapping::new();
+        for name in fields {
+            let value = match all.get(name.as_str()) {
+                Some(value) => value.clone(),
+                None => derived(name).ok_or_else(|| format!("Unknown person info field '{}'", name))?,
+            };
+            selected.insert(Value::String(name.clone()), value);
+        }
+        Ok(selected)
+    }
+}
+
+/// Make a YAML value representable as a plain JS object: non-string keys
+/// are written out as text and tags are dropped
+fn string_keys(value: &Value) -> Value {
+    match value {
+        Value::Sequence(items) => Value::Sequence(items.iter().map(string_keys).collect()),
+        Value::Mapping(map) => Value::Mapping(
+            map.iter()
+                .map(|(k, v)| {
+                    let key = match k {
+                        Value::String(s) => s.clone(),
+                        other => serde_yaml::to_string(other).unwrap_or_default().trim().to_string(),
+                    };
+                    (Value::String(key), string_keys(v))
+                })
+                .collect(),
+        ),
+        Value::Tagged(tagged) => string_keys(&tagged.value),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = r#"
+family:
+  name: "Info"
+  root: "gran"
+people:
+  - id: "gran"
+    name: "Gran"
+    birth_year: 1900
+    children: ["mum"]
+  - id: "mum"
+    name: "Mum"
+    gender: f
+    events:
+      - kind: migration
+        year: 1950
+        description: "Crossed the sea"
+    meta:
+      record: 12
+      1: one
+"#;
+
+    #[test]
+    fn test_person_info_structure() {
+        let tree = FamilyTree::from_yaml(YAML).unwrap();
+        let locale = LabelLocale::default();
+
+        let root = PersonInfo::new(&tree, tree.get("gran").unwrap(), &locale);
+        assert_eq!(root.parent, None);
+        assert_eq!(root.generation, Some(0));
+        assert_eq!(root.children, vec!["mum"]);
+        assert_eq!(root.lifespan, "1900 - present");
+
+        let mum = PersonInfo::new(&tree, tree.get("mum").unwrap(), &locale);
+        assert_eq!(mum.parent.as_deref(), Some("gran"));
+        assert_eq!(mum.generation, Some(1));
+        assert_eq!(mum.gender, Some(Gender::Female));
+        assert_eq!(mum.events[0].year, "1950");
+    }
+
+    #[test]
+    fn test_meta_keys_become_strings() {
+        let tree = FamilyTree::from_yaml(YAML).unwrap();
+        let mum = PersonInfo::new(&tree, tree.get("mum").unwrap(), &LabelLocale::default());
+
+        let meta = mum.meta.as_mapping().unwrap();
+        assert!(meta.keys().all(|k| k.is_string()));
+        assert_eq!(meta.get("1").and_then(|v| v.as_str()), Some("one"));
+    }
+
+    #[test]
+    fn test_select_fields() {
+        let tree = FamilyTree::from_yaml(YAML).unwrap();
+        let info = PersonInfo::new(&tree, tree.get("gran").unwrap(), &LabelLocale::default());
+        let fields: Vec<String> = ["name", "descendant_count"].iter().map(|s| s.to_string()).collect();
+
+        let selected = info
+            .select(&fields, |name| (name == "descendant_count").then(|| Value::from(1)))
+            .unwrap();
+        assert_eq!(selected.len(), 2);
+        assert_eq!(selected.get("name").and_then(|v| v.as_str()), Some("Gran"));
+        assert_eq!(selected.get("descendant_count").and_then(|v| v.as_u64()), Some(1));
+
+        assert!(info.select(&["nope".to_string()], |_| None).is_err());
+    }
+
+    #[test]
+    fn test_redact() {
+        let tree = FamilyTree::from_yaml(YAML).unwrap();
+        let mut info = PersonInfo::new(&tree, tree.get("mum").unwrap(), &LabelLocale::default());
+        let glow = info.visual.glow_intensity;
+        info.redact();
+
+        assert_eq!(info.name, LIVING_LABEL);
+        assert!(info.events.is_empty());
+        assert_eq!(info.parent.as_deref(), Some("gran"));
+        assert!(info.visual.glow_intensity < glow);
+    }
+}
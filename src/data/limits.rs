@@ -0,0 +1,166 @@
+use serde::Deserialize;
+use super::family_tree::FamilyTree;
+use super::person::Person;
+
+/// Size limits applied to uploaded family documents
+///
+/// Untrusted files are rejected with a readable error before they can stall
+/// tree growth or blow up mesh and label buffers.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct InputLimits {
+    /// Raw document size in bytes, checked before parsing
+    pub max_document_bytes: usize,
+    pub max_people: usize,
+    /// Biography length in characters
+    pub max_bio_chars: usize,
+    pub max_children: usize,
+    /// Person id length in bytes
+    pub max_id_len: usize,
+}
+
+impl Default for InputLimits {
+    fn default() -> Self {
+        Self {
+            max_document_bytes: 16 * 1024 * 1024,
+            max_people: 20_000,
+            max_bio_chars: 20_000,
+            max_children: 500,
+            max_id_len: 128,
+        }
+    }
+}
+
+impl InputLimits {
+    pub fn check_document(&self, text: &str) -> Result<(), String> {
+        if text.len() > self.max_document_bytes {
+            return Err(format!(
+                "Document is {} bytes, more than the limit of {}",
+                text.len(),
+                self.max_document_bytes
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn check_people(&self, people: &[Person]) -> Result<(), String> {
+        self.check_count(people.len())?;
+        people.iter().try_for_each(|person| self.check_person(person))
+    }
+
+    /// Check every person of an assembled family (after an edit or merge)
+    pub fn check_family(&self, family: &FamilyTree) -> Result<(), String> {
+        self.check_count(family.people.len())?;
+        family.people.values().try_for_each(|person| self.check_person(person))
+    }
+
+    pub fn check_count(&self, people: usize) -> Result<(), String> {
+        if people > self.max_people {
+            return Err(format!(
+                "Family has {} people, more than the limit of {}",
+                people,
+                self.max_people
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn check_person(&self, person: &Person) -> Result<(), String> {
+        if person.id.is_empty() || person.id.len() > self.max_id_len {
+            return Err(format!(
+                "Person id '{}' must be 1 to {} bytes long",
+                truncated(&person.id, 32),
+                self.max_id_len
+            ));
+        }
+        if person.children.len() > self.max_children {
+            return Err(format!(
+                "'{}' has {} children, more than the limit of {}",
+                person.id,
+                person.children.len(),
+                self.max_children
+            ));
+        }
+        let bio_chars = person.biography.chars().count();
+        if bio_chars > self.max_bio_chars {
+            return Err(format!(
+                "Biography of '{}' is {} characters, more than the limit of {}",
+                person.id, bio_chars, self.max_bio_chars
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn truncated(text: &str, max_chars: usize) -> String {
+    text.chars().take(max_chars).collect()
+}
+
+/// Replace control characters (including line breaks) with spaces
+fn sanitize_line(text: &str) -> String {
+    if !text.chars().any(char::is_control) {
+        return text.to_string();
+    }
+    let cleaned: String = text
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+    cleaned.trim().to_string()
+}
+
+/// Drop control characters other than newlines and tabs
+fn sanitize_text(text: &str) -> String {
+    text.chars()
+        .filter(|&c| !c.is_control() || c == '\n' || c == '\t')
+        .collect()
+}
+
+/// Strip control characters from the text shown in labels and JSON output
+pub fn sanitize_person(person: &mut Person) {
+    person.name = sanitize_line(&person.name);
+    if let Some(surname) = &mut person.surname {
+        *surname = sanitize_line(surname);
+    }
+    for name in person.name_localized.values_mut() {
+        *name = sanitize_line(name);
+    }
+    person.biography = sanitize_text(&person.biography);
+    for event in &mut person.events {
+        event.description = sanitize_text(&event.description);
+    }
+    for source in &mut person.sources {
+        source.title = sanitize_line(&source.title);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_person() {
+        let mut person = Person::new("a", "Ann\u{0}a\nBerg\u{1b}");
+        person.biography = "Line one\nLine\u{7} two\r".to_string();
+        sanitize_person(&mut person);
+
+        assert_eq!(person.name, "Ann a Berg");
+        assert_eq!(person.biography, "Line one\nLine two");
+    }
+
+    #[test]
+    fn test_limits_reject_oversized_input() {
+        let limits = InputLimits { max_bio_chars: 5, max_children: 1, max_id_len: 4, ..Default::default() };
+
+        let mut long_bio = Person::new("a", "A");
+        long_bio.biography = "too long".to_string();
+        assert!(limits.check_people(&[long_bio]).is_err());
+
+        let mut parent = Person::new("p", "P");
+        parent.children = vec!["a".into(), "b".into()];
+        assert!(limits.check_people(&[parent]).is_err());
+
+        assert!(limits.check_people(&[Person::new("toolong", "T")]).is_err());
+        assert!(limits.check_people(&[Person::new("ok", "Fine")]).is_ok());
+        assert!(InputLimits { max_document_bytes: 3, ..Default::default() }.check_document("abcd").is_err());
+    }
+}
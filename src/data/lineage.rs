@@ -0,0 +1,113 @@
+use std::collections::{HashMap, VecDeque};
+use super::family_tree::FamilyTree;
+use super::person::Person;
+
+/// A person's own surname: the explicit `surname`, else the last word of a
+/// multi-word name (lowercased, so "SMITH" and "Smith" are one line)
+pub fn surname_of(person: &Person) -> Option<String> {
+    let surname = match &person.surname {
+        Some(surname) => surname.trim(),
+        None => {
+            let words: Vec<&str> = person.name.split_whitespace().collect();
+            match words.as_slice() {
+                [_, .., last] => *last,
+                _ => return None,
+            }
+        }
+    };
+    (!surname.is_empty()).then(|| surname.to_lowercase())
+}
+
+/// Surname line of every person, carried down from parents to children
+/// who have no surname of their own
+#[derive(Debug, Clone, Default)]
+pub struct SurnameLineage {
+    lines: HashMap<String, String>,
+    /// Distinct surnames in order of first appearance from the root
+    surnames: Vec<String>,
+}
+
+impl SurnameLineage {
+    pub fn compute(tree: &FamilyTree) -> Self {
+        let mut lineage = Self::default();
+        let mut queue = VecDeque::from([(tree.root_id.as_str(), None::<String>)]);
+
+        while let Some((id, inherited)) = queue.pop_front() {
+            let Some(person) = tree.get(id) else { continue };
+            if lineage.lines.contains_key(id) {
+                continue;
+            }
+            let line = surname_of(person).or(inherited);
+            if let Some(line) = &line {
+                if !lineage.surnames.contains(line) {
+                    lineage.surnames.push(line.clone());
+                }
+                lineage.lines.insert(id.to_string(), line.clone());
+            }
+            for child in &person.children {
+                queue.push_back((child.as_str(), line.clone()));
+            }
+        }
+
+        lineage
+    }
+
+    /// The surname line a person belongs to
+    pub fn surname(&self, id: &str) -> Option<&str> {
+        self.lines.get(id).map(String::as_str)
+    }
+
+    /// Position of a person's line among all lines (stable for a given tree)
+    pub fn line_index(&self, id: &str) -> Option<usize> {
+        let surname = self.surname(id)?;
+        self.surnames.iter().position(|s| s == surname)
+    }
+
+    pub fn surnames(&self) -> &[String] {
+        &self.surnames
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = r#"
+family:
+  name: "Lines"
+  root: "john"
+people:
+  - id: "john"
+    name: "John Carter"
+    children: ["mary", "tom"]
+  - id: "mary"
+    name: "Mary Ellis"
+    children: ["ann"]
+  - id: "tom"
+    name: "Tom"
+    children: ["kit"]
+  - id: "ann"
+    name: "Ann Ellis"
+  - id: "kit"
+    name: "Kit"
+    surname: "CARTER"
+"#;
+
+    #[test]
+    fn test_surname_of() {
+        assert_eq!(surname_of(&Person::new("a", "Ada King")).as_deref(), Some("king"));
+        assert_eq!(surname_of(&Person::new("b", "Ada")), None);
+    }
+
+    #[test]
+    fn test_lineage_propagates() {
+        let tree = FamilyTree::from_yaml(YAML).unwrap();
+        let lineage = SurnameLineage::compute(&tree);
+
+        assert_eq!(lineage.surname("tom"), Some("carter"));
+        assert_eq!(lineage.surname("kit"), Some("carter"));
+        assert_eq!(lineage.surname("ann"), Some("ellis"));
+        assert_eq!(lineage.surnames(), ["carter", "ellis"]);
+        assert_eq!(lineage.line_index("mary"), Some(1));
+    }
+}
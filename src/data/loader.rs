@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use serde::Deserialize;
+use super::family_tree::{FamilyTree, PersonLink};
+use super::limits::{self, InputLimits};
+use super::person::Person;
+
+/// One chunk of a streamed family: a bare list of people, or a mapping
+/// with optional `people` and `links`
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Chunk {
+    People(Vec<Person>),
+    Mixed {
+        #[serde(default)]
+        people: Vec<Person>,
+        #[serde(default)]
+        links: Vec<PersonLink>,
+    },
+}
+
+/// Incremental loader for very large families
+///
+/// People arrive in YAML chunks that can be parsed one per frame; references
+/// are only validated in [`FamilyLoader::finish`], so chunks may arrive in any order.
+#[derive(Debug)]
+pub struct FamilyLoader {
+    name: String,
+    root_id: String,
+    people: HashMap<String, Person>,
+    links: Vec<PersonLink>,
+    limits: InputLimits,
+}
+
+impl FamilyLoader {
+    pub fn new(name: &str, root_id: &str) -> Self {
+        Self::with_limits(name, root_id, InputLimits::default())
+    }
+
+    /// Loader that checks each chunk, and the running total, against `limits`
+    pub fn with_limits(name: &str, root_id: &str, limits: InputLimits) -> Self {
+        Self {
+            name: name.to_string(),
+            root_id: root_id.to_string(),
+            people: HashMap::new(),
+            links: Vec::new(),
+            limits,
+        }
+    }
+
+    /// Parse and index one chunk, returning how many people it contained
+    pub fn append_yaml(&mut self, yaml: &str) -> Result<usize, String> {
+        self.limits.check_document(yaml)?;
+        let (people, links) = match serde_yaml::from_str(yaml)
+            .map_err(|e| format!("YAML parse error: {}", e))?
+        {
+            Chunk::People(people) => (people, Vec::new()),
+            Chunk::Mixed { people, links } => (people, links),
+        };
+
+        if let Some(dup) = people.iter().find(|p| self.people.contains_key(&p.id)) {
+            return Err(format!("Duplicate person '{}'", dup.id));
+        }
+
+        self.limits.check_people(&people)?;
+        self.limits.check_count(self.people.len() + people.len())?;
+
+        let count = people.len();
+        for mut person in people {
+            limits::sanitize_person(&mut person);
+            self.people.insert(person.id.clone(), person);
+        }
+        self.links.extend(links);
+        Ok(count)
+    }
+
+    /// Number of people indexed so far
+    pub fn len(&self) -> usize {
+        self.people.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.people.is_empty()
+    }
+
+    /// Validate references and produce the finished tree
+    pub fn finish(self) -> Result<FamilyTree, String> {
+        FamilyTree::from_parts(self.name, self.root_id, self.people, self.links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunked_load() {
+        let mut loader = FamilyLoader::new("Chunked", "root");
+
+        // Children may be referenced before they arrive
+        let first = r#"
+- id: "root"
+  name: "Root"
+  children: ["a", "b"]
+"#;
+        let second = r#"
+people:
+  - id: "a"
+    name: "A"
+  - id: "b"
+    name: "B"
+links:
+  - from: "a"
+    to: "b"
+"#;
+        assert_eq!(loader.append_yaml(first).unwrap(), 1);
+        assert_eq!(loader.append_yaml(second).unwrap(), 2);
+        assert_eq!(loader.len(), 3);
+
+        let tree = loader.finish().unwrap();
+        assert_eq!(tree.children_of("root").len(), 2);
+        assert_eq!(tree.links.len(), 1);
+    }
+
+    #[test]
+    fn test_duplicate_rejected() {
+        let mut loader = FamilyLoader::new("Dup", "root");
+        loader.append_yaml("- id: root\n  name: Root\n").unwrap();
+
+        assert!(loader.append_yaml("- id: root\n  name: Again\n").is_err());
+        assert_eq!(loader.len(), 1);
+    }
+
+    #[test]
+    fn test_missing_reference_fails_on_finish() {
+        let mut loader = FamilyLoader::new("Broken", "root");
+        loader.append_yaml("- id: root\n  name: Root\n  children: [ghost]\n").unwrap();
+
+        assert!(loader.finish().is_err());
+    }
+
+    #[test]
+    fn test_limits_span_chunks() {
+        let limits = InputLimits { max_people: 2, ..Default::default() };
+        let mut loader = FamilyLoader::with_limits("Capped", "root", limits);
+        loader.append_yaml("- id: root\n  name: Root\n- id: a\n  name: A\n").unwrap();
+
+        assert!(loader.append_yaml("- id: b\n  name: B\n").is_err());
+        assert_eq!(loader.len(), 2);
+    }
+}
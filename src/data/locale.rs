@@ -0,0 +1,224 @@
+use std::borrow::Cow;
+use super::date::{DatePrecision, DateValue};
+use super::person::Person;
+
+/// Callback producing a name in another script: `(name, locale_tag) -> name`
+pub type Transliterator = Box<dyn Fn(&str, &str) -> Option<String>>;
+
+/// Active label language for names shown to the viewer
+///
+/// Names are chosen from `name_localized` by BCP 47 fallback
+/// (`sr-Latn-RS` → `sr-Latn` → `sr`), then by the transliteration hook,
+/// then the primary `name`.
+#[derive(Default)]
+pub struct LabelLocale {
+    tag: String,
+    transliterator: Option<Transliterator>,
+}
+
+impl LabelLocale {
+    pub fn new(tag: &str) -> Self {
+        Self {
+            tag: tag.to_string(),
+            transliterator: None,
+        }
+    }
+
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    pub fn set_tag(&mut self, tag: &str) {
+        self.tag = tag.to_string();
+    }
+
+    /// Install (or clear) the transliteration hook
+    pub fn set_transliterator(&mut self, transliterator: Option<Transliterator>) {
+        self.transliterator = transliterator;
+    }
+
+    /// The name to display for a person in this locale
+    pub fn label<'a>(&self, person: &'a Person) -> Cow<'a, str> {
+        if self.tag.is_empty() {
+            return Cow::Borrowed(&person.name);
+        }
+        if let Some(name) = person.localized_name(&self.tag) {
+            return Cow::Borrowed(name);
+        }
+        self.transliterator
+            .as_ref()
+            .and_then(|f| f(&person.name, &self.tag))
+            .map(Cow::Owned)
+            .unwrap_or(Cow::Borrowed(&person.name))
+    }
+
+    /// Lifespan in this locale's conventions ("c. 1850 – 1920", "1850年代 – 現在")
+    ///
+    /// With no locale set this matches [`Person::lifespan_string`].
+    pub fn lifespan(&self, person: &Person) -> String {
+        if self.tag.is_empty() {
+            return person.lifespan_string();
+        }
+        let style = LifespanStyle::for_tag(&self.tag);
+        let date = |d: Option<DateValue>| d.map(|d| style.date(&d));
+        match (date(person.birth_year), date(person.death_year)) {
+            (Some(b), Some(d)) => format!("{} – {}", b, d),
+            (Some(b), None) => format!("{} – {}", b, style.present),
+            (None, Some(d)) => format!("? – {}", d),
+            (None, None) => String::new(),
+        }
+    }
+
+    /// A single date in this locale's conventions
+    pub fn date(&self, date: &DateValue) -> String {
+        if self.tag.is_empty() {
+            return date.to_string();
+        }
+        LifespanStyle::for_tag(&self.tag).date(date)
+    }
+}
+
+/// Per-language conventions for writing dates in a lifespan
+struct LifespanStyle {
+    /// Word for a still-living person's end date
+    present: &'static str,
+    /// Marks an approximate date; `{}` is replaced by the date
+    approximate: &'static str,
+    /// Writes a decade; `{}` is replaced by its first year
+    decade: &'static str,
+    /// Zero digit of the script's numerals (`'0'` for Latin digits)
+    zero: char,
+}
+
+impl LifespanStyle {
+    fn for_tag(tag: &str) -> Self {
+        let language = locale_fallbacks(tag).last().unwrap_or("").to_ascii_lowercase();
+        let (present, approximate, decade, zero) = match language.as_str() {
+            "de" => ("heute", "ca. {}", "{}er", '0'),
+            "fr" => ("aujourd'hui", "vers {}", "années {}", '0'),
+            "es" => ("presente", "c. {}", "década de {}", '0'),
+            "it" => ("oggi", "ca. {}", "anni {}", '0'),
+            "pt" => ("presente", "c. {}", "década de {}", '0'),
+            "nl" => ("heden", "ca. {}", "jaren {}", '0'),
+            "sv" => ("nu", "ca {}", "{}-talet", '0'),
+            "ru" => ("н. в.", "ок. {}", "{}-е", '0'),
+            "uk" => ("тепер", "бл. {}", "{}-ті", '0'),
+            "el" => ("σήμερα", "περ. {}", "δεκαετία του {}", '0'),
+            "ja" => ("現在", "{}年頃", "{}年代", '0'),
+            "zh" => ("至今", "约{}年", "{}年代", '0'),
+            "ko" => ("현재", "{}년경", "{}년대", '0'),
+            "ar" => ("حتى الآن", "حوالي {}", "عقد {}", '\u{0660}'),
+            "fa" => ("تاکنون", "حدود {}", "دهه {}", '\u{06F0}'),
+            "hi" => ("वर्तमान", "लगभग {}", "{} का दशक", '\u{0966}'),
+            "bn" => ("বর্তমান", "আনুমানিক {}", "{}-এর দশক", '\u{09E6}'),
+            _ => ("present", "c. {}", "{}s", '0'),
+        };
+        Self { present, approximate, decade, zero }
+    }
+
+    fn date(&self, date: &DateValue) -> String {
+        let text = match (date.precision, date.month, date.day) {
+            (DatePrecision::Day, Some(m), Some(d)) => format!("{}-{:02}-{:02}", date.year, m, d),
+            (DatePrecision::Month, Some(m), _) => format!("{}-{:02}", date.year, m),
+            (DatePrecision::Decade, _, _) => self.decade.replace("{}", &date.year.to_string()),
+            _ => date.year.to_string(),
+        };
+        let text = if date.approximate {
+            self.approximate.replace("{}", &text)
+        } else {
+            text
+        };
+        self.digits(&text)
+    }
+
+    /// Rewrite ASCII digits into the script's own numerals
+    fn digits(&self, text: &str) -> String {
+        if self.zero == '0' {
+            return text.to_string();
+        }
+        text.chars()
+            .map(|c| match c.to_digit(10) {
+                Some(d) => char::from_u32(self.zero as u32 + d).unwrap_or(c),
+                None => c,
+            })
+            .collect()
+    }
+}
+
+/// Locale tags to try, most specific first (`sr-Latn-RS`, `sr-Latn`, `sr`)
+pub fn locale_fallbacks(tag: &str) -> impl Iterator<Item = &str> {
+    let tag = tag.trim();
+    let ends: Vec<usize> = tag
+        .char_indices()
+        .filter(|&(_, c)| c == '-' || c == '_')
+        .map(|(i, _)| i)
+        .chain(std::iter::once(tag.len()))
+        .collect();
+    ends.into_iter().rev().map(move |end| &tag[..end]).filter(|t| !t.is_empty())
+}
+
+/// Compare locale tags ignoring case and `-`/`_` differences
+pub fn same_locale(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.chars().zip(b.chars()).all(|(x, y)| {
+            let norm = |c: char| if c == '_' { '-' } else { c.to_ascii_lowercase() };
+            norm(x) == norm(y)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn person() -> Person {
+        let mut p = Person::new("p", "Nikola Tesla");
+        p.name_localized = BTreeMap::from([
+            ("sr".to_string(), "Никола Тесла".to_string()),
+            ("sr-Latn".to_string(), "Nikola Tesla (sr)".to_string()),
+        ]);
+        p
+    }
+
+    #[test]
+    fn test_fallback_chain() {
+        let chain: Vec<&str> = locale_fallbacks("sr-Latn-RS").collect();
+        assert_eq!(chain, vec!["sr-Latn-RS", "sr-Latn", "sr"]);
+        assert_eq!(locale_fallbacks("en").collect::<Vec<_>>(), vec!["en"]);
+    }
+
+    #[test]
+    fn test_label_fallback() {
+        let p = person();
+        assert_eq!(LabelLocale::new("sr-Latn-RS").label(&p), "Nikola Tesla (sr)");
+        assert_eq!(LabelLocale::new("SR_cyrl").label(&p), "Никола Тесла");
+        assert_eq!(LabelLocale::new("de").label(&p), "Nikola Tesla");
+        assert_eq!(LabelLocale::default().label(&p), "Nikola Tesla");
+    }
+
+    #[test]
+    fn test_lifespan_formatting() {
+        let p = person().with_dates(Some(DateValue::approximate(1856)), None);
+        assert_eq!(LabelLocale::default().lifespan(&p), "~1856 - present");
+        assert_eq!(LabelLocale::new("en-GB").lifespan(&p), "c. 1856 – present");
+        assert_eq!(LabelLocale::new("ja").lifespan(&p), "1856年頃 – 現在");
+
+        let p = person().with_dates(Some(DateValue::decade(1850)), Some(DateValue::year(1943)));
+        assert_eq!(LabelLocale::new("de").lifespan(&p), "1850er – 1943");
+        assert_eq!(LabelLocale::new("ar").lifespan(&p), "عقد ١٨٥٠ – ١٩٤٣");
+    }
+
+    #[test]
+    fn test_transliteration_hook() {
+        let p = person();
+        let mut locale = LabelLocale::new("ja");
+        locale.set_transliterator(Some(Box::new(|name, tag| {
+            (tag == "ja").then(|| format!("[{}]", name))
+        })));
+        assert_eq!(locale.label(&p), "[Nikola Tesla]");
+
+        // Explicit localized names win over the hook
+        locale.set_tag("sr");
+        assert_eq!(locale.label(&p), "Никола Тесла");
+    }
+}
@@ -0,0 +1,327 @@
+use std::collections::HashMap;
+use super::family_tree::{FamilyTree, PersonLink};
+
+/// What to do when the other tree uses an id that already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Give the incoming person a fresh id (`id-2`, `id-3`, ...); one the
+    /// other tree gives no parent is grown beside the existing person
+    Rename,
+    /// Replace the existing person with the incoming one, keeping both
+    /// children lists so no branch is cut off; an existing person keeps
+    /// their parent rather than gaining a second one
+    Overwrite,
+    /// Fail without changing anything
+    Reject,
+}
+
+impl MergeStrategy {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "rename" => Ok(Self::Rename),
+            "overwrite" => Ok(Self::Overwrite),
+            "reject" => Ok(Self::Reject),
+            other => Err(format!("Unknown merge strategy '{}'", other)),
+        }
+    }
+}
+
+/// Outcome of a merge
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MergeReport {
+    /// Ids of people that were new to this tree
+    pub added: Vec<String>,
+    /// Colliding ids and the ids they were renamed to
+    pub renamed: Vec<(String, String)>,
+    /// Ids of existing people replaced by the other tree's version
+    pub overwritten: Vec<String>,
+}
+
+impl MergeReport {
+    /// Serialize to a JSON object
+    pub fn to_json(&self) -> String {
+        let quote = |ids: &[String]| {
+            ids.iter().map(|id| format!("\"{}\"", crate::escape_json(id))).collect::<Vec<_>>().join(",")
+        };
+        let renamed: Vec<String> = self
+            .renamed
+            .iter()
+            .map(|(from, to)| {
+                format!(
+                    r#"{{"from":"{}","to":"{}"}}"#,
+                    crate::escape_json(from),
+                    crate::escape_json(to)
+                )
+            })
+            .collect();
+
+        format!(
+            r#"{{"added":[{}],"renamed":[{}],"overwritten":[{}]}}"#,
+            quote(&self.added),
+            renamed.join(","),
+            quote(&self.overwritten)
+        )
+    }
+}
+
+/// Merge `other` into `tree`; the root of `tree` is kept
+pub fn merge_into(
+    tree: &mut FamilyTree,
+    other: &FamilyTree,
+    strategy: MergeStrategy,
+) -> Result<MergeReport, String> {
+    let mut collisions: Vec<&String> = other.people.keys().filter(|id| tree.people.contains_key(*id)).collect();
+    collisions.sort();
+
+    if strategy == MergeStrategy::Reject {
+        if let Some(id) = collisions.first() {
+            return Err(format!(
+                "Person '{}' exists in both trees ({} collision(s))",
+                id,
+                collisions.len()
+            ));
+        }
+    }
+
+    let mut report = MergeReport::default();
+
+    // Work out the final id of every incoming person
+    let mut ids: HashMap<&str, String> = HashMap::new();
+    for id in other.people.keys() {
+        ids.insert(id.as_str(), id.clone());
+    }
+    if strategy == MergeStrategy::Rename {
+        for &id in &collisions {
+            let fresh = (2..)
+                .map(|n| format!("{}-{}", id, n))
+                .find(|c| !tree.people.contains_key(c) && !other.people.contains_key(c))
+                .expect("unbounded search");
+            report.renamed.push((id.clone(), fresh.clone()));
+            ids.insert(id.as_str(), fresh);
+        }
+    }
+
+    // Where each colliding person already hangs in this tree
+    let existing_parents: HashMap<String, Vec<String>> = collisions
+        .iter()
+        .map(|id| (id.to_string(), tree.parents_of(id).iter().map(|p| p.id.clone()).collect()))
+        .collect();
+
+    let mut incoming: Vec<&String> = other.people.keys().collect();
+    incoming.sort();
+    for id in incoming {
+        let mut person = other.people[id].clone();
+        person.id = ids[id.as_str()].clone();
+        person.children = person.children.iter().map(|c| ids[c.as_str()].clone()).collect();
+        person.partner = person.partner.map(|p| ids[p.as_str()].clone());
+
+        match tree.people.get_mut(&person.id) {
+            Some(existing) => {
+                let mut children = std::mem::take(&mut existing.children);
+                for child in person.children.drain(..) {
+                    if !children.contains(&child) {
+                        children.push(child);
+                    }
+                }
+                person.children = children;
+                report.overwritten.push(person.id.clone());
+                *existing = person;
+            }
+            None => {
+                if !report.renamed.iter().any(|(_, to)| *to == person.id) {
+                    report.added.push(person.id.clone());
+                }
+                tree.people.insert(person.id.clone(), person);
+            }
+        }
+    }
+
+    match strategy {
+        // A renamed person the other tree gives no parent would float free;
+        // grow them from the same parents as the person they collided with
+        MergeStrategy::Rename => {
+            for (id, fresh) in &report.renamed {
+                if !other.parents_of(id).is_empty() {
+                    continue;
+                }
+                for parent in &existing_parents[id] {
+                    if let Some(parent) = tree.people.get_mut(parent) {
+                        parent.children.push(fresh.clone());
+                    }
+                }
+            }
+        }
+        // An overwritten person keeps the parents they had here, so they
+        // aren't grown a second time under the other tree's parent
+        MergeStrategy::Overwrite => {
+            for (id, parents) in &existing_parents {
+                if parents.is_empty() {
+                    continue;
+                }
+                for person in tree.people.values_mut() {
+                    if !parents.contains(&person.id) {
+                        person.children.retain(|c| c != id);
+                    }
+                }
+            }
+        }
+        MergeStrategy::Reject => {}
+    }
+
+    for link in &other.links {
+        let link = PersonLink {
+            from: ids[link.from.as_str()].clone(),
+            to: ids[link.to.as_str()].clone(),
+            label: link.label.clone(),
+        };
+        let duplicate = tree
+            .links
+            .iter()
+            .any(|l| l.from == link.from && l.to == link.to && l.label == link.label);
+        if !duplicate {
+            tree.links.push(link);
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OURS: &str = r#"
+family:
+  name: "Ours"
+  root: "gran"
+people:
+  - id: "gran"
+    name: "Gran"
+    children: ["mum"]
+  - id: "mum"
+    name: "Mum"
+"#;
+
+    const THEIRS: &str = r#"
+family:
+  name: "Theirs"
+  root: "gran"
+people:
+  - id: "gran"
+    name: "Granny Smith"
+    biography: "Researched by a cousin."
+    children: ["aunt"]
+  - id: "aunt"
+    name: "Aunt"
+links:
+  - from: "aunt"
+    to: "gran"
+    label: "named after"
+"#;
+
+    /// Shares "mum" with OURS, but under a parent OURS doesn't have
+    const COUSINS: &str = r#"
+family:
+  name: "Cousins"
+  root: "grandad"
+people:
+  - id: "grandad"
+    name: "Grandad"
+    children: ["mum"]
+  - id: "mum"
+    name: "Mum"
+    partner: "dad"
+  - id: "dad"
+    name: "Dad"
+"#;
+
+    /// Shares "mum" with OURS, with no parent of its own
+    const ORPHAN: &str = r#"
+family:
+  name: "Orphan"
+  root: "mum"
+people:
+  - id: "mum"
+    name: "Mum (other record)"
+    children: ["baby"]
+  - id: "baby"
+    name: "Baby"
+"#;
+
+    fn trees() -> (FamilyTree, FamilyTree) {
+        (FamilyTree::from_yaml(OURS).unwrap(), FamilyTree::from_yaml(THEIRS).unwrap())
+    }
+
+    #[test]
+    fn test_merge_overwrite_keeps_branches() {
+        let (mut ours, theirs) = trees();
+        let report = merge_into(&mut ours, &theirs, MergeStrategy::Overwrite).unwrap();
+
+        assert_eq!(report.overwritten, vec!["gran"]);
+        assert_eq!(report.added, vec!["aunt"]);
+        let gran = ours.get("gran").unwrap();
+        assert_eq!(gran.name, "Granny Smith");
+        assert_eq!(gran.children, vec!["mum", "aunt"]);
+        assert_eq!(ours.links.len(), 1);
+    }
+
+    #[test]
+    fn test_merge_overwrite_keeps_one_parent() {
+        let (mut ours, _) = trees();
+        let cousins = FamilyTree::from_yaml(COUSINS).unwrap();
+        merge_into(&mut ours, &cousins, MergeStrategy::Overwrite).unwrap();
+
+        let parents: Vec<&str> = ours.parents_of("mum").iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(parents, vec!["gran"]);
+        assert!(ours.get("grandad").unwrap().children.is_empty());
+    }
+
+    #[test]
+    fn test_merge_rename() {
+        let (mut ours, theirs) = trees();
+        let report = merge_into(&mut ours, &theirs, MergeStrategy::Rename).unwrap();
+
+        assert_eq!(report.renamed, vec![("gran".to_string(), "gran-2".to_string())]);
+        assert_eq!(ours.get("gran").unwrap().name, "Gran");
+        assert_eq!(ours.get("gran-2").unwrap().children, vec!["aunt"]);
+        assert_eq!(ours.links[0].to, "gran-2");
+        assert_eq!(ours.len(), 4);
+    }
+
+    #[test]
+    fn test_merge_rename_reconnects() {
+        let (mut ours, _) = trees();
+        let cousins = FamilyTree::from_yaml(COUSINS).unwrap();
+        merge_into(&mut ours, &cousins, MergeStrategy::Rename).unwrap();
+
+        // The other tree's "mum" stays with the parent it came with...
+        let parents: Vec<&str> = ours.parents_of("mum-2").iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(parents, vec!["grandad"]);
+        // ...and its references follow the rename
+        assert_eq!(ours.get("mum-2").unwrap().partner.as_deref(), Some("dad"));
+        assert_eq!(ours.get("mum").unwrap().partner, None);
+
+        // A renamed person without a parent there grows beside the one it collided with
+        let (mut ours, _) = trees();
+        let orphan = FamilyTree::from_yaml(ORPHAN).unwrap();
+        merge_into(&mut ours, &orphan, MergeStrategy::Rename).unwrap();
+        assert_eq!(ours.get("gran").unwrap().children, vec!["mum", "mum-2"]);
+    }
+
+    #[test]
+    fn test_merge_reject() {
+        let (mut ours, theirs) = trees();
+        assert!(merge_into(&mut ours, &theirs, MergeStrategy::Reject).is_err());
+        assert_eq!(ours.len(), 2);
+    }
+
+    #[test]
+    fn test_report_json() {
+        let (mut ours, theirs) = trees();
+        let report = merge_into(&mut ours, &theirs, MergeStrategy::Rename).unwrap();
+        assert_eq!(
+            report.to_json(),
+            r#"{"added":["aunt"],"renamed":[{"from":"gran","to":"gran-2"}],"overwritten":[]}"#
+        );
+    }
+}
@@ -1,5 +1,32 @@
 pub mod person;
 pub mod family_tree;
+pub mod date;
+pub mod history;
+pub mod loader;
+pub mod schema;
+pub mod locale;
+pub mod csv;
+pub mod merge;
+pub mod diff;
+pub mod info;
+pub mod lineage;
+pub mod synthetic;
+pub mod duplicates;
+pub mod limits;
+pub mod builder;
 
-pub use person::{Person, VisualParams};
-pub use family_tree::FamilyTree;
+pub use person::{Person, VisualParams, LifeEvent, EventKind, Gender, GenderStyle, Source};
+pub use family_tree::{FamilyTree, PersonLink, FamilyStatistics, GenerationStats, ChildOrder};
+pub use date::{DateValue, DatePrecision};
+pub use history::EditHistory;
+pub use loader::FamilyLoader;
+pub use schema::SCHEMA_VERSION;
+pub use locale::LabelLocale;
+pub use merge::{MergeStrategy, MergeReport};
+pub use diff::{FamilyDiff, PersonChange};
+pub use info::{PersonInfo, EventInfo, LIVING_LABEL};
+pub use lineage::SurnameLineage;
+pub use synthetic::SyntheticParams;
+pub use duplicates::ValidationWarning;
+pub use limits::InputLimits;
+pub use builder::{FamilyTreeBuilder, BuiltFamily};
@@ -1,16 +1,181 @@
+use std::collections::BTreeMap;
 use serde::{Deserialize, Serialize};
+use super::date::DateValue;
+use super::locale::{locale_fallbacks, same_locale};
 
 /// A person in the family tree
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Person {
     pub id: String,
     pub name: String,
+    /// Family name, when it isn't simply the last word of `name`
+    #[serde(default)]
+    pub surname: Option<String>,
+    /// Name in other languages or scripts, keyed by locale tag (`sr`, `ja-Latn`)
+    #[serde(default)]
+    pub name_localized: BTreeMap<String, String>,
     #[serde(default)]
     pub biography: String,
-    pub birth_year: Option<i32>,
-    pub death_year: Option<i32>,
+    pub birth_year: Option<DateValue>,
+    pub death_year: Option<DateValue>,
     #[serde(default)]
     pub children: Vec<String>,
+    /// Notable life events, shown as nodules along the branch
+    #[serde(default)]
+    pub events: Vec<LifeEvent>,
+    /// Optional gender, only used for visuals when a [`GenderStyle`] is enabled
+    #[serde(default)]
+    pub gender: Option<Gender>,
+    /// Application-defined data (record ids, links, ...) passed through untouched
+    #[serde(default)]
+    pub meta: serde_yaml::Mapping,
+    /// Unconfirmed or stand-in person, drawn as a ghost branch
+    #[serde(default)]
+    pub placeholder: bool,
+    /// Explicit position among siblings (lower first), overriding birth order
+    #[serde(default)]
+    pub order: Option<i32>,
+    /// Narration clip played when the camera lingers near this branch
+    #[serde(default)]
+    pub audio_id: Option<String>,
+    /// Whether the person is alive; inferred from a missing `death_year` if unset
+    #[serde(default)]
+    pub living: Option<bool>,
+    /// Records backing this person's data
+    #[serde(default)]
+    pub sources: Vec<Source>,
+    /// Partner's id; a partner with no parents in the family has no branch of
+    /// their own and twines around this person's branch instead
+    #[serde(default)]
+    pub partner: Option<String>,
+}
+
+/// Gender of a person
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Gender {
+    #[serde(alias = "f")]
+    Female,
+    #[serde(alias = "m")]
+    Male,
+    #[serde(other)]
+    Other,
+}
+
+impl Gender {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Gender::Female => "female",
+            Gender::Male => "male",
+            Gender::Other => "other",
+        }
+    }
+}
+
+/// Opt-in mapping from gender to subtle hue and bark differences,
+/// so maternal and paternal lines can be told apart at a glance
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct GenderStyle {
+    /// Hue (degrees) for women's branches
+    pub female_hue: f32,
+    /// Hue (degrees) for men's branches
+    pub male_hue: f32,
+    /// Hue (degrees) for other genders
+    pub other_hue: f32,
+    /// Per-person hue variation kept around the gender hue (degrees)
+    pub hue_jitter: f32,
+    /// Bark roughness multiplier for women's branches
+    pub female_roughness: f32,
+    /// Bark roughness multiplier for men's branches
+    pub male_roughness: f32,
+}
+
+impl Default for GenderStyle {
+    fn default() -> Self {
+        Self {
+            female_hue: 300.0,
+            male_hue: 60.0,
+            other_hue: 180.0,
+            hue_jitter: 40.0,
+            female_roughness: 0.6,
+            male_roughness: 1.4,
+        }
+    }
+}
+
+impl GenderStyle {
+    /// Adjust a person's visuals; people without a gender are left unchanged
+    pub fn apply(&self, gender: Option<Gender>, visual: &mut VisualParams) {
+        let (hue, roughness) = match gender {
+            Some(Gender::Female) => (self.female_hue, self.female_roughness),
+            Some(Gender::Male) => (self.male_hue, self.male_roughness),
+            Some(Gender::Other) => (self.other_hue, 1.0),
+            None => return,
+        };
+        // Keep a little of the personal hue so siblings still differ
+        let jitter = (visual.hue_shift / 360.0 - 0.5) * self.hue_jitter;
+        visual.hue_shift = (hue + jitter).rem_euclid(360.0);
+        visual.bark_roughness = roughness;
+    }
+}
+
+/// Kind of life event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventKind {
+    Marriage,
+    Migration,
+    Achievement,
+    #[serde(other)]
+    Other,
+}
+
+impl EventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventKind::Marriage => "marriage",
+            EventKind::Migration => "migration",
+            EventKind::Achievement => "achievement",
+            EventKind::Other => "other",
+        }
+    }
+}
+
+/// A dated event in a person's life
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LifeEvent {
+    pub kind: EventKind,
+    pub year: Option<DateValue>,
+    #[serde(default)]
+    pub description: String,
+}
+
+impl LifeEvent {
+    pub fn new(kind: EventKind, year: Option<i32>, description: &str) -> Self {
+        Self {
+            kind,
+            year: year.map(DateValue::year),
+            description: description.to_string(),
+        }
+    }
+}
+
+/// A citation for a person's data (census record, parish register, ...)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Source {
+    pub title: String,
+    #[serde(default)]
+    pub url: Option<String>,
+    /// How much the source is trusted (0.0 to 1.0)
+    #[serde(default = "Source::default_confidence")]
+    pub confidence: f32,
+}
+
+impl Source {
+    fn default_confidence() -> f32 {
+        0.5
+    }
 }
 
 impl Person {
@@ -18,10 +183,21 @@ impl Person {
         Self {
             id: id.to_string(),
             name: name.to_string(),
+            surname: None,
+            name_localized: BTreeMap::new(),
             biography: String::new(),
             birth_year: None,
             death_year: None,
             children: Vec::new(),
+            events: Vec::new(),
+            gender: None,
+            meta: serde_yaml::Mapping::new(),
+            placeholder: false,
+            order: None,
+            audio_id: None,
+            living: None,
+            sources: Vec::new(),
+            partner: None,
         }
     }
 
@@ -36,11 +212,78 @@ impl Person {
     }
 
     pub fn with_years(mut self, birth: Option<i32>, death: Option<i32>) -> Self {
+        self.birth_year = birth.map(DateValue::year);
+        self.death_year = death.map(DateValue::year);
+        self
+    }
+
+    pub fn with_dates(mut self, birth: Option<DateValue>, death: Option<DateValue>) -> Self {
         self.birth_year = birth;
         self.death_year = death;
         self
     }
 
+    pub fn with_gender(mut self, gender: Gender) -> Self {
+        self.gender = Some(gender);
+        self
+    }
+
+    pub fn with_events(mut self, events: Vec<LifeEvent>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Localized name for a locale tag, falling back through its parent tags
+    pub fn localized_name(&self, tag: &str) -> Option<&str> {
+        locale_fallbacks(tag).find_map(|candidate| {
+            self.name_localized
+                .iter()
+                .find(|(key, _)| same_locale(key, candidate))
+                .map(|(_, name)| name.as_str())
+        })
+    }
+
+    /// The primary name followed by all localized names
+    pub fn all_names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.name.as_str()).chain(self.name_localized.values().map(|n| n.as_str()))
+    }
+
+    /// Position of each event along the branch (0.0 = base, 1.0 = tip)
+    ///
+    /// Events are placed by year within the lifespan when possible, otherwise
+    /// they are spread evenly in listed order.
+    pub fn event_positions(&self) -> Vec<f32> {
+        let n = self.events.len();
+        let lifespan = self.birth_year.map(|b| {
+            let end = self.death_year.map(|d| d.year).unwrap_or(b.year + 80);
+            (b.year, (end - b.year).max(1))
+        });
+
+        self.events
+            .iter()
+            .enumerate()
+            .map(|(i, event)| match (event.year, lifespan) {
+                (Some(year), Some((birth, span))) => (year.year - birth) as f32 / span as f32,
+                _ => (i + 1) as f32 / (n + 1) as f32,
+            })
+            .map(|t| t.clamp(0.05, 0.95))
+            .collect()
+    }
+
+    /// Treated as alive (and so kept private in privacy mode)
+    pub fn is_living(&self) -> bool {
+        self.living.unwrap_or(self.death_year.is_none())
+    }
+
+    /// Combined uncertainty of birth and death dates (0.0 = exact, 1.0 = very vague)
+    pub fn date_uncertainty(&self) -> f32 {
+        [self.birth_year, self.death_year]
+            .iter()
+            .flatten()
+            .map(|d| d.uncertainty())
+            .fold(0.0, f32::max)
+    }
+
     /// Calculate biography influence (0.0 to 1.0) based on length
     pub fn biography_influence(&self) -> f32 {
         let len = self.biography.len();
@@ -52,16 +295,61 @@ impl Person {
         1.0 - (-normalized * 2.0).exp()
     }
 
+    /// How well documented the person is (0.0 to 1.0), or `None` without sources
+    ///
+    /// Sources count as independent evidence, so several weak ones add up.
+    pub fn source_confidence(&self) -> Option<f32> {
+        if self.sources.is_empty() {
+            return None;
+        }
+        let doubt: f32 = self.sources.iter().map(|s| 1.0 - s.confidence.clamp(0.0, 1.0)).product();
+        Some(1.0 - doubt)
+    }
+
     /// Generate visual parameters based on person's data
     pub fn visual_params(&self) -> VisualParams {
         let influence = self.biography_influence();
+        let uncertainty = self.date_uncertainty();
+
+        // Uncertain dates soften the glow rather than dimming the branch outright
+        let softness = 1.0 - uncertainty * 0.35;
 
         VisualParams {
-            glow_intensity: 0.2 + influence * 0.8,
-            color_vibrancy: 0.3 + influence * 0.7,
+            glow_intensity: (0.2 + influence * 0.8) * softness,
+            color_vibrancy: (0.3 + influence * 0.7) * softness,
             branch_thickness: 0.5 + influence * 0.5,
             luminance: 0.1 + influence * 0.9,
             hue_shift: (self.id.bytes().fold(0u32, |acc, b| acc.wrapping_add(b as u32)) % 360) as f32,
+            uncertainty,
+            bark_roughness: 1.0,
+            opacity: self.opacity(),
+            age: self.age(),
+            deceased: !self.is_living(),
+        }
+    }
+
+    /// Branch solidity: placeholders are ghosts, speculative people are
+    /// partly see-through and undocumented people stay solid
+    fn opacity(&self) -> f32 {
+        // Opacity of placeholder branches
+        const PLACEHOLDER_OPACITY: f32 = 0.35;
+        // Opacity of a person whose sources carry no confidence at all
+        const SPECULATIVE_OPACITY: f32 = 0.55;
+
+        if self.placeholder {
+            return PLACEHOLDER_OPACITY;
+        }
+        match self.source_confidence() {
+            Some(confidence) => SPECULATIVE_OPACITY + (1.0 - SPECULATIVE_OPACITY) * confidence,
+            None => 1.0,
+        }
+    }
+
+    /// Years lived, when both birth and death years are known
+    pub fn age(&self) -> Option<f32> {
+        match (&self.birth_year, &self.death_year) {
+            (Some(birth), Some(death)) if death.year >= birth.year => Some((death.year - birth.year) as f32),
+            _ => None,
         }
     }
 
@@ -77,7 +365,7 @@ impl Person {
 }
 
 /// Visual parameters derived from person data
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize)]
 pub struct VisualParams {
     /// Glow intensity (0.0 to 1.0)
     pub glow_intensity: f32,
@@ -89,6 +377,25 @@ pub struct VisualParams {
     pub luminance: f32,
     /// Hue rotation in degrees (0 to 360)
     pub hue_shift: f32,
+    /// Date uncertainty (0.0 = exact, 1.0 = very vague), drives a fuzzier glow
+    pub uncertainty: f32,
+    /// Multiplier on bark displacement (1.0 = default texture)
+    pub bark_roughness: f32,
+    /// Coverage (0.0 = invisible, 1.0 = solid); below 1.0 the branch is dithered
+    pub opacity: f32,
+    /// Years lived, if known; old enough and the branch is weathered
+    pub age: Option<f32>,
+    /// No longer living; the branch wears a memorial ring
+    pub deceased: bool,
+}
+
+impl VisualParams {
+    /// Dim the branch of someone whose details are hidden
+    pub fn conceal(&mut self) {
+        self.glow_intensity *= 0.25;
+        self.color_vibrancy *= 0.25;
+        self.luminance *= 0.25;
+    }
 }
 
 impl Default for VisualParams {
@@ -99,6 +406,11 @@ impl Default for VisualParams {
             branch_thickness: 0.7,
             luminance: 0.3,
             hue_shift: 0.0,
+            uncertainty: 0.0,
+            bark_roughness: 1.0,
+            opacity: 1.0,
+            age: None,
+            deceased: false,
         }
     }
 }
@@ -123,7 +435,7 @@ mod tests {
             .with_years(Some(1950), Some(2020));
 
         assert_eq!(person.children.len(), 2);
-        assert_eq!(person.birth_year, Some(1950));
+        assert_eq!(person.birth_year, Some(DateValue::year(1950)));
         assert!(!person.biography.is_empty());
     }
 
@@ -164,6 +476,42 @@ mod tests {
         assert!(long_params.branch_thickness > short_params.branch_thickness);
     }
 
+    #[test]
+    fn test_gender_style() {
+        let style = GenderStyle::default();
+        let woman = Person::new("a", "A").with_gender(Gender::Female);
+        let man = Person::new("b", "B").with_gender(Gender::Male);
+        let unknown = Person::new("c", "C");
+
+        let styled = |p: &Person| {
+            let mut visual = p.visual_params();
+            style.apply(p.gender, &mut visual);
+            visual
+        };
+
+        let (w, m) = (styled(&woman), styled(&man));
+        assert!((w.hue_shift - style.female_hue).abs() <= style.hue_jitter / 2.0);
+        assert!((m.hue_shift - style.male_hue).abs() <= style.hue_jitter / 2.0);
+        assert!(w.bark_roughness < m.bark_roughness);
+        assert_eq!(styled(&unknown).hue_shift, unknown.visual_params().hue_shift);
+    }
+
+    #[test]
+    fn test_meta_passthrough() {
+        let yaml = "id: a\nname: A\nmeta:\n  record_id: 1234\n  sources: [census, parish]\n";
+        let person: Person = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(person.meta.get("record_id").and_then(|v| v.as_u64()), Some(1234));
+
+        let round_trip: Person = serde_yaml::from_str(&serde_yaml::to_string(&person).unwrap()).unwrap();
+        assert_eq!(round_trip.meta, person.meta);
+    }
+
+    #[test]
+    fn test_gender_parsing() {
+        let genders: Vec<Gender> = serde_yaml::from_str("[female, m, nonbinary]").unwrap();
+        assert_eq!(genders, vec![Gender::Female, Gender::Male, Gender::Other]);
+    }
+
     #[test]
     fn test_lifespan_string() {
         let p1 = Person::new("a", "A").with_years(Some(1900), Some(1980));
@@ -171,8 +519,100 @@ mod tests {
 
         let p2 = Person::new("b", "B").with_years(Some(1990), None);
         assert_eq!(p2.lifespan_string(), "1990 - present");
+        assert_eq!(p1.age(), Some(80.0));
+        assert_eq!(p2.age(), None);
 
         let p3 = Person::new("c", "C");
         assert_eq!(p3.lifespan_string(), "");
+
+        let p4 = Person::new("d", "D")
+            .with_dates(Some(DateValue::approximate(1850)), Some(DateValue::decade(1910)));
+        assert_eq!(p4.lifespan_string(), "~1850 - 1910s");
+    }
+
+    #[test]
+    fn test_event_positions_follow_years() {
+        let person = Person::new("a", "A")
+            .with_years(Some(1900), Some(2000))
+            .with_events(vec![
+                LifeEvent::new(EventKind::Marriage, Some(1925), "Married"),
+                LifeEvent::new(EventKind::Migration, Some(1975), "Moved west"),
+            ]);
+
+        let positions = person.event_positions();
+        assert!((positions[0] - 0.25).abs() < 0.001);
+        assert!((positions[1] - 0.75).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_event_positions_without_dates() {
+        let person = Person::new("a", "A").with_events(vec![
+            LifeEvent::new(EventKind::Achievement, None, ""),
+            LifeEvent::new(EventKind::Other, None, ""),
+        ]);
+
+        let positions = person.event_positions();
+        assert!(positions[0] < positions[1]);
+        assert!(positions.iter().all(|t| (0.0..=1.0).contains(t)));
+    }
+
+    #[test]
+    fn test_uncertain_dates_soften_glow() {
+        let exact = Person::new("a", "A").with_years(Some(1850), Some(1910));
+        let vague = Person::new("a", "A")
+            .with_dates(Some(DateValue::decade(1850)), Some(DateValue::approximate(1910)));
+
+        assert_eq!(exact.visual_params().uncertainty, 0.0);
+        assert!(vague.visual_params().uncertainty > 0.0);
+        assert!(vague.visual_params().glow_intensity < exact.visual_params().glow_intensity);
+    }
+
+    #[test]
+    fn test_placeholder_is_ghosted() {
+        let yaml = "id: a\nname: A\nplaceholder: true\n";
+        let ghost: Person = serde_yaml::from_str(yaml).unwrap();
+
+        assert!(ghost.placeholder);
+        assert!(ghost.visual_params().opacity < 1.0);
+        assert_eq!(Person::new("b", "B").visual_params().opacity, 1.0);
+    }
+
+    #[test]
+    fn test_living_inferred_from_death_year() {
+        assert!(Person::new("a", "A").with_years(Some(1990), None).is_living());
+        assert!(!Person::new("b", "B").with_years(Some(1900), Some(1980)).is_living());
+
+        let mut recorded = Person::new("c", "C").with_years(Some(1850), None);
+        recorded.living = Some(false);
+        assert!(!recorded.is_living());
+        assert!(recorded.visual_params().deceased);
+    }
+
+    #[test]
+    fn test_sources_modulate_solidity() {
+        let yaml = r#"
+id: a
+name: A
+sources:
+  - title: "1881 census"
+    url: "https://example.org/census"
+    confidence: 0.9
+  - title: "Family letter"
+"#;
+        let documented: Person = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(documented.sources[1].confidence, 0.5);
+        assert!((documented.source_confidence().unwrap() - 0.95).abs() < 1e-5);
+
+        let mut speculative = Person::new("b", "B");
+        speculative.sources.push(Source {
+            title: "Hearsay".to_string(),
+            url: None,
+            confidence: 0.1,
+        });
+
+        let solid = documented.visual_params().opacity;
+        assert!(speculative.visual_params().opacity < solid);
+        assert_eq!(Person::new("c", "C").source_confidence(), None);
+        assert_eq!(Person::new("c", "C").visual_params().opacity, 1.0);
     }
 }
@@ -0,0 +1,113 @@
+use serde_yaml::{Mapping, Value};
+use super::family_tree::FamilyInput;
+
+/// Version of the YAML family format written by this build
+///
+/// Files without a `version:` key are treated as version 1.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Upgrades a raw document from version `n + 1` to `n + 2` (index `n`)
+pub type Migration = fn(&mut Mapping) -> Result<(), String>;
+
+/// Registered migrations, one per format change
+///
+/// When the format changes, bump `SCHEMA_VERSION` and append the step that
+/// rewrites the previous layout, so older files keep loading.
+const MIGRATIONS: &[Migration] = &[];
+
+/// Parse a family document, migrating older versions to the current layout
+pub fn parse_family_input(yaml: &str) -> Result<FamilyInput, String> {
+    let mut value: Value = serde_yaml::from_str(yaml)
+        .map_err(|e| format!("YAML parse error: {}", e))?;
+
+    if let Value::Mapping(doc) = &mut value {
+        migrate(doc, MIGRATIONS, SCHEMA_VERSION)?;
+    }
+
+    serde_yaml::from_value(value).map_err(|e| format!("YAML parse error: {}", e))
+}
+
+/// Read the document version and apply each migration step up to `target`
+fn migrate(doc: &mut Mapping, migrations: &[Migration], target: u32) -> Result<(), String> {
+    let version = match doc.get("version") {
+        None => 1,
+        Some(v) => v
+            .as_u64()
+            .filter(|&v| v >= 1)
+            .map(|v| v as u32)
+            .ok_or_else(|| format!("Invalid schema version: {:?}", v))?,
+    };
+
+    if version > target {
+        return Err(format!(
+            "File uses schema version {}, but this build only supports up to {}",
+            version, target
+        ));
+    }
+
+    for step in &migrations[(version - 1) as usize..(target - 1) as usize] {
+        step(doc)?;
+    }
+
+    doc.insert(Value::from("version"), Value::from(target));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNVERSIONED: &str = r#"
+family:
+  name: "Old"
+  root: "root"
+people:
+  - id: "root"
+    name: "Root"
+"#;
+
+    #[test]
+    fn test_unversioned_loads() {
+        let input = parse_family_input(UNVERSIONED).unwrap();
+        assert_eq!(input.version, SCHEMA_VERSION);
+        assert_eq!(input.people.len(), 1);
+    }
+
+    #[test]
+    fn test_future_version_rejected() {
+        let yaml = format!("version: {}\n{}", SCHEMA_VERSION + 1, UNVERSIONED);
+        let err = parse_family_input(&yaml).unwrap_err();
+        assert!(err.contains("schema version"));
+    }
+
+    #[test]
+    fn test_migrations_applied_in_order() {
+        fn rename_people(doc: &mut Mapping) -> Result<(), String> {
+            let people = doc.remove("persons").ok_or("missing persons")?;
+            doc.insert(Value::from("people"), people);
+            Ok(())
+        }
+        fn default_name(doc: &mut Mapping) -> Result<(), String> {
+            if let Some(Value::Mapping(family)) = doc.get_mut("family") {
+                family.entry(Value::from("name")).or_insert(Value::from("Unnamed"));
+            }
+            Ok(())
+        }
+
+        let yaml = r#"
+family:
+  root: "root"
+persons:
+  - id: "root"
+    name: "Root"
+"#;
+        let mut value: Value = serde_yaml::from_str(yaml).unwrap();
+        let doc = value.as_mapping_mut().unwrap();
+        migrate(doc, &[rename_people, default_name], 3).unwrap();
+
+        let input: FamilyInput = serde_yaml::from_value(value).unwrap();
+        assert_eq!(input.version, 3);
+        assert_eq!(input.family.name, "Unnamed");
+        assert_eq!(input.people.len(), 1);
+    }
+}
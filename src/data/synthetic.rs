@@ -0,0 +1,292 @@
+//! Procedurally generated families for benchmarks, fuzzing and demos
+//!
+//! The same [`SyntheticParams`] (including the seed) always produce the same
+//! family, so generated trees can stand in for hand-written fixtures.
+
+use std::collections::{HashMap, VecDeque};
+use serde::Deserialize;
+use super::date::{current_year, DateValue};
+use super::family_tree::FamilyTree;
+use super::limits::InputLimits;
+use super::person::Person;
+
+const GIVEN_NAMES: &[&str] = &[
+    "Ada", "Anna", "Arthur", "Clara", "Edith", "Elias", "Ella", "Felix", "Grace", "Hugo",
+    "Ida", "Jonas", "Lena", "Leo", "Maria", "Nora", "Oscar", "Rosa", "Samuel", "Vera",
+];
+
+const SURNAMES: &[&str] = &[
+    "Almqvist", "Berg", "Carter", "Ellis", "Fischer", "Hale", "Lind", "Moreau", "Novak", "Reyes",
+];
+
+const BIO_WORDS: &[&str] = &[
+    "worked", "as", "a", "farmer", "teacher", "sailor", "weaver", "in", "the", "village",
+    "moved", "to", "city", "married", "raised", "family", "near", "river", "was", "known",
+    "for", "music", "stories", "and", "kindness", "during", "hard", "winters", "later", "years",
+];
+
+/// Shape of a generated family
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SyntheticParams {
+    pub seed: u32,
+    /// Upper bound on the number of people
+    pub max_people: usize,
+    /// Generations below the root
+    pub max_depth: usize,
+    /// Children per person are drawn uniformly from this range
+    pub min_children: usize,
+    pub max_children: usize,
+    /// Biography lengths in characters lie in this range
+    pub min_bio_chars: usize,
+    pub max_bio_chars: usize,
+    /// Shape of the length distribution: 1.0 is uniform, larger values favour
+    /// short biographies with a long tail of long ones
+    pub bio_skew: f32,
+    /// Birth year of the root person
+    pub first_year: i32,
+    /// Average years between a parent's and a child's birth
+    pub generation_years: i32,
+    /// Year people still alive are alive in: anyone whose drawn lifespan
+    /// reaches past it gets no death year (None = this year)
+    pub present_year: Option<i32>,
+}
+
+impl Default for SyntheticParams {
+    fn default() -> Self {
+        Self {
+            seed: 1,
+            max_people: 60,
+            max_depth: 5,
+            min_children: 1,
+            max_children: 3,
+            min_bio_chars: 0,
+            max_bio_chars: 800,
+            bio_skew: 2.0,
+            first_year: 1850,
+            generation_years: 28,
+            present_year: None,
+        }
+    }
+}
+
+/// Earliest and latest years a generated family may span
+const YEAR_RANGE: (i32, i32) = (-10_000, 10_000);
+
+impl SyntheticParams {
+    /// Check the params stay within `limits`, as an uploaded family would,
+    /// and keep their years within a sensible range
+    pub fn validate(&self, limits: &InputLimits) -> Result<(), String> {
+        let counts = [
+            ("max_people", self.max_people, limits.max_people),
+            ("max_children", self.max_children, limits.max_children),
+            ("max_bio_chars", self.max_bio_chars, limits.max_bio_chars),
+            // A chain of single children is as deep as the family is large
+            ("max_depth", self.max_depth, limits.max_people),
+        ];
+        for (name, value, max) in counts {
+            if value > max {
+                return Err(format!("Synthetic param '{}' must be at most {} (got {})", name, max, value));
+            }
+        }
+        let (earliest, latest) = YEAR_RANGE;
+        let years = [("first_year", self.first_year), ("present_year", self.present_year.unwrap_or(earliest))];
+        for (name, year) in years {
+            if !(earliest..=latest).contains(&year) {
+                return Err(format!("Synthetic param '{}' must be between {} and {} (got {})", name, earliest, latest, year));
+            }
+        }
+        if !(1..=100).contains(&self.generation_years) {
+            return Err(format!(
+                "Synthetic param 'generation_years' must be between 1 and 100 (got {})",
+                self.generation_years
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Small deterministic generator (xorshift32); plenty for test data
+struct Rng(u32);
+
+impl Rng {
+    fn new(seed: u32) -> Self {
+        // Zero is a fixed point of xorshift
+        Self(seed.wrapping_mul(0x9E37_79B9) | 1)
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        x
+    }
+
+    /// Uniform in 0.0..1.0
+    fn unit(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    /// Uniform in `min..=max`
+    fn range(&mut self, min: usize, max: usize) -> usize {
+        if max <= min {
+            return min;
+        }
+        min + (self.next_u32() as usize) % (max - min + 1)
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.range(0, items.len() - 1)]
+    }
+}
+
+/// Generate a family; the root is `p0` and ids follow breadth-first order
+pub fn generate(params: &SyntheticParams) -> FamilyTree {
+    let mut rng = Rng::new(params.seed);
+    let max_people = params.max_people.max(1);
+    let present_year = params.present_year.unwrap_or_else(current_year);
+    let surname = rng.pick(SURNAMES);
+
+    let mut people: HashMap<String, Person> = HashMap::new();
+    let root = new_person(&mut rng, params, 0, surname, params.first_year, present_year);
+    let mut queue = VecDeque::from([(root.id.clone(), 0usize)]);
+    people.insert(root.id.clone(), root);
+
+    while let Some((parent_id, depth)) = queue.pop_front() {
+        if depth >= params.max_depth {
+            continue;
+        }
+        let parent_birth = people[&parent_id].birth_year.map(|d| d.year).unwrap_or(params.first_year);
+        let parent_surname = people[&parent_id].surname.clone().unwrap_or_default();
+
+        let count = rng.range(params.min_children, params.max_children.max(params.min_children));
+        for _ in 0..count {
+            if people.len() >= max_people {
+                break;
+            }
+            let spread = rng.range(0, 16) as i32 - 8;
+            let birth = parent_birth + params.generation_years + spread;
+            let child = new_person(&mut rng, params, people.len(), &parent_surname, birth, present_year);
+
+            people.get_mut(&parent_id).expect("parent exists").children.push(child.id.clone());
+            queue.push_back((child.id.clone(), depth + 1));
+            people.insert(child.id.clone(), child);
+        }
+    }
+
+    let name = format!("{} family (synthetic #{})", surname, params.seed);
+    FamilyTree::from_parts(name, "p0".to_string(), people, Vec::new())
+        .expect("generated family is consistent")
+}
+
+fn new_person(rng: &mut Rng, params: &SyntheticParams, index: usize, surname: &str, birth: i32, present_year: i32) -> Person {
+    let given = rng.pick(GIVEN_NAMES);
+    let mut person = Person::new(&format!("p{}", index), &format!("{} {}", given, surname));
+    person.surname = Some(surname.to_string());
+    person.biography = biography(rng, params);
+
+    let lifespan = rng.range(45, 95) as i32;
+    person.birth_year = Some(DateValue::year(birth));
+    // People who would still be alive today get no death year
+    if birth + lifespan <= present_year {
+        person.death_year = Some(DateValue::year(birth + lifespan));
+    }
+    person
+}
+
+fn biography(rng: &mut Rng, params: &SyntheticParams) -> String {
+    let min = params.min_bio_chars;
+    let max = params.max_bio_chars.max(min);
+    let t = rng.unit().powf(params.bio_skew.max(0.01));
+    let target = min + ((max - min) as f32 * t) as usize;
+
+    let mut bio = String::with_capacity(target + 16);
+    while bio.len() < target {
+        if !bio.is_empty() {
+            bio.push(' ');
+        }
+        bio.push_str(rng.pick(BIO_WORDS));
+    }
+    bio.truncate(target);
+    bio.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deterministic() {
+        let params = SyntheticParams::default();
+        let a = generate(&params);
+        let b = generate(&params);
+        assert_eq!(a.len(), b.len());
+        assert!(a.diff(&b).is_empty());
+
+        let other = generate(&SyntheticParams { seed: 2, ..params });
+        assert!(!a.diff(&other).is_empty());
+    }
+
+    #[test]
+    fn test_respects_size_and_depth() {
+        let params = SyntheticParams {
+            max_people: 500,
+            max_depth: 3,
+            min_children: 2,
+            max_children: 2,
+            ..Default::default()
+        };
+        let family = generate(&params);
+
+        // Full binary tree of depth 3
+        assert_eq!(family.len(), 15);
+        assert_eq!(family.max_depth(), 4);
+
+        let capped = generate(&SyntheticParams { max_people: 10, ..params });
+        assert_eq!(capped.len(), 10);
+    }
+
+    #[test]
+    fn test_biography_lengths() {
+        let params = SyntheticParams {
+            min_bio_chars: 20,
+            max_bio_chars: 40,
+            ..Default::default()
+        };
+        let family = generate(&params);
+        for person in family.people.values() {
+            let len = person.biography.len();
+            assert!(len <= 40, "biography too long: {}", len);
+            assert!(len >= 18, "biography too short: {}", len);
+        }
+    }
+
+    #[test]
+    fn test_living_by_present_year() {
+        let params = SyntheticParams { first_year: 1950, ..Default::default() };
+        let size = generate(&params).len();
+        let living = |present_year| {
+            let family = generate(&SyntheticParams { present_year: Some(present_year), ..params });
+            family.people.values().filter(|p| p.death_year.is_none()).count()
+        };
+        // Everyone is alive at the root's birth, and nobody centuries later
+        assert_eq!(living(1950), size);
+        assert_eq!(living(2400), 0);
+        assert!(living(current_year()) < size);
+    }
+
+    #[test]
+    fn test_validate_caps_inputs() {
+        let limits = InputLimits::default();
+        assert!(SyntheticParams::default().validate(&limits).is_ok());
+
+        let too_many = SyntheticParams { max_people: limits.max_people + 1, ..Default::default() };
+        assert!(too_many.validate(&limits).unwrap_err().contains("max_people"));
+        let too_late = SyntheticParams { first_year: i32::MAX - 10, ..Default::default() };
+        assert!(too_late.validate(&limits).unwrap_err().contains("first_year"));
+        let no_gap = SyntheticParams { generation_years: 0, ..Default::default() };
+        assert!(no_gap.validate(&limits).is_err());
+    }
+}
@@ -1,8 +1,20 @@
-use crate::data::{FamilyTree, Person, VisualParams};
-use crate::math::Vec3;
+use crate::data::{ChildOrder, FamilyTree, GenderStyle, Person, SurnameLineage, VisualParams};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use crate::math::{CatmullRomSpline, Vec3};
+use crate::math::spline::hermite_curve;
+use super::foliage::FoliageCluster;
+use super::lsystem::{LSystem, LSystemPreset};
+use super::error::GrowthError;
+use super::rng::{avalanche, GrowthRng, StringHash};
 
 /// Parameters controlling tree growth appearance
-#[derive(Debug, Clone, Copy)]
+///
+/// Can be read from YAML; missing keys keep their defaults and angles are in
+/// radians. A family file's `growth:` section only reaches the layout and
+/// shape keys (see [`GrowthParams::merge_yaml`]).
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
 pub struct GrowthParams {
     /// Base height for trunk/initial segment
     pub base_height: f32,
@@ -10,7 +22,8 @@ pub struct GrowthParams {
     pub height_decay: f32,
     /// Base radius for trunk
     pub base_radius: f32,
-    /// Radius reduction per generation (multiplier)
+    /// Radius reduction per generation (multiplier); with `pipe_model`, only
+    /// the taper of childless tips
     pub radius_decay: f32,
     /// Angle spread for binary splits (radians)
     pub branch_spread: f32,
@@ -20,6 +33,242 @@ pub struct GrowthParams {
     pub curvature: f32,
     /// Vertical tendency (0.0 = horizontal, 1.0 = vertical)
     pub verticality: f32,
+    /// Optional gender-based hue and bark differences (off by default)
+    pub gender_style: Option<GenderStyle>,
+    /// Left-to-right arrangement of siblings
+    pub child_order: ChildOrder,
+    /// Dim the branches of living people
+    pub privacy_mode: bool,
+    /// How branch hues are chosen
+    pub hue_mode: HueMode,
+    /// Overall arrangement of the generations
+    pub layout: LayoutMode,
+    /// Angular width of the fan in [`LayoutMode::Radial`] (radians)
+    pub fan_angle: f32,
+    /// Crown attractor points per person in [`LayoutMode::Colonized`]
+    pub attractors_per_person: usize,
+    /// Silhouette grammar for [`LayoutMode::LSystem`]
+    pub lsystem: LSystemPreset,
+    /// How much siblings with many descendants widen their share of the fan:
+    /// each sibling is weighted by `(1 + descendants) ^ descendant_weight`
+    /// (0 = even spacing)
+    pub descendant_weight: f32,
+    /// Grow the root person's ancestors as roots below the trunk
+    pub root_system: bool,
+    /// Vertical squash of the root system relative to the crown
+    pub root_scale: f32,
+    /// Direction toward the light (e.g. the sun or the camera)
+    pub light_direction: Vec3,
+    /// How strongly each branch bends toward `light_direction` (0 = not at all)
+    pub phototropism: f32,
+    /// Horizontal direction the prevailing wind blows toward
+    pub wind_direction: Vec3,
+    /// Lateral shear from the wind, growing with each generation (0 = calm)
+    pub wind_strength: f32,
+    /// Place each child by a hash of its own id and its parent's, so adding
+    /// or removing a sibling leaves the rest of the tree where it was
+    ///
+    /// Trades the sibling packing (which guarantees branches don't overlap)
+    /// for layouts that stay put between edits and loads.
+    pub stable_placement: bool,
+    /// Generations to grow (the trunk is the first); deeper lines end in
+    /// buds that can be expanded one generation at a time
+    pub max_generations: Option<usize>,
+    /// Leaf clusters per childless tip (0 = bare)
+    pub foliage_density: f32,
+    /// Cluster radius as a fraction of the tip branch's length
+    pub foliage_size: f32,
+    /// Height of one generation band: each branch ends exactly on its
+    /// generation's band, so height reads like a chart (None = free growth)
+    pub generation_height: Option<f32>,
+    /// Size branches by the pipe model: every person adds the same
+    /// cross-section, so a branch is as thick as all the lines it carries
+    pub pipe_model: bool,
+    /// Swing lopsided lineages around their parents so the crown stays
+    /// centered over the trunk
+    pub balance_crown: bool,
+    /// Trunk tilt away from vertical (radians); the crown moves with the
+    /// trunk's end but keeps growing upward
+    pub trunk_lean: f32,
+    /// Compass heading the trunk leans and sways toward (radians around up)
+    pub trunk_heading: f32,
+    /// Sideways S-curve of the trunk as a fraction of its length (0 = straight)
+    pub trunk_sway: f32,
+    /// Half-waves in the trunk's sway (1 = a bow, 2 = an S, ...)
+    pub trunk_bends: f32,
+    /// How siblings are placed around their parent
+    pub child_arrangement: ChildArrangement,
+    /// Turn between successive children in a phyllotaxis spiral (radians)
+    pub divergence_angle: f32,
+    /// Distance along the parent between successive children in a
+    /// phyllotaxis spiral (world units; at most half the parent is climbed)
+    pub child_pitch: f32,
+    /// Most branches a single tree may grow before giving up
+    pub max_branches: usize,
+    /// Distance between neighbouring trunks when several founding
+    /// ancestors are grown as a grove
+    pub grove_spacing: f32,
+}
+
+/// Overall shape the family is laid out in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LayoutMode {
+    /// Organic tree with upward-growing branches
+    #[default]
+    #[serde(alias = "tree")]
+    Botanical,
+    /// Fan chart: each generation on a ring around the root
+    #[serde(alias = "fan")]
+    Radial,
+    /// Branches routed toward a cloud of attractors filling the crown
+    #[serde(alias = "space_colonization")]
+    Colonized,
+    /// Branches shaped by a per-generation turtle grammar (see [`LSystem`])
+    #[serde(alias = "l-system")]
+    LSystem,
+}
+
+impl LayoutMode {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "botanical" | "tree" => Ok(Self::Botanical),
+            "radial" | "fan" => Ok(Self::Radial),
+            "colonized" | "space_colonization" => Ok(Self::Colonized),
+            "lsystem" | "l-system" => Ok(Self::LSystem),
+            other => Err(format!("Unknown layout mode '{}'", other)),
+        }
+    }
+}
+
+/// Strategy for assigning branch hues
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HueMode {
+    /// Each person gets their own hue
+    #[default]
+    Personal,
+    /// Hue families follow surname lines, so name continuity is visible
+    Surname,
+}
+
+impl HueMode {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "personal" => Ok(Self::Personal),
+            "surname" => Ok(Self::Surname),
+            other => Err(format!("Unknown hue mode '{}'", other)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Personal => "personal",
+            Self::Surname => "surname",
+        }
+    }
+}
+
+/// How a branch with many children places them (botanical layout)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChildArrangement {
+    /// A weighted fan, widening to a ring when crowded
+    #[default]
+    Fan,
+    /// A spiral up the parent's branch: each child sprouts `child_pitch`
+    /// above the last and turns `divergence_angle` around it
+    #[serde(alias = "spiral")]
+    Phyllotaxis,
+}
+
+impl ChildArrangement {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "fan" => Ok(Self::Fan),
+            "phyllotaxis" | "spiral" => Ok(Self::Phyllotaxis),
+            other => Err(format!("Unknown child arrangement '{}'", other)),
+        }
+    }
+}
+
+impl GrowthParams {
+    /// Names accepted by [`GrowthParams::preset`]
+    pub const PRESETS: &'static [&'static str] =
+        &["default", "oak", "willow", "bonsai", "cypress", "baobab", "windswept"];
+
+    /// A curated growth style by name (see [`GrowthParams::PRESETS`])
+    pub fn preset(name: &str) -> Result<Self, String> {
+        use std::f32::consts::PI;
+        let defaults = Self::default();
+        let params = match name.trim().to_ascii_lowercase().as_str() {
+            "default" => defaults,
+            // Broad and sturdy, spreading wide
+            "oak" => Self {
+                base_radius: 0.38,
+                radius_decay: 0.72,
+                height_decay: 0.72,
+                branch_spread: PI / 3.0,
+                curvature: 0.35,
+                verticality: 0.45,
+                ..defaults
+            },
+            "willow" => Self {
+                layout: LayoutMode::LSystem,
+                lsystem: LSystemPreset::Willow,
+                base_radius: 0.32,
+                ..defaults
+            },
+            // Small, gnarled and windblown
+            "bonsai" => Self {
+                base_height: 1.6,
+                height_decay: 0.65,
+                base_radius: 0.4,
+                radius_decay: 0.6,
+                branch_spread: PI / 2.6,
+                angle_variance: 0.35,
+                curvature: 0.6,
+                verticality: 0.25,
+                wind_strength: 0.2,
+                ..defaults
+            },
+            // Tall and narrow
+            "cypress" => Self {
+                base_height: 3.5,
+                height_decay: 0.85,
+                base_radius: 0.25,
+                radius_decay: 0.75,
+                branch_spread: PI / 12.0,
+                verticality: 0.9,
+                ..defaults
+            },
+            // Massive trunk under a flat crown
+            "baobab" => Self {
+                base_height: 2.2,
+                height_decay: 0.6,
+                base_radius: 0.8,
+                radius_decay: 0.5,
+                branch_spread: PI / 2.2,
+                verticality: 0.3,
+                ..defaults
+            },
+            "windswept" | "wind-swept" => Self::wind_swept(Vec3::RIGHT),
+            other => return Err(format!("Unknown growth style '{}'", other)),
+        };
+        Ok(params)
+    }
+
+    /// Coastal wind-swept style: a low, spreading crown sheared toward `direction`
+    pub fn wind_swept(direction: Vec3) -> Self {
+        Self {
+            wind_direction: direction,
+            wind_strength: 0.55,
+            verticality: 0.45,
+            height_decay: 0.7,
+            branch_spread: std::f32::consts::PI / 5.0,
+            ..Default::default()
+        }
+    }
 }
 
 impl Default for GrowthParams {
@@ -33,6 +282,37 @@ impl Default for GrowthParams {
             angle_variance: 0.1,
             curvature: 0.3,
             verticality: 0.6,
+            gender_style: None,
+            child_order: ChildOrder::default(),
+            privacy_mode: false,
+            hue_mode: HueMode::default(),
+            layout: LayoutMode::default(),
+            fan_angle: std::f32::consts::PI,
+            attractors_per_person: 12,
+            lsystem: LSystemPreset::default(),
+            descendant_weight: 0.5,
+            root_system: false,
+            root_scale: 0.6,
+            light_direction: Vec3::UP,
+            phototropism: 0.0,
+            wind_direction: Vec3::RIGHT,
+            wind_strength: 0.0,
+            stable_placement: false,
+            max_generations: None,
+            foliage_density: 3.0,
+            foliage_size: 0.35,
+            generation_height: None,
+            pipe_model: true,
+            balance_crown: false,
+            trunk_lean: 0.0,
+            trunk_heading: 0.0,
+            trunk_sway: 0.0,
+            trunk_bends: 2.0,
+            child_arrangement: ChildArrangement::default(),
+            divergence_angle: 137.508f32.to_radians(),
+            child_pitch: 0.15,
+            max_branches: 100_000,
+            grove_spacing: 8.0,
         }
     }
 }
@@ -58,19 +338,148 @@ pub struct BranchNode {
     pub end_radius: f32,
     /// Generation (depth from root, 0 = trunk)
     pub generation: usize,
-    /// Child branch nodes
+    /// Positions of life events along the branch (0.0 = start, 1.0 = end)
+    pub event_positions: Vec<f32>,
+    /// Child branch nodes (none within a root)
     pub children: Vec<BranchNode>,
+    /// Ancestor branches: at the trunk, its parents growing down from the
+    /// base; within a root, that ancestor's own parents, carrying on from
+    /// its end
+    pub roots: Vec<BranchNode>,
+    /// Branches beyond `max_generations` held back in a bud (0 = not a bud)
+    pub hidden_descendants: usize,
+    /// A partner's strand braided around this branch, merging into it at the
+    /// end where the shared children branch off
+    pub partner: Option<Box<BranchNode>>,
+    /// Points the branch curve passes through between start and end, in
+    /// order (a sculpted trunk); empty for a plain curve
+    pub waypoints: Vec<Vec3>,
+    /// Other founding ancestors' trees planted around this one (primary
+    /// trunk only)
+    pub grove: Vec<BranchNode>,
 }
 
 impl BranchNode {
+    /// Point `t` of the way along the branch's center line, through any
+    /// waypoints
+    pub fn point_along(&self, t: f32) -> Vec3 {
+        if self.waypoints.is_empty() {
+            let length = self.start.distance(&self.end);
+            let m0 = self.start_direction.scale(length * 0.4);
+            let m1 = self.end_direction.scale(length * 0.4);
+            return hermite_curve(self.start, self.end, m0, m1, t);
+        }
+        let mut points = Vec::with_capacity(self.waypoints.len() + 2);
+        points.push(self.start);
+        points.extend_from_slice(&self.waypoints);
+        points.push(self.end);
+        CatmullRomSpline::new(points).evaluate(t)
+    }
+
+    /// The point `distance` back from the branch's end along its curve (the
+    /// start, on a shorter branch)
+    pub fn point_back_from_end(&self, distance: f32) -> Vec3 {
+        let points: Vec<Vec3> = (0..=CURVE_SAMPLES).rev().map(|i| self.point_along(i as f32 / CURVE_SAMPLES as f32)).collect();
+        let mut left = distance;
+        for pair in points.windows(2) {
+            let step = pair[0].distance(&pair[1]);
+            if left <= step {
+                return pair[0].lerp(&pair[1], left / step.max(1e-6));
+            }
+            left -= step;
+        }
+        self.start
+    }
+
+    /// Length of the branch's curve
+    pub fn curve_length(&self) -> f32 {
+        let points: Vec<Vec3> = (0..=CURVE_SAMPLES).map(|i| self.point_along(i as f32 / CURVE_SAMPLES as f32)).collect();
+        points.windows(2).map(|pair| pair[0].distance(&pair[1])).sum()
+    }
+
     /// Get all nodes in pre-order (self first, then children)
     pub fn iter_preorder(&self) -> impl Iterator<Item = &BranchNode> {
         PreorderNodeIter { stack: vec![self] }
     }
 
+    /// Find a person's branch among this node and its descendants, including
+    /// other trees in the grove (not roots)
+    pub fn find_crown_mut(&mut self, person_id: &str) -> Option<&mut BranchNode> {
+        if self.person_id == person_id {
+            return Some(self);
+        }
+        self.children.iter_mut().chain(&mut self.grove).find_map(|c| c.find_crown_mut(person_id))
+    }
+
+    /// The branch a crown person grows out of (None for trunks, or when
+    /// they're not in the crown)
+    pub fn crown_parent(&self, person_id: &str) -> Option<&BranchNode> {
+        if self.children.iter().any(|child| child.person_id == person_id) {
+            return Some(self);
+        }
+        self.children.iter().chain(&self.grove).find_map(|c| c.crown_parent(person_id))
+    }
+
     /// Total number of nodes in subtree
     pub fn count(&self) -> usize {
-        1 + self.children.iter().map(|c| c.count()).sum::<usize>()
+        let partner = self.partner.as_ref().map_or(0, |p| p.count());
+        let rest = self.children.iter().chain(&self.roots).chain(&self.grove);
+        1 + partner + rest.map(|c| c.count()).sum::<usize>()
+    }
+
+    /// Corners of the box around every branch, grove included
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        let mut min = Vec3::new(f32::MAX, f32::MAX, f32::MAX);
+        let mut max = Vec3::new(f32::MIN, f32::MIN, f32::MIN);
+        for node in self.iter_preorder() {
+            for (point, radius) in [(node.start, node.start_radius), (node.end, node.end_radius)] {
+                min = Vec3::new(min.x.min(point.x - radius), min.y.min(point.y - radius), min.z.min(point.z - radius));
+                max = Vec3::new(max.x.max(point.x + radius), max.y.max(point.y + radius), max.z.max(point.z + radius));
+            }
+        }
+        (min, max)
+    }
+
+    /// Brightest point of the canopy: the luminance-weighted center of the
+    /// bright branch tips, or the top of the tree when none stand out
+    pub fn canopy_light(&self) -> Vec3 {
+        let mut sum = Vec3::ZERO;
+        let mut weight = 0.0;
+        for node in self.iter_preorder().filter(|node| node.visual.luminance > 0.5) {
+            sum = sum + node.end.scale(node.visual.luminance);
+            weight += node.visual.luminance;
+        }
+        if weight > 0.0 {
+            return sum.scale(1.0 / weight);
+        }
+        let (min, max) = self.bounds();
+        Vec3::new((min.x + max.x) * 0.5, max.y, (min.z + max.z) * 0.5)
+    }
+}
+
+#[cfg(test)]
+impl BranchNode {
+    /// A straight, childless test branch, 0.3 thick at its base and 0.2 at
+    /// its end
+    pub(crate) fn straight(id: &str, start: Vec3, end: Vec3) -> Self {
+        BranchNode {
+            person_id: id.to_string(),
+            visual: VisualParams::default(),
+            start,
+            end,
+            start_direction: (end - start).normalize(),
+            end_direction: (end - start).normalize(),
+            start_radius: 0.3,
+            end_radius: 0.2,
+            generation: 0,
+            event_positions: vec![],
+            children: vec![],
+            roots: vec![],
+            hidden_descendants: 0,
+            partner: None,
+            waypoints: Vec::new(),
+            grove: Vec::new(),
+        }
     }
 }
 
@@ -83,66 +492,406 @@ impl<'a> Iterator for PreorderNodeIter<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         let node = self.stack.pop()?;
-        for child in node.children.iter().rev() {
+        let partner = node.partner.as_deref();
+        let branches = node.grove.iter().rev().chain(node.roots.iter().rev()).chain(node.children.iter().rev());
+        for child in branches.chain(partner) {
             self.stack.push(child);
         }
         Some(node)
     }
 }
 
+/// Hue spacing between consecutive surname lines (golden angle, degrees)
+const LINE_HUE_STEP: f32 = 137.508;
+/// How far a person's hue may stray from their line's hue (degrees)
+const LINE_HUE_JITTER: f32 = 16.0;
+
+/// Recolor branches so each surname line shares a hue family; people
+/// without a known line keep their personal hue
+fn apply_lineage_hues(node: &mut BranchNode, lineage: &SurnameLineage) {
+    if let Some(index) = lineage.line_index(&node.person_id) {
+        let jitter = (node.visual.hue_shift / 360.0 - 0.5) * LINE_HUE_JITTER;
+        node.visual.hue_shift = (index as f32 * LINE_HUE_STEP + jitter).rem_euclid(360.0);
+    }
+    let partner = node.partner.as_deref_mut();
+    for child in node.children.iter_mut().chain(&mut node.roots).chain(&mut node.grove).chain(partner) {
+        apply_lineage_hues(child, lineage);
+    }
+}
+
+/// Fewest children spiralled by [`ChildArrangement::Phyllotaxis`]; pairs
+/// still split left and right
+const PHYLLOTAXIS_MIN_CHILDREN: usize = 3;
+/// Most of a parent's length a phyllotaxis spiral may climb
+const MAX_SPIRAL_SPAN: f32 = 0.5;
+/// Points sampled along a branch's curve to measure it
+const CURVE_SAMPLES: usize = 32;
+
+/// Direction `tilt` radians off `axis`, turned `azimuth` around it
+fn cone_direction(axis: Vec3, azimuth: f32, tilt: f32) -> Vec3 {
+    let axis = axis.normalize();
+    let u = axis.perpendicular();
+    let v = axis.cross(&u);
+    let around = u.scale(azimuth.cos()) + v.scale(azimuth.sin());
+    (axis.scale(tilt.cos()) + around.scale(tilt.sin())).normalize()
+}
+
+/// Sections a swaying trunk is split into (waypoints = sections - 1)
+const TRUNK_WAYPOINTS: usize = 6;
+
+/// Move a branch and everything growing from it by `shift`
+fn translate(node: &mut BranchNode, shift: Vec3) {
+    node.start = node.start + shift;
+    node.end = node.end + shift;
+    for point in &mut node.waypoints {
+        *point = *point + shift;
+    }
+    for child in &mut node.children {
+        translate(child, shift);
+    }
+}
+
+/// One person's pipe-model radius as a fraction of `base_radius`
+const PIPE_UNIT: f32 = 0.6;
+/// Widest a pipe-model branch may grow, as a multiple of `base_radius`
+const PIPE_MAX_RADIUS: f32 = 3.0;
+
+/// Extra glow on root-system branches so they read beneath the ground,
+/// though never past a crown branch's brightest
+const ROOT_GLOW: f32 = 1.4;
+
+/// Which relatives a branch grows into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Lineage {
+    /// Children, upward into the crown
+    Descendants,
+    /// Parents, downward into the roots
+    Ancestors,
+}
+
+impl Lineage {
+    fn next<'a>(self, family: &'a FamilyTree, id: &str, order: ChildOrder) -> Vec<&'a Person> {
+        match self {
+            Self::Descendants => family.ordered_children_of(id, order),
+            Self::Ancestors => family.parents_of(id),
+        }
+    }
+
+    /// How many branches grow beyond each person reachable from `id` in this
+    /// direction, by person id
+    ///
+    /// One post-order pass over a child (or parent) index built up front; a
+    /// relative reached along two lines counts for both, as they're grown twice.
+    pub(super) fn reach_counts(self, family: &FamilyTree, id: &str) -> HashMap<String, usize> {
+        let mut beyond: HashMap<&str, Vec<&str>> = HashMap::new();
+        for person in family.people.values() {
+            for child in person.children.iter().filter(|c| family.people.contains_key(c.as_str())) {
+                let (from, to) = match self {
+                    Self::Descendants => (person.id.as_str(), child.as_str()),
+                    Self::Ancestors => (child.as_str(), person.id.as_str()),
+                };
+                beyond.entry(from).or_default().push(to);
+            }
+        }
+
+        fn count(id: &str, beyond: &HashMap<&str, Vec<&str>>, counts: &mut HashMap<String, usize>) -> usize {
+            if let Some(&n) = counts.get(id) {
+                return n;
+            }
+            // Held at 0 while counting, so a cycle back here adds nothing
+            counts.insert(id.to_string(), 0);
+            let n = beyond.get(id).into_iter().flatten().map(|next| 1 + count(next, beyond, counts)).sum();
+            counts.insert(id.to_string(), n);
+            n
+        }
+
+        let mut counts = HashMap::new();
+        if family.people.contains_key(id) {
+            count(id, &beyond, &mut counts);
+        }
+        counts
+    }
+}
+
+/// Reflect a root branch grown upward from `origin` so it points down, squashed by `scale`
+fn mirror_root(node: &mut BranchNode, origin: Vec3, scale: f32) {
+    let flip_point = |p: Vec3| Vec3::new(p.x, origin.y - (p.y - origin.y) * scale, p.z);
+    let flip_dir = |d: Vec3| Vec3::new(d.x, -d.y * scale, d.z).normalize();
+    node.start = flip_point(node.start);
+    node.end = flip_point(node.end);
+    node.start_direction = flip_dir(node.start_direction);
+    node.end_direction = flip_dir(node.end_direction);
+    node.visual.glow_intensity = (node.visual.glow_intensity * ROOT_GLOW).min(1.0);
+    for root in &mut node.roots {
+        mirror_root(root, origin, scale);
+    }
+}
+
+/// Snap crown branch ends onto their generation's height band
+///
+/// Each branch keeps its sideways reach and is carried along by `shift`, the
+/// distance its parent's end moved; only the height of its end changes.
+/// Waypoints (a leaning, swaying trunk's curve) follow, each moved by its
+/// share of the start's and end's shifts.
+fn layer_generations(node: &mut BranchNode, base: f32, band: f32, shift: Vec3) {
+    let start = node.start + shift;
+    let mut end = node.end + shift;
+    end.y = base + band * (node.generation + 1) as f32;
+
+    let moved = end - node.end;
+    let count = node.waypoints.len() + 1;
+    for (i, point) in node.waypoints.iter_mut().enumerate() {
+        let t = (i + 1) as f32 / count as f32;
+        *point = *point + shift.scale(1.0 - t) + moved.scale(t);
+    }
+    node.start = start;
+    node.end = end;
+    // Bend the tip toward the new chord so the curve stays smooth
+    node.end_direction = (node.end_direction + (end - start).normalize()).normalize();
+    for child in &mut node.children {
+        layer_generations(child, base, band, moved);
+    }
+}
+
 /// Tree growth algorithm
 pub struct TreeGrowth {
     pub params: GrowthParams,
-    seed: u32,
+    /// Source of per-branch variation
+    rng: Box<dyn GrowthRng>,
+    /// World position of the trunk base
+    origin: Vec3,
+    /// Custom grammar overriding `params.lsystem`
+    lsystem: Option<LSystem>,
+    /// Buds opened past `max_generations`
+    expanded: HashSet<String>,
+    /// People faded back to ghosts, leaving a focused line solid
+    ghosted: HashSet<String>,
 }
 
 impl TreeGrowth {
     pub fn new(params: GrowthParams) -> Self {
-        Self { params, seed: 42 }
+        Self {
+            params,
+            rng: Box::new(StringHash::new(42)),
+            origin: Vec3::ZERO,
+            lsystem: None,
+            expanded: HashSet::new(),
+            ghosted: HashSet::new(),
+        }
     }
 
+    /// Reseed the random source
     pub fn with_seed(mut self, seed: u32) -> Self {
-        self.seed = seed;
+        self.rng.reseed(seed);
+        self
+    }
+
+    /// Draw per-branch variation from another source (keeps its own seed)
+    pub fn with_rng(mut self, rng: impl GrowthRng + 'static) -> Self {
+        self.rng = Box::new(rng);
+        self
+    }
+
+    /// Plant the trunk base at a world position
+    pub fn with_origin(mut self, origin: Vec3) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Use a custom grammar in [`LayoutMode::LSystem`] instead of the preset
+    pub fn with_lsystem(mut self, lsystem: LSystem) -> Self {
+        self.lsystem = Some(lsystem);
+        self
+    }
+
+    /// Grow one more generation from these people's buds
+    pub fn with_expanded(mut self, expanded: HashSet<String>) -> Self {
+        self.expanded = expanded;
+        self
+    }
+
+    /// Draw these people as ghosts, however well documented they are
+    pub fn with_ghosted(mut self, ghosted: HashSet<String>) -> Self {
+        self.ghosted = ghosted;
         self
     }
 
     /// Grow a tree structure from a family tree
-    pub fn grow(&self, family: &FamilyTree) -> Option<BranchNode> {
-        let root = family.root()?;
-        Some(self.grow_branch(family, root, Vec3::ZERO, Vec3::UP, 0))
+    ///
+    /// With several founding ancestors (see [`FamilyTree::trunks`]) each is
+    /// grown as its own trunk, planted evenly on a circle around the origin
+    /// `grove_spacing` apart; the others hang off the first in
+    /// [`BranchNode::grove`].
+    pub fn grow(&self, family: &FamilyTree) -> Result<BranchNode, GrowthError> {
+        let trunks = family.trunks();
+        if trunks.len() < 2 {
+            return self.grow_from(family, &family.root_id);
+        }
+
+        let n = trunks.len() as f32;
+        let radius = self.params.grove_spacing / (2.0 * (std::f32::consts::PI / n).sin());
+        // One branch budget for the whole grove
+        let mut budget = self.params.max_branches;
+        let mut planted = trunks.iter().enumerate().map(|(i, id)| {
+            let angle = i as f32 / n * std::f32::consts::TAU;
+            let origin = self.origin + Vec3::new(angle.cos(), 0.0, angle.sin()).scale(radius);
+            self.grow_at(family, id, origin, &mut budget)
+        });
+        let mut tree = planted.next().expect("at least two trunks")?;
+        tree.grove = planted.collect::<Result<_, _>>()?;
+        Ok(tree)
+    }
+
+    /// Grow with another person as the trunk, leaving the family's root as is
+    pub fn grow_from(&self, family: &FamilyTree, person_id: &str) -> Result<BranchNode, GrowthError> {
+        let mut budget = self.params.max_branches;
+        self.grow_at(family, person_id, self.origin, &mut budget)
+    }
+
+    /// Grow one trunk from a person, its base at `origin`, taking its
+    /// branches out of `budget`
+    fn grow_at(&self, family: &FamilyTree, person_id: &str, origin: Vec3, budget: &mut usize) -> Result<BranchNode, GrowthError> {
+        if family.is_empty() {
+            return Err(GrowthError::EmptyTree);
+        }
+        let root = family.get(person_id).ok_or_else(|| GrowthError::MissingRoot(person_id.to_string()))?;
+        self.check_growable(family, root, 0, Lineage::Descendants, &mut Vec::new(), budget)?;
+        if self.params.root_system {
+            self.check_growable(family, root, 0, Lineage::Ancestors, &mut Vec::new(), budget)?;
+        }
+
+        let reach = Lineage::Descendants.reach_counts(family, &root.id);
+        let mut tree = match self.params.layout {
+            LayoutMode::Botanical => self.grow_branch(family, &reach, root, origin, Vec3::UP, 0, Lineage::Descendants),
+            LayoutMode::Radial => super::radial::grow_fan(self, family, &reach, root, origin),
+            LayoutMode::Colonized => super::colonization::grow_colonized(self, family, &reach, root, origin),
+            LayoutMode::LSystem => {
+                let lsystem = self.lsystem.clone().unwrap_or_else(|| LSystem::preset(self.params.lsystem));
+                super::lsystem::grow_lsystem(self, &lsystem, family, &reach, root, origin)
+            }
+        };
+        self.shape_trunk(&mut tree);
+        self.seat_spirals(&mut tree);
+        if self.params.balance_crown {
+            super::balance::balance_crown(&mut tree, self.params.branch_spread);
+        }
+        if let Some(band) = self.params.generation_height {
+            layer_generations(&mut tree, origin.y, band, Vec3::ZERO);
+        }
+        if self.params.root_system {
+            tree.roots = self.grow_roots(family, root, origin);
+        }
+        if self.params.pipe_model {
+            self.pipe_radii(&mut tree, Lineage::Descendants);
+        }
+        super::partners::twine_partners(self, family, &root.id, &mut tree);
+        if self.params.hue_mode == HueMode::Surname {
+            apply_lineage_hues(&mut tree, &SurnameLineage::compute(family));
+        }
+        Ok(tree)
+    }
+
+    /// Leaf clusters for the childless tips of a grown tree
+    pub fn foliage(&self, tree: &BranchNode) -> Vec<FoliageCluster> {
+        super::foliage::foliage_clusters(self, tree)
     }
 
+    /// Regrow one person's crown branch and everything above it in place
+    ///
+    /// The branch keeps its start, heading and generation, so the rest of the
+    /// tree is untouched; use after edits to that person or their descendants.
+    /// Ancestors' fan slots, crown balance and pipe-model thickness still
+    /// reflect the old descendant counts until the next full
+    /// [`TreeGrowth::grow`]. Only the botanical layout places branches
+    /// independently enough for this; other layouts, and trunks (which are
+    /// shaped, balanced and rooted as a whole), return an error.
+    pub fn regrow_subtree(&self, tree: &mut BranchNode, person_id: &str, family: &FamilyTree) -> Result<(), String> {
+        if self.params.layout != LayoutMode::Botanical {
+            return Err("Incremental regrowth needs the botanical layout".to_string());
+        }
+        if tree.person_id == person_id || tree.grove.iter().any(|trunk| trunk.person_id == person_id) {
+            return Err(format!("'{}' is a trunk; regrow the whole tree", person_id));
+        }
+        let person = family.get(person_id).ok_or_else(|| format!("Person '{}' not found", person_id))?;
+        let trunk_id = tree.person_id.clone();
+        let total = tree.count();
+        let node = tree
+            .find_crown_mut(person_id)
+            .ok_or_else(|| format!("'{}' has no branch in the crown", person_id))?;
+        // The rest of the tree keeps its share of the branch budget
+        let mut budget = self.params.max_branches.saturating_sub(total - node.count());
+        self.check_growable(family, person, node.generation, Lineage::Descendants, &mut Vec::new(), &mut budget)
+            .map_err(|e| e.to_string())?;
+
+        let reach = Lineage::Descendants.reach_counts(family, &person.id);
+        let mut regrown =
+            self.grow_branch(family, &reach, person, node.start, node.start_direction, node.generation, Lineage::Descendants);
+        self.seat_spirals(&mut regrown);
+        if let Some(band) = self.params.generation_height {
+            layer_generations(&mut regrown, self.origin.y, band, Vec3::ZERO);
+        }
+        if self.params.pipe_model {
+            self.pipe_radii(&mut regrown, Lineage::Descendants);
+        }
+        super::partners::twine_partners(self, family, &trunk_id, &mut regrown);
+        if self.params.hue_mode == HueMode::Surname {
+            apply_lineage_hues(&mut regrown, &SurnameLineage::compute(family));
+        }
+        *node = regrown;
+        Ok(())
+    }
+
+    /// Grow ancestors like a crown from the trunk base, then mirror them below it
+    fn grow_roots(&self, family: &FamilyTree, root: &Person, origin: Vec3) -> Vec<BranchNode> {
+        let parents = family.parents_of(&root.id);
+        let reach = Lineage::Ancestors.reach_counts(family, &root.id);
+        let mut roots = self.grow_children(family, &reach, &parents, &root.id, origin, Vec3::UP, 0, Lineage::Ancestors);
+        for node in &mut roots {
+            mirror_root(node, origin, self.params.root_scale);
+        }
+        roots
+    }
+
+    /// `reach` counts the branches beyond each person (see [`Lineage::reach_counts`])
+    #[allow(clippy::too_many_arguments)]
     fn grow_branch(
         &self,
         family: &FamilyTree,
+        reach: &HashMap<String, usize>,
         person: &Person,
         start: Vec3,
         direction: Vec3,
         generation: usize,
+        lineage: Lineage,
     ) -> BranchNode {
-        let visual = person.visual_params();
         let params = &self.params;
+        let visual = self.person_visual(person);
 
         // Calculate segment length and radius based on generation and visual params
-        let gen_factor = params.height_decay.powi(generation as i32);
-        let length = params.base_height * gen_factor * (0.8 + 0.4 * visual.branch_thickness);
-        let start_radius = params.base_radius * gen_factor * visual.branch_thickness;
+        let (length, start_radius) = self.segment_size(&visual, generation);
         let end_radius = start_radius * params.radius_decay;
 
         // Add slight random variation for organic feel
-        let hash = self.hash_string(&person.id);
-        let angle_var = (hash as f32 / u32::MAX as f32 - 0.5) * params.angle_variance;
+        let angle_var = (self.rng.unit(&person.id) - 0.5) * params.angle_variance;
 
         // Adjust direction with some upward bias
         let end_direction = self.blend_direction(direction, Vec3::UP, params.verticality);
         let end_direction = self.rotate_slightly(end_direction, angle_var);
+        let end_direction = match lineage {
+            Lineage::Descendants => self.in_wind(self.toward_light(end_direction), generation),
+            Lineage::Ancestors => end_direction,
+        };
 
         // Calculate end position
         let end = start + end_direction.scale(length);
 
-        // Grow children
-        let children_data = family.children_of(&person.id);
-        let children = self.grow_children(family, &children_data, end, end_direction, generation);
+        // Grow children, or an ancestor's own parents as further roots
+        let (next_data, hidden_descendants) = self.next_generation(family, reach, person, generation, lineage);
+        let next = self.grow_children(family, reach, &next_data, &person.id, end, end_direction, generation, lineage);
+        let (children, roots) = match lineage {
+            Lineage::Descendants => (next, Vec::new()),
+            Lineage::Ancestors => (Vec::new(), next),
+        };
 
         BranchNode {
             person_id: person.id.clone(),
@@ -154,17 +903,27 @@ impl TreeGrowth {
             start_radius,
             end_radius,
             generation,
+            event_positions: person.event_positions(),
             children,
+            roots,
+            hidden_descendants,
+            partner: None,
+            waypoints: Vec::new(),
+            grove: Vec::new(),
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn grow_children(
         &self,
         family: &FamilyTree,
+        reach: &HashMap<String, usize>,
         children: &[&Person],
+        parent_id: &str,
         parent_end: Vec3,
         parent_direction: Vec3,
         parent_generation: usize,
+        lineage: Lineage,
     ) -> Vec<BranchNode> {
         let n = children.len();
         if n == 0 {
@@ -174,40 +933,406 @@ impl TreeGrowth {
         let spread = self.params.branch_spread;
         let next_gen = parent_generation + 1;
 
+        if self.params.stable_placement {
+            return children
+                .iter()
+                .map(|child| {
+                    let direction = self.stable_direction(parent_id, &child.id, parent_direction);
+                    self.grow_branch(family, reach, child, parent_end, direction, next_gen, lineage)
+                })
+                .collect();
+        }
+
+        if self.spirals(n) {
+            // Children fan out around the parent's axis here, and are seated
+            // up its curve once it's shaped (see `seat_spirals`)
+            let axis = parent_direction.normalize();
+            let first = self.rng.unit(parent_id) * std::f32::consts::TAU;
+            return children
+                .iter()
+                .enumerate()
+                .map(|(i, child)| {
+                    let azimuth = first + i as f32 * self.params.divergence_angle;
+                    let direction = cone_direction(axis, azimuth, spread);
+                    self.grow_branch(family, reach, child, parent_end, direction, next_gen, lineage)
+                })
+                .collect();
+        }
+
+        let (offsets, tilt) = if n > 1 {
+            let weights: Vec<f32> = children
+                .iter()
+                .map(|c| (1.0 + reach[&c.id] as f32).powf(self.params.descendant_weight))
+                .collect();
+            self.pack_siblings(children, &weights, parent_direction, next_gen)
+        } else {
+            (Vec::new(), None)
+        };
+        let fan_direction = tilt
+            .map(|tilt| self.tilt_from_up(parent_direction, tilt))
+            .unwrap_or(parent_direction);
+
         children
             .iter()
             .enumerate()
             .map(|(i, child)| {
                 let direction = if n == 1 {
                     // Single child continues mostly straight with slight deviation
-                    let hash = self.hash_string(&child.id);
-                    let deviation = (hash as f32 / u32::MAX as f32 - 0.5) * spread * 0.3;
+                    let deviation = (self.rng.unit(&child.id) - 0.5) * spread * 0.3;
                     self.rotate_around_up(parent_direction, deviation)
                 } else {
-                    // Multiple children: spread them out
-                    let angle = if n == 2 {
-                        // Binary split: left and right
-                        if i == 0 { -spread } else { spread }
-                    } else {
-                        // Multiple: evenly spaced
-                        let t = i as f32 / (n - 1) as f32;
-                        spread * (t * 2.0 - 1.0)
-                    };
-                    self.rotate_around_up(parent_direction, angle)
+                    // Multiple children: spread them out, packed so they don't overlap
+                    self.rotate_around_up(fan_direction, offsets[i])
                 };
 
-                self.grow_branch(family, child, parent_end, direction.normalize(), next_gen)
+                self.grow_branch(family, reach, child, parent_end, direction.normalize(), next_gen, lineage)
             })
             .collect()
     }
 
-    /// Simple deterministic hash for consistent randomness
-    fn hash_string(&self, s: &str) -> u32 {
-        let mut h = self.seed;
-        for b in s.bytes() {
-            h = h.wrapping_mul(31).wrapping_add(b as u32);
+    /// Whether `n` children are arranged in a phyllotaxis spiral
+    fn spirals(&self, n: usize) -> bool {
+        self.params.layout == LayoutMode::Botanical
+            && !self.params.stable_placement
+            && self.params.child_arrangement == ChildArrangement::Phyllotaxis
+            && n >= PHYLLOTAXIS_MIN_CHILDREN
+    }
+
+    /// Move spiralled children up their parent's shaped curve, `child_pitch`
+    /// apart by arc length and the last at its tip
+    ///
+    /// The pitch is clamped so the spiral climbs at most half the parent
+    /// (and to 0 when negative or NaN).
+    fn seat_spirals(&self, node: &mut BranchNode) {
+        let n = node.children.len();
+        if self.spirals(n) {
+            let span = node.curve_length() * MAX_SPIRAL_SPAN / (n - 1) as f32;
+            let pitch = self.params.child_pitch.max(0.0).min(span);
+            let seats: Vec<Vec3> = (0..n).map(|i| node.point_back_from_end(pitch * (n - 1 - i) as f32)).collect();
+            for (child, seat) in node.children.iter_mut().zip(seats) {
+                let shift = seat - child.start;
+                translate(child, shift);
+            }
+        }
+        for child in &mut node.children {
+            self.seat_spirals(child);
+        }
+    }
+
+    /// Direction for a child on a cone around the parent's axis, chosen only
+    /// from the parent and child ids
+    fn stable_direction(&self, parent_id: &str, child_id: &str, parent_direction: Vec3) -> Vec3 {
+        // Mixed, since a plain string hash of "p>a" and "p>b" differs only in its low bits
+        let hash = avalanche(self.rng.bits(&format!("{}>{}", parent_id, child_id)));
+        let azimuth = (hash & 0xffff) as f32 / 65535.0 * std::f32::consts::TAU;
+        let tilt = self.params.branch_spread * (0.6 + 0.4 * (hash >> 16) as f32 / 65535.0);
+        cone_direction(parent_direction, azimuth, tilt)
+    }
+
+    /// Walk the branches a person would grow, stopping at cycles or once
+    /// `budget` branches have been used up
+    fn check_growable<'a>(
+        &self,
+        family: &'a FamilyTree,
+        person: &'a Person,
+        generation: usize,
+        lineage: Lineage,
+        line: &mut Vec<&'a str>,
+        budget: &mut usize,
+    ) -> Result<(), GrowthError> {
+        if line.contains(&person.id.as_str()) {
+            return Err(match lineage {
+                Lineage::Descendants => GrowthError::Cycle(person.id.clone()),
+                Lineage::Ancestors => GrowthError::AncestorCycle(person.id.clone()),
+            });
+        }
+        if *budget == 0 {
+            return Err(GrowthError::NodeBudgetExceeded { budget: self.params.max_branches });
+        }
+        *budget -= 1;
+
+        line.push(&person.id);
+        for next in self.grown_next(family, person, generation, lineage).unwrap_or_default() {
+            self.check_growable(family, next, generation + 1, lineage, line, budget)?;
+        }
+        line.pop();
+        Ok(())
+    }
+
+    /// Relatives to grow from a person's branch, and how many branches
+    /// (counted in `reach`) are held back in a bud when `max_generations`
+    /// stops growth there
+    pub(super) fn next_generation<'a>(
+        &self,
+        family: &'a FamilyTree,
+        reach: &HashMap<String, usize>,
+        person: &Person,
+        generation: usize,
+        lineage: Lineage,
+    ) -> (Vec<&'a Person>, usize) {
+        match self.grown_next(family, person, generation, lineage) {
+            Some(next) => (next, 0),
+            None => (Vec::new(), reach[&person.id]),
+        }
+    }
+
+    /// Relatives to grow from a person's branch, or None when
+    /// `max_generations` holds them back in a bud
+    fn grown_next<'a>(&self, family: &'a FamilyTree, person: &Person, generation: usize, lineage: Lineage) -> Option<Vec<&'a Person>> {
+        let next = lineage.next(family, &person.id, self.params.child_order);
+        let cut = self.params.max_generations.is_some_and(|max| generation + 1 >= max)
+            && !self.expanded.contains(&person.id);
+        (!cut || next.is_empty()).then_some(next)
+    }
+
+    /// Visual parameters for a person with the style options applied
+    pub(super) fn person_visual(&self, person: &Person) -> VisualParams {
+        // Opacity of people outside a focused line
+        const GHOST_OPACITY: f32 = 0.3;
+
+        let mut visual = person.visual_params();
+        if self.ghosted.contains(&person.id) {
+            visual.opacity = visual.opacity.min(GHOST_OPACITY);
+        }
+        if let Some(style) = &self.params.gender_style {
+            style.apply(person.gender, &mut visual);
+        }
+        if self.params.privacy_mode && person.is_living() {
+            visual.conceal();
+        }
+        visual
+    }
+
+    /// Length and start radius of a branch segment
+    pub(super) fn segment_size(&self, visual: &VisualParams, generation: usize) -> (f32, f32) {
+        let params = &self.params;
+        let gen_factor = params.height_decay.powi(generation as i32);
+        let length = params.base_height * gen_factor * (0.8 + 0.4 * visual.branch_thickness);
+        let radius = params.base_radius * gen_factor * visual.branch_thickness;
+        (length, radius)
+    }
+
+    /// Lean the trunk and thread its S-curve waypoints, carrying the crown
+    /// along with the trunk's end
+    fn shape_trunk(&self, trunk: &mut BranchNode) {
+        let params = &self.params;
+        if params.trunk_lean == 0.0 && params.trunk_sway == 0.0 {
+            return;
+        }
+        let heading = Vec3::new(params.trunk_heading.cos(), 0.0, params.trunk_heading.sin());
+        let length = trunk.start.distance(&trunk.end);
+        let axis = (Vec3::UP.scale(params.trunk_lean.cos()) + heading.scale(params.trunk_lean.sin())).normalize();
+
+        let end = trunk.start + axis.scale(length);
+        let shift = end - trunk.end;
+        for child in &mut trunk.children {
+            translate(child, shift);
+        }
+        trunk.end = end;
+        trunk.start_direction = axis;
+
+        // Sway sideways within the lean plane, across the trunk's axis
+        let side = heading - axis.scale(heading.dot(&axis));
+        let side = if side.length_squared() > 1e-8 { side.normalize() } else { axis.perpendicular() };
+        trunk.waypoints = if params.trunk_sway != 0.0 {
+            (1..TRUNK_WAYPOINTS)
+                .map(|i| {
+                    let t = i as f32 / TRUNK_WAYPOINTS as f32;
+                    let sway = (t * params.trunk_bends * std::f32::consts::PI).sin() * params.trunk_sway * length;
+                    trunk.start + axis.scale(t * length) + side.scale(sway)
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+    }
+
+    /// Resize a subtree by the pipe model, returning its start radius before
+    /// clamping
+    ///
+    /// A branch ends as wide as its children's combined cross-section and
+    /// starts one person's cross-section wider; people held back in a bud
+    /// still count. Roots are sized the same way from their own ancestors.
+    /// Large families would grow ever thicker trunks, so no branch is drawn
+    /// wider than [`PIPE_MAX_RADIUS`] base radii; parents stay at least as
+    /// wide as their children.
+    fn pipe_radii(&self, node: &mut BranchNode, lineage: Lineage) -> f32 {
+        let unit = self.params.base_radius * PIPE_UNIT * node.visual.branch_thickness;
+        let hidden = node.hidden_descendants as f32 * (self.params.base_radius * PIPE_UNIT).powi(2);
+        let onward = match lineage {
+            Lineage::Descendants => &mut node.children,
+            Lineage::Ancestors => &mut node.roots,
+        };
+        let carried: f32 = onward.iter_mut().map(|c| self.pipe_radii(c, lineage).powi(2)).sum::<f32>() + hidden;
+        if lineage == Lineage::Descendants {
+            for root in &mut node.roots {
+                self.pipe_radii(root, Lineage::Ancestors);
+            }
+        }
+
+        let start = (carried + unit * unit).sqrt();
+        let end = if carried > 0.0 { carried.sqrt() } else { start * self.params.radius_decay };
+        let widest = self.params.base_radius * PIPE_MAX_RADIUS;
+        node.start_radius = start.min(widest);
+        node.end_radius = end.min(widest);
+        start
+    }
+
+    /// Default fan angles around the up axis for siblings with the given weights
+    ///
+    /// Each sibling owns a slot proportional to its weight and sits at the slot's
+    /// centre; the outermost siblings stay at `±branch_spread`, so equal weights
+    /// give even spacing and heavy siblings push their neighbours further away.
+    fn default_offsets(&self, weights: &[f32]) -> Vec<f32> {
+        let spread = self.params.branch_spread;
+        let n = weights.len();
+        if n == 2 {
+            // Binary split: left and right
+            return vec![-spread, spread];
+        }
+        let mut centers = Vec::with_capacity(n);
+        let mut filled = 0.0;
+        for &w in weights {
+            centers.push(filled + w * 0.5);
+            filled += w;
+        }
+        let (first, last) = (centers[0], centers[n - 1]);
+        centers
+            .iter()
+            .map(|&c| {
+                let t = if last > first { (c - first) / (last - first) } else { 0.5 };
+                spread * (t * 2.0 - 1.0)
+            })
+            .collect()
+    }
+
+    /// Arc-pack siblings around the up axis by their radii
+    ///
+    /// Siblings are compared at the spread distance (a fraction of the shortest
+    /// sibling). Neighbouring gaps are widened until branches clear each other;
+    /// if the whole circle is not enough, the fan is tilted further from vertical
+    /// and siblings are spaced around the full circle. Very large sibling sets
+    /// that cannot clear even when fanned flat separate further out instead.
+    /// Returns the angle offsets and the tilt to apply, if any.
+    fn pack_siblings(
+        &self,
+        children: &[&Person],
+        weights: &[f32],
+        parent_direction: Vec3,
+        generation: usize,
+    ) -> (Vec<f32>, Option<f32>) {
+        const SPREAD_DISTANCE: f32 = 0.5;
+        const CLEARANCE: f32 = 1.2;
+        let tau = std::f32::consts::TAU;
+
+        let n = children.len();
+        let sizes: Vec<(f32, f32)> = children
+            .iter()
+            .map(|c| self.segment_size(&c.visual_params(), generation))
+            .collect();
+        let distance = sizes.iter().map(|&(l, _)| l).fold(f32::MAX, f32::min) * SPREAD_DISTANCE;
+
+        // Chord each neighbouring pair needs, including the wrap-around pair
+        let chords: Vec<f32> = (0..n)
+            .map(|i| (sizes[i].1 + sizes[(i + 1) % n].1) * CLEARANCE)
+            .collect();
+
+        // Horizontal reach at the spread distance, after the upward blend
+        let tilt = parent_direction.normalize().dot(&Vec3::UP).clamp(-1.0, 1.0).acos();
+        let reach = distance * self.settled_tilt(tilt).sin();
+        let angle_for = |chord: f32| {
+            if reach <= 1e-5 {
+                f32::MAX
+            } else {
+                2.0 * (chord / (2.0 * reach)).min(1.0).asin()
+            }
+        };
+
+        let defaults = self.default_offsets(weights);
+        let gaps: Vec<f32> = (0..n - 1)
+            .map(|i| (defaults[i + 1] - defaults[i]).max(angle_for(chords[i])))
+            .collect();
+        let total: f32 = gaps.iter().sum();
+
+        if total + angle_for(chords[n - 1]) <= tau {
+            if total <= defaults[n - 1] - defaults[0] + 1e-6 {
+                return (defaults, None);
+            }
+            // Widen the fan, keeping it centred
+            let mut offsets = vec![-total / 2.0];
+            for gap in &gaps {
+                offsets.push(offsets[offsets.len() - 1] + gap);
+            }
+            return (offsets, None);
+        }
+
+        // Full circle: spread the chords around a ring wide enough to fit them
+        let circumference: f32 = chords.iter().sum();
+        let needed_reach = circumference / tau;
+        let settled = (needed_reach / distance).min(1.0).asin();
+        let new_tilt = self.tilt_for_settled(settled).max(tilt);
+
+        let mut offsets = vec![0.0];
+        for chord in &chords[..n - 1] {
+            offsets.push(offsets[offsets.len() - 1] + chord / circumference * tau);
+        }
+        (offsets, (new_tilt > tilt).then_some(new_tilt))
+    }
+
+    /// Angle from vertical a branch ends up at after blending toward up
+    fn settled_tilt(&self, tilt: f32) -> f32 {
+        let v = self.params.verticality.clamp(0.0, 0.999);
+        let (sin, cos) = tilt.sin_cos();
+        ((1.0 - v) * sin).atan2((1.0 - v) * cos + v)
+    }
+
+    /// Starting tilt needed to settle at `settled` (inverse of `settled_tilt`)
+    fn tilt_for_settled(&self, settled: f32) -> f32 {
+        let (mut lo, mut hi) = (0.0, std::f32::consts::FRAC_PI_2);
+        for _ in 0..24 {
+            let mid = (lo + hi) * 0.5;
+            if self.settled_tilt(mid) < settled {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
         }
-        h
+        hi
+    }
+
+    /// Tilt a direction to a given angle from vertical, keeping its heading
+    fn tilt_from_up(&self, dir: Vec3, tilt: f32) -> Vec3 {
+        let horizontal = Vec3::new(dir.x, 0.0, dir.z);
+        let heading = if horizontal.length() < 1e-4 {
+            Vec3::new(1.0, 0.0, 0.0)
+        } else {
+            horizontal.normalize()
+        };
+        Vec3::UP.scale(tilt.cos()) + heading.scale(tilt.sin())
+    }
+
+    /// Random source for layouts and passes outside this module
+    pub(super) fn rng(&self) -> &dyn GrowthRng {
+        self.rng.as_ref()
+    }
+
+    /// Bend a crown direction toward the light by the phototropism strength
+    pub(super) fn toward_light(&self, dir: Vec3) -> Vec3 {
+        let light = self.params.light_direction;
+        if self.params.phototropism <= 0.0 || light.length_squared() < 1e-8 {
+            return dir;
+        }
+        self.blend_direction(dir, light.normalize(), self.params.phototropism.min(1.0))
+    }
+
+    /// Shear a crown direction downwind, more strongly in higher generations
+    pub(super) fn in_wind(&self, dir: Vec3, generation: usize) -> Vec3 {
+        let wind = Vec3::new(self.params.wind_direction.x, 0.0, self.params.wind_direction.z);
+        if self.params.wind_strength <= 0.0 || wind.length_squared() < 1e-8 {
+            return dir;
+        }
+        let exposure = generation as f32 / (generation as f32 + 1.0);
+        self.blend_direction(dir, wind.normalize(), (self.params.wind_strength * exposure).min(1.0))
     }
 
     /// Blend two directions
@@ -239,6 +1364,7 @@ impl TreeGrowth {
 mod tests {
     use super::*;
     use crate::data::FamilyTree;
+    use crate::growth::SplitMix;
 
     const TEST_YAML: &str = r#"
 family:
@@ -278,6 +1404,22 @@ people:
         assert_eq!(tree.start, Vec3::ZERO);
     }
 
+    #[test]
+    fn test_tree_starts_at_custom_origin() {
+        let family = FamilyTree::from_yaml(TEST_YAML).unwrap();
+        let origin = Vec3::new(5.0, 1.0, -2.0);
+        let at_zero = TreeGrowth::new(GrowthParams::default()).grow(&family).unwrap();
+        let planted = TreeGrowth::new(GrowthParams::default())
+            .with_origin(origin)
+            .grow(&family)
+            .unwrap();
+
+        assert_eq!(planted.start, origin);
+        // Whole tree is translated, shape unchanged
+        let offset = planted.children[1].end - at_zero.children[1].end;
+        assert!(offset.distance(&origin) < 0.0001);
+    }
+
     #[test]
     fn test_tree_grows_upward() {
         let family = FamilyTree::from_yaml(TEST_YAML).unwrap();
@@ -347,6 +1489,586 @@ people:
         assert_eq!(ids.len(), 3);
     }
 
+    #[test]
+    fn test_surname_hue_mode() {
+        let yaml = r#"
+family:
+  name: "Lines"
+  root: "root"
+people:
+  - id: "root"
+    name: "Ada Carter"
+    children: ["a", "b", "c"]
+  - id: "a"
+    name: "Bo"
+  - id: "b"
+    name: "Cy Carter"
+  - id: "c"
+    name: "Di Ellis"
+"#;
+        let family = FamilyTree::from_yaml(yaml).unwrap();
+        let params = GrowthParams {
+            hue_mode: HueMode::Surname,
+            ..Default::default()
+        };
+        let tree = TreeGrowth::new(params).grow(&family).unwrap();
+        let hue = |id: &str| tree.iter_preorder().find(|n| n.person_id == id).unwrap().visual.hue_shift;
+        let gap = |a: f32, b: f32| {
+            let d = (a - b).rem_euclid(360.0);
+            d.min(360.0 - d)
+        };
+
+        assert!(gap(hue("root"), hue("a")) <= LINE_HUE_JITTER);
+        assert!(gap(hue("root"), hue("b")) <= LINE_HUE_JITTER);
+        assert!(gap(hue("root"), hue("c")) > LINE_HUE_JITTER * 2.0);
+    }
+
+    #[test]
+    fn test_many_siblings_do_not_overlap() {
+        let mut yaml = String::from(
+            "family:\n  name: \"Large\"\n  root: \"root\"\npeople:\n  - id: \"root\"\n    name: \"Root\"\n    children: [",
+        );
+        let ids: Vec<String> = (0..9).map(|i| format!("\"c{}\"", i)).collect();
+        yaml.push_str(&ids.join(", "));
+        yaml.push_str("]\n");
+        for i in 0..9 {
+            let bio = "A long and well documented life. ".repeat(i * 2);
+            yaml.push_str(&format!("  - id: \"c{}\"\n    name: \"C{}\"\n    biography: \"{}\"\n", i, i, bio));
+        }
+
+        let family = FamilyTree::from_yaml(&yaml).unwrap();
+        let tree = TreeGrowth::new(GrowthParams::default()).grow(&family).unwrap();
+        let kids = &tree.children;
+        assert_eq!(kids.len(), 9);
+
+        let shortest = kids.iter().map(|k| k.start.distance(&k.end)).fold(f32::MAX, f32::min);
+        let probe = |k: &BranchNode| k.start + (k.end - k.start).normalize().scale(shortest * 0.5);
+
+        for i in 0..kids.len() {
+            for j in (i + 1)..kids.len() {
+                let gap = probe(&kids[i]).distance(&probe(&kids[j]));
+                let needed = kids[i].start_radius + kids[j].start_radius;
+                assert!(gap >= needed, "c{} and c{} overlap: {} < {}", i, j, gap, needed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_small_fan_unchanged() {
+        let growth = TreeGrowth::new(GrowthParams::default());
+        let family = FamilyTree::from_yaml(TEST_YAML).unwrap();
+        let children = family.children_of("root");
+        let tilted = Vec3::new(0.6, 0.8, 0.0);
+
+        let (offsets, tilt) = growth.pack_siblings(&children, &[1.0, 1.0], tilted, 1);
+        assert_eq!(offsets, growth.default_offsets(&[1.0, 1.0]));
+        assert!(tilt.is_none());
+    }
+
+    #[test]
+    fn test_descendants_widen_fan_slot() {
+        let growth = TreeGrowth::new(GrowthParams::default());
+        let even = growth.default_offsets(&[1.0, 1.0, 1.0, 1.0]);
+        let weighted = growth.default_offsets(&[1.0, 4.0, 1.0, 1.0]);
+
+        assert!((even[1] - even[0] - (even[2] - even[1])).abs() < 1e-5);
+        // The heavy second sibling gets more room on both sides
+        assert!(weighted[1] - weighted[0] > weighted[3] - weighted[2]);
+        assert!(weighted[2] - weighted[1] > weighted[3] - weighted[2]);
+        assert_eq!(weighted[0], -growth.params.branch_spread);
+        assert_eq!(weighted[3], growth.params.branch_spread);
+    }
+
+    #[test]
+    fn test_root_system_grows_down() {
+        let yaml = r#"
+family:
+  name: "Hourglass"
+  root: "me"
+people:
+  - id: "mother"
+    name: "Mother"
+    children: ["me"]
+  - id: "father"
+    name: "Father"
+    children: ["me"]
+  - id: "grandma"
+    name: "Grandma"
+    children: ["mother"]
+  - id: "me"
+    name: "Me"
+    children: ["kid"]
+  - id: "kid"
+    name: "Kid"
+"#;
+        let family = FamilyTree::from_yaml(yaml).unwrap();
+        let plain = TreeGrowth::new(GrowthParams::default()).grow(&family).unwrap();
+        assert!(plain.roots.is_empty());
+
+        let params = GrowthParams { root_system: true, ..Default::default() };
+        let tree = TreeGrowth::new(params).grow(&family).unwrap();
+        let ids: Vec<_> = tree.roots.iter().map(|r| r.person_id.as_str()).collect();
+        assert_eq!(ids, ["father", "mother"]);
+        assert_eq!(tree.roots[1].roots[0].person_id, "grandma");
+        assert!(tree.roots.iter().all(|r| r.children.is_empty()));
+        assert!(tree.iter_preorder().all(|n| n.visual.glow_intensity <= 1.0));
+        assert_eq!(tree.count(), 5);
+
+        for root in tree.iter_preorder().filter(|n| n.person_id != "me" && n.person_id != "kid") {
+            assert!(root.end.y < root.start.y, "{} should grow downward", root.person_id);
+            assert!(root.end.y < 0.0);
+        }
+    }
+
+    #[test]
+    fn test_grow_from_focus_person() {
+        let family = FamilyTree::from_yaml(TEST_YAML).unwrap();
+        let params = GrowthParams { root_system: true, ..Default::default() };
+        let tree = TreeGrowth::new(params).grow_from(&family, "right").unwrap();
+
+        assert_eq!(tree.person_id, "right");
+        assert_eq!(tree.generation, 0);
+        assert_eq!(tree.roots[0].person_id, "root");
+        assert_eq!(family.root_id, "root");
+        assert_eq!(
+            TreeGrowth::new(params).grow_from(&family, "ghost").unwrap_err(),
+            GrowthError::MissingRoot("ghost".to_string())
+        );
+    }
+
+    #[test]
+    fn test_params_from_yaml() {
+        let params: GrowthParams = serde_yaml::from_str(
+            "base_height: 4.5\nlayout: fan\nhue_mode: surname\nchild_order: listed\nlsystem: willow\n",
+        )
+        .unwrap();
+
+        assert_eq!(params.base_height, 4.5);
+        assert_eq!(params.layout, LayoutMode::Radial);
+        assert_eq!(params.hue_mode, HueMode::Surname);
+        assert_eq!(params.child_order, ChildOrder::Listed);
+        assert_eq!(params.lsystem, LSystemPreset::Willow);
+        assert_eq!(params.height_decay, GrowthParams::default().height_decay);
+        assert!(serde_yaml::from_str::<GrowthParams>("layout: spiral").is_err());
+    }
+
+    #[test]
+    fn test_phototropism_leans_toward_light() {
+        let family = FamilyTree::from_yaml(TEST_YAML).unwrap();
+        let lean = |strength: f32| {
+            let params = GrowthParams {
+                light_direction: Vec3::new(1.0, 0.0, 0.0),
+                phototropism: strength,
+                ..Default::default()
+            };
+            let tree = TreeGrowth::new(params).grow(&family).unwrap();
+            tree.iter_preorder().map(|n| n.end.x).sum::<f32>()
+        };
+
+        assert_eq!(lean(0.0), lean(-1.0));
+        assert!(lean(0.3) > lean(0.0) + 0.5);
+    }
+
+    #[test]
+    fn test_wind_shears_higher_generations() {
+        let family = FamilyTree::from_yaml(TEST_YAML).unwrap();
+        let calm = TreeGrowth::new(GrowthParams::default()).grow(&family).unwrap();
+        let windy = TreeGrowth::new(GrowthParams::wind_swept(Vec3::new(0.0, 0.0, -1.0)))
+            .grow(&family)
+            .unwrap();
+
+        // The trunk stands; the crown is pushed downwind
+        assert_eq!(windy.end_direction, calm.end_direction);
+        for child in &windy.children {
+            assert!(child.end_direction.z < -0.2, "{} not sheared", child.person_id);
+        }
+    }
+
+    #[test]
+    fn test_presets() {
+        let family = FamilyTree::from_yaml(TEST_YAML).unwrap();
+        for name in GrowthParams::PRESETS {
+            let params = GrowthParams::preset(name).unwrap();
+            assert_eq!(TreeGrowth::new(params).grow(&family).unwrap().count(), 3);
+        }
+
+        let cypress = GrowthParams::preset("Cypress").unwrap();
+        let baobab = GrowthParams::preset("baobab").unwrap();
+        assert!(cypress.branch_spread < baobab.branch_spread);
+        assert!(baobab.base_radius > cypress.base_radius);
+        assert!(GrowthParams::preset("palm").is_err());
+    }
+
+    #[test]
+    fn test_stable_placement_survives_new_sibling() {
+        let params = GrowthParams { stable_placement: true, ..Default::default() };
+        let growth = TreeGrowth::new(params);
+        let before = growth.grow(&FamilyTree::from_yaml(TEST_YAML).unwrap()).unwrap();
+
+        let mut family = FamilyTree::from_yaml(TEST_YAML).unwrap();
+        family.add_child("root", Person::new("middle", "Middle Child")).unwrap();
+        let after = growth.grow(&family).unwrap();
+
+        assert_eq!(after.children.len(), 3);
+        for id in ["left", "right"] {
+            let find = |tree: &BranchNode| tree.iter_preorder().find(|n| n.person_id == id).unwrap().end;
+            assert_eq!(find(&before), find(&after), "{} moved", id);
+        }
+
+        // Ids differing only in their last letter still head apart
+        family.add_child("root", Person::new("k1", "Kid")).unwrap();
+        family.add_child("root", Person::new("k2", "Kid")).unwrap();
+        let tree = growth.grow(&family).unwrap();
+        let end = |id: &str| tree.iter_preorder().find(|n| n.person_id == id).unwrap().end;
+        assert!(end("k1").distance(&end("k2")) > 0.1);
+    }
+
+    #[test]
+    fn test_regrow_subtree_matches_full_growth() {
+        let growth = TreeGrowth::new(GrowthParams::default());
+        let mut tree = growth.grow(&FamilyTree::from_yaml(TEST_YAML).unwrap()).unwrap();
+
+        let mut family = FamilyTree::from_yaml(TEST_YAML).unwrap();
+        family.add_child("right", Person::new("grandchild", "Grandchild")).unwrap();
+        growth.regrow_subtree(&mut tree, "right", &family).unwrap();
+
+        let full = growth.grow(&family).unwrap();
+        assert_eq!(tree.count(), 4);
+        let ends = |t: &BranchNode| t.iter_preorder().map(|n| (n.person_id.clone(), n.end)).collect::<Vec<_>>();
+        assert_eq!(ends(&tree), ends(&full));
+
+        assert!(growth.regrow_subtree(&mut tree, "ghost", &family).is_err());
+        assert!(growth.regrow_subtree(&mut tree, "root", &family).is_err());
+        let radial = TreeGrowth::new(GrowthParams { layout: LayoutMode::Radial, ..Default::default() });
+        assert!(radial.regrow_subtree(&mut tree, "right", &family).is_err());
+    }
+
+    #[test]
+    fn test_max_generations_leaves_buds() {
+        let yaml = r#"
+family:
+  name: "Deep"
+  root: "root"
+people:
+  - id: "root"
+    name: "Root"
+    children: ["a"]
+  - id: "a"
+    name: "A"
+    children: ["b"]
+  - id: "b"
+    name: "B"
+    children: ["c"]
+  - id: "c"
+    name: "C"
+"#;
+        let family = FamilyTree::from_yaml(yaml).unwrap();
+        let params = GrowthParams { max_generations: Some(2), ..Default::default() };
+        let tree = TreeGrowth::new(params).grow(&family).unwrap();
+
+        assert_eq!(tree.count(), 2);
+        assert_eq!(tree.hidden_descendants, 0);
+        assert_eq!(tree.children[0].hidden_descendants, 2);
+
+        let expanded = TreeGrowth::new(params)
+            .with_expanded(HashSet::from(["a".to_string()]))
+            .grow(&family)
+            .unwrap();
+        let b = expanded.iter_preorder().find(|n| n.person_id == "b").unwrap();
+        assert_eq!(expanded.count(), 3);
+        assert_eq!(b.hidden_descendants, 1);
+    }
+
+    #[test]
+    fn test_reach_counts_every_line() {
+        let yaml = r#"
+family:
+  name: "Collapse"
+  root: "root"
+people:
+  - id: "root"
+    name: "Root"
+    children: ["a", "b"]
+  - id: "a"
+    name: "A"
+    children: ["c"]
+  - id: "b"
+    name: "B"
+    children: ["c"]
+  - id: "c"
+    name: "C"
+"#;
+        let family = FamilyTree::from_yaml(yaml).unwrap();
+
+        // "c" grows under both parents, so it's counted along both lines
+        let down = Lineage::Descendants.reach_counts(&family, "root");
+        assert_eq!((down["root"], down["a"], down["b"], down["c"]), (4, 1, 1, 0));
+        let up = Lineage::Ancestors.reach_counts(&family, "c");
+        assert_eq!((up["c"], up["a"], up["root"]), (4, 1, 0));
+
+        let tree = TreeGrowth::new(GrowthParams::default()).grow(&family).unwrap();
+        assert_eq!(tree.count(), 1 + down["root"]);
+    }
+
+    #[test]
+    fn test_ghosted_people_fade() {
+        let family = FamilyTree::from_yaml(TEST_YAML).unwrap();
+        let solid = TreeGrowth::new(GrowthParams::default()).grow(&family).unwrap();
+        let ghost_id = solid.children[0].person_id.clone();
+        let tree = TreeGrowth::new(GrowthParams::default())
+            .with_ghosted(HashSet::from([ghost_id.clone()]))
+            .grow(&family)
+            .unwrap();
+
+        for (node, before) in tree.iter_preorder().zip(solid.iter_preorder()) {
+            if node.person_id == ghost_id {
+                assert!(node.visual.opacity < before.visual.opacity);
+                assert!(node.visual.opacity <= 0.3);
+            } else {
+                assert_eq!(node.visual.opacity, before.visual.opacity);
+            }
+        }
+    }
+
+    #[test]
+    fn test_generation_height_bands() {
+        let family = FamilyTree::from_yaml(TEST_YAML).unwrap();
+        let free = TreeGrowth::new(GrowthParams::default()).grow(&family).unwrap();
+        let params = GrowthParams { generation_height: Some(2.0), ..Default::default() };
+        let tree = TreeGrowth::new(params).grow(&family).unwrap();
+
+        assert!((tree.end.y - 2.0).abs() < 1e-4);
+        for (child, free_child) in tree.children.iter().zip(&free.children) {
+            assert!((child.end.y - 4.0).abs() < 1e-4);
+            assert_eq!(child.start, tree.end);
+            // Sideways reach is kept
+            assert!(((child.end.x - child.start.x) - (free_child.end.x - free_child.start.x)).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_pipe_model_radii() {
+        let family = FamilyTree::from_yaml(TEST_YAML).unwrap();
+        let tree = TreeGrowth::new(GrowthParams::default()).grow(&family).unwrap();
+
+        let carried: f32 = tree.children.iter().map(|c| c.start_radius.powi(2)).sum();
+        assert!((tree.end_radius - carried.sqrt()).abs() < 1e-5);
+        assert!(tree.start_radius > tree.end_radius);
+
+        // A bigger family grows a thicker trunk
+        let mut bigger = family.clone();
+        for i in 0..6 {
+            bigger.add_child("left", crate::data::Person::new(&format!("k{}", i), "Kid")).unwrap();
+        }
+        let big = TreeGrowth::new(GrowthParams::default()).grow(&bigger).unwrap();
+        assert!(big.start_radius > tree.start_radius * 1.5);
+
+        // ...but only up to a point
+        let mut huge = family.clone();
+        for i in 0..400 {
+            huge.add_child("right", crate::data::Person::new(&format!("h{}", i), "Kid")).unwrap();
+        }
+        let huge = TreeGrowth::new(GrowthParams::default()).grow(&huge).unwrap();
+        let widest = GrowthParams::default().base_radius * PIPE_MAX_RADIUS;
+        assert!((huge.start_radius - widest).abs() < 1e-5);
+        for node in huge.iter_preorder() {
+            assert!(node.end_radius <= node.start_radius + 1e-5);
+            assert!(node.children.iter().all(|c| c.start_radius <= node.end_radius + 1e-5));
+        }
+
+        let fixed = GrowthParams { pipe_model: false, ..Default::default() };
+        let tree = TreeGrowth::new(fixed).grow(&family).unwrap();
+        assert!((tree.end_radius - tree.start_radius * fixed.radius_decay).abs() < 1e-5);
+    }
+
+    /// Always the middle of the range: no variation at all
+    struct Centered;
+
+    impl GrowthRng for Centered {
+        fn reseed(&mut self, _seed: u32) {}
+
+        fn bits(&self, _key: &str) -> u32 {
+            u32::MAX / 2
+        }
+    }
+
+    #[test]
+    fn test_injected_rng() {
+        let family = FamilyTree::from_yaml(TEST_YAML).unwrap();
+        let params = GrowthParams { verticality: 0.0, ..Default::default() };
+        let tree = TreeGrowth::new(params).with_rng(Centered).grow(&family).unwrap();
+
+        // No jitter: the trunk grows straight up
+        assert!(tree.end.x.abs() < 1e-4 && tree.end.z.abs() < 1e-4);
+
+        let a = TreeGrowth::new(GrowthParams::default()).with_rng(SplitMix::new(1)).grow(&family).unwrap();
+        let b = TreeGrowth::new(GrowthParams::default()).with_rng(SplitMix::new(2)).grow(&family).unwrap();
+        assert_ne!(a.children[0].end, b.children[0].end);
+    }
+
+    #[test]
+    fn test_trunk_lean_and_sway() {
+        let family = FamilyTree::from_yaml(TEST_YAML).unwrap();
+        let straight = TreeGrowth::new(GrowthParams::default()).grow(&family).unwrap();
+        let params = GrowthParams { trunk_lean: 0.3, trunk_sway: 0.1, ..Default::default() };
+        let tree = TreeGrowth::new(params).grow(&family).unwrap();
+
+        // Leans toward +x and carries the crown along
+        assert!(tree.end.x > 0.5);
+        assert_eq!(tree.children[0].start, tree.end);
+        assert!((tree.start.distance(&tree.end) - straight.start.distance(&straight.end)).abs() < 1e-4);
+
+        // An S: the waypoints swing to both sides of the leaning axis
+        let axis = (tree.end - tree.start).normalize();
+        let side = |p: Vec3| (p - tree.start).cross(&axis).z;
+        assert_eq!(tree.waypoints.len(), TRUNK_WAYPOINTS - 1);
+        assert!(tree.waypoints.iter().any(|p| side(*p) > 0.01));
+        assert!(tree.waypoints.iter().any(|p| side(*p) < -0.01));
+        assert!(straight.waypoints.is_empty());
+
+        // Layered into generation bands, the curve follows the trunk's new end
+        let layered = TreeGrowth::new(GrowthParams { generation_height: Some(1.0), ..params }).grow(&family).unwrap();
+        assert!((layered.end.y - 1.0).abs() < 1e-4);
+        assert!(layered.waypoints.iter().all(|p| p.y > layered.start.y && p.y < layered.end.y));
+    }
+
+    #[test]
+    fn test_phyllotaxis_spiral() {
+        let mut family = FamilyTree::from_yaml(TEST_YAML).unwrap();
+        for i in 0..30 {
+            family.add_child("right", crate::data::Person::new(&format!("k{}", i), "Kid")).unwrap();
+        }
+        let closest_pair = |arrangement| {
+            let params = GrowthParams { child_arrangement: arrangement, ..Default::default() };
+            let tree = TreeGrowth::new(params).grow(&family).unwrap();
+            let right = tree.children.iter().find(|c| c.person_id == "right").unwrap();
+            let ends: Vec<Vec3> = right.children.iter().map(|c| c.end).collect();
+            let mut closest = f32::MAX;
+            for (i, a) in ends.iter().enumerate() {
+                for b in &ends[i + 1..] {
+                    closest = closest.min(a.distance(b));
+                }
+            }
+            (closest, right.children.len())
+        };
+
+        let (fan, _) = closest_pair(ChildArrangement::Fan);
+        let (spiral, count) = closest_pair(ChildArrangement::Phyllotaxis);
+        assert_eq!(count, 30);
+        assert!(spiral > fan * 1.2, "{} vs {}", spiral, fan);
+
+        // Children are seated on the parent's curve, the last at its tip
+        let spiral_right = |child_pitch| {
+            let params = GrowthParams { child_arrangement: ChildArrangement::Phyllotaxis, child_pitch, ..Default::default() };
+            let tree = TreeGrowth::new(params).grow(&family).unwrap();
+            tree.children.into_iter().find(|c| c.person_id == "right").unwrap()
+        };
+        let right = spiral_right(0.15);
+        let curve: Vec<Vec3> = (0..=200).map(|i| right.point_along(i as f32 / 200.0)).collect();
+        for child in &right.children {
+            assert!(curve.iter().any(|p| p.distance(&child.start) < 0.02), "{:?} is off the curve", child.start);
+        }
+        assert!(right.children.last().unwrap().start.distance(&right.end) < 1e-4);
+        let lowest = right.children[0].start;
+        assert!(right.start.distance(&lowest) > right.curve_length() * 0.45);
+        // A nonsense pitch puts them all at the tip rather than anywhere odd
+        for pitch in [f32::NAN, -1.0] {
+            assert!(spiral_right(pitch).children.iter().all(|c| c.start.distance(&right.end) < 1e-4));
+        }
+        assert_eq!(ChildArrangement::parse("spiral"), Ok(ChildArrangement::Phyllotaxis));
+    }
+
+    #[test]
+    fn test_growth_errors() {
+        let mut family = FamilyTree::from_yaml(TEST_YAML).unwrap();
+        let growth = TreeGrowth::new(GrowthParams::default());
+
+        let small = TreeGrowth::new(GrowthParams { max_branches: 2, ..Default::default() });
+        assert_eq!(small.grow(&family).unwrap_err(), GrowthError::NodeBudgetExceeded { budget: 2 });
+
+        // A loop among the roots' ancestors is named as one
+        let mut looped = family.clone();
+        looped.people.get_mut("right").unwrap().children.push("root".to_string());
+        let rooted = TreeGrowth::new(GrowthParams { root_system: true, ..Default::default() });
+        let err = rooted.grow_from(&looped, "left").unwrap_err();
+        assert_eq!(err, GrowthError::AncestorCycle("root".to_string()));
+        assert_eq!(err.to_string(), "'root' is listed as their own ancestor");
+
+        family.people.get_mut("left").unwrap().children.push("root".to_string());
+        assert_eq!(growth.grow(&family).unwrap_err(), GrowthError::Cycle("root".to_string()));
+
+        family.people.clear();
+        assert_eq!(growth.grow(&family).unwrap_err(), GrowthError::EmptyTree);
+    }
+
+    #[test]
+    fn test_grove_of_founders() {
+        let yaml = r#"
+family:
+  name: "Grove"
+  root: "a"
+  roots: ["b", "c"]
+people:
+  - id: "a"
+    name: "A"
+    children: ["a1"]
+  - id: "a1"
+    name: "A1"
+  - id: "b"
+    name: "B"
+  - id: "c"
+    name: "C"
+    children: ["c1"]
+  - id: "c1"
+    name: "C1"
+"#;
+        let family = FamilyTree::from_yaml(yaml).unwrap();
+        let params = GrowthParams::default();
+        let tree = TreeGrowth::new(params).grow(&family).unwrap();
+
+        assert_eq!(tree.person_id, "a");
+        let trunks: Vec<&BranchNode> = std::iter::once(&tree).chain(&tree.grove).collect();
+        assert_eq!(trunks.iter().map(|t| t.person_id.as_str()).collect::<Vec<_>>(), ["a", "b", "c"]);
+        assert_eq!(tree.count(), 5);
+
+        // Evenly spaced on a circle around the origin
+        for (i, trunk) in trunks.iter().enumerate() {
+            assert!(trunk.start.y.abs() < 1e-5);
+            let next = trunks[(i + 1) % 3];
+            assert!((trunk.start.distance(&next.start) - params.grove_spacing).abs() < 1e-3);
+        }
+
+        let (min, max) = tree.bounds();
+        for node in tree.iter_preorder() {
+            assert!(node.end.x >= min.x && node.end.x <= max.x && node.end.z >= min.z && node.end.z <= max.z);
+        }
+        assert!(max.x - min.x > params.grove_spacing * 0.8);
+
+        // The branch budget covers the whole grove, not each trunk
+        let tight = GrowthParams { max_branches: 4, ..params };
+        assert_eq!(TreeGrowth::new(tight).grow(&family).unwrap_err(), GrowthError::NodeBudgetExceeded { budget: 4 });
+    }
+
+    #[test]
+    fn test_canopy_light() {
+        let family = FamilyTree::from_yaml(TEST_YAML).unwrap();
+        let mut tree = TreeGrowth::new(GrowthParams::default()).grow(&family).unwrap();
+        let (min, max) = tree.bounds();
+        let light = tree.canopy_light();
+        assert!(light.y >= min.y && light.y <= max.y);
+
+        // With nothing bright, the light sits on top of the crown
+        fn dim(node: &mut BranchNode) {
+            node.visual.luminance = 0.0;
+            node.children.iter_mut().chain(&mut node.roots).chain(&mut node.grove).for_each(dim);
+            if let Some(partner) = node.partner.as_deref_mut() {
+                dim(partner);
+            }
+        }
+        dim(&mut tree);
+        assert_eq!(tree.canopy_light().y, max.y);
+    }
+
     #[test]
     fn test_deterministic_with_seed() {
         let family = FamilyTree::from_yaml(TEST_YAML).unwrap();
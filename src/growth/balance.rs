@@ -0,0 +1,178 @@
+use crate::math::Vec3;
+use super::algorithm::BranchNode;
+
+/// Closest a child may be swung to a sibling's heading (radians)
+const MIN_SEPARATION: f32 = 0.35;
+
+/// Swing children's subtrees around their parent so the crown's weight sits
+/// over the trunk base
+///
+/// Working down from the trunk, each child's subtree turns about the vertical
+/// through its own base, heaviest first, by at most `max_turn` radians,
+/// toward whichever heading best cancels the lean of everything else. A child
+/// never swings past, or within [`MIN_SEPARATION`] of, a sibling. Weight is
+/// the number of branches, placed at their ends.
+pub(super) fn balance_crown(tree: &mut BranchNode, max_turn: f32) {
+    let axis = tree.start;
+    balance_children(tree, axis, max_turn);
+}
+
+fn balance_children(node: &mut BranchNode, axis: Vec3, max_turn: f32) {
+    if node.children.len() > 1 && max_turn > 0.0 {
+        let pivot = node.end;
+        let moments: Vec<(f32, Vec3)> = node.children.iter().map(|c| moment(c, pivot)).collect();
+        let mass: f32 = moments.iter().map(|(m, _)| m).sum();
+        // Lean of this subtree away from the trunk, before any turning
+        let mut lean = horizontal(pivot - axis).scale(mass) + moments.iter().fold(Vec3::ZERO, |sum, (_, r)| sum + *r);
+
+        let mut order: Vec<usize> = (0..moments.len()).collect();
+        order.sort_by(|&a, &b| moments[b].0.total_cmp(&moments[a].0));
+        for i in order {
+            let r = moments[i].1;
+            let rest = lean - r;
+            if r.length_squared() < 1e-10 || rest.length_squared() < 1e-10 {
+                continue;
+            }
+            // Point this child's moment against the rest, as far as allowed
+            let wanted = heading(-rest) - heading(r);
+            let turn = wrap(wanted).clamp(-max_turn, max_turn);
+            let turn = clear_of_siblings(&node.children, i, turn);
+            let child = &mut node.children[i];
+            let base = child.start;
+            rotate_subtree(child, base, turn);
+            lean = rest + moment(child, pivot).1;
+        }
+    }
+    for child in &mut node.children {
+        balance_children(child, axis, max_turn);
+    }
+}
+
+/// `turn` cut short so child `i` doesn't swing onto or past a sibling's
+/// heading
+fn clear_of_siblings(children: &[BranchNode], i: usize, turn: f32) -> f32 {
+    let tau = std::f32::consts::TAU;
+    let Some(own) = branch_heading(&children[i]) else {
+        return turn;
+    };
+    let room = children
+        .iter()
+        .enumerate()
+        .filter(|&(j, _)| j != i)
+        .filter_map(|(_, sibling)| branch_heading(sibling))
+        .map(|other| {
+            // Angle to the sibling going the way of the turn
+            let gap = if turn > 0.0 { other - own } else { own - other };
+            (gap.rem_euclid(tau) - MIN_SEPARATION).max(0.0)
+        })
+        .fold(f32::MAX, f32::min);
+    turn.clamp(-room, room)
+}
+
+/// Heading of a branch around the up axis, if it leans at all
+fn branch_heading(node: &BranchNode) -> Option<f32> {
+    let run = horizontal(node.end - node.start);
+    (run.length_squared() > 1e-8).then(|| heading(run))
+}
+
+/// Branch count and horizontal first moment of a subtree about `pivot`
+fn moment(node: &BranchNode, pivot: Vec3) -> (f32, Vec3) {
+    node.children.iter().fold((1.0, horizontal(node.end - pivot)), |(m, r), child| {
+        let (cm, cr) = moment(child, pivot);
+        (m + cm, r + cr)
+    })
+}
+
+fn rotate_subtree(node: &mut BranchNode, pivot: Vec3, angle: f32) {
+    node.start = pivot + rotate(node.start - pivot, angle);
+    node.end = pivot + rotate(node.end - pivot, angle);
+    for point in &mut node.waypoints {
+        *point = pivot + rotate(*point - pivot, angle);
+    }
+    node.start_direction = rotate(node.start_direction, angle);
+    node.end_direction = rotate(node.end_direction, angle);
+    for child in &mut node.children {
+        rotate_subtree(child, pivot, angle);
+    }
+}
+
+fn horizontal(v: Vec3) -> Vec3 {
+    Vec3::new(v.x, 0.0, v.z)
+}
+
+/// Angle of a vector around the up axis, matching [`rotate`]
+fn heading(v: Vec3) -> f32 {
+    v.z.atan2(v.x)
+}
+
+fn wrap(angle: f32) -> f32 {
+    let tau = std::f32::consts::TAU;
+    (angle + std::f32::consts::PI).rem_euclid(tau) - std::f32::consts::PI
+}
+
+/// Rotate around the up axis (x toward z)
+fn rotate(v: Vec3, angle: f32) -> Vec3 {
+    let (sin, cos) = angle.sin_cos();
+    Vec3::new(v.x * cos - v.z * sin, v.y, v.x * sin + v.z * cos)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::{FamilyTree, Person};
+    use crate::growth::{BranchNode, GrowthParams, TreeGrowth};
+    use crate::math::Vec3;
+
+    fn lopsided() -> FamilyTree {
+        let yaml = r#"
+family:
+  name: "Lopsided"
+  root: "root"
+people:
+  - id: "root"
+    name: "Root"
+    children: ["big", "small"]
+  - id: "big"
+    name: "Big"
+  - id: "small"
+    name: "Small"
+"#;
+        let mut family = FamilyTree::from_yaml(yaml).unwrap();
+        for i in 0..8 {
+            family.add_child("big", Person::new(&format!("k{}", i), "Kid")).unwrap();
+        }
+        family
+    }
+
+    fn lean(tree: &BranchNode) -> f32 {
+        let sum = tree.iter_preorder().fold(Vec3::ZERO, |sum, n| sum + Vec3::new(n.end.x, 0.0, n.end.z));
+        (sum.scale(1.0 / tree.count() as f32) - Vec3::new(tree.start.x, 0.0, tree.start.z)).length()
+    }
+
+    #[test]
+    fn test_balancing_centers_crown() {
+        let family = lopsided();
+        let free = TreeGrowth::new(GrowthParams::default()).grow(&family).unwrap();
+        let params = GrowthParams { balance_crown: true, ..Default::default() };
+        let balanced = TreeGrowth::new(params).grow(&family).unwrap();
+
+        assert!(lean(&balanced) < lean(&free) * 0.8, "{} vs {}", lean(&balanced), lean(&free));
+        // Branches only swing around their parents; lengths are unchanged
+        for (a, b) in balanced.iter_preorder().zip(free.iter_preorder()) {
+            assert!((a.start.distance(&a.end) - b.start.distance(&b.end)).abs() < 1e-4);
+            assert!((a.end.y - b.end.y).abs() < 1e-4);
+        }
+        // ...and never onto a sibling
+        let apart = |a: &BranchNode, b: &BranchNode| {
+            let gap = (super::branch_heading(a).unwrap() - super::branch_heading(b).unwrap()).rem_euclid(std::f32::consts::TAU);
+            gap.min(std::f32::consts::TAU - gap)
+        };
+        for (a, b) in balanced.iter_preorder().zip(free.iter_preorder()) {
+            for i in 0..a.children.len() {
+                for j in i + 1..a.children.len() {
+                    let before = apart(&b.children[i], &b.children[j]);
+                    assert!(apart(&a.children[i], &a.children[j]) >= before.min(super::MIN_SEPARATION) - 1e-3);
+                }
+            }
+        }
+    }
+}
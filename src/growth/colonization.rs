@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use crate::data::{FamilyTree, Person};
+use crate::math::Vec3;
+use super::algorithm::{BranchNode, Lineage, TreeGrowth};
+use super::rng::XorShift;
+
+/// How strongly a branch turns toward its attractors (0 = ignore, 1 = aim straight at them)
+const ATTRACTION: f32 = 0.7;
+/// Attractors closer than this fraction of a segment's length to its end are consumed
+const KILL_DISTANCE: f32 = 0.6;
+
+/// Grow the family by space colonization
+///
+/// A cloud of attractor points fills the crown envelope. Each branch aims at
+/// the centroid of the attractors in its territory, consumes the ones it
+/// reaches, and splits what is left between its children in proportion to
+/// their descendant counts. Territories never overlap, so even large families
+/// fill the crown without branches crossing.
+pub(super) fn grow_colonized(
+    growth: &TreeGrowth,
+    family: &FamilyTree,
+    reach: &HashMap<String, usize>,
+    root: &Person,
+    origin: Vec3,
+) -> BranchNode {
+    let attractors = crown_attractors(growth, family, origin, 1 + reach[&root.id]);
+    grow_node(growth, family, reach, root, origin, Vec3::UP, attractors, 0)
+}
+
+/// Scatter attractors through an ellipsoid above the trunk
+fn crown_attractors(growth: &TreeGrowth, family: &FamilyTree, origin: Vec3, people: usize) -> Vec<Vec3> {
+    let params = &growth.params;
+    let depth = family.max_depth();
+    let height: f32 = (0..depth).map(|g| params.base_height * params.height_decay.powi(g as i32)).sum();
+    let trunk = params.base_height;
+    let crown = (height - trunk).max(trunk);
+    let center = origin + Vec3::UP.scale(trunk + crown * 0.5);
+    let radii = Vec3::new(crown * 0.6, crown * 0.5, crown * 0.6);
+
+    let count = (people * params.attractors_per_person).clamp(32, 8192);
+    let mut stream = XorShift::new(growth.rng().bits("attractors"));
+    let mut next = move || stream.signed();
+
+    let mut points = Vec::with_capacity(count);
+    while points.len() < count {
+        let p = Vec3::new(next(), next(), next());
+        if p.length_squared() <= 1.0 {
+            points.push(center + Vec3::new(p.x * radii.x, p.y * radii.y, p.z * radii.z));
+        }
+    }
+    points
+}
+
+#[allow(clippy::too_many_arguments)]
+fn grow_node(
+    growth: &TreeGrowth,
+    family: &FamilyTree,
+    reach: &HashMap<String, usize>,
+    person: &Person,
+    start: Vec3,
+    direction: Vec3,
+    territory: Vec<Vec3>,
+    generation: usize,
+) -> BranchNode {
+    let params = &growth.params;
+    let visual = growth.person_visual(person);
+    let (length, start_radius) = growth.segment_size(&visual, generation);
+
+    // The trunk always rises; higher branches steer toward their attractors
+    let end_direction = if generation == 0 || territory.is_empty() {
+        direction
+    } else {
+        let centroid = territory.iter().fold(Vec3::ZERO, |acc, p| acc + *p).scale(1.0 / territory.len() as f32);
+        let pull = (centroid - start).normalize();
+        growth.in_wind(growth.toward_light(direction.lerp(&pull, ATTRACTION).normalize()), generation)
+    };
+    let end = start + end_direction.scale(length);
+
+    let kill = length * KILL_DISTANCE;
+    let remaining: Vec<Vec3> = territory.into_iter().filter(|p| p.distance(&end) > kill).collect();
+
+    let (children_data, hidden_descendants) = growth.next_generation(family, reach, person, generation, Lineage::Descendants);
+    let weights: Vec<usize> = children_data.iter().map(|c| 1 + reach[&c.id]).collect();
+    let shares = split_territory(remaining, end, end_direction, &weights);
+
+    let children = children_data
+        .iter()
+        .zip(shares)
+        .map(|(child, share)| grow_node(growth, family, reach, child, end, end_direction, share, generation + 1))
+        .collect();
+
+    BranchNode {
+        person_id: person.id.clone(),
+        visual,
+        start,
+        end,
+        start_direction: direction,
+        end_direction,
+        start_radius,
+        end_radius: start_radius * params.radius_decay,
+        generation,
+        event_positions: person.event_positions(),
+        children,
+        roots: Vec::new(),
+        hidden_descendants,
+        partner: None,
+        waypoints: Vec::new(),
+        grove: Vec::new(),
+    }
+}
+
+/// Divide points into angular sectors around `axis`, sized by `weights`
+fn split_territory(mut points: Vec<Vec3>, apex: Vec3, axis: Vec3, weights: &[usize]) -> Vec<Vec<Vec3>> {
+    if weights.is_empty() {
+        return Vec::new();
+    }
+    let u = axis.perpendicular().normalize();
+    let v = axis.cross(&u).normalize();
+    let angle = |p: &Vec3| {
+        let d = *p - apex;
+        d.dot(&v).atan2(d.dot(&u))
+    };
+    points.sort_by(|a, b| angle(a).total_cmp(&angle(b)));
+
+    let total: usize = weights.iter().sum();
+    let mut shares = Vec::with_capacity(weights.len());
+    let mut taken = 0;
+    let mut cumulative = 0;
+    for &weight in weights {
+        cumulative += weight;
+        let upto = points.len() * cumulative / total;
+        shares.push(points[taken..upto].to_vec());
+        taken = upto;
+    }
+    shares
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::growth::{GrowthParams, LayoutMode};
+
+    fn family(children: usize) -> FamilyTree {
+        let mut yaml = String::from("family:\n  name: \"Crown\"\n  root: \"root\"\npeople:\n  - id: \"root\"\n    name: \"Root\"\n    children: [");
+        let ids: Vec<String> = (0..children).map(|i| format!("\"c{}\"", i)).collect();
+        yaml.push_str(&ids.join(", "));
+        yaml.push_str("]\n");
+        for i in 0..children {
+            yaml.push_str(&format!("  - id: \"c{}\"\n    name: \"C{}\"\n    children: [\"g{}\"]\n", i, i, i));
+            yaml.push_str(&format!("  - id: \"g{}\"\n    name: \"G{}\"\n", i, i));
+        }
+        FamilyTree::from_yaml(&yaml).unwrap()
+    }
+
+    #[test]
+    fn test_split_territory_by_weight() {
+        let points: Vec<Vec3> = (0..100)
+            .map(|i| {
+                let a = i as f32 / 100.0 * std::f32::consts::TAU;
+                Vec3::new(a.cos(), 1.0, a.sin())
+            })
+            .collect();
+        let shares = split_territory(points, Vec3::ZERO, Vec3::UP, &[3, 1]);
+
+        assert_eq!(shares[0].len(), 75);
+        assert_eq!(shares[1].len(), 25);
+    }
+
+    #[test]
+    fn test_colonized_crown_spreads_out() {
+        let family = family(6);
+        let params = GrowthParams { layout: LayoutMode::Colonized, ..Default::default() };
+        let tree = TreeGrowth::new(params).grow(&family).unwrap();
+
+        assert_eq!(tree.count(), 13);
+        assert!(tree.end.y > tree.start.y);
+        // Every pair of first-generation branches heads somewhere different
+        let tips: Vec<Vec3> = tree.children.iter().map(|c| c.end).collect();
+        for i in 0..tips.len() {
+            for j in (i + 1)..tips.len() {
+                assert!(tips[i].distance(&tips[j]) > 0.1);
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use std::fmt;
+
+/// Why a family could not be grown into a tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GrowthError {
+    /// The family has no people at all
+    EmptyTree,
+    /// The trunk person (root or focus) is not in the family
+    MissingRoot(String),
+    /// A person is listed among their own descendants
+    Cycle(String),
+    /// A person is listed among their own ancestors (met growing roots)
+    AncestorCycle(String),
+    /// Growing would take more branches than `GrowthParams::max_branches`
+    NodeBudgetExceeded { budget: usize },
+}
+
+impl fmt::Display for GrowthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GrowthError::EmptyTree => write!(f, "The family has no people to grow"),
+            GrowthError::MissingRoot(id) => write!(f, "Root person '{}' not found", id),
+            GrowthError::Cycle(id) => write!(f, "'{}' is listed as their own descendant", id),
+            GrowthError::AncestorCycle(id) => write!(f, "'{}' is listed as their own ancestor", id),
+            GrowthError::NodeBudgetExceeded { budget } => write!(
+                f,
+                "The tree would need more than {} branches; limit the generations shown",
+                budget
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GrowthError {}
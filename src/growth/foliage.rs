@@ -0,0 +1,116 @@
+use crate::math::Vec3;
+use super::algorithm::{BranchNode, TreeGrowth};
+
+/// A sphere of leaves at the tip of a childless branch
+#[derive(Debug, Clone, PartialEq)]
+pub struct FoliageCluster {
+    /// Person whose branch carries the cluster
+    pub person_id: String,
+    pub center: Vec3,
+    pub radius: f32,
+    /// Hue of the branch, so leaves can match their line (degrees)
+    pub hue: f32,
+    /// Brightness of the branch (0.0 to 1.0), for glowing canopies
+    pub glow: f32,
+}
+
+/// Clusters around every crown tip, sized to the tip's branch
+///
+/// Tips that are buds are skipped; their line is not grown yet.
+pub(super) fn foliage_clusters(growth: &TreeGrowth, tree: &BranchNode) -> Vec<FoliageCluster> {
+    let mut clusters = Vec::new();
+    for trunk in std::iter::once(tree).chain(&tree.grove) {
+        collect(growth, trunk, &mut clusters);
+    }
+    clusters
+}
+
+fn collect(growth: &TreeGrowth, node: &BranchNode, clusters: &mut Vec<FoliageCluster>) {
+    if !node.children.is_empty() {
+        for child in &node.children {
+            collect(growth, child, clusters);
+        }
+        return;
+    }
+    let params = &growth.params;
+    if node.hidden_descendants > 0 || params.foliage_density <= 0.0 {
+        return;
+    }
+
+    let length = node.start.distance(&node.end);
+    let count = (params.foliage_density * (0.6 + 0.4 * node.visual.branch_thickness)).round().max(1.0) as usize;
+    let radius = length * params.foliage_size;
+    let axis = node.end_direction.normalize();
+    let u = axis.perpendicular();
+    let v = axis.cross(&u);
+
+    for i in 0..count {
+        // Deterministic scatter so canopies don't shimmer between rebuilds
+        let hash = growth.rng().bits(&format!("{}#leaf{}", node.person_id, i));
+        let unit = |shift: u32| ((hash >> shift) & 0x3ff) as f32 / 1023.0;
+        let angle = unit(0) * std::f32::consts::TAU;
+        let offset = if count == 1 { 0.0 } else { radius * (0.4 + 0.5 * unit(10)) };
+        let lift = radius * (0.3 + 0.5 * unit(20));
+
+        let center = node.end + axis.scale(lift) + (u.scale(angle.cos()) + v.scale(angle.sin())).scale(offset);
+        clusters.push(FoliageCluster {
+            person_id: node.person_id.clone(),
+            center,
+            radius: radius * (0.7 + 0.3 * unit(5)),
+            hue: node.visual.hue_shift,
+            glow: node.visual.glow_intensity,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::FamilyTree;
+    use crate::growth::{GrowthParams, TreeGrowth};
+
+    const YAML: &str = r#"
+family:
+  name: "Leaves"
+  root: "root"
+people:
+  - id: "root"
+    name: "Root"
+    children: ["a", "b"]
+  - id: "a"
+    name: "A"
+  - id: "b"
+    name: "B"
+    children: ["b1"]
+  - id: "b1"
+    name: "B1"
+"#;
+
+    #[test]
+    fn test_clusters_at_tips_only() {
+        let family = FamilyTree::from_yaml(YAML).unwrap();
+        let growth = TreeGrowth::new(GrowthParams::default());
+        let tree = growth.grow(&family).unwrap();
+        let clusters = growth.foliage(&tree);
+
+        assert!(!clusters.is_empty());
+        assert!(clusters.iter().all(|c| c.person_id == "a" || c.person_id == "b1"));
+        let tip = tree.iter_preorder().find(|n| n.person_id == "a").unwrap();
+        for cluster in clusters.iter().filter(|c| c.person_id == "a") {
+            assert!(cluster.center.distance(&tip.end) < tip.start.distance(&tip.end));
+            assert!(cluster.radius > 0.0);
+        }
+        assert_eq!(clusters, growth.foliage(&tree));
+    }
+
+    #[test]
+    fn test_density_controls_count() {
+        let family = FamilyTree::from_yaml(YAML).unwrap();
+        let count = |density: f32| {
+            let growth = TreeGrowth::new(GrowthParams { foliage_density: density, ..Default::default() });
+            growth.foliage(&growth.grow(&family).unwrap()).len()
+        };
+
+        assert_eq!(count(0.0), 0);
+        assert!(count(6.0) > count(2.0));
+    }
+}
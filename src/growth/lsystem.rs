@@ -0,0 +1,376 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use crate::data::{FamilyTree, Person};
+use crate::math::Vec3;
+use super::algorithm::{BranchNode, Lineage, TreeGrowth};
+
+/// Turn angle used when a command has no argument (degrees)
+const DEFAULT_ANGLE: f32 = 25.0;
+
+/// One turtle instruction
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Command {
+    /// `F(s)`: move forward `s` segment lengths
+    Forward(f32),
+    /// `+(a)` / `-(a)`: turn left or right
+    Yaw(f32),
+    /// `&(a)` / `^(a)`: pitch down (away from the parent) or up
+    Pitch(f32),
+    /// `/(a)` / `\(a)`: roll around the heading
+    Roll(f32),
+    /// `*(a)`: roll by `a` times the sibling index, fanning siblings out
+    SiblingRoll(f32),
+    /// `~(t)`: bend the heading toward the ground by `t` (0 to 1)
+    Droop(f32),
+}
+
+/// Curated L-system silhouettes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LSystemPreset {
+    /// Broad, spreading crown
+    #[default]
+    Oak,
+    /// Arching branches that weep toward the ground
+    Willow,
+    /// Narrow columnar crown
+    Poplar,
+}
+
+impl LSystemPreset {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "oak" => Ok(Self::Oak),
+            "willow" => Ok(Self::Willow),
+            "poplar" => Ok(Self::Poplar),
+            other => Err(format!("Unknown L-system style '{}'", other)),
+        }
+    }
+
+    /// Turtle program per generation (the last repeats for deeper ones)
+    pub fn rules(&self) -> &'static [&'static str] {
+        match self {
+            Self::Oak => &["F", "*(137.5)&(55)F", "*(137.5)&(40)F^(10)"],
+            Self::Willow => &["F(1.1)", "*(137.5)&(45)F", "*(137.5)&(20)F~(0.6)"],
+            Self::Poplar => &["F(1.3)", "*(137.5)&(14)F(0.9)", "*(137.5)&(8)F(0.8)"],
+        }
+    }
+}
+
+/// A turtle-graphics grammar that shapes each branch by generation
+///
+/// The family decides where branches split; the program for a branch's
+/// generation decides which way it turns and how far it reaches. Segment
+/// lengths come from the usual biography-driven sizes, so `F` is relative,
+/// and a program with several `F`s bends the branch through each step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LSystem {
+    generations: Vec<Vec<Command>>,
+}
+
+impl LSystem {
+    /// Parse one program per generation, e.g. `["F", "*(137.5)&(40)F~(0.2)"]`
+    pub fn parse(rules: &[&str]) -> Result<Self, String> {
+        if rules.is_empty() {
+            return Err("L-system needs at least one rule".to_string());
+        }
+        let generations = rules.iter().map(|r| parse_program(r)).collect::<Result<_, _>>()?;
+        Ok(Self { generations })
+    }
+
+    /// Derive each generation's program by rewriting: generation 0 runs
+    /// `axiom`, and every later one rewrites the previous program once,
+    /// replacing each symbol that has a production (and its argument) with
+    /// that production; the last of `generations` repeats for deeper ones
+    pub fn rewrite(axiom: &str, productions: &[(char, &str)], generations: usize) -> Result<Self, String> {
+        for (_, production) in productions {
+            parse_program(production)?;
+        }
+        let mut program = tokens(axiom)?;
+        let mut rules = Vec::with_capacity(generations.max(1));
+        rules.push(program.concat());
+        for _ in 1..generations {
+            program = program
+                .into_iter()
+                .flat_map(|token| {
+                    let symbol = token.chars().next().expect("tokens are never empty");
+                    match productions.iter().find(|(from, _)| *from == symbol) {
+                        Some((_, production)) => tokens(production).expect("checked above"),
+                        None => vec![token],
+                    }
+                })
+                .collect();
+            rules.push(program.concat());
+        }
+        let rules: Vec<&str> = rules.iter().map(String::as_str).collect();
+        Self::parse(&rules)
+    }
+
+    pub fn preset(preset: LSystemPreset) -> Self {
+        Self::parse(preset.rules()).expect("preset rules are valid")
+    }
+
+    fn program(&self, generation: usize) -> &[Command] {
+        &self.generations[generation.min(self.generations.len() - 1)]
+    }
+}
+
+/// Split a rule into symbols, each with its `(argument)` if it has one
+fn tokens(rule: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = rule.chars().filter(|c| !c.is_whitespace()).peekable();
+    while let Some(symbol) = chars.next() {
+        if symbol == '(' || symbol == ')' {
+            return Err(format!("Unmatched '{}' in rule '{}'", symbol, rule));
+        }
+        let mut token = symbol.to_string();
+        if chars.peek() == Some(&'(') {
+            token.push(chars.next().expect("peeked"));
+            loop {
+                match chars.next() {
+                    Some(')') => break,
+                    Some('(') | None => return Err(format!("Unterminated '(' in rule '{}'", rule)),
+                    Some(c) => token.push(c),
+                }
+            }
+            token.push(')');
+        }
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+fn parse_program(rule: &str) -> Result<Vec<Command>, String> {
+    let mut commands = Vec::new();
+    for token in tokens(rule)? {
+        let mut chars = token.chars();
+        let symbol = chars.next().expect("tokens are never empty");
+        let arg = match chars.as_str().strip_prefix('(').and_then(|rest| rest.strip_suffix(')')) {
+            Some(text) => Some(text.parse::<f32>().map_err(|_| format!("Bad argument '{}' in rule '{}'", text, rule))?),
+            None => None,
+        };
+        let angle = arg.unwrap_or(DEFAULT_ANGLE).to_radians();
+        commands.push(match symbol {
+            'F' => Command::Forward(arg.unwrap_or(1.0)),
+            '+' => Command::Yaw(angle),
+            '-' => Command::Yaw(-angle),
+            '&' => Command::Pitch(angle),
+            '^' => Command::Pitch(-angle),
+            '/' => Command::Roll(angle),
+            '\\' => Command::Roll(-angle),
+            '*' => Command::SiblingRoll(angle),
+            '~' => Command::Droop(arg.unwrap_or(0.2).clamp(0.0, 1.0)),
+            other => return Err(format!("Unknown symbol '{}' in rule '{}'", other, rule)),
+        });
+    }
+    Ok(commands)
+}
+
+/// Rotate `v` around unit `axis` by `angle` (Rodrigues)
+fn rotate(v: Vec3, axis: Vec3, angle: f32) -> Vec3 {
+    let (sin, cos) = angle.sin_cos();
+    v.scale(cos) + axis.cross(&v).scale(sin) + axis.scale(axis.dot(&v) * (1.0 - cos))
+}
+
+/// Turtle orientation: heading, left and up
+struct Turtle {
+    heading: Vec3,
+    left: Vec3,
+    up: Vec3,
+}
+
+impl Turtle {
+    fn facing(heading: Vec3) -> Self {
+        let heading = heading.normalize();
+        let left = heading.perpendicular();
+        Self { heading, left, up: heading.cross(&left) }
+    }
+
+    fn turn(&mut self, command: Command, sibling: usize) {
+        match command {
+            Command::Yaw(a) => {
+                self.heading = rotate(self.heading, self.up, a);
+                self.left = rotate(self.left, self.up, a);
+            }
+            Command::Pitch(a) => {
+                self.heading = rotate(self.heading, self.left, a);
+                self.up = rotate(self.up, self.left, a);
+            }
+            Command::Roll(a) => {
+                self.left = rotate(self.left, self.heading, a);
+                self.up = rotate(self.up, self.heading, a);
+            }
+            Command::SiblingRoll(a) => self.turn(Command::Roll(a * sibling as f32), 0),
+            Command::Droop(t) => *self = Self::facing(self.heading.lerp(&-Vec3::UP, t)),
+            Command::Forward(_) => {}
+        }
+    }
+}
+
+pub(super) fn grow_lsystem(
+    growth: &TreeGrowth,
+    lsystem: &LSystem,
+    family: &FamilyTree,
+    reach: &HashMap<String, usize>,
+    root: &Person,
+    origin: Vec3,
+) -> BranchNode {
+    grow_node(growth, lsystem, family, reach, root, origin, Vec3::UP, 0, 0)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn grow_node(
+    growth: &TreeGrowth,
+    lsystem: &LSystem,
+    family: &FamilyTree,
+    reach: &HashMap<String, usize>,
+    person: &Person,
+    start: Vec3,
+    direction: Vec3,
+    sibling: usize,
+    generation: usize,
+) -> BranchNode {
+    let params = &growth.params;
+    let visual = growth.person_visual(person);
+    let (length, start_radius) = growth.segment_size(&visual, generation);
+
+    let mut turtle = Turtle::facing(direction);
+    // Small per-person roll so identical rules don't look mechanical
+    let jitter = (growth.rng().unit(&person.id) - 0.5) * params.angle_variance;
+    turtle.turn(Command::Roll(jitter), 0);
+
+    let mut position = start;
+    let mut start_direction = None;
+    // Where each step but the last ends, for the branch curve to pass through
+    let mut waypoints = Vec::new();
+    for &command in lsystem.program(generation) {
+        if let Command::Forward(step) = command {
+            if start_direction.is_some() {
+                waypoints.push(position);
+            }
+            start_direction.get_or_insert(turtle.heading);
+            position = position + turtle.heading.scale(length * step);
+        } else {
+            turtle.turn(command, sibling);
+        }
+    }
+    let start_direction = start_direction.unwrap_or(turtle.heading);
+    if position == start {
+        position = start + turtle.heading.scale(length);
+    }
+
+    let (children_data, hidden_descendants) = growth.next_generation(family, reach, person, generation, Lineage::Descendants);
+    let children = children_data
+        .iter()
+        .enumerate()
+        .map(|(i, child)| grow_node(growth, lsystem, family, reach, child, position, turtle.heading, i, generation + 1))
+        .collect();
+
+    BranchNode {
+        person_id: person.id.clone(),
+        visual,
+        start,
+        end: position,
+        start_direction,
+        end_direction: turtle.heading,
+        start_radius,
+        end_radius: start_radius * params.radius_decay,
+        generation,
+        event_positions: person.event_positions(),
+        children,
+        roots: Vec::new(),
+        hidden_descendants,
+        partner: None,
+        waypoints,
+        grove: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::growth::{GrowthParams, LayoutMode};
+
+    const YAML: &str = r#"
+family:
+  name: "Shapes"
+  root: "root"
+people:
+  - id: "root"
+    name: "Root"
+    children: ["a", "b", "c"]
+  - id: "a"
+    name: "A"
+    children: ["a1", "a2"]
+  - id: "b"
+    name: "B"
+  - id: "c"
+    name: "C"
+  - id: "a1"
+    name: "A1"
+  - id: "a2"
+    name: "A2"
+"#;
+
+    fn grow(preset: LSystemPreset) -> BranchNode {
+        let family = FamilyTree::from_yaml(YAML).unwrap();
+        let params = GrowthParams { layout: LayoutMode::LSystem, lsystem: preset, ..Default::default() };
+        TreeGrowth::new(params).grow(&family).unwrap()
+    }
+
+    /// Widest horizontal reach over height of the whole tree
+    fn aspect(tree: &BranchNode) -> f32 {
+        let width = tree.iter_preorder().map(|n| n.end.x.hypot(n.end.z)).fold(0.0, f32::max);
+        let height = tree.iter_preorder().map(|n| n.end.y).fold(0.0, f32::max);
+        width / height
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let system = LSystem::parse(&["F(2)", "*(90)&~(0.5)F"]).unwrap();
+        assert_eq!(system.program(0), &[Command::Forward(2.0)]);
+        assert_eq!(system.program(5).len(), 4);
+        assert_eq!(system.program(5)[1], Command::Pitch(DEFAULT_ANGLE.to_radians()));
+
+        assert!(LSystem::parse(&["F(x)"]).is_err());
+        assert!(LSystem::parse(&["Q"]).is_err());
+        assert!(LSystem::parse(&[]).is_err());
+        // Parentheses must pair up
+        for unbalanced in ["F(2", "&(40F", "F(2))", "F)", "F((2)"] {
+            assert!(LSystem::parse(&[unbalanced]).is_err(), "accepted {}", unbalanced);
+        }
+    }
+
+    #[test]
+    fn test_rewriting() {
+        let system = LSystem::rewrite("&(30)F", &[('F', "F^(10)F(0.5)")], 3).unwrap();
+        let forwards = |generation| {
+            system.program(generation).iter().filter(|c| matches!(c, Command::Forward(_))).count()
+        };
+        // Every rewrite doubles the steps; deeper generations keep the last
+        assert_eq!([forwards(0), forwards(1), forwards(2), forwards(9)], [1, 2, 4, 4]);
+        assert_eq!(system.program(1)[0], Command::Pitch(30f32.to_radians()));
+        assert!(LSystem::rewrite("F", &[('F', "F(")], 2).is_err());
+
+        // The turtle's path bends each branch through its steps
+        let family = FamilyTree::from_yaml(YAML).unwrap();
+        let params = GrowthParams { layout: LayoutMode::LSystem, ..Default::default() };
+        let tree = TreeGrowth::new(params).with_lsystem(system).grow(&family).unwrap();
+        assert!(tree.waypoints.is_empty());
+        let a1 = tree.iter_preorder().find(|n| n.person_id == "a1").unwrap();
+        assert_eq!(a1.waypoints.len(), 3);
+    }
+
+    #[test]
+    fn test_presets_change_silhouette() {
+        let oak = grow(LSystemPreset::Oak);
+        let poplar = grow(LSystemPreset::Poplar);
+        let willow = grow(LSystemPreset::Willow);
+
+        assert_eq!(oak.count(), 6);
+        assert!(aspect(&poplar) < aspect(&oak));
+        // Willow tips hang below their parents
+        let a1 = willow.iter_preorder().find(|n| n.person_id == "a1").unwrap();
+        assert!(a1.end_direction.y < 0.0);
+    }
+}
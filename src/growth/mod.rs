@@ -1,3 +1,16 @@
 pub mod algorithm;
+pub mod lsystem;
+mod radial;
+mod colonization;
+mod balance;
+pub mod error;
+pub mod foliage;
+mod partners;
+mod overrides;
+pub mod rng;
 
-pub use algorithm::{TreeGrowth, GrowthParams, BranchNode};
+pub use algorithm::{TreeGrowth, GrowthParams, BranchNode, ChildArrangement, HueMode, LayoutMode};
+pub use lsystem::{LSystem, LSystemPreset};
+pub use foliage::FoliageCluster;
+pub use rng::{GrowthRng, StringHash, SplitMix};
+pub use error::GrowthError;
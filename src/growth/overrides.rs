@@ -0,0 +1,195 @@
+//! Growth settings a family file or host config may change
+//!
+//! Only layout and shape keys are read; privacy, hue, sibling order and the
+//! other host-owned settings can't be changed by an uploaded file.
+
+use serde::Deserialize;
+use std::f32::consts::{PI, TAU};
+use crate::math::Vec3;
+use super::algorithm::{ChildArrangement, GrowthParams, LayoutMode};
+use super::lsystem::LSystemPreset;
+
+/// The layout and shape keys present in a `growth:` mapping
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GrowthOverrides {
+    base_height: Option<f32>,
+    height_decay: Option<f32>,
+    base_radius: Option<f32>,
+    radius_decay: Option<f32>,
+    branch_spread: Option<f32>,
+    angle_variance: Option<f32>,
+    curvature: Option<f32>,
+    verticality: Option<f32>,
+    layout: Option<LayoutMode>,
+    fan_angle: Option<f32>,
+    attractors_per_person: Option<usize>,
+    lsystem: Option<LSystemPreset>,
+    descendant_weight: Option<f32>,
+    root_system: Option<bool>,
+    root_scale: Option<f32>,
+    light_direction: Option<Vec3>,
+    phototropism: Option<f32>,
+    wind_direction: Option<Vec3>,
+    wind_strength: Option<f32>,
+    foliage_density: Option<f32>,
+    foliage_size: Option<f32>,
+    generation_height: Option<f32>,
+    pipe_model: Option<bool>,
+    balance_crown: Option<bool>,
+    trunk_lean: Option<f32>,
+    trunk_heading: Option<f32>,
+    trunk_sway: Option<f32>,
+    trunk_bends: Option<f32>,
+    child_arrangement: Option<ChildArrangement>,
+    divergence_angle: Option<f32>,
+    child_pitch: Option<f32>,
+    grove_spacing: Option<f32>,
+}
+
+impl GrowthOverrides {
+    fn apply(self, params: &mut GrowthParams) {
+        fn set<T>(field: &mut T, value: Option<T>) {
+            if let Some(value) = value {
+                *field = value;
+            }
+        }
+        set(&mut params.base_height, self.base_height);
+        set(&mut params.height_decay, self.height_decay);
+        set(&mut params.base_radius, self.base_radius);
+        set(&mut params.radius_decay, self.radius_decay);
+        set(&mut params.branch_spread, self.branch_spread);
+        set(&mut params.angle_variance, self.angle_variance);
+        set(&mut params.curvature, self.curvature);
+        set(&mut params.verticality, self.verticality);
+        set(&mut params.layout, self.layout);
+        set(&mut params.fan_angle, self.fan_angle);
+        set(&mut params.attractors_per_person, self.attractors_per_person);
+        set(&mut params.lsystem, self.lsystem);
+        set(&mut params.descendant_weight, self.descendant_weight);
+        set(&mut params.root_system, self.root_system);
+        set(&mut params.root_scale, self.root_scale);
+        set(&mut params.light_direction, self.light_direction);
+        set(&mut params.phototropism, self.phototropism);
+        set(&mut params.wind_direction, self.wind_direction);
+        set(&mut params.wind_strength, self.wind_strength);
+        set(&mut params.foliage_density, self.foliage_density);
+        set(&mut params.foliage_size, self.foliage_size);
+        set(&mut params.generation_height, self.generation_height.map(Some));
+        set(&mut params.pipe_model, self.pipe_model);
+        set(&mut params.balance_crown, self.balance_crown);
+        set(&mut params.trunk_lean, self.trunk_lean);
+        set(&mut params.trunk_heading, self.trunk_heading);
+        set(&mut params.trunk_sway, self.trunk_sway);
+        set(&mut params.trunk_bends, self.trunk_bends);
+        set(&mut params.child_arrangement, self.child_arrangement);
+        set(&mut params.divergence_angle, self.divergence_angle);
+        set(&mut params.child_pitch, self.child_pitch);
+        set(&mut params.grove_spacing, self.grove_spacing);
+    }
+}
+
+impl GrowthParams {
+    /// Merge the layout and shape keys of a YAML/JSON mapping (a family
+    /// file's `growth:` section) into these params
+    ///
+    /// Keys the mapping leaves out keep their current values, and host-owned
+    /// settings (privacy, hues, sibling order, generation and branch limits)
+    /// are never read. Out-of-range values reject the whole mapping, leaving
+    /// the params unchanged.
+    pub fn merge_yaml(&mut self, config: &serde_yaml::Value) -> Result<(), String> {
+        let overrides: GrowthOverrides = serde_yaml::from_value(config.clone())
+            .map_err(|e| format!("Invalid growth params: {}", e))?;
+        let mut merged = *self;
+        overrides.apply(&mut merged);
+        merged.validate()?;
+        *self = merged;
+        Ok(())
+    }
+
+    /// Check the layout and shape settings are within sensible ranges
+    pub fn validate(&self) -> Result<(), String> {
+        let ranges = [
+            ("base_height", self.base_height, 0.01, 100.0),
+            ("height_decay", self.height_decay, 0.01, 1.5),
+            ("base_radius", self.base_radius, 0.001, 10.0),
+            ("radius_decay", self.radius_decay, 0.01, 1.5),
+            ("branch_spread", self.branch_spread, 0.0, PI),
+            ("angle_variance", self.angle_variance, 0.0, PI),
+            ("curvature", self.curvature, 0.0, 1.0),
+            ("verticality", self.verticality, 0.0, 1.0),
+            ("fan_angle", self.fan_angle, 0.01, TAU),
+            ("descendant_weight", self.descendant_weight, 0.0, 4.0),
+            ("root_scale", self.root_scale, 0.01, 4.0),
+            ("phototropism", self.phototropism, 0.0, 4.0),
+            ("wind_strength", self.wind_strength, 0.0, 4.0),
+            ("foliage_density", self.foliage_density, 0.0, 50.0),
+            ("foliage_size", self.foliage_size, 0.0, 4.0),
+            ("generation_height", self.generation_height.unwrap_or(1.0), 0.01, 100.0),
+            ("trunk_lean", self.trunk_lean, -PI / 2.0, PI / 2.0),
+            ("trunk_heading", self.trunk_heading, -TAU, TAU),
+            ("trunk_sway", self.trunk_sway, -1.0, 1.0),
+            ("trunk_bends", self.trunk_bends, 0.0, 16.0),
+            ("divergence_angle", self.divergence_angle, -TAU, TAU),
+            ("child_pitch", self.child_pitch, 0.001, 10.0),
+            ("grove_spacing", self.grove_spacing, 0.1, 1000.0),
+        ];
+        for (name, value, min, max) in ranges {
+            // NaN fails both comparisons
+            if !(value >= min && value <= max) {
+                return Err(format!("Growth param '{}' must be between {} and {} (got {})", name, min, max, value));
+            }
+        }
+        if !(1..=200).contains(&self.attractors_per_person) {
+            return Err(format!(
+                "Growth param 'attractors_per_person' must be between 1 and 200 (got {})",
+                self.attractors_per_person
+            ));
+        }
+        for (name, direction) in [("light_direction", self.light_direction), ("wind_direction", self.wind_direction)] {
+            if !(direction.x.is_finite() && direction.y.is_finite() && direction.z.is_finite()) {
+                return Err(format!("Growth param '{}' must be finite", name));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::growth::HueMode;
+
+    fn yaml(text: &str) -> serde_yaml::Value {
+        serde_yaml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn test_merge_keeps_host_settings() {
+        let mut params = GrowthParams {
+            privacy_mode: true,
+            hue_mode: HueMode::Surname,
+            stable_placement: true,
+            ..GrowthParams::default()
+        };
+        params.merge_yaml(&yaml("base_height: 5.0\nlayout: fan\nprivacy_mode: false\nhue_mode: personal\n")).unwrap();
+
+        assert_eq!(params.base_height, 5.0);
+        assert_eq!(params.layout, LayoutMode::Radial);
+        // Keys the file left out, and host-owned ones it tried to set, stay put
+        assert_eq!(params.height_decay, GrowthParams::default().height_decay);
+        assert!(params.privacy_mode && params.stable_placement);
+        assert_eq!(params.hue_mode, HueMode::Surname);
+    }
+
+    #[test]
+    fn test_merge_rejects_out_of_range() {
+        let mut params = GrowthParams::default();
+        let err = params.merge_yaml(&yaml("base_height: 2.0\ncurvature: 7.0\n")).unwrap_err();
+        assert!(err.contains("curvature"));
+        // Nothing from the rejected mapping is applied
+        assert_eq!(params.base_height, GrowthParams::default().base_height);
+        assert!(params.merge_yaml(&yaml("layout: spiral")).is_err());
+        assert!(GrowthParams::default().validate().is_ok());
+    }
+}
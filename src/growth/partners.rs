@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use crate::data::FamilyTree;
+use super::algorithm::{BranchNode, TreeGrowth};
+
+/// Partner strands are a little thinner than the branch they twine around
+const PARTNER_RADIUS: f32 = 0.8;
+
+/// Braid married-in partners around their crown branches
+///
+/// Only partners with no parents in the family are braided; anyone with
+/// parents already grows a branch of their own. Each partner joins at most
+/// one branch, and trunk people are never braided onto someone else.
+pub(super) fn twine_partners(growth: &TreeGrowth, family: &FamilyTree, trunk_id: &str, node: &mut BranchNode) {
+    let hosts = partner_hosts(family, trunk_id);
+    if !hosts.is_empty() {
+        attach(growth, family, &hosts, node);
+    }
+}
+
+/// Host id -> partner id, read from either side's `partner` field
+fn partner_hosts(family: &FamilyTree, trunk_id: &str) -> HashMap<String, String> {
+    let trunks = family.trunks();
+    let married_in = |id: &str| id != trunk_id && !trunks.contains(&id) && family.parents_of(id).is_empty();
+
+    let mut ids: Vec<&String> = family.people.keys().collect();
+    ids.sort();
+
+    let mut hosts = HashMap::new();
+    let mut braided = HashMap::new();
+    for id in ids {
+        let Some(partner) = family.people[id].partner.as_deref().filter(|partner| partner != id) else {
+            continue;
+        };
+        // Whichever of the pair has no branch of their own becomes the strand
+        let (host, strand) = if married_in(partner) {
+            (id.as_str(), partner)
+        } else if married_in(id) {
+            (partner, id.as_str())
+        } else {
+            continue;
+        };
+        if hosts.contains_key(host) || braided.contains_key(strand) || hosts.contains_key(strand) {
+            continue;
+        }
+        hosts.insert(host.to_string(), strand.to_string());
+        braided.insert(strand.to_string(), host.to_string());
+    }
+    hosts
+}
+
+fn attach(growth: &TreeGrowth, family: &FamilyTree, hosts: &HashMap<String, String>, node: &mut BranchNode) {
+    if let Some(partner) = hosts.get(&node.person_id).and_then(|id| family.get(id)) {
+        node.partner = Some(Box::new(BranchNode {
+            person_id: partner.id.clone(),
+            visual: growth.person_visual(partner),
+            start: node.start,
+            end: node.end,
+            start_direction: node.start_direction,
+            end_direction: node.end_direction,
+            start_radius: node.start_radius * PARTNER_RADIUS,
+            end_radius: node.end_radius * PARTNER_RADIUS,
+            generation: node.generation,
+            event_positions: partner.event_positions(),
+            children: Vec::new(),
+            roots: Vec::new(),
+            hidden_descendants: 0,
+            partner: None,
+            waypoints: node.waypoints.clone(),
+            grove: Vec::new(),
+        }));
+    }
+    for child in &mut node.children {
+        attach(growth, family, hosts, child);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::FamilyTree;
+    use crate::growth::{GrowthParams, TreeGrowth};
+
+    const YAML: &str = r#"
+family:
+  name: "Partners"
+  root: "root"
+people:
+  - id: "root"
+    name: "Root"
+    partner: "wife"
+    children: ["a", "b"]
+  - id: "wife"
+    name: "Wife"
+  - id: "a"
+    name: "A"
+  - id: "b"
+    name: "B"
+  - id: "b_husband"
+    name: "B's Husband"
+    partner: "b"
+"#;
+
+    #[test]
+    fn test_partners_braid_onto_branches() {
+        let family = FamilyTree::from_yaml(YAML).unwrap();
+        let tree = TreeGrowth::new(GrowthParams::default()).grow(&family).unwrap();
+
+        let wife = tree.partner.as_ref().unwrap();
+        assert_eq!(wife.person_id, "wife");
+        assert_eq!(wife.end, tree.end);
+        assert!(wife.start_radius < tree.start_radius);
+
+        // Named from the partner's side only
+        let b = tree.children.iter().find(|c| c.person_id == "b").unwrap();
+        assert_eq!(b.partner.as_ref().unwrap().person_id, "b_husband");
+        assert_eq!(tree.count(), 5);
+        assert_eq!(tree.iter_preorder().filter(|n| n.person_id == "wife").count(), 1);
+    }
+
+    #[test]
+    fn test_blood_relatives_are_not_braided() {
+        let yaml = YAML.replace("partner: \"b\"", "partner: \"a\"").replace("partner: \"wife\"", "partner: \"b\"");
+        let family = FamilyTree::from_yaml(&yaml).unwrap();
+        let tree = TreeGrowth::new(GrowthParams::default()).grow(&family).unwrap();
+
+        // Root and b both have branches; b_husband braids onto a
+        assert!(tree.partner.is_none());
+        let a = tree.children.iter().find(|c| c.person_id == "a").unwrap();
+        assert_eq!(a.partner.as_ref().unwrap().person_id, "b_husband");
+    }
+
+    #[test]
+    fn test_no_self_partners() {
+        let yaml = YAML.replace("partner: \"b\"", "partner: \"b_husband\"");
+        assert!(FamilyTree::from_yaml(&yaml).unwrap_err().contains("own partner"));
+
+        // A self-partner slipping past validation doesn't claim the strand
+        // from the person it's really partnered with
+        let mut family = FamilyTree::from_yaml(YAML).unwrap();
+        let mut ann = crate::data::Person::new("ann", "Ann");
+        ann.partner = Some("ann".to_string());
+        family.people.insert("ann".to_string(), ann);
+        family.people.get_mut("root").unwrap().partner = Some("ann".to_string());
+        let tree = TreeGrowth::new(GrowthParams::default()).grow(&family).unwrap();
+        assert_eq!(tree.partner.as_ref().unwrap().person_id, "ann");
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use crate::data::{FamilyTree, Person};
+use crate::math::Vec3;
+use super::algorithm::{BranchNode, Lineage, TreeGrowth};
+
+/// Lay the family out as an upright fan
+///
+/// The root is a short stem ending at the fan's centre. Generation `g` ends on
+/// the ring of radius `g * base_height`, and each person gets a wedge of the
+/// parent's wedge in proportion to how many leaves their line has, so busy
+/// lines spread out instead of crowding.
+pub(super) fn grow_fan(
+    growth: &TreeGrowth,
+    family: &FamilyTree,
+    reach: &HashMap<String, usize>,
+    root: &Person,
+    origin: Vec3,
+) -> BranchNode {
+    let center = origin + Vec3::UP.scale(growth.params.base_height);
+    let half = growth.params.fan_angle.clamp(0.1, std::f32::consts::TAU) * 0.5;
+    let mut node = grow_wedge(growth, family, reach, root, origin, center, center, (-half, half), 0);
+    node.start_direction = Vec3::UP;
+    node
+}
+
+#[allow(clippy::too_many_arguments)]
+fn grow_wedge(
+    growth: &TreeGrowth,
+    family: &FamilyTree,
+    reach: &HashMap<String, usize>,
+    person: &Person,
+    start: Vec3,
+    end: Vec3,
+    center: Vec3,
+    wedge: (f32, f32),
+    generation: usize,
+) -> BranchNode {
+    let params = &growth.params;
+    let visual = growth.person_visual(person);
+    let (_, start_radius) = growth.segment_size(&visual, generation);
+    let direction = (end - start).normalize();
+
+    let (children_data, hidden_descendants) = growth.next_generation(family, reach, person, generation, Lineage::Descendants);
+    let weights: Vec<f32> = children_data.iter().map(|c| leaf_count(family, c) as f32).collect();
+    let total: f32 = weights.iter().sum();
+
+    let ring = params.base_height * (generation + 1) as f32;
+    let mut from = wedge.0;
+    let children = children_data
+        .iter()
+        .zip(&weights)
+        .map(|(child, weight)| {
+            let to = from + (wedge.1 - wedge.0) * weight / total;
+            let angle = (from + to) * 0.5;
+            let child_end = center + Vec3::new(angle.sin(), angle.cos(), 0.0).scale(ring);
+            let node = grow_wedge(growth, family, reach, child, end, child_end, center, (from, to), generation + 1);
+            from = to;
+            node
+        })
+        .collect();
+
+    BranchNode {
+        person_id: person.id.clone(),
+        visual,
+        start,
+        end,
+        start_direction: direction,
+        end_direction: direction,
+        start_radius,
+        end_radius: start_radius * params.radius_decay,
+        generation,
+        event_positions: person.event_positions(),
+        children,
+        roots: Vec::new(),
+        hidden_descendants,
+        partner: None,
+        waypoints: Vec::new(),
+        grove: Vec::new(),
+    }
+}
+
+/// Number of childless descendants (or 1 for a childless person)
+fn leaf_count(family: &FamilyTree, person: &Person) -> usize {
+    let children = family.children_of(&person.id);
+    if children.is_empty() {
+        1
+    } else {
+        children.iter().map(|c| leaf_count(family, c)).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::FamilyTree;
+    use crate::growth::{GrowthParams, LayoutMode, TreeGrowth};
+
+    const YAML: &str = r#"
+family:
+  name: "Fan"
+  root: "root"
+people:
+  - id: "root"
+    name: "Root"
+    children: ["a", "b"]
+  - id: "a"
+    name: "A"
+    children: ["a1", "a2", "a3"]
+  - id: "b"
+    name: "B"
+  - id: "a1"
+    name: "A1"
+  - id: "a2"
+    name: "A2"
+  - id: "a3"
+    name: "A3"
+"#;
+
+    #[test]
+    fn test_generations_on_rings() {
+        let family = FamilyTree::from_yaml(YAML).unwrap();
+        let params = GrowthParams { layout: LayoutMode::Radial, ..Default::default() };
+        let tree = TreeGrowth::new(params).grow(&family).unwrap();
+        let center = tree.end;
+
+        for node in tree.iter_preorder().skip(1) {
+            let ring = params.base_height * node.generation as f32;
+            assert!((node.end.distance(&center) - ring).abs() < 1e-4, "{} off its ring", node.person_id);
+            assert!(node.end.z.abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_wedges_follow_leaf_counts() {
+        let family = FamilyTree::from_yaml(YAML).unwrap();
+        let params = GrowthParams { layout: LayoutMode::Radial, ..Default::default() };
+        let tree = TreeGrowth::new(params).grow(&family).unwrap();
+        let angle = |n: &crate::growth::BranchNode| {
+            let d = n.end - tree.end;
+            d.x.atan2(d.y)
+        };
+
+        // "a" has three leaves and "b" one, so "a" sits at the centre of the
+        // first three quarters of the fan
+        let half = params.fan_angle / 2.0;
+        assert!((angle(&tree.children[0]) - (-half + params.fan_angle * 0.375)).abs() < 1e-4);
+        assert!((angle(&tree.children[1]) - (half - params.fan_angle * 0.125)).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,126 @@
+/// Source of the random variation in a grown tree
+///
+/// Growth asks for values by key (a person id, a parent>child pair, ...)
+/// rather than in sequence, so a branch comes out the same however many
+/// relatives were grown before it.
+pub trait GrowthRng {
+    /// Restart the source from a seed
+    fn reseed(&mut self, seed: u32);
+
+    /// Random bits for a key; the same seed and key always give the same bits
+    fn bits(&self, key: &str) -> u32;
+
+    /// Uniform in `0.0..=1.0` for a key
+    fn unit(&self, key: &str) -> f32 {
+        self.bits(key) as f32 / u32::MAX as f32
+    }
+}
+
+/// Seeded polynomial string hash; the classic look of every tree
+#[derive(Debug, Clone, Copy)]
+pub struct StringHash {
+    seed: u32,
+}
+
+impl StringHash {
+    pub fn new(seed: u32) -> Self {
+        Self { seed }
+    }
+}
+
+impl GrowthRng for StringHash {
+    fn reseed(&mut self, seed: u32) {
+        self.seed = seed;
+    }
+
+    fn bits(&self, key: &str) -> u32 {
+        let mut h = self.seed;
+        for b in key.bytes() {
+            h = h.wrapping_mul(31).wrapping_add(b as u32);
+        }
+        h
+    }
+}
+
+/// FNV-1a key hash through a SplitMix finalizer; evenly spread bits even
+/// for short, similar ids
+#[derive(Debug, Clone, Copy)]
+pub struct SplitMix {
+    seed: u32,
+}
+
+impl SplitMix {
+    pub fn new(seed: u32) -> Self {
+        Self { seed }
+    }
+}
+
+impl GrowthRng for SplitMix {
+    fn reseed(&mut self, seed: u32) {
+        self.seed = seed;
+    }
+
+    fn bits(&self, key: &str) -> u32 {
+        let mut h: u32 = 0x811C_9DC5 ^ self.seed;
+        for b in key.bytes() {
+            h = (h ^ b as u32).wrapping_mul(0x0100_0193);
+        }
+        avalanche(h)
+    }
+}
+
+/// SplitMix finalizer: every input bit flips about half the output bits
+pub(super) fn avalanche(mut h: u32) -> u32 {
+    h = (h ^ (h >> 16)).wrapping_mul(0x7FEB_352D);
+    h = (h ^ (h >> 15)).wrapping_mul(0x846C_A68B);
+    h ^ (h >> 16)
+}
+
+/// Sequential xorshift32 stream, for layouts that scatter many points from
+/// one keyed seed
+pub(super) struct XorShift(u32);
+
+impl XorShift {
+    pub(super) fn new(seed: u32) -> Self {
+        // Zero is a fixed point of xorshift
+        Self(seed | 1)
+    }
+
+    /// Uniform in `-1.0..=1.0`
+    pub(super) fn signed(&mut self) -> f32 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        x as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyed_and_seeded() {
+        let mut rng = SplitMix::new(1);
+        assert_eq!(rng.bits("anna"), rng.bits("anna"));
+        assert_ne!(rng.bits("anna"), rng.bits("anne"));
+
+        let before = rng.bits("anna");
+        rng.reseed(2);
+        assert_ne!(rng.bits("anna"), before);
+    }
+
+    #[test]
+    fn test_unit_distribution() {
+        // Sequential ids are the worst case for a string hash
+        let rng = SplitMix::new(7);
+        let values: Vec<f32> = (0..2000).map(|i| rng.unit(&format!("p{}", i))).collect();
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        let low = values.iter().filter(|v| **v < 0.25).count();
+        assert!(values.iter().all(|v| (0.0..=1.0).contains(v)));
+        assert!((mean - 0.5).abs() < 0.05, "mean {}", mean);
+        assert!((400..600).contains(&low), "{} in the lowest quarter", low);
+    }
+}
@@ -1,3 +1,7 @@
 pub mod picking;
+pub mod views;
+pub mod narration;
 
-pub use picking::{RayPicker, HitInfo};
+pub use picking::{RayPicker, HitInfo, ScreenAnchor};
+pub use views::{CameraState, NamedView, ViewLibrary};
+pub use narration::{NarrationEvent, NarrationParams, NarrationTracker, NarrationZone};
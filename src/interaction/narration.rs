@@ -0,0 +1,231 @@
+use serde::Deserialize;
+use crate::data::FamilyTree;
+use crate::growth::BranchNode;
+use crate::math::Vec3;
+
+/// Distances (world units) and timing for narration triggers
+///
+/// A clip starts once the camera has stayed within `trigger_distance` of a
+/// branch for `dwell_seconds`, and stops only after the camera moves beyond
+/// `release_distance`, so hovering at the edge doesn't toggle it.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct NarrationParams {
+    pub trigger_distance: f32,
+    pub release_distance: f32,
+    pub dwell_seconds: f32,
+}
+
+impl Default for NarrationParams {
+    fn default() -> Self {
+        Self {
+            trigger_distance: 3.0,
+            release_distance: 4.5,
+            dwell_seconds: 2.0,
+        }
+    }
+}
+
+/// A branch with a narration clip attached
+#[derive(Debug, Clone)]
+pub struct NarrationZone {
+    pub person_id: String,
+    pub audio_id: String,
+    pub start: Vec3,
+    pub end: Vec3,
+}
+
+impl NarrationZone {
+    /// Distance from `point` to the branch's centre line
+    fn distance(&self, point: Vec3) -> f32 {
+        let axis = self.end - self.start;
+        let length_sq = axis.length_squared();
+        let t = if length_sq > 0.0 {
+            ((point - self.start).dot(&axis) / length_sq).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        point.distance(&self.start.lerp(&self.end, t))
+    }
+}
+
+/// A narration clip should start or stop
+#[derive(Debug, Clone, PartialEq)]
+pub enum NarrationEvent {
+    Start { person_id: String, audio_id: String },
+    Stop { person_id: String, audio_id: String },
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct ZoneState {
+    dwell: f32,
+    active: bool,
+}
+
+/// Tracks how long the camera lingers near each narrated branch
+#[derive(Debug, Clone, Default)]
+pub struct NarrationTracker {
+    params: NarrationParams,
+    zones: Vec<NarrationZone>,
+    states: Vec<ZoneState>,
+}
+
+impl NarrationTracker {
+    pub fn new(params: NarrationParams) -> Self {
+        Self {
+            params,
+            ..Default::default()
+        }
+    }
+
+    pub fn set_params(&mut self, params: NarrationParams) {
+        self.params = params;
+    }
+
+    pub fn params(&self) -> NarrationParams {
+        self.params
+    }
+
+    /// Replace the zones (e.g. after regrowing); clips that are still present
+    /// keep playing, the rest are stopped
+    pub fn set_zones(&mut self, zones: Vec<NarrationZone>) -> Vec<NarrationEvent> {
+        let mut events = Vec::new();
+        let states = zones
+            .iter()
+            .map(|zone| {
+                self.zones
+                    .iter()
+                    .position(|z| z.person_id == zone.person_id && z.audio_id == zone.audio_id)
+                    .map(|i| self.states[i])
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        for (zone, state) in self.zones.iter().zip(&self.states) {
+            let kept = zones
+                .iter()
+                .any(|z| z.person_id == zone.person_id && z.audio_id == zone.audio_id);
+            if state.active && !kept {
+                events.push(NarrationEvent::Stop {
+                    person_id: zone.person_id.clone(),
+                    audio_id: zone.audio_id.clone(),
+                });
+            }
+        }
+
+        self.zones = zones;
+        self.states = states;
+        events
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.zones.is_empty()
+    }
+
+    /// Advance dwell timers by `dt` seconds for a camera at `camera`
+    pub fn update(&mut self, camera: Vec3, dt: f32) -> Vec<NarrationEvent> {
+        let params = self.params;
+        let mut events = Vec::new();
+
+        for (zone, state) in self.zones.iter().zip(self.states.iter_mut()) {
+            let distance = zone.distance(camera);
+
+            if state.active {
+                if distance > params.release_distance.max(params.trigger_distance) {
+                    *state = ZoneState::default();
+                    events.push(NarrationEvent::Stop {
+                        person_id: zone.person_id.clone(),
+                        audio_id: zone.audio_id.clone(),
+                    });
+                }
+            } else if distance <= params.trigger_distance {
+                state.dwell += dt;
+                if state.dwell >= params.dwell_seconds {
+                    state.active = true;
+                    events.push(NarrationEvent::Start {
+                        person_id: zone.person_id.clone(),
+                        audio_id: zone.audio_id.clone(),
+                    });
+                }
+            } else {
+                state.dwell = 0.0;
+            }
+        }
+
+        events
+    }
+}
+
+/// Zones for every grown branch whose person has an `audio_id`
+pub fn zones_from_tree(tree: &BranchNode, family: &FamilyTree) -> Vec<NarrationZone> {
+    tree.iter_preorder()
+        .filter_map(|node| {
+            let audio_id = family.get(&node.person_id)?.audio_id.clone()?;
+            Some(NarrationZone {
+                person_id: node.person_id.clone(),
+                audio_id,
+                start: node.start,
+                end: node.end,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> NarrationTracker {
+        let mut tracker = NarrationTracker::new(NarrationParams {
+            trigger_distance: 1.0,
+            release_distance: 2.0,
+            dwell_seconds: 0.5,
+        });
+        tracker.set_zones(vec![NarrationZone {
+            person_id: "gran".to_string(),
+            audio_id: "gran-story".to_string(),
+            start: Vec3::ZERO,
+            end: Vec3::new(0.0, 4.0, 0.0),
+        }]);
+        tracker
+    }
+
+    #[test]
+    fn test_dwell_starts_narration() {
+        let mut tracker = tracker();
+        let near = Vec3::new(0.8, 2.0, 0.0);
+
+        assert!(tracker.update(near, 0.3).is_empty());
+        let events = tracker.update(near, 0.3);
+        assert!(matches!(&events[..], [NarrationEvent::Start { audio_id, .. }] if audio_id == "gran-story"));
+        assert!(tracker.update(near, 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_passing_by_resets_dwell() {
+        let mut tracker = tracker();
+        tracker.update(Vec3::new(0.8, 2.0, 0.0), 0.4);
+        tracker.update(Vec3::new(5.0, 2.0, 0.0), 0.1);
+        assert!(tracker.update(Vec3::new(0.8, 2.0, 0.0), 0.4).is_empty());
+    }
+
+    #[test]
+    fn test_hysteresis() {
+        let mut tracker = tracker();
+        tracker.update(Vec3::new(0.5, 2.0, 0.0), 1.0);
+
+        // Between trigger and release: keeps playing
+        assert!(tracker.update(Vec3::new(1.5, 2.0, 0.0), 1.0).is_empty());
+        let events = tracker.update(Vec3::new(2.5, 2.0, 0.0), 0.1);
+        assert!(matches!(&events[..], [NarrationEvent::Stop { .. }]));
+    }
+
+    #[test]
+    fn test_removed_zone_stops() {
+        let mut tracker = tracker();
+        tracker.update(Vec3::new(0.5, 2.0, 0.0), 1.0);
+        let events = tracker.set_zones(Vec::new());
+        assert_eq!(events.len(), 1);
+        assert!(tracker.is_empty());
+    }
+}
@@ -1,24 +1,49 @@
 use crate::math::{Vec3, Mat4};
 use crate::mesh::generator::BranchMeshInfo;
+use crate::mesh::threads::ThreadMeshInfo;
+
+/// Minimum pick radius for thin memory threads, so they can be hovered at all
+const THREAD_PICK_RADIUS: f32 = 0.06;
 
 /// Information about a ray-branch intersection
 #[derive(Debug, Clone)]
 pub struct HitInfo {
+    /// Person whose branch was hit (the link's `from` person for threads)
     pub person_id: String,
     pub distance: f32,
     pub hit_point: Vec3,
+    /// Set when a memory thread was hit instead of a branch
+    pub link_index: Option<usize>,
+}
+
+/// A branch's label anchor projected onto the screen
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScreenAnchor {
+    pub person_id: String,
+    /// Pixels from the left edge
+    pub x: f32,
+    /// Pixels from the top edge
+    pub y: f32,
+    /// Distance in front of the camera, for ordering labels
+    pub depth: f32,
+    /// Inside the viewport (off-screen anchors are still reported so labels
+    /// can be parked at the edge)
+    pub on_screen: bool,
 }
 
 /// Ray-based picking for selecting branches
 pub struct RayPicker {
     /// Cached branch bounds for efficient picking
     branch_bounds: Vec<BranchMeshInfo>,
+    /// Memory threads between linked people
+    threads: Vec<ThreadMeshInfo>,
 }
 
 impl RayPicker {
     pub fn new() -> Self {
         Self {
             branch_bounds: Vec::new(),
+            threads: Vec::new(),
         }
     }
 
@@ -27,7 +52,18 @@ impl RayPicker {
         self.branch_bounds = branches;
     }
 
+    /// Set memory threads for picking
+    pub fn set_threads(&mut self, threads: Vec<ThreadMeshInfo>) {
+        self.threads = threads;
+    }
+
+    /// Get a memory thread by link index
+    pub fn thread(&self, link_index: usize) -> Option<&ThreadMeshInfo> {
+        self.threads.iter().find(|t| t.link_index == link_index)
+    }
+
     /// Cast a ray from screen coordinates and find the closest hit
+    #[allow(clippy::too_many_arguments)]
     pub fn pick(
         &self,
         screen_x: f32,
@@ -50,26 +86,84 @@ impl RayPicker {
         let mut min_dist = f32::MAX;
 
         for branch in &self.branch_bounds {
-            if let Some(dist) = self.ray_sphere_intersect(
+            // Bounding sphere first, then the branch's tighter capsules
+            let Some(sphere_dist) = self.ray_sphere_intersect(
                 camera_pos,
                 ray_dir,
                 branch.bounds_center,
                 branch.bounds_radius,
-            ) {
+            ) else {
+                continue;
+            };
+            let hit = if branch.colliders.is_empty() {
+                Some(sphere_dist)
+            } else {
+                branch.colliders
+                    .iter()
+                    .filter_map(|capsule| capsule.ray_intersect(camera_pos, ray_dir))
+                    .min_by(|a, b| a.total_cmp(b))
+            };
+            if let Some(dist) = hit {
                 if dist < min_dist {
                     min_dist = dist;
                     closest = Some(HitInfo {
                         person_id: branch.person_id.clone(),
                         distance: dist,
                         hit_point: camera_pos + ray_dir.scale(dist),
+                        link_index: None,
                     });
                 }
             }
         }
 
+        // Threads are approximated by spheres at each sample point
+        for thread in &self.threads {
+            let radius = thread.radius.max(THREAD_PICK_RADIUS);
+            for &point in &thread.points {
+                if let Some(dist) = self.ray_sphere_intersect(camera_pos, ray_dir, point, radius) {
+                    if dist < min_dist {
+                        min_dist = dist;
+                        closest = Some(HitInfo {
+                            person_id: thread.from_id.clone(),
+                            distance: dist,
+                            hit_point: camera_pos + ray_dir.scale(dist),
+                            link_index: Some(thread.link_index),
+                        });
+                    }
+                }
+            }
+        }
+
         closest
     }
 
+    /// Project the label anchors of the given people (every branch when
+    /// None) to screen pixels; anchors behind the camera are left out
+    pub fn screen_anchors(
+        &self,
+        person_ids: Option<&[String]>,
+        view: &Mat4,
+        projection: &Mat4,
+        screen_width: f32,
+        screen_height: f32,
+    ) -> Vec<ScreenAnchor> {
+        self.branch_bounds
+            .iter()
+            .filter(|branch| person_ids.is_none_or(|ids| ids.contains(&branch.person_id)))
+            .filter_map(|branch| {
+                let eye = view.transform_point(branch.anchor);
+                let ndc = projection.project_point(eye)?;
+                Some(ScreenAnchor {
+                    person_id: branch.person_id.clone(),
+                    x: (ndc.x + 1.0) * 0.5 * screen_width,
+                    y: (1.0 - ndc.y) * 0.5 * screen_height,
+                    depth: -eye.z,
+                    on_screen: ndc.x.abs() <= 1.0 && ndc.y.abs() <= 1.0 && ndc.z.abs() <= 1.0,
+                })
+            })
+            .collect()
+    }
+
     /// Convert screen coordinates to world ray direction
     fn screen_to_world_ray(&self, ndc_x: f32, ndc_y: f32, view: &Mat4, projection: &Mat4) -> Vec3 {
         // Inverse projection and view matrices
@@ -183,6 +277,7 @@ fn invert_view(m: &Mat4) -> Mat4 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::math::Capsule;
 
     #[test]
     fn test_picker_creation() {
@@ -202,12 +297,105 @@ mod tests {
                 index_count: 30,
                 bounds_center: Vec3::new(0.0, 2.0, 0.0),
                 bounds_radius: 1.0,
+                v_start: 0.0,
+                generation: 0,
+                anchor: Vec3::new(0.0, 3.0, 0.0),
+                colliders: Vec::new(),
             },
         ];
         picker.set_branches(branches);
         assert_eq!(picker.branch_bounds.len(), 1);
     }
 
+    #[test]
+    fn test_screen_anchors() {
+        let mut picker = RayPicker::new();
+        let branch = |id: &str, anchor: Vec3| BranchMeshInfo {
+            person_id: id.to_string(),
+            vertex_start: 0,
+            vertex_count: 0,
+            index_start: 0,
+            index_count: 0,
+            bounds_center: anchor,
+            bounds_radius: 1.0,
+            v_start: 0.0,
+            generation: 1,
+            anchor,
+            colliders: Vec::new(),
+        };
+        picker.set_branches(vec![
+            branch("centre", Vec3::ZERO),
+            branch("aside", Vec3::new(50.0, 0.0, 0.0)),
+            branch("behind", Vec3::new(0.0, 0.0, 20.0)),
+        ]);
+
+        let camera = Vec3::new(0.0, 0.0, 10.0);
+        let view = Mat4::look_at(camera, Vec3::ZERO, Vec3::UP);
+        let projection = Mat4::perspective(std::f32::consts::FRAC_PI_4, 2.0, 0.1, 100.0);
+        let anchors = picker.screen_anchors(None, &view, &projection, 200.0, 100.0);
+
+        assert_eq!(anchors.len(), 2);
+        assert!((anchors[0].x - 100.0).abs() < 1e-3 && (anchors[0].y - 50.0).abs() < 1e-3);
+        assert!((anchors[0].depth - 10.0).abs() < 1e-3);
+        assert!(anchors[0].on_screen);
+        assert!(!anchors[1].on_screen && anchors[1].x > 200.0);
+
+        let only = ["aside".to_string()];
+        let filtered = picker.screen_anchors(Some(&only), &view, &projection, 200.0, 100.0);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].person_id, "aside");
+    }
+
+    #[test]
+    fn test_pick_uses_colliders() {
+        let mut picker = RayPicker::new();
+        let capsule = Capsule::new(Vec3::new(0.0, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0), 0.1);
+        picker.set_branches(vec![BranchMeshInfo {
+            person_id: "thin".to_string(),
+            vertex_start: 0,
+            vertex_count: 0,
+            index_start: 0,
+            index_count: 0,
+            bounds_center: Vec3::ZERO,
+            bounds_radius: 1.1,
+            v_start: 0.0,
+            generation: 0,
+            anchor: Vec3::new(0.0, 1.0, 0.0),
+            colliders: vec![capsule],
+        }]);
+
+        let camera = Vec3::new(0.0, 0.0, 10.0);
+        let view = Mat4::look_at(camera, Vec3::ZERO, Vec3::UP);
+        let projection = Mat4::perspective(std::f32::consts::FRAC_PI_4, 1.0, 0.1, 100.0);
+
+        // Straight at the branch hits its surface, not the bounding sphere
+        let hit = picker.pick(50.0, 50.0, 100.0, 100.0, &view, &projection, camera).unwrap();
+        assert!((hit.distance - 9.9).abs() < 1e-3);
+        // Inside the sphere but beside the branch misses
+        assert!(picker.pick(60.0, 50.0, 100.0, 100.0, &view, &projection, camera).is_none());
+    }
+
+    #[test]
+    fn test_pick_thread() {
+        let mut picker = RayPicker::new();
+        picker.set_threads(vec![ThreadMeshInfo {
+            link_index: 3,
+            from_id: "a".to_string(),
+            to_id: "b".to_string(),
+            label: "named after".to_string(),
+            points: vec![Vec3::new(-0.5, 0.0, 0.0), Vec3::ZERO, Vec3::new(0.5, 0.0, 0.0)],
+            radius: 0.01,
+        }]);
+
+        let camera = Vec3::new(0.0, 0.0, 10.0);
+        let view = Mat4::look_at(camera, Vec3::ZERO, Vec3::UP);
+        let projection = Mat4::perspective(std::f32::consts::FRAC_PI_4, 1.0, 0.1, 100.0);
+
+        let hit = picker.pick(50.0, 50.0, 100.0, 100.0, &view, &projection, camera).unwrap();
+        assert_eq!(hit.link_index, Some(3));
+        assert_eq!(picker.thread(3).unwrap().to_id, "b");
+    }
+
     #[test]
     fn test_ray_sphere_hit() {
         let picker = RayPicker::new();
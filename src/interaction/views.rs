@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+use crate::math::Vec3;
+
+/// Orbit camera state
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CameraState {
+    pub distance: f32,
+    pub angle_x: f32,
+    pub angle_y: f32,
+    pub target: [f32; 3],
+}
+
+impl CameraState {
+    pub fn new(distance: f32, angle_x: f32, angle_y: f32, target: Vec3) -> Self {
+        Self {
+            distance,
+            angle_x,
+            angle_y,
+            target: [target.x, target.y, target.z],
+        }
+    }
+
+    pub fn target(&self) -> Vec3 {
+        Vec3::new(self.target[0], self.target[1], self.target[2])
+    }
+}
+
+/// A named perspective on the tree ("maternal line", "immigrant generation")
+///
+/// The engine restores the camera, focus person, opened buds, theme, season
+/// and hue mode; views saved without the last three leave them as they are.
+/// `settings` carries the host's own filters as an opaque JSON string.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NamedView {
+    pub name: String,
+    pub camera: CameraState,
+    #[serde(default)]
+    pub focus: Option<String>,
+    /// Buds opened past the generation limit
+    #[serde(default)]
+    pub expanded: Vec<String>,
+    #[serde(default)]
+    pub theme: Option<String>,
+    #[serde(default)]
+    pub season: Option<String>,
+    #[serde(default)]
+    pub hue_mode: Option<String>,
+    #[serde(default)]
+    pub settings: Option<String>,
+}
+
+impl NamedView {
+    /// Serialize to a JSON object
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("named views serialize to JSON")
+    }
+}
+
+/// Collection of named views, unique by name
+#[derive(Debug, Clone, Default)]
+pub struct ViewLibrary {
+    views: Vec<NamedView>,
+}
+
+impl ViewLibrary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a view, replacing any existing view with the same name
+    pub fn save(&mut self, view: NamedView) {
+        match self.views.iter_mut().find(|v| v.name == view.name) {
+            Some(existing) => *existing = view,
+            None => self.views.push(view),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&NamedView> {
+        self.views.iter().find(|v| v.name == name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.views.len();
+        self.views.retain(|v| v.name != name);
+        self.views.len() != before
+    }
+
+    pub fn views(&self) -> &[NamedView] {
+        &self.views
+    }
+
+    /// Serialize all views to a JSON array
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.views).expect("named views serialize to JSON")
+    }
+
+    /// Merge views from a JSON array (as produced by `to_json`)
+    pub fn import_json(&mut self, json: &str) -> Result<usize, String> {
+        let views: Vec<NamedView> = serde_json::from_str(json)
+            .map_err(|e| format!("Invalid views: {}", e))?;
+        let count = views.len();
+        for view in views {
+            self.save(view);
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(name: &str, distance: f32) -> NamedView {
+        NamedView {
+            name: name.to_string(),
+            camera: CameraState::new(distance, 0.3, 1.0, Vec3::new(0.0, 3.5, 0.0)),
+            focus: Some("grandma".to_string()),
+            expanded: vec!["uncle".to_string()],
+            theme: Some("midnight".to_string()),
+            season: Some("autumn".to_string()),
+            hue_mode: Some("surname".to_string()),
+            settings: Some(r#"{"filter":"maternal"}"#.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_save_replaces_by_name() {
+        let mut library = ViewLibrary::new();
+        library.save(view("maternal line", 10.0));
+        library.save(view("maternal line", 15.0));
+
+        assert_eq!(library.views().len(), 1);
+        assert_eq!(library.get("maternal line").unwrap().camera.distance, 15.0);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut library = ViewLibrary::new();
+        library.save(view("maternal line", 10.0));
+        library.save(view("immigrant \"generation\"", 20.0));
+
+        let json = library.to_json();
+        let mut restored = ViewLibrary::new();
+        assert_eq!(restored.import_json(&json).unwrap(), 2);
+        assert_eq!(restored.views(), library.views());
+
+        // Views saved before focus and visual state were kept still load
+        let camera_only = r#"[{"name":"old","camera":{"distance":8,"angle_x":0.3,"angle_y":1,"target":[0,3,0]}}]"#;
+        assert_eq!(restored.import_json(camera_only).unwrap(), 1);
+        assert_eq!(restored.get("old").unwrap().focus, None);
+        // Only JSON is accepted, not YAML that happens to parse
+        assert!(restored.import_json("- name: old\n  camera: {distance: 8, angle_x: 0.3, angle_y: 1, target: [0, 3, 0]}\n").is_err());
+    }
+}
@@ -1,5 +1,8 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
 use wasm_bindgen::prelude::*;
-use web_sys::{WebGl2RenderingContext, HtmlCanvasElement};
+use web_sys::{WebGl2RenderingContext, HtmlCanvasElement, OffscreenCanvas};
+use serde::Serialize;
 
 pub mod data;
 pub mod growth;
@@ -13,15 +16,20 @@ pub mod animation;
 
 // Re-export visual analyzer for JavaScript
 pub use visual::metrics::VisualAnalyzer;
+// Re-export the family builder for JavaScript
+pub use data::{FamilyTreeBuilder, BuiltFamily};
 
-use data::FamilyTree;
-use growth::{TreeGrowth, GrowthParams, BranchNode};
-use mesh::generator::{MeshParams, TrackedMeshGenerator};
-use particles::{FireflySystem, OrbSystem};
-use render::RenderPipeline;
-use interaction::RayPicker;
+use data::{ChildOrder, FamilyTree, FamilyLoader, EditHistory, GenderStyle, InputLimits, LabelLocale, MergeStrategy, Person, PersonInfo, SyntheticParams, LIVING_LABEL};
+use growth::{TreeGrowth, GrowthParams, BranchNode, ChildArrangement, FoliageCluster, HueMode, LayoutMode, LSystemPreset};
+use mesh::{FruitGenerator, FruitParams, GroundParams, LabelParams, LeafGenerator, LeafParams, Mesh, MeshStats, SdfFont, generate_ground, generate_labels, ground_radius, simplify, twig_instance_data};
+use mesh::generator::{BranchMeshInfo, MeshParams, MeshPatch, TrackedMeshGenerator};
+use mesh::threads::{ThreadParams, generate_threads};
+use particles::{FallingSystem, FireflySystem, OrbSystem, PARTICLE_FLOATS};
+use render::{Antialiasing, RenderPipeline, Season, SeasonLook, Theme, ToneMapping};
+use interaction::{RayPicker, CameraState, NamedView, ViewLibrary, NarrationEvent, NarrationParams, NarrationTracker};
+use interaction::narration::zones_from_tree;
 use math::{Vec3, Mat4};
-use animation::GrowthAnimation;
+use animation::{GrowthAnimation, GrowthTiming};
 
 /// Initialize panic hook for better error messages
 #[wasm_bindgen(start)]
@@ -30,19 +38,56 @@ pub fn init() {
     console_error_panic_hook::set_once();
 }
 
+/// Seconds to cross-fade geometry after re-rooting
+const CROSSFADE_DURATION: f32 = 0.8;
+/// Coarser tree meshes generated for drawing from afar
+const LOD_LEVELS: usize = 2;
+/// Most bloom blur passes (each a horizontal and a vertical one)
+const MAX_BLOOM_PASSES: u32 = 8;
+/// Seconds to blend leaf colors and fog into a new season
+const SEASON_TRANSITION: f32 = 2.5;
+/// Particles each system keeps alive at most
+const MAX_FIREFLIES: usize = 150;
+const MAX_ORBS: usize = 50;
+const MAX_FALLING: usize = 120;
+
 /// Main engine state exposed to JavaScript
 #[wasm_bindgen]
 pub struct AncestralVisionTree {
     pipeline: RenderPipeline,
     fireflies: FireflySystem,
     orbs: OrbSystem,
+    /// Seasonal petals, leaves or snow
+    falling: FallingSystem,
+    season: Season,
+    /// Look blended from and seconds into a season change, if running
+    season_transition: Option<(SeasonLook, f32)>,
     picker: RayPicker,
     family_tree: Option<FamilyTree>,
+    /// Seconds into the geometry cross-fade after re-rooting, if running
+    crossfade_elapsed: Option<f32>,
+    /// Undo/redo snapshots of family edits
+    history: EditHistory,
+    /// Family being streamed in chunk by chunk
+    pending_load: Option<FamilyLoader>,
+    /// Size limits for uploaded family documents
+    input_limits: InputLimits,
+    /// Parameters used to grow the tree
+    growth_params: GrowthParams,
     /// Stored tree structure for animation
     tree_structure: Option<BranchNode>,
+    /// Tree geometry without memory threads, kept for partial regrowth
+    tree_mesh: Mesh,
+    /// Per-branch ranges within `tree_mesh`
+    branch_infos: Vec<BranchMeshInfo>,
     /// Growth animation controller
     growth_animation: GrowthAnimation,
+    /// Timing overrides applied to every growth animation
+    growth_timing: GrowthTiming,
+    /// Scene clock driving shader pulses and particles
     time: f32,
+    /// Scene seconds per wall-clock second (0.0 = frozen)
+    time_scale: f32,
     width: i32,
     height: i32,
     // Camera orbit controls
@@ -50,8 +95,49 @@ pub struct AncestralVisionTree {
     camera_angle_x: f32,
     camera_angle_y: f32,
     camera_target: Vec3,
+    /// World position of the trunk base
+    tree_origin: Vec3,
+    /// Person grown as the trunk instead of the family root, with ancestors as roots
+    focus_id: Option<String>,
+    /// Person whose direct line stays solid while everyone else is ghosted
+    focus_line: Option<String>,
+    /// Buds opened past `max_generations`
+    expanded_buds: HashSet<String>,
+    /// Seed for per-branch variation in growth
+    growth_seed: u32,
+    /// Most triangles uploaded for the tree; larger meshes are simplified
+    triangle_budget: Option<usize>,
+    /// Tree mesh generation params
+    mesh_params: MeshParams,
+    /// Fruit hung along branches, one per child (None = no fruit)
+    fruit_params: Option<FruitParams>,
+    /// Where light shafts radiate from (None = the canopy's brightest point)
+    light_source: Option<Vec3>,
+    /// Saved named views
+    views: ViewLibrary,
+    /// Camera dwell tracking for per-branch narration clips
+    narration: NarrationTracker,
+    /// `(event, audio_id, person_id)` hook for narration start/stop
+    narration_callback: Option<js_sys::Function>,
+    /// Language used for person labels
+    locale: LabelLocale,
+    /// Font for names drawn in the scene; none until the host sets one
+    label_font: Option<SdfFont>,
+    /// Draw names at branch tips (with a font set)
+    scene_labels: bool,
     // Hover state
     hovered_person_id: Option<String>,
+    hovered_link: Option<usize>,
+    /// Person whose branch is outlined
+    selected_person_id: Option<String>,
+    /// The uploaded tree was simplified, so `branch_infos` ranges don't
+    /// index into it
+    tree_simplified: bool,
+    /// Memory threads merged into the uploaded tree mesh
+    thread_stats: MeshStats,
+    /// Fingerprints of the scene parts last uploaded, so local edits can
+    /// skip re-uploading the ones they left unchanged
+    scene_fingerprints: HashMap<&'static str, u64>,
 }
 
 #[wasm_bindgen]
@@ -67,32 +153,92 @@ impl AncestralVisionTree {
             .ok_or("Failed to get WebGL2 context")?
             .dyn_into::<WebGl2RenderingContext>()?;
 
+        Self::with_context(gl, width, height)
+    }
+
+    /// Create an engine drawing into an `OffscreenCanvas`, e.g. one handed
+    /// to a Web Worker with `transferControlToOffscreen()`
+    ///
+    /// The engine never touches the DOM, so it runs the same in a worker;
+    /// the host forwards input and size changes from the main thread.
+    #[wasm_bindgen]
+    pub fn from_offscreen_canvas(canvas: OffscreenCanvas) -> Result<AncestralVisionTree, JsValue> {
+        let width = canvas.width() as i32;
+        let height = canvas.height() as i32;
+
+        let gl = canvas
+            .get_context("webgl2")?
+            .ok_or("Failed to get WebGL2 context")?
+            .dyn_into::<WebGl2RenderingContext>()?;
+
+        Self::with_context(gl, width, height)
+    }
+}
+
+impl AncestralVisionTree {
+    fn with_context(gl: WebGl2RenderingContext, width: i32, height: i32) -> Result<AncestralVisionTree, JsValue> {
         let pipeline = RenderPipeline::new(gl, width, height)
             .map_err(|e| JsValue::from_str(&e))?;
 
-        let fireflies = FireflySystem::new(150);
-        let orbs = OrbSystem::new(50); // Fewer orbs, larger and more prominent
+        let fireflies = FireflySystem::new(MAX_FIREFLIES);
+        let orbs = OrbSystem::new(MAX_ORBS); // Fewer orbs, larger and more prominent
+        let falling = FallingSystem::new(MAX_FALLING);
         let picker = RayPicker::new();
 
         Ok(Self {
             pipeline,
             fireflies,
             orbs,
+            falling,
+            season: Season::default(),
+            season_transition: None,
             picker,
             family_tree: None,
+            crossfade_elapsed: None,
+            history: EditHistory::default(),
+            pending_load: None,
+            input_limits: InputLimits::default(),
+            growth_params: GrowthParams::default(),
             tree_structure: None,
+            tree_mesh: Mesh::new(),
+            branch_infos: Vec::new(),
             growth_animation: GrowthAnimation::instant(), // Default to fully grown
+            growth_timing: GrowthTiming::default(),
             time: 0.0,
+            time_scale: 1.0,
             width,
             height,
             camera_distance: 12.0,
             camera_angle_x: 0.3,
             camera_angle_y: 0.0,
             camera_target: Vec3::new(0.0, 3.5, 0.0),
+            tree_origin: Vec3::ZERO,
+            focus_id: None,
+            focus_line: None,
+            expanded_buds: HashSet::new(),
+            growth_seed: 42,
+            triangle_budget: None,
+            mesh_params: MeshParams::default(),
+            fruit_params: None,
+            light_source: None,
+            views: ViewLibrary::new(),
+            narration: NarrationTracker::default(),
+            narration_callback: None,
+            locale: LabelLocale::default(),
+            label_font: None,
+            scene_labels: true,
             hovered_person_id: None,
+            hovered_link: None,
+            selected_person_id: None,
+            tree_simplified: false,
+            thread_stats: MeshStats::default(),
+            scene_fingerprints: HashMap::new(),
         })
     }
+}
 
+#[wasm_bindgen]
+impl AncestralVisionTree {
     /// Load family tree from YAML string
     #[wasm_bindgen]
     pub fn load_family(&mut self, yaml: &str) -> Result<(), JsValue> {
@@ -110,249 +256,2015 @@ impl AncestralVisionTree {
     }
 
     fn load_family_internal(&mut self, yaml: &str, animated: bool, duration: f32) -> Result<(), JsValue> {
-        let family = FamilyTree::from_yaml(yaml)
+        let family = FamilyTree::from_yaml_with_limits(yaml, &self.input_limits)
+            .map_err(|e| JsValue::from_str(&e))?;
+
+        self.install_family(family, animated, duration)
+    }
+
+    /// Load family tree from CSV (`id,name,parent_id,birth,death,biography`)
+    #[wasm_bindgen]
+    pub fn load_family_csv(&mut self, csv: &str) -> Result<(), JsValue> {
+        let family = FamilyTree::from_csv_with_limits(csv, &self.input_limits)
             .map_err(|e| JsValue::from_str(&e))?;
+        self.install_family(family, false, 5.0)
+    }
+
+    /// Load a procedurally generated family (for demos and stress tests)
+    ///
+    /// `config` is an optional YAML/JSON mapping of `seed`, `max_people`,
+    /// `max_depth`, `min_children`, `max_children`, `min_bio_chars`,
+    /// `max_bio_chars`, `bio_skew`, `first_year`, `generation_years` and
+    /// `present_year` (default: this year), within the input limits.
+    #[wasm_bindgen]
+    pub fn load_synthetic_family(&mut self, config: Option<String>, animated: bool) -> Result<(), JsValue> {
+        let params = match config.as_deref().map(str::trim) {
+            Some(text) if !text.is_empty() => serde_yaml::from_str::<SyntheticParams>(text)
+                .map_err(|e| JsValue::from_str(&format!("Invalid synthetic family config: {}", e)))?,
+            _ => SyntheticParams::default(),
+        };
+        params.validate(&self.input_limits).map_err(|e| JsValue::from_str(&e))?;
+        let family = data::synthetic::generate(&params);
+        self.install_family(family, animated, 5.0)
+    }
+
+    /// Configure limits for uploaded documents from a YAML/JSON mapping of
+    /// `max_document_bytes`, `max_people`, `max_bio_chars`, `max_children`
+    /// and `max_id_len` (missing keys keep their defaults)
+    #[wasm_bindgen]
+    pub fn set_input_limits(&mut self, config: &str) -> Result<(), JsValue> {
+        self.input_limits = serde_yaml::from_str(config)
+            .map_err(|e| JsValue::from_str(&format!("Invalid input limits: {}", e)))?;
+        Ok(())
+    }
 
-        // Generate tree structure
-        let growth = TreeGrowth::new(GrowthParams::default());
-        let tree = growth.grow(&family)
-            .ok_or_else(|| JsValue::from_str("Failed to grow tree"))?;
+    /// Load a family assembled with `FamilyTreeBuilder`, within the limits
+    /// set with `set_input_limits`
+    #[wasm_bindgen]
+    pub fn load_built_family(&mut self, family: &BuiltFamily, animated: bool, duration: f32) -> Result<(), JsValue> {
+        self.input_limits.check_family(family.tree()).map_err(|e| JsValue::from_str(&e))?;
+        self.install_family(family.tree().clone(), animated, duration)
+    }
+
+    /// Start streaming a large family in chunks (see `append_people_yaml`)
+    #[wasm_bindgen]
+    pub fn begin_family_load(&mut self, name: &str, root_id: &str) {
+        self.pending_load = Some(FamilyLoader::with_limits(name, root_id, self.input_limits));
+    }
+
+    /// Parse one chunk of people (a YAML list, or a mapping with `people` and `links`)
+    ///
+    /// Returns the number of people loaded so far. Call once per frame to keep
+    /// the main thread responsive.
+    #[wasm_bindgen]
+    pub fn append_people_yaml(&mut self, yaml: &str) -> Result<usize, JsValue> {
+        let loader = self.pending_load.as_mut().ok_or("No family load in progress")?;
+        loader.append_yaml(yaml).map_err(|e| JsValue::from_str(&e))?;
+        Ok(loader.len())
+    }
+
+    /// Validate the streamed family and build the scene
+    #[wasm_bindgen]
+    pub fn finish_family_load(&mut self, animated: bool, duration: f32) -> Result<(), JsValue> {
+        let loader = self.pending_load.take().ok_or("No family load in progress")?;
+        let family = loader.finish().map_err(|e| JsValue::from_str(&e))?;
+        self.install_family(family, animated, duration)
+    }
+
+    fn install_family(&mut self, family: FamilyTree, animated: bool, duration: f32) -> Result<(), JsValue> {
+        // A new family starts at its own root, with its buds closed
+        self.focus_id = None;
+        self.focus_line = None;
+        self.expanded_buds.clear();
+        if let Some(growth) = &family.growth {
+            self.growth_params.merge_yaml(growth).map_err(|e| JsValue::from_str(&e))?;
+        }
+        let tree = self.build_scene(&family)?;
+        // Pull back so every trunk of a grove is in view
+        if !tree.grove.is_empty() {
+            self.frame_bounds(tree.bounds());
+        }
 
         // Initialize animation
         if animated {
             self.growth_animation = GrowthAnimation::new(duration);
             self.growth_animation.init_from_tree(&tree);
+            self.growth_animation.set_timing(&self.growth_timing);
             self.growth_animation.start();
         } else {
             self.growth_animation = GrowthAnimation::instant();
         }
 
+        // Initial particle upload, pre-allocated for every system at capacity
+        let particle_data = self.fireflies.get_particle_data();
+        let mut initial_data = vec![0.0f32; (MAX_FIREFLIES + MAX_ORBS + MAX_FALLING) * PARTICLE_FLOATS];
+        for (i, &v) in particle_data.iter().enumerate() {
+            if i < initial_data.len() {
+                initial_data[i] = v;
+            }
+        }
+        self.pipeline.upload_particles(&initial_data)
+            .map_err(|e| JsValue::from_str(&e))?;
+
+        // Store tree structure for animation updates
+        self.tree_structure = Some(tree);
+        self.family_tree = Some(family);
+        self.history.clear();
+
+        Ok(())
+    }
+
+    /// Grow the tree and rebuild geometry, picking and particle attractors
+    fn build_scene(&mut self, family: &FamilyTree) -> Result<BranchNode, JsValue> {
+        let (growth, trunk) = self.tree_growth(family);
+        // The whole grove unless a focus person is the trunk
+        let grown = if trunk == family.root_id { growth.grow(family) } else { growth.grow_from(family, &trunk) };
+        let tree = grown.map_err(|e| JsValue::from_str(&format!("Failed to grow tree: {}", e)))?;
+
         // Generate mesh with tracking for picking
-        let mesh_params = MeshParams::default();
-        let generator = TrackedMeshGenerator::new(mesh_params);
+        let generator = TrackedMeshGenerator::new(self.mesh_params);
         let (mesh, branch_infos) = generator.generate_tree_tracked(&tree);
+        self.tree_mesh = mesh;
+        self.branch_infos = branch_infos;
+
+        self.finish_scene(&tree, family, None)?;
+        Ok(tree)
+    }
+
+    /// Regrow only `person_id`'s branch and descendants after an edit
+    ///
+    /// Falls back to a full rebuild when the person has no crown branch or the
+    /// layout can't be regrown piecewise.
+    fn regrow_scene(&mut self, family: &FamilyTree, person_id: &str) -> Result<BranchNode, JsValue> {
+        let Some(mut tree) = self.tree_structure.clone() else {
+            return self.build_scene(family);
+        };
+        let Some(old_count) = tree.find_crown_mut(person_id).map(|node| node.count()) else {
+            return self.build_scene(family);
+        };
+
+        let (growth, trunk) = self.tree_growth(family);
+        if trunk != tree.person_id || growth.regrow_subtree(&mut tree, person_id, family).is_err() {
+            return self.build_scene(family);
+        }
+        let generator = TrackedMeshGenerator::new(self.mesh_params);
+        let subtree = tree.iter_preorder().find(|node| node.person_id == person_id).expect("regrown above");
+        let Ok(patch) =
+            generator.regenerate_subtree(&mut self.tree_mesh, &mut self.branch_infos, old_count, subtree, tree.crown_parent(person_id))
+        else {
+            return self.build_scene(family);
+        };
 
-        // Upload to GPU
-        self.pipeline.upload_tree_mesh(&mesh)
+        self.finish_scene(&tree, family, patch)?;
+        Ok(tree)
+    }
+
+    /// Growth set up for the current params, and the person to use as the trunk
+    fn tree_growth(&mut self, family: &FamilyTree) -> (TreeGrowth, String) {
+        // Forget a focus person removed by an edit
+        if self.focus_id.as_deref().is_some_and(|id| family.get(id).is_none()) {
+            self.focus_id = None;
+        }
+        if self.focus_line.as_deref().is_some_and(|id| family.get(id).is_none()) {
+            self.focus_line = None;
+        }
+        let mut params = self.growth_params;
+        params.root_system |= self.focus_id.is_some();
+        let trunk = self.focus_id.clone().unwrap_or_else(|| family.root_id.clone());
+        let ghosted = match &self.focus_line {
+            Some(id) => {
+                let line: HashSet<&str> = family
+                    .ancestors_of(id)
+                    .into_iter()
+                    .chain(family.descendants_of(id))
+                    .map(|p| p.id.as_str())
+                    .chain([id.as_str()])
+                    .collect();
+                family.people.keys().filter(|p| !line.contains(p.as_str())).cloned().collect()
+            }
+            None => HashSet::new(),
+        };
+        let growth = TreeGrowth::new(params)
+            .with_seed(self.growth_seed)
+            .with_origin(self.tree_origin)
+            .with_expanded(self.expanded_buds.clone())
+            .with_ghosted(ghosted);
+        (growth, trunk)
+    }
+
+    /// Leaf clusters at `tree`'s branch tips, from the same seeded random
+    /// stream the tree grew with
+    fn foliage(&self, tree: &BranchNode) -> Vec<FoliageCluster> {
+        TreeGrowth::new(self.growth_params).with_seed(self.growth_seed).foliage(tree)
+    }
+
+    /// Upload the tree mesh with fresh memory threads and leaves, and point
+    /// picking, particles and narration at the new branches
+    ///
+    /// `patch` is the part of the tree mesh an edit rewrote in place; only it
+    /// (and the threads after the tree) is re-uploaded when the layout allows.
+    fn finish_scene(&mut self, tree: &BranchNode, family: &FamilyTree, mut patch: Option<MeshPatch>) -> Result<(), JsValue> {
+        // Memory threads between linked people share the tree mesh
+        let mut mesh = self.tree_mesh.clone();
+        let (thread_mesh, thread_infos) = generate_threads(
+            tree,
+            &family.links,
+            &ThreadParams { hue_stops: self.mesh_params.hue_stops, ..ThreadParams::default() },
+        );
+        mesh.merge(&thread_mesh);
+        self.thread_stats = thread_mesh.stats();
+        self.tree_simplified = false;
+        if let Some(budget) = self.triangle_budget.filter(|&b| mesh.triangle_count() > b) {
+            mesh = simplify(&mesh, budget as f32 / mesh.triangle_count() as f32);
+            patch = None;
+            self.tree_simplified = true;
+        }
+
+        // Upload to GPU, with coarser copies for when the camera backs away
+        let patched = patch.is_some_and(|patch| {
+            let threads = self.tree_mesh.vertex_count()..mesh.vertex_count();
+            let thread_indices = self.tree_mesh.indices.len()..mesh.indices.len();
+            self.pipeline.patch_tree_mesh(&mesh, patch.vertices, patch.indices)
+                && self.pipeline.patch_tree_mesh(&mesh, threads, thread_indices)
+        });
+        if !patched {
+            self.pipeline.upload_tree_mesh(&mesh)
+                .map_err(|e| JsValue::from_str(&e))?;
+        }
+        // A local edit draws the patched mesh at every distance until the
+        // next full rebuild rather than regenerating the whole tree twice more
+        let lods = if patched {
+            Vec::new()
+        } else {
+            let mut lods = TrackedMeshGenerator::new(self.mesh_params).generate_lods(tree, LOD_LEVELS);
+            for lod in &mut lods {
+                lod.merge(&thread_mesh);
+            }
+            lods
+        };
+        self.pipeline.upload_tree_lods(&lods)
             .map_err(|e| JsValue::from_str(&e))?;
 
+        // The remaining parts are cheap to regenerate; after a local edit
+        // only the ones it changed are uploaded again
+
+        // Thin childless branches are left out of the meshes above and drawn
+        // as instances of one twig (when enabled)
+        let generator = TrackedMeshGenerator::new(self.mesh_params);
+        let twigs = twig_instance_data(&generator.twig_instances(tree));
+        if self.needs_upload("twigs", fingerprint(&twigs, &[]), patched) {
+            self.pipeline.upload_twigs(&generator.twig_mesh(), &twigs)
+                .map_err(|e| JsValue::from_str(&e))?;
+        }
+
+        // Memorial rings of the deceased, faded in by the growth animation
+        let memorials = generator.generate_memorials(tree);
+        if self.needs_upload("memorials", fingerprint(&memorials.vertex_data(), &memorials.indices), patched) {
+            self.pipeline.upload_memorials(&memorials)
+                .map_err(|e| JsValue::from_str(&e))?;
+        }
+
+        // Names at the branch tips, when the host has supplied a font
+        self.upload_labels(tree, family, patched)?;
+
+        // Leaves fill the foliage clusters, drawn as instances of one blade
+        let clusters = self.foliage(tree);
+        let leaves = LeafGenerator::new(LeafParams { seed: self.growth_seed, ..LeafParams::default() });
+        let leaf_data = leaves.instance_data(&clusters);
+        if self.needs_upload("leaves", fingerprint(&leaf_data, &[]), patched) {
+            self.pipeline.upload_leaves(&leaves.leaf_mesh(), &leaf_data)
+                .map_err(|e| JsValue::from_str(&e))?;
+        }
+
+        // Fruit for each child, drawn as instances of one sphere
+        let fruit = self.fruit_params.map(|params| FruitGenerator::new(FruitParams { seed: self.growth_seed, ..params }));
+        let fruit_data = fruit.as_ref().map(|fruit| fruit.instance_data(tree)).unwrap_or_default();
+        if self.needs_upload("fruit", fingerprint(&fruit_data, &[]), patched) {
+            let fruit_mesh = fruit.as_ref().map(|fruit| fruit.fruit_mesh()).unwrap_or_default();
+            self.pipeline.upload_fruit(&fruit_mesh, &fruit_data)
+                .map_err(|e| JsValue::from_str(&e))?;
+        }
+
+        // Ground around the trunk, sized to the crown; see-through when
+        // ancestor roots grow beneath it. Only regenerated when that changes.
+        let has_roots = tree.iter_preorder().any(|node| !node.roots.is_empty());
+        let ground_params = GroundParams {
+            opacity: if has_roots { 0.6 } else { 1.0 },
+            seed: self.growth_seed,
+            ..GroundParams::default()
+        };
+        let radius = ground_radius(tree.start, tree.bounds());
+        let footprint = [tree.start.x, tree.start.y, tree.start.z, radius, ground_params.opacity];
+        if self.needs_upload("ground", fingerprint(&footprint, &[ground_params.seed]), patched) {
+            let ground = generate_ground(tree.start, radius, &ground_params);
+            self.pipeline.upload_ground(&ground)
+                .map_err(|e| JsValue::from_str(&e))?;
+        }
+
+        // Branch ranges moved with the new mesh
+        self.update_outline();
+
         // Set up picking
-        self.picker.set_branches(branch_infos);
+        self.picker.set_branches(self.branch_infos.clone());
+        self.picker.set_threads(thread_infos);
 
         // Configure particle systems based on tree
-        self.fireflies.configure_from_tree(&tree);
-        self.orbs.configure_from_tree(&tree);
+        self.fireflies.configure_from_tree(tree);
+        self.orbs.configure_from_tree(tree);
+        self.falling.configure_from_tree(tree);
 
-        // Initial particle upload
-        let particle_data = self.fireflies.get_particle_data();
-        if !particle_data.is_empty() {
-            // Pre-allocate with some initial particles
-            let mut initial_data = vec![0.0f32; 150 * 8];
-            for (i, &v) in particle_data.iter().enumerate() {
-                if i < initial_data.len() {
-                    initial_data[i] = v;
-                }
-            }
-            self.pipeline.upload_particles(&initial_data)
-                .map_err(|e| JsValue::from_str(&e))?;
+        // Light shafts stream from the brightest part of the crown unless
+        // the host has placed the light
+        self.pipeline.light_position = self.light_source.unwrap_or_else(|| tree.canopy_light());
+
+        // Narration zones follow the regrown branches
+        let stopped = self.narration.set_zones(zones_from_tree(tree, family));
+        self.emit_narration(stopped);
+        Ok(())
+    }
+
+    /// Regenerate and upload the scene's name labels for `tree`, skipping
+    /// the upload when `only_if_changed` and they're as last uploaded
+    fn upload_labels(&mut self, tree: &BranchNode, family: &FamilyTree, only_if_changed: bool) -> Result<(), JsValue> {
+        let mesh = match (&self.label_font, self.scene_labels) {
+            (Some(font), true) => generate_labels(tree, font, &LabelParams::default(), |id| {
+                let person = family.get(id)?;
+                Some(if self.growth_params.privacy_mode && person.is_living() {
+                    LIVING_LABEL.to_string()
+                } else {
+                    self.locale.label(person).into_owned()
+                })
+            }),
+            _ => Mesh::new(),
+        };
+        if !self.needs_upload("labels", fingerprint(&mesh.vertex_data(), &mesh.indices), only_if_changed) {
+            return Ok(());
         }
+        self.pipeline.upload_labels(&mesh)
+            .map_err(|e| JsValue::from_str(&e))
+    }
 
-        // Store tree structure for animation updates
+    /// Record `part`'s new fingerprint; it needs uploading unless
+    /// `only_if_changed` and the fingerprint is as last uploaded
+    fn needs_upload(&mut self, part: &'static str, fingerprint: u64, only_if_changed: bool) -> bool {
+        let previous = self.scene_fingerprints.insert(part, fingerprint);
+        !only_if_changed || previous != Some(fingerprint)
+    }
+
+    /// Relabel the current scene (after a locale or font change)
+    fn refresh_labels(&mut self) -> Result<(), JsValue> {
+        let (Some(tree), Some(family)) = (self.tree_structure.take(), self.family_tree.take()) else {
+            return Ok(());
+        };
+        let result = self.upload_labels(&tree, &family, false);
         self.tree_structure = Some(tree);
         self.family_tree = Some(family);
+        result
+    }
 
-        Ok(())
+    /// Forward narration events to the host callback, if any
+    fn emit_narration(&self, events: Vec<NarrationEvent>) {
+        let Some(callback) = &self.narration_callback else {
+            return;
+        };
+        for event in events {
+            let (kind, person_id, audio_id) = match event {
+                NarrationEvent::Start { person_id, audio_id } => ("start", person_id, audio_id),
+                NarrationEvent::Stop { person_id, audio_id } => ("stop", person_id, audio_id),
+            };
+            let _ = callback.call3(
+                &JsValue::NULL,
+                &JsValue::from_str(kind),
+                &JsValue::from_str(&audio_id),
+                &JsValue::from_str(&person_id),
+            );
+        }
     }
 
-    /// Update and render a frame
-    #[wasm_bindgen]
-    pub fn render(&mut self, dt: f32) {
-        self.time += dt;
+    fn frame_bounds(&mut self, (min, max): (Vec3, Vec3)) {
+        self.camera_target = min.lerp(&max, 0.5);
+        self.camera_distance = (min.distance(&max) * 0.9).clamp(3.0, 30.0);
+    }
 
-        // Update growth animation
-        self.growth_animation.update(dt);
+    /// Apply an edit to a copy of the family, recording the old state for undo
+    fn apply_edit(
+        &mut self,
+        edit: impl FnOnce(&mut FamilyTree) -> Result<(), String>,
+    ) -> Result<(), JsValue> {
+        let current = self.family_tree.as_ref().ok_or("No family loaded")?;
+        let mut edited = current.clone();
+        edit(&mut edited).map_err(|e| JsValue::from_str(&e))?;
+        self.input_limits.check_family(&edited).map_err(|e| JsValue::from_str(&e))?;
 
-        // Pass animation progress to pipeline for shader-based animation
-        self.pipeline.set_growth_progress(self.growth_animation.get_progress());
+        let snapshot = current.clone();
+        self.replace_family(edited)?;
+        self.history.record(snapshot);
+        Ok(())
+    }
 
-        // Update particle systems (scale activity with growth)
-        let growth_scale = self.growth_animation.get_progress();
+    /// Swap in a new family state, regrowing the scene around it
+    fn replace_family(&mut self, family: FamilyTree) -> Result<(), JsValue> {
+        let tree = self.build_scene(&family)?;
+        self.adopt_scene(tree, family);
+        Ok(())
+    }
 
-        // Update fireflies
-        self.fireflies.set_activity_scale(growth_scale);
-        self.fireflies.update(dt, self.time);
+    /// Like `apply_edit` for edits confined to `person_id`'s subtree, regrowing
+    /// only that part of the scene
+    fn apply_local_edit(
+        &mut self,
+        person_id: &str,
+        edit: impl FnOnce(&mut FamilyTree) -> Result<(), String>,
+    ) -> Result<(), JsValue> {
+        let current = self.family_tree.as_ref().ok_or("No family loaded")?;
+        let mut edited = current.clone();
+        edit(&mut edited).map_err(|e| JsValue::from_str(&e))?;
+        self.input_limits.check_family(&edited).map_err(|e| JsValue::from_str(&e))?;
 
-        // Update orbs (attracted to high-luminance branches)
-        self.orbs.set_activity_scale(growth_scale);
-        self.orbs.update(dt, self.time);
+        let snapshot = current.clone();
+        let tree = self.regrow_scene(&edited, person_id)?;
+        self.adopt_scene(tree, edited);
+        self.history.record(snapshot);
+        Ok(())
+    }
 
-        // Combine particle data from both systems
-        let mut particle_data = self.fireflies.get_particle_data();
-        particle_data.extend(self.orbs.get_particle_data());
+    /// Point the outline pass at the selected person's branch ranges
+    fn update_outline(&mut self) {
+        let ranges = match &self.selected_person_id {
+            Some(id) if !self.tree_simplified => self.branch_infos
+                .iter()
+                .filter(|info| &info.person_id == id)
+                .map(|info| info.index_start..info.index_start + info.index_count)
+                .collect(),
+            _ => Vec::new(),
+        };
+        self.pipeline.set_outline(ranges);
+    }
 
-        if !particle_data.is_empty() {
-            self.pipeline.update_particles(&particle_data);
+    /// Make a freshly grown tree and its family current
+    fn adopt_scene(&mut self, tree: BranchNode, family: FamilyTree) {
+        // Keep the animation position; new branches pick up the current progress
+        self.growth_animation.init_from_tree(&tree);
+        if !self.growth_animation.is_playing() {
+            let progress = self.growth_animation.get_progress();
+            self.growth_animation.set_progress(progress);
         }
 
-        // Update camera position from orbit angles
-        let cos_x = self.camera_angle_x.cos();
-        let sin_x = self.camera_angle_x.sin();
-        let cos_y = self.camera_angle_y.cos();
-        let sin_y = self.camera_angle_y.sin();
-
-        self.pipeline.camera_position = Vec3::new(
-            self.camera_target.x + self.camera_distance * cos_x * sin_y,
-            self.camera_target.y + self.camera_distance * sin_x,
-            self.camera_target.z + self.camera_distance * cos_x * cos_y,
-        );
-        self.pipeline.camera_target = self.camera_target;
+        if self
+            .hovered_person_id
+            .as_ref()
+            .is_some_and(|id| family.get(id).is_none())
+        {
+            self.hovered_person_id = None;
+        }
+        self.hovered_link = None;
 
-        // Render
-        self.pipeline.render(self.time);
+        self.tree_structure = Some(tree);
+        self.family_tree = Some(family);
     }
 
-    /// Resize the canvas
+    /// Re-root the loaded family at another person (their descendant view),
+    /// cross-fading from the current tree to the regrown one
     #[wasm_bindgen]
-    pub fn resize(&mut self, width: i32, height: i32) -> Result<(), JsValue> {
-        self.width = width;
-        self.height = height;
-        self.pipeline.resize(width, height)
-            .map_err(|e| JsValue::from_str(&e))
+    pub fn set_root(&mut self, person_id: &str) -> Result<(), JsValue> {
+        let mut family = self.family_tree.clone().ok_or("No family loaded")?;
+        family.set_root(person_id).map_err(|e| JsValue::from_str(&e))?;
+
+        self.crossfade_to(|engine| engine.replace_family(family))
     }
 
-    /// Handle mouse move for hover detection
+    /// Pivot the view on any person: they become the trunk, their ancestors
+    /// grow as roots and their descendants as the crown
+    ///
+    /// The family itself is unchanged; pass `undefined` to return to the
+    /// family root.
     #[wasm_bindgen]
-    pub fn on_mouse_move(&mut self, x: f32, y: f32) -> Option<String> {
-        let aspect = self.width as f32 / self.height as f32;
-        let projection = Mat4::perspective(self.pipeline.fov, aspect, 0.1, 100.0);
-        let view = Mat4::look_at(
-            self.pipeline.camera_position,
-            self.pipeline.camera_target,
-            Vec3::UP,
-        );
+    pub fn set_focus_person(&mut self, person_id: Option<String>) -> Result<(), JsValue> {
+        let family = self.family_tree.as_ref().ok_or("No family loaded")?;
+        if let Some(id) = &person_id {
+            family.get(id).ok_or_else(|| JsValue::from_str(&format!("Person '{}' not found", id)))?;
+        }
+        self.focus_id = person_id;
 
-        if let Some(hit) = self.picker.pick(
-            x,
-            y,
-            self.width as f32,
-            self.height as f32,
-            &view,
-            &projection,
-            self.pipeline.camera_position,
-        ) {
-            self.hovered_person_id = Some(hit.person_id.clone());
-            Some(hit.person_id)
-        } else {
-            self.hovered_person_id = None;
-            None
+        self.crossfade_to(Self::rebuild_scene)
+    }
+
+    /// Focus on one person's direct line: their ancestors and descendants
+    /// stay solid while everyone else fades to a ghost
+    ///
+    /// Pass `undefined` to bring everyone back.
+    #[wasm_bindgen]
+    pub fn set_focus_line(&mut self, person_id: Option<String>) -> Result<(), JsValue> {
+        let family = self.family_tree.as_ref().ok_or("No family loaded")?;
+        if let Some(id) = &person_id {
+            family.get(id).ok_or_else(|| JsValue::from_str(&format!("Person '{}' not found", id)))?;
         }
+        self.focus_line = person_id;
+
+        self.crossfade_to(Self::rebuild_scene)
     }
 
-    /// Get person info by ID (returns JSON string)
+    /// Id of the person whose line is in focus (see `set_focus_line`)
     #[wasm_bindgen]
-    pub fn get_person_info(&self, id: &str) -> Option<String> {
-        self.family_tree.as_ref().and_then(|tree| {
-            tree.get(id).map(|person| {
-                format!(
-                    r#"{{"id":"{}","name":"{}","biography":"{}","lifespan":"{}"}}"#,
-                    escape_json(&person.id),
-                    escape_json(&person.name),
-                    escape_json(&person.biography),
-                    escape_json(&person.lifespan_string())
-                )
-            })
-        })
+    pub fn get_focus_line(&self) -> Option<String> {
+        self.focus_line.clone()
     }
 
-    /// Orbit camera
+    /// Run `rebuild`, fading its tree in over the current one; the current
+    /// tree stays on screen if the rebuild fails
+    fn crossfade_to(&mut self, rebuild: impl FnOnce(&mut Self) -> Result<(), JsValue>) -> Result<(), JsValue> {
+        self.pipeline.begin_crossfade();
+        let result = rebuild(self);
+        // Once the new tree is uploaded the fade runs, even if a later step failed
+        if !self.pipeline.cancel_crossfade() {
+            self.crossfade_elapsed = Some(0.0);
+        }
+        result
+    }
+
+    /// Id of the focus person, if the view is pivoted (see `set_focus_person`)
     #[wasm_bindgen]
-    pub fn orbit(&mut self, delta_x: f32, delta_y: f32) {
-        self.camera_angle_y += delta_x * 0.01;
-        self.camera_angle_x = (self.camera_angle_x + delta_y * 0.01)
-            .clamp(-std::f32::consts::FRAC_PI_2 + 0.1, std::f32::consts::FRAC_PI_2 - 0.1);
+    pub fn get_focus_person(&self) -> Option<String> {
+        self.focus_id.clone()
     }
 
-    /// Zoom camera
+    /// Id of the person the tree is currently grown from
     #[wasm_bindgen]
-    pub fn zoom(&mut self, delta: f32) {
-        self.camera_distance = (self.camera_distance + delta * 0.5).clamp(3.0, 30.0);
+    pub fn get_root(&self) -> Option<String> {
+        self.family_tree.as_ref().map(|f| f.root_id.clone())
     }
 
-    /// Pan camera target
+    /// Plant the trunk base at a world position
+    ///
+    /// Geometry, picking and particles are rebuilt around the new origin and
+    /// the camera target moves with the tree.
     #[wasm_bindgen]
-    pub fn pan(&mut self, delta_x: f32, delta_y: f32) {
-        // Pan in camera-relative space
-        let right = Vec3::new(
-            self.camera_angle_y.cos(),
-            0.0,
-            -self.camera_angle_y.sin(),
-        );
-        let up = Vec3::UP;
+    pub fn set_tree_origin(&mut self, x: f32, y: f32, z: f32) -> Result<(), JsValue> {
+        let origin = Vec3::new(x, y, z);
+        self.camera_target = self.camera_target + (origin - self.tree_origin);
+        self.tree_origin = origin;
+        self.pipeline.tree_origin = origin;
 
-        self.camera_target = self.camera_target
-            + right.scale(-delta_x * 0.01)
-            + up.scale(delta_y * 0.01);
+        self.rebuild_scene()
     }
 
-    /// Get current hovered person ID
+    /// Reseed the random variation in branch angles, so the same family can
+    /// grow into a differently shaped tree
     #[wasm_bindgen]
-    pub fn get_hovered_person(&self) -> Option<String> {
-        self.hovered_person_id.clone()
+    pub fn with_seed(&mut self, seed: u32) -> Result<(), JsValue> {
+        self.growth_seed = seed;
+        self.rebuild_scene()
     }
 
-    // === Animation Controls ===
+    /// Person info with the branch's actual visual parameters
+    fn person_info(&self, id: &str) -> Option<PersonInfo> {
+        let tree = self.family_tree.as_ref()?;
+        let person = tree.get(id)?;
+        let mut info = PersonInfo::new(tree, person, &self.locale);
+        if let Some(style) = &self.growth_params.gender_style {
+            style.apply(person.gender, &mut info.visual);
+        }
+        if self.growth_params.privacy_mode && person.is_living() {
+            info.redact();
+        }
+        Some(info)
+    }
 
-    /// Start the growth animation
+    /// World-space midpoint of a person's branch in the current scene
+    fn branch_midpoint(&self, person_id: &str) -> Option<Vec3> {
+        self.tree_structure
+            .as_ref()?
+            .iter_preorder()
+            .find(|node| node.person_id == person_id)
+            .map(|node| node.start.lerp(&node.end, 0.5))
+    }
+
+    /// Regrow the loaded family after a parameter change
+    fn rebuild_scene(&mut self) -> Result<(), JsValue> {
+        if let Some(family) = self.family_tree.take() {
+            let result = self.build_scene(&family);
+            self.family_tree = Some(family);
+            self.tree_structure = Some(result?);
+        }
+
+        Ok(())
+    }
+
+    // === Editing ===
+
+    /// Add a person (a YAML person entry) as the last child of `parent_id`
     #[wasm_bindgen]
-    pub fn start_growth_animation(&mut self) {
-        self.growth_animation.start();
+    pub fn add_person(&mut self, parent_id: &str, person_yaml: &str) -> Result<(), JsValue> {
+        let person = parse_person(person_yaml)?;
+        self.apply_local_edit(parent_id, |family| family.add_child(parent_id, person))
     }
 
-    /// Reset and restart the growth animation
+    /// Replace a person's data with a YAML person entry (matched by id)
     #[wasm_bindgen]
-    pub fn reset_growth_animation(&mut self) {
-        self.growth_animation.reset();
-        self.growth_animation.start();
+    pub fn update_person(&mut self, person_yaml: &str) -> Result<(), JsValue> {
+        let person = parse_person(person_yaml)?;
+        // Regrow from the parent so siblings re-pack around the new size
+        let anchor = self.family_tree.as_ref()
+            .and_then(|family| family.parents_of(&person.id).first().map(|p| p.id.clone()))
+            .unwrap_or_else(|| person.id.clone());
+        self.apply_local_edit(&anchor, |family| family.update_person(person))
     }
 
-    /// Complete the growth instantly
+    /// Remove a childless person
     #[wasm_bindgen]
-    pub fn complete_growth(&mut self) {
-        self.growth_animation.complete_instantly();
+    pub fn remove_person(&mut self, id: &str) -> Result<(), JsValue> {
+        let anchor = self.family_tree.as_ref()
+            .and_then(|family| family.parents_of(id).first().map(|p| p.id.clone()))
+            .unwrap_or_else(|| id.to_string());
+        self.apply_local_edit(&anchor, |family| family.remove_person(id).map(|_| ()))
     }
 
-    /// Set growth progress manually (0.0 to 1.0)
+    /// Merge another family (YAML) into the loaded one
+    ///
+    /// `strategy` is "rename", "overwrite" or "reject" for ids present in both;
+    /// returns a JSON report of added, renamed and overwritten people.
     #[wasm_bindgen]
-    pub fn set_growth_progress(&mut self, progress: f32) {
-        self.growth_animation.set_progress(progress);
+    pub fn merge_family_yaml(&mut self, yaml: &str, strategy: &str) -> Result<String, JsValue> {
+        let strategy = MergeStrategy::parse(strategy).map_err(|e| JsValue::from_str(&e))?;
+        let other = FamilyTree::from_yaml_with_limits(yaml, &self.input_limits).map_err(|e| JsValue::from_str(&e))?;
+
+        let mut report = None;
+        self.apply_edit(|family| {
+            report = Some(family.merge(&other, strategy)?);
+            Ok(())
+        })?;
+        Ok(report.map(|r| r.to_json()).unwrap_or_default())
     }
 
-    /// Get current growth progress (0.0 to 1.0)
+    /// Compare the loaded family with another version (YAML or JSON)
+    ///
+    /// Returns `{added, removed, changed}` as JSON, where `changed` lists each
+    /// person's differing fields.
     #[wasm_bindgen]
-    pub fn get_growth_progress(&self) -> f32 {
-        self.growth_animation.get_progress()
+    pub fn diff_family_yaml(&self, yaml: &str) -> Result<String, JsValue> {
+        let current = self.family_tree.as_ref().ok_or("No family loaded")?;
+        let other = FamilyTree::from_yaml_with_limits(yaml, &self.input_limits).map_err(|e| JsValue::from_str(&e))?;
+        Ok(current.diff(&other).to_json())
     }
 
-    /// Check if growth animation is complete
+    /// Non-fatal problems with the loaded family as a JSON array of
+    /// `{kind, ids, message}` (e.g. likely duplicate people)
     #[wasm_bindgen]
-    pub fn is_growth_complete(&self) -> bool {
-        self.growth_animation.is_complete()
+    pub fn get_validation_warnings(&self) -> String {
+        let warnings: Vec<String> = self
+            .family_tree
+            .as_ref()
+            .map(|tree| tree.validate().iter().map(|w| w.to_json()).collect())
+            .unwrap_or_default();
+        format!("[{}]", warnings.join(","))
     }
 
-    /// Check if growth animation is playing
+    /// Merge a duplicate person into `keep_id`, combining their data (undoable)
+    #[wasm_bindgen]
+    pub fn merge_duplicate_people(&mut self, keep_id: &str, remove_id: &str) -> Result<(), JsValue> {
+        self.apply_edit(|family| family.merge_duplicates(keep_id, remove_id))
+    }
+
+    /// Undo the last edit; returns false if there was nothing to undo
+    #[wasm_bindgen]
+    pub fn undo(&mut self) -> Result<bool, JsValue> {
+        let (Some(current), Some(previous)) = (&self.family_tree, self.history.peek_undo()) else {
+            return Ok(false);
+        };
+        let (current, previous) = (current.clone(), previous.clone());
+        // Step the history only once the regrow succeeds, so it stays in sync
+        let tree = self.build_scene(&previous)?;
+        self.history.undo(&current);
+        self.adopt_scene(tree, previous);
+        Ok(true)
+    }
+
+    /// Redo the last undone edit; returns false if there was nothing to redo
+    #[wasm_bindgen]
+    pub fn redo(&mut self) -> Result<bool, JsValue> {
+        let (Some(current), Some(next)) = (&self.family_tree, self.history.peek_redo()) else {
+            return Ok(false);
+        };
+        let (current, next) = (current.clone(), next.clone());
+        let tree = self.build_scene(&next)?;
+        self.history.redo(&current);
+        self.adopt_scene(tree, next);
+        Ok(true)
+    }
+
+    #[wasm_bindgen]
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    #[wasm_bindgen]
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    // === Appearance ===
+
+    /// Hide names, biographies and dates of living people in person info and
+    /// listings, and dim their branches (for sharing visualizations publicly)
+    ///
+    /// People count as living when marked `living: true` or when they have no
+    /// `death_year`.
+    #[wasm_bindgen]
+    pub fn set_privacy_mode(&mut self, enabled: bool) -> Result<(), JsValue> {
+        self.growth_params.privacy_mode = enabled;
+        self.rebuild_scene()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_privacy_mode(&self) -> bool {
+        self.growth_params.privacy_mode
+    }
+
+    /// Choose branch hues "personal" (the default) or by "surname", where each
+    /// family-name line shares a hue family passed down to children
+    #[wasm_bindgen]
+    pub fn set_hue_mode(&mut self, mode: &str) -> Result<(), JsValue> {
+        self.growth_params.hue_mode = HueMode::parse(mode).map_err(|e| JsValue::from_str(&e))?;
+        self.rebuild_scene()
+    }
+
+    /// Place many children in a "fan" (the default) or a "phyllotaxis"
+    /// spiral around their parent's branch
+    #[wasm_bindgen]
+    pub fn set_child_arrangement(&mut self, arrangement: &str) -> Result<(), JsValue> {
+        self.growth_params.child_arrangement =
+            ChildArrangement::parse(arrangement).map_err(|e| JsValue::from_str(&e))?;
+        self.rebuild_scene()
+    }
+
+    /// Tune the phyllotaxis spiral: degrees turned between children (137.5 is
+    /// the golden angle) and the distance each child sprouts above the last
+    #[wasm_bindgen]
+    pub fn set_phyllotaxis(&mut self, divergence_degrees: f32, pitch: f32) -> Result<(), JsValue> {
+        self.growth_params.divergence_angle = divergence_degrees.to_radians();
+        self.growth_params.child_pitch = pitch;
+        self.rebuild_scene()
+    }
+
+    /// Lay the family out as a "botanical" tree (the default), a "radial" fan
+    /// chart, a "colonized" crown routed by space colonization, or an
+    /// "lsystem" silhouette (see `set_lsystem_style`)
+    #[wasm_bindgen]
+    pub fn set_layout_mode(&mut self, mode: &str) -> Result<(), JsValue> {
+        self.growth_params.layout = LayoutMode::parse(mode).map_err(|e| JsValue::from_str(&e))?;
+        self.rebuild_scene()
+    }
+
+    /// Keep each person's branch in place when siblings are added or removed,
+    /// at the cost of sibling packing (branches may occasionally overlap)
+    #[wasm_bindgen]
+    pub fn set_stable_placement(&mut self, enabled: bool) -> Result<(), JsValue> {
+        self.growth_params.stable_placement = enabled;
+        self.rebuild_scene()
+    }
+
+    /// Regrow with a named style: "oak", "willow", "bonsai", "cypress",
+    /// "baobab", "windswept" or "default"
+    ///
+    /// Display settings (privacy, hue mode, gender style, child order, stable
+    /// placement, generation limit) are kept.
+    #[wasm_bindgen]
+    pub fn set_growth_style(&mut self, name: &str) -> Result<(), JsValue> {
+        let current = self.growth_params;
+        self.growth_params = GrowthParams {
+            gender_style: current.gender_style,
+            child_order: current.child_order,
+            privacy_mode: current.privacy_mode,
+            hue_mode: current.hue_mode,
+            stable_placement: current.stable_placement,
+            max_generations: current.max_generations,
+            generation_height: current.generation_height,
+            ..GrowthParams::preset(name).map_err(|e| JsValue::from_str(&e))?
+        };
+        self.rebuild_scene()
+    }
+
+    /// Grow at most this many generations (undefined = all); deeper lines end
+    /// in glowing buds that `expand_bud` opens
+    #[wasm_bindgen]
+    pub fn set_max_generations(&mut self, max: Option<usize>) -> Result<(), JsValue> {
+        self.growth_params.max_generations = max.map(|m| m.max(1));
+        self.expanded_buds.clear();
+        self.rebuild_scene()
+    }
+
+    /// Size branches by how many people they carry (pipe model) instead of
+    /// by generation alone
+    #[wasm_bindgen]
+    pub fn set_pipe_model(&mut self, enabled: bool) -> Result<(), JsValue> {
+        self.growth_params.pipe_model = enabled;
+        self.rebuild_scene()
+    }
+
+    /// Keep the crown centered over the trunk by swinging lopsided lineages
+    /// around their parents
+    #[wasm_bindgen]
+    pub fn set_crown_balancing(&mut self, enabled: bool) -> Result<(), JsValue> {
+        self.growth_params.balance_crown = enabled;
+        self.rebuild_scene()
+    }
+
+    /// Sculpt the trunk: tilt `lean` radians toward compass `heading`, and
+    /// sway sideways by `sway` of its length over `bends` half-waves (2 = S)
+    #[wasm_bindgen]
+    pub fn set_trunk_shape(&mut self, lean: f32, heading: f32, sway: f32, bends: f32) -> Result<(), JsValue> {
+        self.growth_params.trunk_lean = lean;
+        self.growth_params.trunk_heading = heading;
+        self.growth_params.trunk_sway = sway;
+        self.growth_params.trunk_bends = bends.max(0.0);
+        self.rebuild_scene()
+    }
+
+    /// End every generation's branches on one height band of `height` world
+    /// units (undefined = free growth), so height reads as generations
+    #[wasm_bindgen]
+    pub fn set_generation_height(&mut self, height: Option<f32>) -> Result<(), JsValue> {
+        self.growth_params.generation_height = height.filter(|h| *h > 0.0);
+        self.rebuild_scene()
+    }
+
+    /// Grow one more generation from a bud, regrowing only that branch
+    #[wasm_bindgen]
+    pub fn expand_bud(&mut self, person_id: &str) -> Result<(), JsValue> {
+        let family = self.family_tree.clone().ok_or("No family loaded")?;
+        family.get(person_id).ok_or_else(|| JsValue::from_str(&format!("Person '{}' not found", person_id)))?;
+        if !self.expanded_buds.insert(person_id.to_string()) {
+            return Ok(());
+        }
+        let tree = self.regrow_scene(&family, person_id)?;
+        self.adopt_scene(tree, family);
+        Ok(())
+    }
+
+    /// Box around every grown branch, all trunks of a grove included, as JSON
+    /// (`{min: [x, y, z], max: [x, y, z], center: [x, y, z], radius}`)
+    #[wasm_bindgen]
+    pub fn get_scene_bounds(&self) -> String {
+        let Some(tree) = &self.tree_structure else {
+            return "null".to_string();
+        };
+        let (min, max) = tree.bounds();
+        let center = min.lerp(&max, 0.5);
+        format!(
+            r#"{{"min":[{},{},{}],"max":[{},{},{}],"center":[{},{},{}],"radius":{}}}"#,
+            min.x, min.y, min.z, max.x, max.y, max.z, center.x, center.y, center.z,
+            min.distance(&max) / 2.0
+        )
+    }
+
+    /// Aim the camera at the middle of the scene, far enough back to see it all
+    #[wasm_bindgen]
+    pub fn frame_scene(&mut self) {
+        if let Some(bounds) = self.tree_structure.as_ref().map(|tree| tree.bounds()) {
+            self.frame_bounds(bounds);
+        }
+    }
+
+    /// Unopened buds as a JSON array of `{id, hidden}` (hidden relatives count)
+    #[wasm_bindgen]
+    pub fn get_buds(&self) -> String {
+        let buds: Vec<String> = self
+            .tree_structure
+            .iter()
+            .flat_map(|tree| tree.iter_preorder())
+            .filter(|node| node.hidden_descendants > 0)
+            .map(|node| {
+                format!(r#"{{"id":"{}","hidden":{}}}"#, escape_json(&node.person_id), node.hidden_descendants)
+            })
+            .collect();
+        format!("[{}]", buds.join(","))
+    }
+
+    /// Leaf clusters at the childless branch tips as JSON
+    /// (`[{id, center: [x, y, z], radius, hue, glow}]`), for attaching canopies
+    #[wasm_bindgen]
+    pub fn get_foliage(&self) -> String {
+        let clusters: Vec<String> = self
+            .tree_structure
+            .iter()
+            .flat_map(|tree| self.foliage(tree))
+            .map(|c| {
+                format!(
+                    r#"{{"id":"{}","center":[{},{},{}],"radius":{},"hue":{},"glow":{}}}"#,
+                    escape_json(&c.person_id), c.center.x, c.center.y, c.center.z, c.radius, c.hue, c.glow
+                )
+            })
+            .collect();
+        format!("[{}]", clusters.join(","))
+    }
+
+    /// Leaf clusters per branch tip (0 = bare) and their size relative to the
+    /// tip branch
+    #[wasm_bindgen]
+    pub fn set_foliage(&mut self, density: f32, size: f32) -> Result<(), JsValue> {
+        self.growth_params.foliage_density = density.max(0.0);
+        self.growth_params.foliage_size = size.max(0.0);
+        self.rebuild_scene()
+    }
+
+    /// Update growth parameters from a YAML/JSON mapping (the same keys as a
+    /// family file's `growth:` section; missing keys keep their current values)
+    #[wasm_bindgen]
+    pub fn set_growth_params(&mut self, config: &str) -> Result<(), JsValue> {
+        let config: serde_yaml::Value = serde_yaml::from_str(config)
+            .map_err(|e| JsValue::from_str(&format!("Invalid growth params: {}", e)))?;
+        self.growth_params.merge_yaml(&config).map_err(|e| JsValue::from_str(&e))?;
+        self.rebuild_scene()
+    }
+
+    /// Bend branches toward a light direction (e.g. the sun or the camera) by
+    /// `strength` (0 = off, 1 = strongly); applies to the botanical and
+    /// colonized layouts
+    #[wasm_bindgen]
+    pub fn set_phototropism(&mut self, x: f32, y: f32, z: f32, strength: f32) -> Result<(), JsValue> {
+        self.growth_params.light_direction = Vec3::new(x, y, z);
+        self.growth_params.phototropism = strength.max(0.0);
+        self.rebuild_scene()
+    }
+
+    /// Shear the crown downwind toward (`x`, `z`), increasingly with each
+    /// generation; `strength` 0 is calm
+    #[wasm_bindgen]
+    pub fn set_wind(&mut self, x: f32, z: f32, strength: f32) -> Result<(), JsValue> {
+        self.growth_params.wind_direction = Vec3::new(x, 0.0, z);
+        self.growth_params.wind_strength = strength.max(0.0);
+        self.rebuild_scene()
+    }
+
+    /// Grow the root person's ancestors as glowing roots beneath the trunk
+    /// (an hourglass of both directions of lineage)
+    #[wasm_bindgen]
+    pub fn set_root_system(&mut self, enabled: bool) -> Result<(), JsValue> {
+        self.growth_params.root_system = enabled;
+        self.rebuild_scene()
+    }
+
+    /// Grow with an L-system silhouette: "oak", "willow" or "poplar"
+    #[wasm_bindgen]
+    pub fn set_lsystem_style(&mut self, style: &str) -> Result<(), JsValue> {
+        self.growth_params.lsystem = LSystemPreset::parse(style).map_err(|e| JsValue::from_str(&e))?;
+        self.growth_params.layout = LayoutMode::LSystem;
+        self.rebuild_scene()
+    }
+
+    /// Arrange siblings "chronological" (by birth date, the default) or
+    /// "listed" (as in the source); an explicit `order` on a person always wins
+    #[wasm_bindgen]
+    pub fn set_child_order(&mut self, order: &str) -> Result<(), JsValue> {
+        self.growth_params.child_order = ChildOrder::parse(order).map_err(|e| JsValue::from_str(&e))?;
+        self.rebuild_scene()
+    }
+
+    /// Enable gender-based hue and bark differences with an optional YAML/JSON
+    /// mapping (`female_hue`, `male_hue`, `other_hue`, `hue_jitter`,
+    /// `female_roughness`, `male_roughness`); disabled by default
+    #[wasm_bindgen]
+    pub fn set_gender_style(&mut self, enabled: bool, config: Option<String>) -> Result<(), JsValue> {
+        self.growth_params.gender_style = if enabled {
+            let style = match config.as_deref().map(str::trim) {
+                Some(text) if !text.is_empty() => serde_yaml::from_str::<GenderStyle>(text)
+                    .map_err(|e| JsValue::from_str(&format!("Invalid gender style: {}", e)))?,
+                _ => GenderStyle::default(),
+            };
+            Some(style)
+        } else {
+            None
+        };
+        self.rebuild_scene()
+    }
+
+    /// Update and render a frame
+    #[wasm_bindgen]
+    pub fn render(&mut self, dt: f32) {
+        // Ambient motion follows the scene clock; growth and cross-fades stay on wall time
+        self.render_at(dt, self.time + dt * self.time_scale);
+    }
+
+    /// Render a frame `dt` seconds of wall time later, with the scene clock at `scene_time`
+    fn render_at(&mut self, dt: f32, scene_time: f32) {
+        let scene_dt = scene_time - self.time;
+        self.time = scene_time;
+
+        // Update growth animation
+        self.growth_animation.update(dt);
+
+        // Advance the re-root cross-fade
+        if let Some(elapsed) = self.crossfade_elapsed.as_mut() {
+            *elapsed += dt;
+            let progress = *elapsed / CROSSFADE_DURATION;
+            self.pipeline.set_crossfade(progress);
+            if progress >= 1.0 {
+                self.crossfade_elapsed = None;
+            }
+        }
+
+        // Blend leaf colors and fog into the new season
+        if let Some((from, elapsed)) = self.season_transition.as_mut() {
+            *elapsed += dt;
+            let t = (*elapsed / SEASON_TRANSITION).min(1.0);
+            self.pipeline.season = from.lerp(&self.season.look(), t * t * (3.0 - 2.0 * t));
+            if t >= 1.0 {
+                self.season_transition = None;
+            }
+        }
+
+        // Pass animation progress to pipeline for shader-based animation
+        self.pipeline.set_growth_progress(self.growth_animation.get_progress());
+
+        // Update particle systems (scale activity with growth)
+        let growth_scale = self.growth_animation.get_progress();
+
+        // Update fireflies
+        self.fireflies.set_activity_scale(growth_scale);
+        self.fireflies.update(scene_dt, self.time);
+
+        // Update orbs (attracted to high-luminance branches)
+        self.orbs.set_activity_scale(growth_scale);
+        self.orbs.update(scene_dt, self.time);
+
+        // Seasonal particles fall once the tree has grown in
+        self.falling.set_activity_scale(growth_scale);
+        self.falling.update(scene_dt, self.time);
+
+        // Combine particle data from all systems
+        let mut particle_data = self.fireflies.get_particle_data();
+        particle_data.extend(self.orbs.get_particle_data());
+        particle_data.extend(self.falling.get_particle_data());
+
+        if !particle_data.is_empty() {
+            self.pipeline.update_particles(&particle_data);
+        }
+
+        // Update camera position from orbit angles
+        let cos_x = self.camera_angle_x.cos();
+        let sin_x = self.camera_angle_x.sin();
+        let cos_y = self.camera_angle_y.cos();
+        let sin_y = self.camera_angle_y.sin();
+
+        self.pipeline.camera_position = Vec3::new(
+            self.camera_target.x + self.camera_distance * cos_x * sin_y,
+            self.camera_target.y + self.camera_distance * sin_x,
+            self.camera_target.z + self.camera_distance * cos_x * cos_y,
+        );
+        self.pipeline.camera_target = self.camera_target;
+
+        // Dwell timers run on wall time so narration isn't affected by time scale
+        if !self.narration.is_empty() {
+            let events = self.narration.update(self.pipeline.camera_position, dt);
+            self.emit_narration(events);
+        }
+
+        // Render
+        self.pipeline.render(self.time);
+    }
+
+    /// Jump the scene clock to `t` seconds (e.g. to sync with an external timeline)
+    #[wasm_bindgen]
+    pub fn set_scene_time(&mut self, t: f32) {
+        self.time = t.max(0.0);
+    }
+
+    /// Current scene clock in seconds
+    #[wasm_bindgen]
+    pub fn get_scene_time(&self) -> f32 {
+        self.time
+    }
+
+    /// Speed of the scene clock: 1.0 is real time, 0.5 half speed, 0.0 frozen
+    #[wasm_bindgen]
+    pub fn set_time_scale(&mut self, scale: f32) {
+        self.time_scale = if scale.is_finite() { scale.max(0.0) } else { 1.0 };
+    }
+
+    #[wasm_bindgen]
+    pub fn get_time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// Stop pulsing, veins and particles in place (resume with `set_time_scale(1.0)`)
+    #[wasm_bindgen]
+    pub fn freeze_time(&mut self) {
+        self.time_scale = 0.0;
+    }
+
+    /// Resize the canvas
+    #[wasm_bindgen]
+    pub fn resize(&mut self, width: i32, height: i32) -> Result<(), JsValue> {
+        self.width = width;
+        self.height = height;
+        self.pipeline.resize(width, height)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Free the GPU buffers, textures and shaders right away rather than
+    /// when the context is collected; frames draw nothing afterwards, and
+    /// loading, resizing or capturing returns an error
+    #[wasm_bindgen]
+    pub fn dispose(&mut self) {
+        self.pipeline.dispose();
+    }
+
+    /// Handle mouse move for hover detection
+    #[wasm_bindgen]
+    pub fn on_mouse_move(&mut self, x: f32, y: f32) -> Option<String> {
+        let (view, projection) = self.camera_matrices();
+
+        if let Some(hit) = self.picker.pick(
+            x,
+            y,
+            self.width as f32,
+            self.height as f32,
+            &view,
+            &projection,
+            self.pipeline.camera_position,
+        ) {
+            if hit.link_index.is_some() {
+                // Threads are reported through get_hovered_link
+                self.hovered_person_id = None;
+                self.hovered_link = hit.link_index;
+                return None;
+            }
+            self.hovered_link = None;
+            self.hovered_person_id = Some(hit.person_id.clone());
+            Some(hit.person_id)
+        } else {
+            self.hovered_person_id = None;
+            self.hovered_link = None;
+            None
+        }
+    }
+
+    /// Screen positions of branch label anchors as JSON, for DOM labels to
+    /// follow each frame: `[{id, x, y, depth, visible}]` in canvas pixels
+    /// (y down)
+    ///
+    /// `ids_json` is an array of person ids to report (all when omitted).
+    /// Anchors behind the camera are left out; `visible` is false for those
+    /// outside the viewport.
+    #[wasm_bindgen]
+    pub fn get_screen_positions(&self, ids_json: Option<String>) -> Result<String, JsValue> {
+        let ids: Option<Vec<String>> = ids_json
+            .map(|json| serde_yaml::from_str(&json))
+            .transpose()
+            .map_err(|e| JsValue::from_str(&format!("Invalid id list: {}", e)))?;
+        let (view, projection) = self.camera_matrices();
+        let anchors = self.picker.screen_anchors(
+            ids.as_deref(),
+            &view,
+            &projection,
+            self.width as f32,
+            self.height as f32,
+        );
+
+        let entries: Vec<String> = anchors
+            .iter()
+            .map(|a| {
+                format!(
+                    r#"{{"id":"{}","x":{:.1},"y":{:.1},"depth":{:.3},"visible":{}}}"#,
+                    escape_json(&a.person_id),
+                    a.x,
+                    a.y,
+                    a.depth,
+                    a.on_screen
+                )
+            })
+            .collect();
+        Ok(format!("[{}]", entries.join(",")))
+    }
+
+    /// View and projection matrices of the current camera
+    fn camera_matrices(&self) -> (Mat4, Mat4) {
+        let aspect = self.width as f32 / self.height as f32;
+        let projection = Mat4::perspective(self.pipeline.fov, aspect, 0.1, 100.0);
+        let view = Mat4::look_at(
+            self.pipeline.camera_position,
+            self.pipeline.camera_target,
+            Vec3::UP,
+        );
+        (view, projection)
+    }
+
+    /// Get the hovered memory thread as JSON (`{from, to, label}`), if any
+    #[wasm_bindgen]
+    pub fn get_hovered_link(&self) -> Option<String> {
+        let thread = self.picker.thread(self.hovered_link?)?;
+        Some(format!(
+            r#"{{"from":"{}","to":"{}","label":"{}"}}"#,
+            escape_json(&thread.from_id),
+            escape_json(&thread.to_id),
+            escape_json(&thread.label)
+        ))
+    }
+
+    /// Get person info by ID as a JS object (`null` if unknown)
+    ///
+    /// Fields: id, name, biography, lifespan, gender, placeholder, audio_id, events,
+    /// sources, confidence, meta, children, parent, generation and visual.
+    #[wasm_bindgen]
+    pub fn get_person_info(&self, id: &str) -> Result<JsValue, JsValue> {
+        let Some(info) = self.person_info(id) else {
+            return Ok(JsValue::NULL);
+        };
+
+        let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+        info.serialize(&serializer)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize person info: {}", e)))
+    }
+
+    /// Get only the requested person info fields as a JS object (`null` if unknown)
+    ///
+    /// `fields_json` is an array of names: any field of `get_person_info`, plus
+    /// the derived `descendant_count` and `branch_world_position` (midpoint of
+    /// the person's branch, `[x, y, z]`).
+    #[wasm_bindgen]
+    pub fn get_person_info_fields(&self, id: &str, fields_json: &str) -> Result<JsValue, JsValue> {
+        let fields: Vec<String> = serde_yaml::from_str(fields_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid field list: {}", e)))?;
+        let Some(info) = self.person_info(id) else {
+            return Ok(JsValue::NULL);
+        };
+        let tree = self.family_tree.as_ref().ok_or("No family loaded")?;
+
+        let selected = info
+            .select(&fields, |name| match name {
+                "descendant_count" => Some(serde_yaml::Value::from(tree.descendants_of(id).len() as u64)),
+                "branch_world_position" => Some(
+                    self.branch_midpoint(id)
+                        .map(|p| serde_yaml::Value::Sequence(vec![p.x.into(), p.y.into(), p.z.into()]))
+                        .unwrap_or(serde_yaml::Value::Null),
+                ),
+                _ => None,
+            })
+            .map_err(|e| JsValue::from_str(&e))?;
+
+        let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+        selected.serialize(&serializer)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize person info: {}", e)))
+    }
+
+    /// Search people by name (returns JSON array of `{id, name}`, best first)
+    #[wasm_bindgen]
+    pub fn search_people(&self, query: &str) -> String {
+        let mut people = self
+            .family_tree
+            .as_ref()
+            .map(|tree| tree.search(query))
+            .unwrap_or_default();
+        // Hidden names must not be discoverable by searching for them
+        if self.growth_params.privacy_mode {
+            people.retain(|p| !p.is_living());
+        }
+        people_json(&people, &self.locale, self.growth_params.privacy_mode)
+    }
+
+    /// Get ancestors of a person, nearest first (JSON array of `{id, name}`)
+    #[wasm_bindgen]
+    pub fn ancestors_of(&self, id: &str) -> String {
+        let people = self
+            .family_tree
+            .as_ref()
+            .map(|tree| tree.ancestors_of(id))
+            .unwrap_or_default();
+        people_json(&people, &self.locale, self.growth_params.privacy_mode)
+    }
+
+    /// Get descendants of a person in pre-order (JSON array of `{id, name}`)
+    #[wasm_bindgen]
+    pub fn descendants_of(&self, id: &str) -> String {
+        let people = self
+            .family_tree
+            .as_ref()
+            .map(|tree| tree.descendants_of(id))
+            .unwrap_or_default();
+        people_json(&people, &self.locale, self.growth_params.privacy_mode)
+    }
+
+    /// Describe how person `b` is related to person `a` (e.g. "first cousin once removed")
+    #[wasm_bindgen]
+    pub fn relationship_between(&self, a: &str, b: &str) -> Option<String> {
+        self.family_tree.as_ref()?.relationship_between(a, b)
+    }
+
+    // === Localization ===
+
+    /// Set the viewer's locale tag (e.g. "sr-Latn")
+    ///
+    /// Names, lifespans and event dates in person info follow the locale;
+    /// an empty tag uses primary names and plain dates.
+    #[wasm_bindgen]
+    pub fn set_locale(&mut self, tag: &str) {
+        self.locale.set_tag(tag);
+        // Names drawn in the scene follow too; if they can't be re-uploaded
+        // they keep the old locale until the next rebuild
+        let _ = self.refresh_labels();
+    }
+
+    /// Draw names at branch tips with a signed distance field font:
+    /// BMFont JSON metrics and the atlas as one byte per pixel, rows top first
+    #[wasm_bindgen]
+    pub fn set_label_font(&mut self, metrics: &str, atlas: &[u8], width: u32, height: u32) -> Result<(), JsValue> {
+        let font = SdfFont::from_json(metrics).map_err(|e| JsValue::from_str(&e))?;
+        self.pipeline.upload_label_atlas(atlas, width as i32, height as i32)
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.label_font = Some(font);
+        self.refresh_labels()
+    }
+
+    /// Show or hide the names drawn in the scene (HTML overlays are unaffected)
+    #[wasm_bindgen]
+    pub fn set_scene_labels(&mut self, enabled: bool) -> Result<(), JsValue> {
+        self.scene_labels = enabled;
+        self.refresh_labels()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_locale(&self) -> String {
+        self.locale.tag().to_string()
+    }
+
+    /// Install a `(name, locale) => string | undefined` hook used when a person
+    /// has no name for the active locale; pass nothing to remove it
+    #[wasm_bindgen]
+    pub fn set_transliterator(&mut self, callback: Option<js_sys::Function>) {
+        self.locale.set_transliterator(callback.map(|f| {
+            Box::new(move |name: &str, tag: &str| {
+                f.call2(&JsValue::NULL, &JsValue::from_str(name), &JsValue::from_str(tag))
+                    .ok()
+                    .and_then(|v| v.as_string())
+            }) as data::locale::Transliterator
+        }));
+    }
+
+    /// Install a `(event, audio_id, person_id)` hook, called with "start" when
+    /// the camera has lingered near a branch with an `audio_id` and "stop"
+    /// once it moves away; pass nothing to remove it
+    #[wasm_bindgen]
+    pub fn set_narration_callback(&mut self, callback: Option<js_sys::Function>) {
+        self.narration_callback = callback;
+    }
+
+    /// Configure narration triggers with a YAML/JSON mapping
+    /// (`trigger_distance`, `release_distance`, `dwell_seconds`)
+    #[wasm_bindgen]
+    pub fn set_narration_params(&mut self, config: &str) -> Result<(), JsValue> {
+        let params: NarrationParams = serde_yaml::from_str(config)
+            .map_err(|e| JsValue::from_str(&format!("Invalid narration params: {}", e)))?;
+        self.narration.set_params(params);
+        Ok(())
+    }
+
+    /// Cap the triangles drawn for the tree (e.g. on low-end devices); bigger
+    /// meshes are simplified before upload (None = no cap)
+    #[wasm_bindgen]
+    pub fn set_triangle_budget(&mut self, budget: Option<u32>) -> Result<(), JsValue> {
+        self.triangle_budget = budget.map(|b| b as usize);
+        self.rebuild_scene()
+    }
+
+    /// Bake the tree's colors into its vertices and shade it with the
+    /// simplified shader (for low-end GPUs)
+    #[wasm_bindgen]
+    pub fn set_vertex_colors(&mut self, enabled: bool) -> Result<(), JsValue> {
+        self.mesh_params.vertex_colors = enabled;
+        self.pipeline.baked_colors = enabled;
+        self.rebuild_scene()
+    }
+
+    /// Draw thin childless branches as instances of one canonical twig
+    /// instead of full ring meshes, saving vertices on large trees
+    #[wasm_bindgen]
+    pub fn set_instanced_twigs(&mut self, enabled: bool) -> Result<(), JsValue> {
+        self.mesh_params.instanced_twigs = enabled;
+        self.rebuild_scene()
+    }
+
+    /// Weather the branches of people who lived at least `age` years (None =
+    /// not by age) and of the `generations` nearest the trunk with knots
+    /// and moss
+    #[wasm_bindgen]
+    pub fn set_weathering(&mut self, age: Option<f32>, generations: usize) -> Result<(), JsValue> {
+        self.mesh_params.weathering_age = age;
+        self.mesh_params.weathering_generations = generations;
+        self.rebuild_scene()
+    }
+
+    /// Hang a glowing fruit along each branch for every child, sized by
+    /// the family it leads to when `scale_by_descendants` is set
+    #[wasm_bindgen]
+    pub fn set_fruit(&mut self, enabled: bool, scale_by_descendants: bool) -> Result<(), JsValue> {
+        self.fruit_params = enabled.then_some(FruitParams { scale_by_descendants, ..FruitParams::default() });
+        self.rebuild_scene()
+    }
+
+    /// Show halo rings around the branches of the deceased
+    #[wasm_bindgen]
+    pub fn set_memorial_rings(&mut self, enabled: bool) -> Result<(), JsValue> {
+        self.mesh_params.memorial_rings = enabled;
+        self.rebuild_scene()
+    }
+
+    /// Camera distances beyond which the coarser tree meshes are drawn
+    #[wasm_bindgen]
+    pub fn set_lod_distances(&mut self, near: f32, far: f32) {
+        self.pipeline.lod_distances = [near, far.max(near)];
+    }
+
+    /// Darken branch junctions and the inner canopy with screen-space
+    /// ambient occlusion: `strength` 0 to 1 (0 = off), `radius` in world units
+    #[wasm_bindgen]
+    pub fn set_ambient_occlusion(&mut self, strength: f32, radius: f32) {
+        self.pipeline.ssao_strength = strength.clamp(0.0, 1.0);
+        self.pipeline.ssao_radius = radius.max(0.01);
+    }
+
+    /// Dress the scene for "spring", "summer" (the default), "autumn" or
+    /// "winter": leaf colors and fog blend over a few seconds, and blossom,
+    /// leaves or snow start to fall
+    #[wasm_bindgen]
+    pub fn set_season(&mut self, name: &str) -> Result<(), JsValue> {
+        self.season = Season::parse(name).map_err(|e| JsValue::from_str(&e))?;
+        self.falling.set_kind(self.season.particles());
+        self.season_transition = Some((self.pipeline.season, 0.0));
+        Ok(())
+    }
+
+    /// Mirror the tree in a dark glossy ground: `strength` 0 to 1 (0 = off)
+    #[wasm_bindgen]
+    pub fn set_ground_reflection(&mut self, strength: f32) {
+        self.pipeline.reflection_strength = strength.clamp(0.0, 1.0);
+    }
+
+    /// Stream god rays from the light source through the glow: `strength`
+    /// 0 to 2 (0 = off)
+    #[wasm_bindgen]
+    pub fn set_light_shafts(&mut self, strength: f32) {
+        self.pipeline.light_shafts = strength.clamp(0.0, 2.0);
+    }
+
+    /// Radiate light shafts from a fixed world position
+    #[wasm_bindgen]
+    pub fn set_light_source(&mut self, x: f32, y: f32, z: f32) {
+        let position = Vec3::new(x, y, z);
+        self.light_source = Some(position);
+        self.pipeline.light_position = position;
+    }
+
+    /// Radiate light shafts from the brightest part of the canopy again
+    /// (the default)
+    #[wasm_bindgen]
+    pub fn use_canopy_light_source(&mut self) {
+        self.light_source = None;
+        if let Some(tree) = &self.tree_structure {
+            self.pipeline.light_position = tree.canopy_light();
+        }
+    }
+
+    /// Fog over the tree, ground and particles: `density` 0 to 4 (1 = the
+    /// default haze, 0 = none), how quickly it thins with height, and an
+    /// optional `[r, g, b]` color in place of the theme's
+    #[wasm_bindgen]
+    pub fn set_fog(&mut self, density: f32, height_falloff: f32, color: Option<Vec<f32>>) -> Result<(), JsValue> {
+        let color = match color.as_deref() {
+            None => None,
+            Some(&[r, g, b]) => Some(Vec3::new(r.max(0.0), g.max(0.0), b.max(0.0))),
+            Some(_) => return Err(JsValue::from_str("Fog color needs three components (r, g, b)")),
+        };
+        self.pipeline.fog_density = density.clamp(0.0, 4.0);
+        self.pipeline.fog_height_falloff = height_falloff.max(0.0);
+        self.pipeline.fog_override = color;
+        Ok(())
+    }
+
+    /// Show the procedural night sky (stars, nebula, moon) behind the tree,
+    /// or the theme's flat background
+    #[wasm_bindgen]
+    pub fn set_sky(&mut self, enabled: bool) {
+        self.pipeline.sky = enabled;
+    }
+
+    /// Recolor the scene with a built-in theme: "bioluminescent" (the
+    /// default), "autumn", "midnight" or "paper". Baked vertex colors are
+    /// rebaked with the theme's hues
+    #[wasm_bindgen]
+    pub fn set_theme(&mut self, name: &str) -> Result<(), JsValue> {
+        let theme = Theme::named(name).map_err(|e| JsValue::from_str(&e))?;
+        self.pipeline.theme = theme;
+        if self.mesh_params.hue_stops == theme.hue_stops {
+            return Ok(());
+        }
+        self.mesh_params.hue_stops = theme.hue_stops;
+        if self.mesh_params.vertex_colors {
+            self.rebuild_scene()?;
+        }
+        Ok(())
+    }
+
+    /// Tune the glow: brightness `threshold` above which it blooms, `strength`
+    /// it's added back with, and `radius_passes` blur passes (0 to 8) for
+    /// how far it spreads
+    #[wasm_bindgen]
+    pub fn set_bloom(&mut self, threshold: f32, strength: f32, radius_passes: u32) {
+        self.pipeline.bloom_threshold = threshold.max(0.0);
+        self.pipeline.bloom_strength = strength.max(0.0);
+        self.pipeline.bloom_passes = radius_passes.min(MAX_BLOOM_PASSES);
+    }
+
+    /// Map the scene's HDR color to the display with "aces" (the default),
+    /// "reinhard", "filmic" or "none"
+    #[wasm_bindgen]
+    pub fn set_tone_mapping(&mut self, mode: &str) -> Result<(), JsValue> {
+        self.pipeline.tone_mapping = ToneMapping::parse(mode).map_err(|e| JsValue::from_str(&e))?;
+        Ok(())
+    }
+
+    /// Brighten or darken the final image by `ev` stops (0 = unchanged)
+    #[wasm_bindgen]
+    pub fn set_exposure(&mut self, ev: f32) {
+        self.pipeline.exposure = ev.clamp(-8.0, 8.0);
+    }
+
+    /// Smooth edges (the thin glowing twigs especially) with "fxaa" (the
+    /// default), "msaa" or "off"
+    #[wasm_bindgen]
+    pub fn set_antialiasing(&mut self, mode: &str) -> Result<(), JsValue> {
+        let mode = Antialiasing::parse(mode).map_err(|e| JsValue::from_str(&e))?;
+        self.pipeline.set_antialiasing(mode).map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Get canvas versus internal render resolution as JSON
+    ///
+    /// `capped` is true when the canvas exceeds what the GPU can allocate and
+    /// the scene is rendered smaller and upscaled.
+    #[wasm_bindgen]
+    pub fn get_render_stats(&self) -> String {
+        self.pipeline.render_stats().to_json()
+    }
+
+    /// Get the size of the meshes on the GPU as JSON: totals, then each
+    /// uploaded part (tree, LODs, twigs, leaves, fruit, ground, memorials,
+    /// labels) with its vertex, triangle, instance and byte counts
+    ///
+    /// The tree's counts include its memory threads (also given alone under
+    /// `threads`). Per-generation and per-branch counts are given only when
+    /// the tree went up unsimplified.
+    #[wasm_bindgen]
+    pub fn get_mesh_stats(&self) -> String {
+        let branches = (!self.tree_simplified).then(|| self.tree_mesh.stats().with_branches(&self.branch_infos));
+        mesh_stats_json(&self.pipeline.uploaded_stats(), &self.thread_stats, branches.as_ref())
+    }
+
+    /// Get per-generation statistics for the loaded family as JSON
+    #[wasm_bindgen]
+    pub fn get_statistics(&self) -> Option<String> {
+        self.family_tree.as_ref().map(|tree| tree.statistics().to_json())
+    }
+
+    /// Orbit camera
+    #[wasm_bindgen]
+    pub fn orbit(&mut self, delta_x: f32, delta_y: f32) {
+        self.camera_angle_y += delta_x * 0.01;
+        self.camera_angle_x = (self.camera_angle_x + delta_y * 0.01)
+            .clamp(-std::f32::consts::FRAC_PI_2 + 0.1, std::f32::consts::FRAC_PI_2 - 0.1);
+    }
+
+    /// Zoom camera
+    #[wasm_bindgen]
+    pub fn zoom(&mut self, delta: f32) {
+        self.camera_distance = (self.camera_distance + delta * 0.5).clamp(3.0, 30.0);
+    }
+
+    /// Pan camera target
+    #[wasm_bindgen]
+    pub fn pan(&mut self, delta_x: f32, delta_y: f32) {
+        // Pan in camera-relative space
+        let right = Vec3::new(
+            self.camera_angle_y.cos(),
+            0.0,
+            -self.camera_angle_y.sin(),
+        );
+        let up = Vec3::UP;
+
+        self.camera_target = self.camera_target
+            + right.scale(-delta_x * 0.01)
+            + up.scale(delta_y * 0.01);
+    }
+
+    // === Named Views ===
+
+    /// Save the current camera, focus person, opened buds, theme, season and
+    /// hue mode as a named view, with optional host settings JSON (filters);
+    /// returns the view as JSON
+    #[wasm_bindgen]
+    pub fn save_view(&mut self, name: &str, settings: Option<String>) -> String {
+        let mut expanded: Vec<String> = self.expanded_buds.iter().cloned().collect();
+        expanded.sort();
+        let view = NamedView {
+            name: name.to_string(),
+            camera: CameraState::new(
+                self.camera_distance,
+                self.camera_angle_x,
+                self.camera_angle_y,
+                self.camera_target,
+            ),
+            focus: self.focus_id.clone(),
+            expanded,
+            theme: Some(self.pipeline.theme.name.to_string()),
+            season: Some(self.season.name().to_string()),
+            hue_mode: Some(self.growth_params.hue_mode.name().to_string()),
+            settings,
+        };
+        let json = view.to_json();
+        self.views.save(view);
+        json
+    }
+
+    /// Restore a named view: its camera, focus person, opened buds and any
+    /// theme, season and hue mode it saved, regrowing the tree when those
+    /// changed. Returns the view as JSON so the host can re-apply its own
+    /// settings, or None if no view has that name
+    #[wasm_bindgen]
+    pub fn apply_view(&mut self, name: &str) -> Result<Option<String>, JsValue> {
+        let Some(view) = self.views.get(name).cloned() else {
+            return Ok(None);
+        };
+        if let Some(theme) = &view.theme {
+            self.set_theme(theme)?;
+        }
+        if let Some(season) = &view.season {
+            if Season::parse(season).map_err(|e| JsValue::from_str(&e))? != self.season {
+                self.set_season(season)?;
+            }
+        }
+
+        let hue_mode = match &view.hue_mode {
+            Some(mode) => HueMode::parse(mode).map_err(|e| JsValue::from_str(&e))?,
+            None => self.growth_params.hue_mode,
+        };
+        let expanded: HashSet<String> = view.expanded.iter().cloned().collect();
+        if hue_mode != self.growth_params.hue_mode || view.focus != self.focus_id || expanded != self.expanded_buds {
+            if let (Some(family), Some(id)) = (&self.family_tree, &view.focus) {
+                family.get(id).ok_or_else(|| JsValue::from_str(&format!("Person '{}' not found", id)))?;
+            }
+            self.growth_params.hue_mode = hue_mode;
+            self.focus_id = view.focus.clone();
+            self.expanded_buds = expanded;
+            if self.family_tree.is_some() {
+                self.crossfade_to(Self::rebuild_scene)?;
+            }
+        }
+
+        self.camera_distance = view.camera.distance;
+        self.camera_angle_x = view.camera.angle_x;
+        self.camera_angle_y = view.camera.angle_y;
+        self.camera_target = view.camera.target();
+        Ok(Some(view.to_json()))
+    }
+
+    /// Delete a named view
+    #[wasm_bindgen]
+    pub fn remove_view(&mut self, name: &str) -> bool {
+        self.views.remove(name)
+    }
+
+    /// All saved views as a JSON array
+    #[wasm_bindgen]
+    pub fn export_views(&self) -> String {
+        self.views.to_json()
+    }
+
+    /// Load views from JSON produced by `export_views`; returns how many were read
+    #[wasm_bindgen]
+    pub fn import_views(&mut self, json: &str) -> Result<usize, JsValue> {
+        self.views.import_json(json).map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Get current hovered person ID
+    #[wasm_bindgen]
+    pub fn get_hovered_person(&self) -> Option<String> {
+        self.hovered_person_id.clone()
+    }
+
+    /// Draw a crisp rim around a person's branch (None clears it)
+    #[wasm_bindgen]
+    pub fn set_selected_person(&mut self, person_id: Option<String>) {
+        self.selected_person_id = person_id;
+        self.update_outline();
+    }
+
+    /// Id of the person whose branch is outlined, if any
+    #[wasm_bindgen]
+    pub fn get_selected_person(&self) -> Option<String> {
+        self.selected_person_id.clone()
+    }
+
+    /// Color and width (pixels) of the selection rim
+    #[wasm_bindgen]
+    pub fn set_selection_outline(&mut self, r: f32, g: f32, b: f32, width: f32) {
+        self.pipeline.outline_color = Vec3::new(r, g, b);
+        self.pipeline.outline_width = width.clamp(1.0, 16.0);
+    }
+
+    // === Animation Controls ===
+
+    /// Start the growth animation
+    #[wasm_bindgen]
+    pub fn start_growth_animation(&mut self) {
+        self.growth_animation.start();
+    }
+
+    /// Reset and restart the growth animation
+    #[wasm_bindgen]
+    pub fn reset_growth_animation(&mut self) {
+        self.growth_animation.reset();
+        self.growth_animation.start();
+    }
+
+    /// Complete the growth instantly
+    #[wasm_bindgen]
+    pub fn complete_growth(&mut self) {
+        self.growth_animation.complete_instantly();
+    }
+
+    /// Set growth progress manually (0.0 to 1.0)
+    #[wasm_bindgen]
+    pub fn set_growth_progress(&mut self, progress: f32) {
+        self.growth_animation.set_progress(progress);
+    }
+
+    /// Get current growth progress (0.0 to 1.0)
+    #[wasm_bindgen]
+    pub fn get_growth_progress(&self) -> f32 {
+        self.growth_animation.get_progress()
+    }
+
+    /// Check if growth animation is complete
+    #[wasm_bindgen]
+    pub fn is_growth_complete(&self) -> bool {
+        self.growth_animation.is_complete()
+    }
+
+    /// Check if growth animation is playing
     #[wasm_bindgen]
     pub fn is_growth_playing(&self) -> bool {
         self.growth_animation.is_playing()
     }
+
+    /// Configure per-generation growth timing from JSON or YAML
+    ///
+    /// Accepts `generation_delay`, `easing` and a `generations` array of
+    /// `{delay, duration, easing}` overrides (fractions of the animation),
+    /// e.g. to linger on the founders and accelerate through recent generations.
+    #[wasm_bindgen]
+    pub fn set_growth_timing(&mut self, config: &str) -> Result<(), JsValue> {
+        let timing: GrowthTiming = serde_yaml::from_str(config)
+            .map_err(|e| JsValue::from_str(&format!("Invalid growth timing: {}", e)))?;
+        self.growth_animation.set_timing(&timing);
+        self.growth_timing = timing;
+        Ok(())
+    }
+
+    // === Export ===
+
+    /// Capture a deterministic frame sequence of the tree growing
+    ///
+    /// Returns an array of RGBA `Uint8Array` frames (top row first) at canvas size,
+    /// ready to be assembled into a GIF or video by the host.
+    #[wasm_bindgen]
+    pub fn capture_sequence(
+        &mut self,
+        frame_count: u32,
+        fps: f32,
+        progress_from: f32,
+        progress_to: f32,
+    ) -> Result<js_sys::Array, JsValue> {
+        let frames = self
+            .capture_frames(frame_count, fps, progress_from, progress_to)
+            .map_err(|e| JsValue::from_str(&e))?;
+
+        Ok(frames
+            .iter()
+            .map(|frame| js_sys::Uint8Array::from(&frame[..]))
+            .collect())
+    }
+
+    /// Render a single high-resolution RGBA frame (top row first) for printing
+    ///
+    /// The frame is rendered in canvas-sized tiles, so it may be larger than
+    /// the browser allows for a canvas, up to 8192x8192 pixels' worth.
+    #[wasm_bindgen]
+    pub fn render_poster(&self, width: u32, height: u32) -> Result<Vec<u8>, JsValue> {
+        self.pipeline
+            .render_poster(self.time, width as i32, height as i32)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Render the current scene again and read back the composited frame,
+    /// e.g. for `VisualAnalyzer.analyze(frame.pixels, frame.width, frame.height)`
+    #[wasm_bindgen]
+    pub fn capture_frame(&self) -> Result<CapturedFrame, JsValue> {
+        self.pipeline.render(self.time);
+        let pixels = self.pipeline.read_pixels().map_err(|e| JsValue::from_str(&e))?;
+        Ok(CapturedFrame {
+            width: self.width as u32,
+            height: self.height as u32,
+            pixels,
+        })
+    }
+}
+
+/// One rendered frame: RGBA bytes, top row first
+#[wasm_bindgen]
+pub struct CapturedFrame {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl CapturedFrame {
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn pixels(&self) -> js_sys::Uint8Array {
+        js_sys::Uint8Array::from(&self.pixels[..])
+    }
+}
+
+impl AncestralVisionTree {
+    /// Step growth and time at a fixed rate, rendering and reading back each frame
+    ///
+    /// Frame `i` is shown at scene time `start + i / fps` with the particles
+    /// respawned from their initial seeds, so capturing the same range twice
+    /// gives the same frames. A re-root cross-fade or season blend in
+    /// progress holds still while capturing, and the live animation state is
+    /// restored afterwards.
+    pub fn capture_frames(
+        &mut self,
+        frame_count: u32,
+        fps: f32,
+        progress_from: f32,
+        progress_to: f32,
+    ) -> Result<Vec<Vec<u8>>, String> {
+        if fps <= 0.0 {
+            return Err("fps must be positive".to_string());
+        }
+
+        let saved_animation = self.growth_animation.clone();
+        let saved_particles = (self.fireflies.clone(), self.orbs.clone(), self.falling.clone());
+        // Taken out so frames don't advance them (a finished cross-fade
+        // would free the outgoing tree)
+        let saved_transitions = (self.crossfade_elapsed.take(), self.season_transition.take());
+        let start = self.time;
+        let dt = 1.0 / fps;
+
+        self.fireflies.reset();
+        self.orbs.reset();
+        self.falling.reset();
+
+        let mut frames = Vec::with_capacity(frame_count as usize);
+        let mut result = Ok(());
+        for i in 0..frame_count {
+            let progress = sequence_progress(i, frame_count, progress_from, progress_to);
+            self.growth_animation.set_progress(progress);
+            self.render_at(dt, start + i as f32 * dt);
+
+            match self.pipeline.read_pixels() {
+                Ok(frame) => frames.push(frame),
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+
+        self.growth_animation = saved_animation;
+        (self.fireflies, self.orbs, self.falling) = saved_particles;
+        (self.crossfade_elapsed, self.season_transition) = saved_transitions;
+        self.pipeline.set_growth_progress(self.growth_animation.get_progress());
+        self.time = start;
+
+        result.map(|_| frames)
+    }
+}
+
+/// Hash of uploaded float data and indices, to tell whether a part changed
+fn fingerprint(floats: &[f32], indices: &[u32]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    floats.iter().for_each(|f| f.to_bits().hash(&mut hasher));
+    indices.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Growth progress for frame `index` of a `count`-frame sequence
+fn sequence_progress(index: u32, count: u32, from: f32, to: f32) -> f32 {
+    let t = if count > 1 {
+        index as f32 / (count - 1) as f32
+    } else {
+        1.0
+    };
+    from + (to - from) * t
+}
+
+/// Parse a single YAML person entry
+fn parse_person(yaml: &str) -> Result<Person, JsValue> {
+    let mut person = serde_yaml::from_str(yaml).map_err(|e| JsValue::from_str(&format!("Invalid person: {}", e)))?;
+    data::limits::sanitize_person(&mut person);
+    Ok(person)
+}
+
+/// JSON array of `{id, name}` objects
+///
+/// With `private` set, living people are listed under a placeholder name.
+fn people_json(people: &[&data::Person], locale: &LabelLocale, private: bool) -> String {
+    let entries: Vec<String> = people
+        .iter()
+        .map(|person| {
+            let name = if private && person.is_living() {
+                LIVING_LABEL.into()
+            } else {
+                locale.label(person)
+            };
+            format!(
+                r#"{{"id":"{}","name":"{}"}}"#,
+                escape_json(&person.id),
+                escape_json(&name)
+            )
+        })
+        .collect();
+
+    format!("[{}]", entries.join(","))
+}
+
+/// Sizes of the uploaded meshes, see `get_mesh_stats`
+#[derive(Serialize)]
+struct MeshStatsReport<'a> {
+    vertices: usize,
+    /// Drawn triangles, instances counted once each
+    triangles: usize,
+    gpu_bytes: usize,
+    simplified: bool,
+    threads: &'a MeshStats,
+    parts: Vec<PartStats<'a>>,
+    generations: Vec<mesh::stats::GenerationMeshStats>,
+    branches: &'a [mesh::stats::BranchStats],
+}
+
+#[derive(Serialize)]
+struct PartStats<'a> {
+    part: &'a str,
+    #[serde(flatten)]
+    stats: &'a MeshStats,
+    gpu_bytes: usize,
+}
+
+/// JSON report of the uploaded `parts`, with the tree's per-branch counts
+/// when `branches` is given (None = the tree was simplified)
+fn mesh_stats_json(parts: &[(String, MeshStats)], threads: &MeshStats, branches: Option<&MeshStats>) -> String {
+    let report = MeshStatsReport {
+        vertices: parts.iter().map(|(_, stats)| stats.vertices).sum(),
+        triangles: parts.iter().map(|(_, stats)| stats.drawn_triangles()).sum(),
+        gpu_bytes: parts.iter().map(|(_, stats)| stats.gpu_bytes()).sum(),
+        simplified: branches.is_none(),
+        threads,
+        parts: parts
+            .iter()
+            .map(|(part, stats)| PartStats { part, stats, gpu_bytes: stats.gpu_bytes() })
+            .collect(),
+        generations: branches.map(MeshStats::generations).unwrap_or_default(),
+        branches: branches.map_or(&[], |stats| &stats.branches),
+    };
+    serde_json::to_string(&report).expect("mesh stats serialize to JSON")
 }
 
 /// Escape special characters for JSON
-fn escape_json(s: &str) -> String {
-    s.replace('\\', "\\\\")
-        .replace('"', "\\\"")
-        .replace('\n', "\\n")
-        .replace('\r', "\\r")
-        .replace('\t', "\\t")
+pub(crate) fn escape_json(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            // Other control characters are invalid raw in JSON strings
+            c if c < '\u{20}' => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
 }
 
 #[cfg(test)]
@@ -364,5 +2276,35 @@ mod tests {
         assert_eq!(escape_json("hello"), "hello");
         assert_eq!(escape_json("hello\nworld"), "hello\\nworld");
         assert_eq!(escape_json(r#"say "hi""#), r#"say \"hi\""#);
+        assert_eq!(escape_json("a\u{0}b\u{1f}"), "a\\u0000b\\u001f");
+    }
+
+    #[test]
+    fn test_mesh_stats_json() {
+        let mut tree = Mesh::new();
+        tree.vertices.push(mesh::Vertex::new(Vec3::ZERO, Vec3::UP));
+        tree.add_triangle(0, 0, 0);
+        let tree_stats = tree.stats();
+        let parts = [
+            ("tree".to_string(), tree.stats()),
+            ("leaves".to_string(), tree.stats().with_instances(4, 11)),
+        ];
+        let json = mesh_stats_json(&parts, &MeshStats::default(), Some(&tree_stats));
+        assert!(json.starts_with(r#"{"vertices":2,"triangles":5,"#));
+        assert!(json.contains(r#""simplified":false"#));
+        assert!(json.contains(r#"{"part":"leaves","vertices":1,"triangles":1,"#));
+        assert!(json.contains(r#""instances":4,"instance_bytes":176,"#));
+
+        let json = mesh_stats_json(&parts, &MeshStats::default(), None);
+        assert!(json.contains(r#""simplified":true"#));
+        assert!(json.ends_with(r#""generations":[],"branches":[]}"#));
+    }
+
+    #[test]
+    fn test_sequence_progress() {
+        assert_eq!(sequence_progress(0, 5, 0.0, 1.0), 0.0);
+        assert_eq!(sequence_progress(4, 5, 0.0, 1.0), 1.0);
+        assert!((sequence_progress(2, 5, 0.2, 0.6) - 0.4).abs() < 0.0001);
+        assert_eq!(sequence_progress(0, 1, 0.0, 0.5), 0.5);
     }
 }
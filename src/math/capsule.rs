@@ -0,0 +1,104 @@
+use super::Vec3;
+
+/// A line segment thickened by a radius: a tight collision shape for a
+/// stretch of branch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Capsule {
+    pub start: Vec3,
+    pub end: Vec3,
+    pub radius: f32,
+}
+
+impl Capsule {
+    pub const fn new(start: Vec3, end: Vec3, radius: f32) -> Self {
+        Self { start, end, radius }
+    }
+
+    /// Point on the capsule's segment nearest `p`
+    pub fn closest_point(&self, p: Vec3) -> Vec3 {
+        let axis = self.end - self.start;
+        let length_squared = axis.length_squared();
+        if length_squared <= f32::EPSILON {
+            return self.start;
+        }
+        let t = ((p - self.start).dot(&axis) / length_squared).clamp(0.0, 1.0);
+        self.start.lerp(&self.end, t)
+    }
+
+    /// Distance from `p` to the capsule's surface (negative inside)
+    pub fn distance(&self, p: Vec3) -> f32 {
+        p.distance(&self.closest_point(p)) - self.radius
+    }
+
+    /// Distance along a ray (unit `dir`) to where it first enters the capsule
+    pub fn ray_intersect(&self, origin: Vec3, dir: Vec3) -> Option<f32> {
+        let axis = self.end - self.start;
+        let offset = origin - self.start;
+        let axis_sq = axis.dot(&axis);
+        let axis_dir = axis.dot(&dir);
+        let axis_offset = axis.dot(&offset);
+
+        // Cylindrical body, hit only between the end caps
+        let a = axis_sq - axis_dir * axis_dir;
+        let b = axis_sq * dir.dot(&offset) - axis_offset * axis_dir;
+        let c = axis_sq * offset.dot(&offset) - axis_offset * axis_offset - self.radius * self.radius * axis_sq;
+        let h = b * b - a * c;
+        let body = (a > 1e-8 && h >= 0.0)
+            .then(|| (-b - h.sqrt()) / a)
+            .filter(|&t| {
+                let along = axis_offset + t * axis_dir;
+                t > 0.0 && along > 0.0 && along < axis_sq
+            });
+
+        // Hemispherical caps
+        [body, ray_sphere(origin, dir, self.start, self.radius), ray_sphere(origin, dir, self.end, self.radius)]
+            .into_iter()
+            .flatten()
+            .min_by(|a, b| a.total_cmp(b))
+    }
+}
+
+/// Nearest positive distance along a ray (unit `dir`) to a sphere
+fn ray_sphere(origin: Vec3, dir: Vec3, center: Vec3, radius: f32) -> Option<f32> {
+    let oc = origin - center;
+    let b = oc.dot(&dir);
+    let h = b * b - (oc.dot(&oc) - radius * radius);
+    if h < 0.0 {
+        return None;
+    }
+    let h = h.sqrt();
+    [-b - h, -b + h].into_iter().find(|&t| t > 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capsule_distance() {
+        let capsule = Capsule::new(Vec3::ZERO, Vec3::new(0.0, 2.0, 0.0), 0.5);
+        assert!((capsule.distance(Vec3::new(1.0, 1.0, 0.0)) - 0.5).abs() < 1e-5);
+        assert!((capsule.distance(Vec3::new(0.0, 3.0, 0.0)) - 0.5).abs() < 1e-5);
+        assert!(capsule.distance(Vec3::new(0.0, 1.0, 0.2)) < 0.0);
+    }
+
+    #[test]
+    fn test_ray_hits_body_and_caps() {
+        let capsule = Capsule::new(Vec3::ZERO, Vec3::new(0.0, 2.0, 0.0), 0.5);
+        let toward = Vec3::new(0.0, 0.0, -1.0);
+
+        // Side of the body
+        let t = capsule.ray_intersect(Vec3::new(0.0, 1.0, 5.0), toward).unwrap();
+        assert!((t - 4.5).abs() < 1e-4);
+        // Rounded top, beyond the segment's end
+        let t = capsule.ray_intersect(Vec3::new(0.0, 2.3, 5.0), toward).unwrap();
+        assert!((t - (5.0 - 0.4)).abs() < 1e-4);
+        // Straight down the axis
+        let t = capsule.ray_intersect(Vec3::new(0.0, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0)).unwrap();
+        assert!((t - 7.5).abs() < 1e-4);
+
+        // Past the side, and pointing away
+        assert!(capsule.ray_intersect(Vec3::new(0.8, 1.0, 5.0), toward).is_none());
+        assert!(capsule.ray_intersect(Vec3::new(0.0, 1.0, 5.0), -toward).is_none());
+    }
+}
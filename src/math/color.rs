@@ -0,0 +1,47 @@
+use super::Vec3;
+
+/// HSV to RGB conversion; hue wraps, so -0.1 and 0.9 are the same
+pub fn hsv_to_rgb(h: f32, s: f32, v: f32) -> Vec3 {
+    let h = h.rem_euclid(1.0) * 6.0;
+    let i = h.floor() as i32;
+    let f = h - h.floor();
+    let p = v * (1.0 - s);
+    let q = v * (1.0 - f * s);
+    let t = v * (1.0 - (1.0 - f) * s);
+
+    match i % 6 {
+        0 => Vec3::new(v, t, p),
+        1 => Vec3::new(q, v, p),
+        2 => Vec3::new(p, v, t),
+        3 => Vec3::new(p, q, v),
+        4 => Vec3::new(t, p, v),
+        _ => Vec3::new(v, p, q),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hsv_to_rgb() {
+        // Red
+        let red = hsv_to_rgb(0.0, 1.0, 1.0);
+        assert!((red.x - 1.0).abs() < 0.01);
+        assert!(red.y.abs() < 0.01);
+        assert!(red.z.abs() < 0.01);
+
+        // Green
+        let green = hsv_to_rgb(1.0 / 3.0, 1.0, 1.0);
+        assert!(green.x.abs() < 0.01);
+        assert!((green.y - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_hue_wraps() {
+        let close = |a: Vec3, b: Vec3| a.distance(&b) < 1e-4;
+        assert!(close(hsv_to_rgb(-0.1, 0.8, 0.9), hsv_to_rgb(0.9, 0.8, 0.9)));
+        assert!(close(hsv_to_rgb(1.25, 0.8, 0.9), hsv_to_rgb(0.25, 0.8, 0.9)));
+        assert!(close(hsv_to_rgb(1.0, 1.0, 1.0), hsv_to_rgb(0.0, 1.0, 1.0)));
+    }
+}
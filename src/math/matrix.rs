@@ -34,6 +34,13 @@ impl Mat4 {
         m
     }
 
+    /// Mirror across the horizontal plane at `height`
+    pub fn reflection_y(height: f32) -> Self {
+        let mut m = Self::scale(1.0, -1.0, 1.0);
+        m.data[13] = 2.0 * height;
+        m
+    }
+
     pub fn rotation_x(angle: f32) -> Self {
         let c = angle.cos();
         let s = angle.sin();
@@ -104,6 +111,22 @@ impl Mat4 {
         }
     }
 
+    /// Off-center perspective projection (glFrustum)
+    pub fn frustum(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Self {
+        let rl = 1.0 / (right - left);
+        let tb = 1.0 / (top - bottom);
+        let nf = 1.0 / (near - far);
+
+        Self {
+            data: [
+                2.0 * near * rl, 0.0, 0.0, 0.0,
+                0.0, 2.0 * near * tb, 0.0, 0.0,
+                (right + left) * rl, (top + bottom) * tb, (far + near) * nf, -1.0,
+                0.0, 0.0, 2.0 * far * near * nf, 0.0,
+            ],
+        }
+    }
+
     /// Look-at view matrix
     pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
         let f = (target - eye).normalize();
@@ -146,6 +169,13 @@ impl Mat4 {
         )
     }
 
+    /// Transform a point and divide by w (e.g. to normalized device
+    /// coordinates); None when it lands on or behind the eye (w <= 0)
+    pub fn project_point(&self, p: Vec3) -> Option<Vec3> {
+        let w = self.data[3] * p.x + self.data[7] * p.y + self.data[11] * p.z + self.data[15];
+        (w > 0.0).then(|| self.transform_point(p).scale(1.0 / w))
+    }
+
     /// Transform a direction (ignores translation)
     pub fn transform_direction(&self, d: Vec3) -> Vec3 {
         Vec3::new(
@@ -203,6 +233,38 @@ mod tests {
         assert!((result.y - 1.0).abs() < 0.0001);
     }
 
+    #[test]
+    fn test_symmetric_frustum_matches_perspective() {
+        let fov = std::f32::consts::FRAC_PI_4;
+        let aspect = 1.5;
+        let top = 0.1 * (fov / 2.0).tan();
+        let right = top * aspect;
+
+        let p = Mat4::perspective(fov, aspect, 0.1, 100.0);
+        let f = Mat4::frustum(-right, right, -top, top, 0.1, 100.0);
+        for i in 0..16 {
+            assert!((p.data[i] - f.data[i]).abs() < 0.0001, "element {}", i);
+        }
+    }
+
+    #[test]
+    fn test_project_point() {
+        let p = Mat4::perspective(std::f32::consts::FRAC_PI_2, 1.0, 0.1, 100.0);
+        let ndc = p.project_point(Vec3::new(1.0, -0.5, -2.0)).unwrap();
+        assert!((ndc.x - 0.5).abs() < 0.0001);
+        assert!((ndc.y + 0.25).abs() < 0.0001);
+        assert!(ndc.z > -1.0 && ndc.z < 1.0);
+        assert!(p.project_point(Vec3::new(0.0, 0.0, 1.0)).is_none());
+    }
+
+    #[test]
+    fn test_reflection_y() {
+        let m = Mat4::reflection_y(1.5);
+        let p = m.transform_point(Vec3::new(2.0, 4.0, -1.0));
+        assert!((p - Vec3::new(2.0, -1.0, -1.0)).length() < 0.0001);
+        assert!((m.transform_point(Vec3::new(0.0, 1.5, 0.0)).y - 1.5).abs() < 0.0001);
+    }
+
     #[test]
     fn test_matrix_mul() {
         let t = Mat4::translation(1.0, 0.0, 0.0);
@@ -1,7 +1,11 @@
+pub mod capsule;
+pub mod color;
 pub mod vec3;
 pub mod matrix;
 pub mod spline;
 
+pub use capsule::Capsule;
+pub use color::hsv_to_rgb;
 pub use vec3::Vec3;
 pub use matrix::Mat4;
 pub use spline::{CatmullRomSpline, evaluate_catmull_rom, generate_branch_curve};
@@ -1,7 +1,13 @@
-use crate::math::Vec3;
+use std::collections::HashMap;
+use std::ops::Range;
+use crate::math::{hsv_to_rgb, Vec3};
+use super::stats::MeshStats;
+
+/// Floats per vertex in the GPU buffer, see [`Vertex::to_array`]
+pub const VERTEX_FLOATS: usize = 18;
 
 /// A vertex with position, normal, UV, and custom attributes
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Vertex {
     pub position: Vec3,
     pub normal: Vec3,
@@ -12,6 +18,15 @@ pub struct Vertex {
     pub luminance: f32,
     /// Hue shift for color variation
     pub hue: f32,
+    /// Coverage for dithered transparency (1.0 = opaque)
+    pub opacity: f32,
+    /// Surface direction in which U grows, for normal mapping; the
+    /// bitangent (V) is `normal × tangent`. Zero (the default) for surfaces
+    /// without bark relief; only bark rings set one.
+    pub tangent: Vec3,
+    /// Baked RGB color, for the simplified shader (black unless the
+    /// generator bakes colors)
+    pub color: Vec3,
 }
 
 impl Vertex {
@@ -23,6 +38,9 @@ impl Vertex {
             glow: 0.3,
             luminance: 0.3,
             hue: 0.0,
+            opacity: 1.0,
+            tangent: Vec3::ZERO,
+            color: Vec3::ZERO,
         }
     }
 
@@ -38,18 +56,62 @@ impl Vertex {
         self
     }
 
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    pub fn with_tangent(mut self, tangent: Vec3) -> Self {
+        self.tangent = tangent;
+        self
+    }
+
+    pub fn with_color(mut self, color: Vec3) -> Self {
+        self.color = color;
+        self
+    }
+
     /// Convert to flat array for WebGL buffer
-    /// Layout: position(3) + normal(3) + uv(2) + glow(1) + luminance(1) + hue(1) = 11 floats
-    pub fn to_array(&self) -> [f32; 11] {
+    /// Layout: position(3) + normal(3) + uv(2) + glow(1) + luminance(1) + hue(1) + opacity(1)
+    /// + tangent(3) + color(3) = 18 floats
+    pub fn to_array(&self) -> [f32; VERTEX_FLOATS] {
         [
             self.position.x, self.position.y, self.position.z,
             self.normal.x, self.normal.y, self.normal.z,
             self.uv[0], self.uv[1],
-            self.glow, self.luminance, self.hue,
+            self.glow, self.luminance, self.hue, self.opacity,
+            self.tangent.x, self.tangent.y, self.tangent.z,
+            self.color.x, self.color.y, self.color.z,
         ]
     }
 }
 
+/// Index buffer contents at the narrowest width a mesh allows
+#[derive(Debug, Clone, PartialEq)]
+pub enum IndexData<'a> {
+    U16(Vec<u16>),
+    U32(&'a [u32]),
+}
+
+impl IndexData<'_> {
+    pub fn len(&self) -> usize {
+        match self {
+            IndexData::U16(indices) => indices.len(),
+            IndexData::U32(indices) => indices.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Position snapped to a fine grid, so rings built twice from the same
+/// numbers are recognised as shared
+fn position_key(p: Vec3) -> (i32, i32, i32) {
+    ((p.x * 1e4).round() as i32, (p.y * 1e4).round() as i32, (p.z * 1e4).round() as i32)
+}
+
 /// A mesh composed of vertices and triangle indices
 #[derive(Debug, Clone, Default)]
 pub struct Mesh {
@@ -72,6 +134,23 @@ impl Mesh {
         start
     }
 
+    /// Set the opacity of every vertex added since `start`
+    pub fn fade_from(&mut self, start: u32, opacity: f32) {
+        if opacity >= 1.0 {
+            return;
+        }
+        for v in &mut self.vertices[start as usize..] {
+            v.opacity = opacity;
+        }
+    }
+
+    /// Bake colors into the vertices from `start` on, see [`baked_color`]
+    pub fn bake_colors_from(&mut self, start: u32, gradient: f32, vibrancy: f32, hue_stops: [f32; 2]) {
+        for v in &mut self.vertices[start as usize..] {
+            v.color = baked_color(v.hue, v.luminance, gradient, vibrancy, hue_stops);
+        }
+    }
+
     /// Add a triangle (indices are relative to the mesh's vertex buffer)
     pub fn add_triangle(&mut self, a: u32, b: u32, c: u32) {
         self.indices.push(a);
@@ -96,6 +175,106 @@ impl Mesh {
         }
     }
 
+    /// Replace a contiguous block of vertices, and the triangles indexing
+    /// them, with another mesh
+    ///
+    /// Triangles outside `indices` must not reference vertices inside
+    /// `vertices`; later indices are shifted to follow the resized block.
+    pub fn splice(&mut self, vertices: Range<usize>, indices: Range<usize>, replacement: &Mesh) {
+        let start = vertices.start as u32;
+        let old_end = vertices.end as u32;
+        let new_end = start + replacement.vertices.len() as u32;
+
+        for (i, idx) in self.indices.iter_mut().enumerate() {
+            if !indices.contains(&i) && *idx >= old_end {
+                *idx = *idx - old_end + new_end;
+            }
+        }
+        self.vertices.splice(vertices, replacement.vertices.iter().cloned());
+        self.indices.splice(indices, replacement.indices.iter().map(|i| i + start));
+    }
+
+    /// Rebuild normals from the triangles, averaged over every vertex at the
+    /// same position
+    ///
+    /// Faces are weighted by area, so seams (a ring's wrap vertex, rings of
+    /// differing segment counts) shade as one smooth surface. Each face is
+    /// turned to agree with the normals it was built with, whatever its
+    /// winding. Vertices no triangle uses keep their normal. Tangents are
+    /// left as built (the shader squares them up), so smoothing again never
+    /// drifts them.
+    pub fn recompute_normals(&mut self) {
+        self.recompute_normals_range(0..self.vertices.len(), 0..self.indices.len());
+    }
+
+    /// [`Self::recompute_normals`] for one block of the mesh, such as a
+    /// single branch's rings
+    ///
+    /// Only the triangles in `indices` are welded, and only the vertices in
+    /// `vertices` change, so neighbouring branches that happen to touch the
+    /// block keep their own shading.
+    pub fn recompute_normals_range(&mut self, vertices: Range<usize>, indices: Range<usize>) {
+        let mut welded: HashMap<(i32, i32, i32), Vec3> = HashMap::new();
+        for t in self.indices[indices].chunks_exact(3) {
+            if t.iter().any(|&i| !vertices.contains(&(i as usize))) {
+                continue;
+            }
+            let corners = [t[0], t[1], t[2]].map(|i| &self.vertices[i as usize]);
+            let [a, b, c] = corners.map(|v| v.position);
+            let built = corners.iter().fold(Vec3::ZERO, |sum, v| sum + v.normal);
+            // Twice the area, along the face normal
+            let mut weighted = (b - a).cross(&(c - a));
+            if weighted.dot(&built) < 0.0 {
+                weighted = -weighted;
+            }
+            for p in [a, b, c] {
+                let sum = welded.entry(position_key(p)).or_insert(Vec3::ZERO);
+                *sum = *sum + weighted;
+            }
+        }
+        for v in &mut self.vertices[vertices] {
+            if let Some(sum) = welded.get(&position_key(v.position)).filter(|sum| sum.length_squared() > 1e-12) {
+                v.normal = sum.normalize();
+            }
+        }
+    }
+
+    /// Give each vertex in `vertices` lying on a vertex in `from` that
+    /// vertex's normal, so a branch grown out of another's end ring shades
+    /// on from it without a crease (and without changing it)
+    pub fn copy_seam_normals(&mut self, vertices: Range<usize>, from: Range<usize>) {
+        if from.is_empty() {
+            return;
+        }
+        let normals: HashMap<_, _> = self.vertices[from].iter().map(|v| (position_key(v.position), v.normal)).collect();
+        for v in &mut self.vertices[vertices] {
+            if let Some(&normal) = normals.get(&position_key(v.position)) {
+                v.normal = normal;
+            }
+        }
+    }
+
+    /// Overwrite a block of vertices, and the triangles indexing them, with
+    /// a mesh of exactly the same size, keeping every offset in the buffers
+    ///
+    /// Returns false, leaving the mesh untouched, when the sizes differ or
+    /// the block runs past the end; use [`Self::splice`] then.
+    pub fn replace_range(&mut self, vertices: Range<usize>, indices: Range<usize>, replacement: &Mesh) -> bool {
+        if vertices.len() != replacement.vertices.len()
+            || indices.len() != replacement.indices.len()
+            || vertices.end > self.vertices.len()
+            || indices.end > self.indices.len()
+        {
+            return false;
+        }
+        let start = vertices.start as u32;
+        self.vertices[vertices].copy_from_slice(&replacement.vertices);
+        for (idx, &new) in self.indices[indices].iter_mut().zip(&replacement.indices) {
+            *idx = new + start;
+        }
+        true
+    }
+
     /// Calculate bounding sphere
     pub fn calculate_bounds(&mut self) {
         if self.vertices.is_empty() {
@@ -135,6 +314,34 @@ impl Mesh {
         &self.indices
     }
 
+    /// Index data as 16-bit indices when every vertex fits, else 32-bit
+    ///
+    /// WebGL2 always restarts primitives at the largest index of the type,
+    /// so 16-bit indices stop one short of `u16::MAX`.
+    pub fn packed_indices(&self) -> IndexData<'_> {
+        if self.fits_u16() {
+            IndexData::U16(self.indices.iter().map(|&i| i as u16).collect())
+        } else {
+            IndexData::U32(&self.indices)
+        }
+    }
+
+    /// Vertex and triangle counts and the bytes the mesh takes on the GPU
+    pub fn stats(&self) -> MeshStats {
+        let index_size = if self.fits_u16() { 2 } else { 4 };
+        MeshStats {
+            vertices: self.vertex_count(),
+            triangles: self.triangle_count(),
+            vertex_bytes: self.vertex_count() * VERTEX_FLOATS * 4,
+            index_bytes: self.indices.len() * index_size,
+            ..MeshStats::default()
+        }
+    }
+
+    fn fits_u16(&self) -> bool {
+        self.vertices.len() <= u16::MAX as usize
+    }
+
     pub fn vertex_count(&self) -> usize {
         self.vertices.len()
     }
@@ -145,6 +352,10 @@ impl Mesh {
 }
 
 /// Create a ring of vertices at a given position/direction/radius
+///
+/// The first vertex is repeated at the end with U = 1, so the texture wraps
+/// without a seam; a ring has `segments + 1` vertices.
+#[allow(clippy::too_many_arguments)]
 pub fn create_ring(
     center: Vec3,
     direction: Vec3,
@@ -158,9 +369,9 @@ pub fn create_ring(
     let tangent = direction.perpendicular();
     let bitangent = direction.cross(&tangent).normalize();
 
-    (0..segments)
+    (0..=segments)
         .map(|i| {
-            let angle = (i as f32 / segments as f32) * std::f32::consts::TAU;
+            let angle = ((i % segments) as f32 / segments as f32) * std::f32::consts::TAU;
             let cos_a = angle.cos();
             let sin_a = angle.sin();
 
@@ -169,9 +380,11 @@ pub fn create_ring(
             let normal = offset.normalize();
             let u = i as f32 / segments as f32;
 
+            // U runs around the ring, so normal × tangent points along it
             Vertex::new(position, normal)
                 .with_uv(u, v_coord)
                 .with_visual(glow, luminance, hue)
+                .with_tangent(bitangent.scale(cos_a) - tangent.scale(sin_a))
         })
         .collect()
 }
@@ -179,7 +392,7 @@ pub fn create_ring(
 /// Connect two rings with triangles
 pub fn connect_rings(mesh: &mut Mesh, ring1_start: u32, ring2_start: u32, segments: usize) {
     for i in 0..segments {
-        let i_next = (i + 1) % segments;
+        let i_next = i + 1;
 
         let a = ring1_start + i as u32;
         let b = ring1_start + i_next as u32;
@@ -190,6 +403,16 @@ pub fn connect_rings(mesh: &mut Mesh, ring1_start: u32, ring2_start: u32, segmen
     }
 }
 
+/// The tree shader's palette before lighting, with `gradient` (0 = the
+/// theme's base hue, 1 = its tip hue, from `hue_stops`) in place of height
+/// and saturation scaled by `vibrancy`
+pub fn baked_color(hue: f32, luminance: f32, gradient: f32, vibrancy: f32, hue_stops: [f32; 2]) -> Vec3 {
+    let base_hue = hue_stops[0] + (hue_stops[1] - hue_stops[0]) * gradient;
+    let hue = (base_hue + hue / 360.0 * 0.2).rem_euclid(1.0);
+    let saturation = ((0.7 + luminance * 0.25) * vibrancy).min(1.0);
+    hsv_to_rgb(hue, saturation, 0.25 + luminance * 0.6)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -201,7 +424,7 @@ mod tests {
             .with_visual(0.8, 0.6, 120.0);
 
         let arr = v.to_array();
-        assert_eq!(arr.len(), 11);
+        assert_eq!(arr.len(), VERTEX_FLOATS);
         assert_eq!(arr[0], 1.0); // position.x
         assert_eq!(arr[4], 1.0); // normal.y (UP)
         assert_eq!(arr[6], 0.5); // uv.u
@@ -221,6 +444,18 @@ mod tests {
         assert_eq!(mesh.vertex_count(), 3);
     }
 
+    #[test]
+    fn test_mesh_fade_from() {
+        let mut mesh = Mesh::new();
+        mesh.add_vertices(vec![Vertex::new(Vec3::ZERO, Vec3::UP)]);
+        let start = mesh.add_vertices(vec![Vertex::new(Vec3::UP, Vec3::UP)]);
+        mesh.fade_from(start, 0.4);
+
+        assert_eq!(mesh.vertices[0].opacity, 1.0);
+        assert_eq!(mesh.vertices[1].opacity, 0.4);
+        assert_eq!(mesh.vertex_data()[VERTEX_FLOATS + 11], 0.4);
+    }
+
     #[test]
     fn test_mesh_add_triangle() {
         let mut mesh = Mesh::new();
@@ -234,6 +469,25 @@ mod tests {
         assert_eq!(mesh.indices.len(), 3);
     }
 
+    #[test]
+    fn test_mesh_splice() {
+        let point = |x: f32| Vertex::new(Vec3::new(x, 0.0, 0.0), Vec3::UP);
+        let mut mesh = Mesh::new();
+        mesh.add_vertices((0..6).map(|i| point(i as f32)));
+        mesh.add_triangle(0, 1, 2);
+        mesh.add_triangle(2, 3, 2); // block to replace: vertices 2..4
+        mesh.add_triangle(4, 5, 4);
+
+        let mut replacement = Mesh::new();
+        replacement.add_vertices((0..3).map(|i| point(10.0 + i as f32)));
+        replacement.add_triangle(0, 1, 2);
+        mesh.splice(2..4, 3..6, &replacement);
+
+        assert_eq!(mesh.vertex_count(), 7);
+        assert_eq!(mesh.vertices[2].position.x, 10.0);
+        assert_eq!(mesh.indices, vec![0, 1, 2, 2, 3, 4, 5, 6, 5]);
+    }
+
     #[test]
     fn test_mesh_merge() {
         let mut mesh1 = Mesh::new();
@@ -252,7 +506,9 @@ mod tests {
     #[test]
     fn test_create_ring() {
         let ring = create_ring(Vec3::ZERO, Vec3::UP, 1.0, 8, 0.0, 0.5, 0.5, 0.0);
-        assert_eq!(ring.len(), 8);
+        assert_eq!(ring.len(), 9);
+        assert_eq!(ring[0].position, ring[8].position);
+        assert_eq!((ring[0].uv[0], ring[8].uv[0]), (0.0, 1.0));
 
         // All vertices should be at distance 1 from center in XZ plane
         for v in &ring {
@@ -261,6 +517,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_ring_tangents() {
+        let direction = Vec3::new(0.3, 1.0, -0.2).normalize();
+        for v in create_ring(Vec3::ZERO, direction, 0.5, 8, 0.0, 0.5, 0.5, 0.0) {
+            // Tangent follows U around the ring; the bitangent runs along the branch
+            assert!(v.tangent.dot(&v.normal).abs() < 1e-5);
+            assert!((v.tangent.length() - 1.0).abs() < 1e-5);
+            assert!(v.normal.cross(&v.tangent).dot(&direction) > 0.999);
+        }
+    }
+
     #[test]
     fn test_connect_rings() {
         let mut mesh = Mesh::new();
@@ -271,10 +538,83 @@ mod tests {
         let start2 = mesh.add_vertices(ring2);
         connect_rings(&mut mesh, start1, start2, 4);
 
-        assert_eq!(mesh.vertex_count(), 8);
+        assert_eq!(mesh.vertex_count(), 10);
         assert_eq!(mesh.triangle_count(), 8); // 4 quads = 8 triangles
     }
 
+    #[test]
+    fn test_recompute_normals_smooths_seams() {
+        let mut mesh = Mesh::new();
+        let ring1 = create_ring(Vec3::ZERO, Vec3::UP, 1.0, 6, 0.0, 0.5, 0.5, 0.0);
+        let ring2 = create_ring(Vec3::UP, Vec3::UP, 1.0, 6, 1.0, 0.5, 0.5, 0.0);
+        let start1 = mesh.add_vertices(ring1);
+        let start2 = mesh.add_vertices(ring2);
+        connect_rings(&mut mesh, start1, start2, 6);
+        mesh.recompute_normals();
+
+        // The wrap vertex shares its twin's normal, which points outward
+        for (i, v) in mesh.vertices.iter().enumerate() {
+            let outward = Vec3::new(v.position.x, 0.0, v.position.z).normalize();
+            assert!(v.normal.dot(&outward) > 0.95, "vertex {}: {:?}", i, v.normal);
+        }
+        assert_eq!(mesh.vertices[0].normal, mesh.vertices[6].normal);
+    }
+
+    #[test]
+    fn test_recompute_normals_range_keeps_neighbours() {
+        let mut mesh = Mesh::new();
+        for y in [0.0, 1.0, 1.0, 2.0] {
+            mesh.add_vertices(create_ring(Vec3::new(0.0, y, 0.0), Vec3::UP, 1.0, 6, y, 0.5, 0.5, 0.0));
+        }
+        connect_rings(&mut mesh, 0, 7, 6);
+        let first_indices = mesh.indices.len();
+        connect_rings(&mut mesh, 14, 21, 6);
+        for v in &mut mesh.vertices {
+            v.normal = Vec3::UP;
+        }
+
+        // The second block's first ring sits on the first block's last ring
+        mesh.recompute_normals_range(14..28, first_indices..mesh.indices.len());
+        assert!(mesh.vertices[..14].iter().all(|v| v.normal == Vec3::UP));
+        assert!(mesh.vertices[14..].iter().all(|v| v.normal.y.abs() < 0.05));
+    }
+
+    #[test]
+    fn test_replace_range() {
+        let mut mesh = Mesh::new();
+        mesh.add_vertices(vec![Vertex::new(Vec3::ZERO, Vec3::UP); 6]);
+        mesh.add_triangle(0, 1, 2);
+        mesh.add_triangle(3, 4, 5);
+
+        let mut patch = Mesh::new();
+        patch.add_vertices(vec![Vertex::new(Vec3::UP, Vec3::UP); 3]);
+        patch.add_triangle(2, 1, 0);
+        assert!(mesh.replace_range(3..6, 3..6, &patch));
+        assert_eq!(mesh.indices, vec![0, 1, 2, 5, 4, 3]);
+        assert_eq!(mesh.vertices[4].position, Vec3::UP);
+        assert_eq!(mesh.vertices[2].position, Vec3::ZERO);
+
+        // A block of another size needs a splice
+        assert!(!mesh.replace_range(0..2, 0..3, &patch));
+        assert_eq!(mesh.vertices[0].position, Vec3::ZERO);
+    }
+
+    #[test]
+    fn test_packed_indices() {
+        let mut mesh = Mesh::new();
+        mesh.add_vertices(vec![Vertex::new(Vec3::ZERO, Vec3::UP); 3]);
+        mesh.add_triangle(0, 1, 2);
+        assert_eq!(mesh.packed_indices(), IndexData::U16(vec![0, 1, 2]));
+
+        // 0xFFFF is the restart index, so a vertex there needs 32 bits
+        mesh.add_vertices(vec![Vertex::new(Vec3::ZERO, Vec3::UP); u16::MAX as usize - 3]);
+        assert!(matches!(mesh.packed_indices(), IndexData::U16(_)));
+        mesh.add_vertices(std::iter::once(Vertex::new(Vec3::ZERO, Vec3::UP)));
+        mesh.add_triangle(0, 1, u16::MAX as u32);
+        assert_eq!(mesh.packed_indices().len(), 6);
+        assert!(matches!(mesh.packed_indices(), IndexData::U32(_)));
+    }
+
     #[test]
     fn test_calculate_bounds() {
         let mut mesh = Mesh::new();
@@ -297,6 +637,6 @@ mod tests {
         ]);
 
         let data = mesh.vertex_data();
-        assert_eq!(data.len(), 22); // 2 vertices * 11 floats
+        assert_eq!(data.len(), 2 * VERTEX_FLOATS);
     }
 }
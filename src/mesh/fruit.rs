@@ -0,0 +1,203 @@
+use std::f32::consts::{PI, TAU};
+use crate::growth::{BranchNode, GrowthRng, SplitMix};
+use crate::math::Vec3;
+use super::branch::{Mesh, Vertex};
+
+/// Floats per fruit instance: center(3) + radius(1) + hue(1) + glow(1)
+pub const FRUIT_INSTANCE_FLOATS: usize = 6;
+
+/// Rings and segments of the shared fruit sphere
+const FRUIT_RINGS: usize = 6;
+const FRUIT_SEGMENTS: usize = 8;
+
+/// Parameters for the fruit hung along branches, one per child
+#[derive(Debug, Clone, Copy)]
+pub struct FruitParams {
+    /// Radius of a fruit standing for one person (world units)
+    pub radius: f32,
+    /// Grow each child's fruit with the size of the family it leads to
+    pub scale_by_descendants: bool,
+    /// Seed for where around the branch each fruit hangs
+    pub seed: u32,
+}
+
+impl Default for FruitParams {
+    fn default() -> Self {
+        Self {
+            radius: 0.06,
+            scale_by_descendants: false,
+            seed: 42,
+        }
+    }
+}
+
+/// One hanging fruit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FruitInstance {
+    pub center: Vec3,
+    pub radius: f32,
+    /// Hue of the child it stands for (degrees)
+    pub hue: f32,
+    pub glow: f32,
+}
+
+impl FruitInstance {
+    pub fn to_array(&self) -> [f32; FRUIT_INSTANCE_FLOATS] {
+        [
+            self.center.x, self.center.y, self.center.z,
+            self.radius, self.hue, self.glow,
+        ]
+    }
+}
+
+/// Hangs glowing fruit along each branch, one per child, for instanced drawing
+pub struct FruitGenerator {
+    params: FruitParams,
+    rng: SplitMix,
+}
+
+impl FruitGenerator {
+    pub fn new(params: FruitParams) -> Self {
+        Self { params, rng: SplitMix::new(params.seed) }
+    }
+
+    /// A unit sphere, shared by every instance
+    pub fn fruit_mesh(&self) -> Mesh {
+        let mut mesh = Mesh::new();
+        for ring in 0..=FRUIT_RINGS {
+            let v = ring as f32 / FRUIT_RINGS as f32;
+            let (sin_polar, cos_polar) = (v * PI).sin_cos();
+            mesh.add_vertices((0..=FRUIT_SEGMENTS).map(|i| {
+                let u = i as f32 / FRUIT_SEGMENTS as f32;
+                let normal = Vec3::new(sin_polar * (u * TAU).cos(), cos_polar, sin_polar * (u * TAU).sin());
+                Vertex::new(normal, normal).with_uv(u, v)
+            }));
+        }
+        let row = FRUIT_SEGMENTS as u32 + 1;
+        for ring in 0..FRUIT_RINGS as u32 {
+            for i in 0..FRUIT_SEGMENTS as u32 {
+                let a = ring * row + i;
+                mesh.add_quad(a, a + 1, a + row + 1, a + row);
+            }
+        }
+        mesh.bounds_center = Vec3::ZERO;
+        mesh.bounds_radius = 1.0;
+        mesh
+    }
+
+    /// Fruit for every branch of the crown (and the rest of the grove);
+    /// ancestor roots bear none
+    pub fn instances(&self, root: &BranchNode) -> Vec<FruitInstance> {
+        let mut fruit = Vec::new();
+        self.collect(root, &mut fruit);
+        fruit
+    }
+
+    fn collect(&self, node: &BranchNode, fruit: &mut Vec<FruitInstance>) {
+        let count = node.children.len();
+        for (i, child) in node.children.iter().enumerate() {
+            // Spread down the outer half of the branch, hanging below it on
+            // a side picked per child
+            let t = 0.35 + 0.55 * (i as f32 + 0.5) / count as f32;
+            let center = node.point_along(t);
+            let along = (node.point_along((t + 0.01).min(1.0)) - node.point_along(t - 0.01)).normalize();
+            let side = along.perpendicular();
+            let angle = self.rng.unit(&format!("{}/fruit", child.person_id)) * TAU;
+            let outward = (side.scale(angle.cos()) + along.cross(&side).scale(angle.sin())).normalize();
+
+            let radius = if self.params.scale_by_descendants {
+                self.params.radius * (1.0 + descendant_count(child) as f32).cbrt()
+            } else {
+                self.params.radius
+            };
+            let branch_radius = node.start_radius + (node.end_radius - node.start_radius) * t;
+            fruit.push(FruitInstance {
+                center: center + outward.scale(branch_radius + radius) - Vec3::UP.scale(radius * 1.5),
+                radius,
+                hue: child.visual.hue_shift,
+                glow: child.visual.glow_intensity,
+            });
+        }
+        for branch in node.children.iter().chain(&node.grove) {
+            self.collect(branch, fruit);
+        }
+    }
+
+    /// Flat per-instance buffer, `FRUIT_INSTANCE_FLOATS` per fruit
+    pub fn instance_data(&self, root: &BranchNode) -> Vec<f32> {
+        self.instances(root).iter().flat_map(|fruit| fruit.to_array()).collect()
+    }
+}
+
+impl Default for FruitGenerator {
+    fn default() -> Self {
+        Self::new(FruitParams::default())
+    }
+}
+
+/// Descendants a branch leads to, including those held back in buds
+fn descendant_count(node: &BranchNode) -> usize {
+    node.hidden_descendants + node.children.iter().map(|child| 1 + descendant_count(child)).sum::<usize>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn family() -> BranchNode {
+        let top = Vec3::new(0.0, 4.0, 0.0);
+        let mut root = BranchNode::straight("root", Vec3::ZERO, top);
+        let mut a = BranchNode::straight("a", top, Vec3::new(2.0, 6.0, 0.0));
+        a.hidden_descendants = 7;
+        let mut b = BranchNode::straight("b", top, Vec3::new(-2.0, 6.0, 0.0));
+        b.children.push(BranchNode::straight("c", b.end, Vec3::new(-3.0, 7.0, 0.0)));
+        root.children = vec![a, b];
+        root.roots.push(BranchNode::straight("ancestor", Vec3::ZERO, Vec3::new(0.0, -2.0, 0.0)));
+        root.roots[0].roots.push(BranchNode::straight("elder", Vec3::new(0.0, -2.0, 0.0), Vec3::new(1.0, -3.0, 0.0)));
+        root
+    }
+
+    #[test]
+    fn test_one_fruit_per_child() {
+        let generator = FruitGenerator::default();
+        let root = family();
+        let fruit = generator.instances(&root);
+
+        // Two on the trunk, one on "b"; none on the roots
+        assert_eq!(fruit.len(), 3);
+        assert!(fruit.iter().all(|f| f.radius == FruitParams::default().radius));
+        assert!(fruit.iter().all(|f| f.center.y > 0.0));
+        // Hung beside the branch, not inside it
+        for f in &fruit[..2] {
+            let axis_distance = (f.center.x * f.center.x + f.center.z * f.center.z).sqrt();
+            assert!(axis_distance > 0.1 + FruitParams::default().radius - 1e-4);
+        }
+        assert_eq!(generator.instance_data(&root).len(), 3 * FRUIT_INSTANCE_FLOATS);
+        assert_eq!(generator.fruit_mesh().vertex_count(), (FRUIT_RINGS + 1) * (FRUIT_SEGMENTS + 1));
+
+        // However large the family
+        let mut big = BranchNode::straight("big", Vec3::ZERO, Vec3::new(0.0, 4.0, 0.0));
+        big.children = (0..20)
+            .map(|i| BranchNode::straight(&format!("c{}", i), big.end, Vec3::new(i as f32, 6.0, 0.0)))
+            .collect();
+        assert_eq!(generator.instances(&big).len(), 20);
+    }
+
+    #[test]
+    fn test_fruit_scaled_by_descendants() {
+        let generator = FruitGenerator::new(FruitParams { scale_by_descendants: true, ..FruitParams::default() });
+        let fruit = generator.instances(&family());
+
+        // "a" leads to 7 hidden descendants, "b" to one child
+        let radius = FruitParams::default().radius;
+        assert!((fruit[0].radius - radius * 2.0).abs() < 1e-5);
+        assert!((fruit[1].radius - radius * 2.0f32.cbrt()).abs() < 1e-5);
+        assert!((fruit[2].radius - radius).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_fruit_deterministic() {
+        let generator = FruitGenerator::default();
+        assert_eq!(generator.instances(&family()), generator.instances(&family()));
+    }
+}
@@ -1,31 +1,202 @@
-use crate::growth::BranchNode;
-use crate::math::{Vec3, generate_branch_curve};
-use super::branch::{Mesh, Vertex, create_ring, connect_rings};
+use crate::growth::{BranchNode, GrowthRng, SplitMix};
+use crate::math::{Capsule, CatmullRomSpline, Vec3, generate_branch_curve};
+use crate::math::spline::hermite_curve;
+use super::branch::{Mesh, Vertex, baked_color, create_ring, connect_rings};
+use super::twigs::{TwigInstance, twig_mesh};
+use std::f32::consts::{PI, TAU};
+use std::ops::Range;
+
+/// Turns a partner braid makes along its branch
+const BRAID_TURNS: f32 = 1.5;
+/// Fraction of the branch after which braided strands have merged
+const BRAID_MERGE: f32 = 0.85;
+/// Fraction of a child branch that bends out of its parent's heading and
+/// girth at a fork
+const JUNCTION_BLEND: f32 = 0.3;
+/// World length of bark covered by one unit of V (U runs once around)
+const BARK_TEXTURE_LENGTH: f32 = 1.0;
+/// Petals on a blossom
+const BLOSSOM_PETALS: usize = 5;
+/// Height of the trunk's root flare, in trunk radii
+const FLARE_HEIGHT: f32 = 2.5;
+/// Generations over which baked colors run from warm to cool
+const COLOR_GENERATIONS: f32 = 6.0;
+/// Knots and moss patches per unit length of a weathered branch
+const KNOTS_PER_LENGTH: f32 = 1.2;
+const MOSS_PER_LENGTH: f32 = 1.6;
+/// Fraction of a weathered branch at either end kept clear, so forks and
+/// tips still line up
+const WEATHER_MARGIN: f32 = 0.15;
+/// Years of life a whole branch stands for when placing memorial rings
+const MEMORIAL_LIFESPAN: f32 = 100.0;
+/// Tube segments around a memorial ring
+const MEMORIAL_SEGMENTS: usize = 24;
+/// Capsules following a branch's curve for collision
+const COLLIDERS_PER_BRANCH: usize = 4;
 
 /// Parameters for mesh generation
 #[derive(Debug, Clone, Copy)]
 pub struct MeshParams {
-    /// Radial segments around each ring (more = smoother)
+    /// Radial segments around the rings of the thickest branches (more = smoother)
     pub radial_segments: usize,
+    /// Radial segments around the rings of the thinnest twigs
+    pub twig_segments: usize,
+    /// Branch radius at or below which rings get `twig_segments`
+    pub twig_radius: f32,
+    /// Branch radius at or above which rings get the full `radial_segments`
+    pub trunk_radius: f32,
     /// Length segments per branch (more = smoother curves)
     pub length_segments: usize,
+    /// Rings per control point along a branch with waypoints
+    pub waypoint_rings: usize,
     /// Amount of bark-like displacement
     pub bark_displacement: f32,
     /// Seed for procedural displacement
     pub seed: u32,
+    /// Petal length of the blossoms at births and partnerships (0 = none)
+    pub blossom_size: f32,
+    /// Glow of blossom petals, independent of the branch they open on
+    pub blossom_glow: f32,
+    /// Trunk radius at the ground relative to its base radius (1 = no flare)
+    pub root_flare: f32,
+    /// Roots spreading along the ground from each trunk's base
+    pub surface_roots: usize,
+    /// Bake full RGB colors into the vertices, for the simplified shader
+    pub vertex_colors: bool,
+    /// Theme base hues (0 to 1) at the trunk and the tips the baked colors
+    /// run between
+    pub hue_stops: [f32; 2],
+    /// Draw childless branches no thicker than `twig_radius` as instances
+    /// of one canonical twig rather than in the mesh
+    pub instanced_twigs: bool,
+    /// People who lived at least this many years get weathered branches,
+    /// with knots and moss (None = not by age)
+    pub weathering_age: Option<f32>,
+    /// Generations nearest the trunk weathered whatever their age (0 = none)
+    pub weathering_generations: usize,
+    /// Halo rings around the branches of the deceased
+    pub memorial_rings: bool,
 }
 
 impl Default for MeshParams {
     fn default() -> Self {
         Self {
-            radial_segments: 12,
+            radial_segments: 16,
+            twig_segments: 5,
+            twig_radius: 0.04,
+            trunk_radius: 0.3,
             length_segments: 8,
+            waypoint_rings: 3,
             bark_displacement: 0.02,
             seed: 42,
+            blossom_size: 0.08,
+            blossom_glow: 1.2,
+            root_flare: 1.8,
+            surface_roots: 5,
+            vertex_colors: false,
+            hue_stops: [0.02, 0.45],
+            instanced_twigs: false,
+            weathering_age: None,
+            weathering_generations: 0,
+            memorial_rings: true,
         }
     }
 }
 
+impl MeshParams {
+    /// Coarser params for detail `level` (0 = these): each level halves the
+    /// ring and length segments (and so the rings of tips and root flares)
+    /// and drops the bark displacement
+    pub fn lod(&self, level: usize) -> MeshParams {
+        if level == 0 {
+            return *self;
+        }
+        let divisor = 1 << level.min(8);
+        MeshParams {
+            radial_segments: (self.radial_segments / divisor).max(4),
+            twig_segments: (self.twig_segments / divisor).max(3),
+            length_segments: (self.length_segments / divisor).max(2),
+            waypoint_rings: (self.waypoint_rings / divisor).max(1),
+            bark_displacement: 0.0,
+            ..*self
+        }
+    }
+
+    /// Radial segments for a branch of `radius`, from `twig_segments` for
+    /// twigs up to `radial_segments` for the trunk
+    pub fn segments_for_radius(&self, radius: f32) -> usize {
+        let most = self.radial_segments.max(3);
+        let fewest = self.twig_segments.clamp(3, most);
+        let s = ((radius - self.twig_radius) / (self.trunk_radius - self.twig_radius).max(1e-6)).clamp(0.0, 1.0);
+        fewest + (s * (most - fewest) as f32).round() as usize
+    }
+
+    /// Whether `node` is drawn as a twig instance instead of in the mesh:
+    /// a thin, straight, childless branch with no partner
+    fn is_twig(&self, node: &BranchNode) -> bool {
+        self.instanced_twigs
+            && node.start_radius <= self.twig_radius
+            && node.children.is_empty()
+            && node.roots.is_empty()
+            && node.partner.is_none()
+            && node.waypoints.is_empty()
+    }
+}
+
+/// A child's base as seen from its parent: the parent's heading and bark V
+/// coordinate where the child sprouts, and the first ring of the child
+#[derive(Debug, Clone, Copy)]
+struct JunctionBase {
+    direction: Vec3,
+    radius: f32,
+    v: f32,
+    /// Ring segments of the first ring; the parent's when the child takes
+    /// over its end ring
+    segments: usize,
+    /// From the child's start to the center of its first ring
+    offset: Vec3,
+}
+
+impl JunctionBase {
+    /// `parent_v` and `parent_length` are the V at the parent's base and the
+    /// arc length of its curve; `crown` is false when both are roots
+    fn new(
+        parent: &BranchNode,
+        child: &BranchNode,
+        crown: bool,
+        parent_v: f32,
+        parent_length: f32,
+        params: &MeshParams,
+    ) -> Self {
+        let axis = parent.end - parent.start;
+        let t = ((child.start - parent.start).dot(&axis) / axis.length_squared().max(1e-6)).clamp(0.0, 1.0);
+        let direction = parent.start_direction.lerp(&parent.end_direction, t).normalize();
+        let parent_radius = lerp(parent.start_radius, parent.end_radius, t);
+        let v = parent_v + t * parent_length / BARK_TEXTURE_LENGTH;
+
+        // A lone child carrying on from the end takes the parent's whole
+        // cross-section; any other starts inside the parent, against the
+        // side it heads out of, so siblings don't share a ring
+        if end_heir(parent, crown, params).is_some_and(|heir| heir.person_id == child.person_id) {
+            return Self {
+                direction,
+                radius: parent_radius,
+                v,
+                segments: params.segments_for_radius(parent.start_radius),
+                offset: Vec3::ZERO,
+            };
+        }
+        let radius = lerp(child.start_radius, parent_radius, 0.5).min(parent_radius);
+        let outward = child.start_direction - direction.scale(child.start_direction.dot(&direction));
+        let offset = if outward.length() > 1e-4 {
+            outward.normalize().scale(parent_radius - radius)
+        } else {
+            Vec3::ZERO
+        };
+        Self { direction, radius, v, segments: params.segments_for_radius(child.start_radius), offset }
+    }
+}
+
 /// Generates organic meshes from tree branch structures
 pub struct MeshGenerator {
     params: MeshParams,
@@ -39,79 +210,339 @@ impl MeshGenerator {
     /// Generate mesh for entire tree
     pub fn generate_tree(&self, root: &BranchNode) -> Mesh {
         let mut mesh = Mesh::new();
-        self.generate_branch_recursive(root, &mut mesh);
+        self.generate_branch_recursive(root, None, true, 0..0, &mut mesh);
         mesh.calculate_bounds();
         mesh
     }
 
-    fn generate_branch_recursive(&self, node: &BranchNode, mesh: &mut Mesh) {
+    /// `base` is where the branch leaves its parent (None for trunks and
+    /// roots) and `seam` the parent's vertices; `crown` is false below
+    /// ground, among the ancestor roots
+    fn generate_branch_recursive(
+        &self,
+        node: &BranchNode,
+        base: Option<JunctionBase>,
+        crown: bool,
+        seam: Range<usize>,
+        mesh: &mut Mesh,
+    ) {
         // Generate this branch segment
-        self.generate_branch_segment(node, mesh);
+        let vertex_start = mesh.vertices.len() as u32;
+        let index_start = mesh.indices.len();
+        let v_start = base.map_or(0.0, |base| base.v);
+        let length = self.generate_branch_segment(node, base, mesh);
+        self.generate_event_nodules(node, mesh);
+        self.generate_bud(node, mesh);
+        if crown {
+            self.generate_birth_blossom(node, mesh);
+            self.generate_trunk_base(node, mesh);
+        }
+        mesh.fade_from(vertex_start, node.visual.opacity);
+        self.bake_colors(node, vertex_start, mesh);
+        self.generate_partner(node, v_start, mesh);
+
+        // A lone child growing out of the end carries the skin on; several
+        // get a dome to grow out of, and otherwise the branch tapers off in
+        // an organic tip (twigs, instanced, don't close it)
+        if end_heir(node, crown, &self.params).is_none() && !self.params.is_twig(node) {
+            let cap_start = mesh.vertices.len() as u32;
+            let v_end = v_start + length / BARK_TEXTURE_LENGTH;
+            if onward(node, crown).iter().any(|child| sprouts_from_end(node, child) && !self.params.is_twig(child)) {
+                self.generate_joint(node, v_end, mesh);
+            } else {
+                self.generate_organic_tip(node, v_end, mesh);
+            }
+            mesh.fade_from(cap_start, node.visual.opacity);
+            self.bake_colors(node, cap_start, mesh);
+        }
+        let own = vertex_start as usize..mesh.vertices.len();
+        mesh.recompute_normals_range(own.clone(), index_start..mesh.indices.len());
+        mesh.copy_seam_normals(own.clone(), seam);
+
+        // Generate children (and, at the trunk, ancestor roots and the rest
+        // of the grove)
+        for (child, base, crown) in branches(node, crown, v_start, length, &self.params) {
+            let seam = if base.is_some() { own.clone() } else { 0..0 };
+            self.generate_branch_recursive(child, base, crown, seam, mesh);
+        }
+    }
 
-        // Generate children
-        for child in &node.children {
-            self.generate_branch_recursive(child, mesh);
+    /// Generate a single branch segment with smooth interpolation
+    ///
+    /// A branch with a partner is drawn as one strand of a braid; see
+    /// [`Self::generate_partner`] for the other. Returns the branch's length
+    /// (0 for twigs, which are drawn as instances instead).
+    fn generate_branch_segment(&self, node: &BranchNode, base: Option<JunctionBase>, mesh: &mut Mesh) -> f32 {
+        if self.params.is_twig(node) {
+            return 0.0;
         }
+        let v_start = base.map_or(0.0, |base| base.v);
+        let strand_start = mesh.vertices.len();
+        let length = self.generate_strand(node, node.partner.as_ref().map(|_| 0.0), base, v_start, mesh);
+        if self.is_weathered(node) {
+            self.weather(node, strand_start, v_start, length, mesh);
+        }
+        length
+    }
 
-        // If we have children, generate a joint to smooth the transition
-        // If no children, generate an organic tip for leaf branches
-        if !node.children.is_empty() {
-            self.generate_joint(node, mesh);
-        } else {
-            self.generate_organic_tip(node, mesh);
+    /// Faint halo rings around the branches of the deceased, in a mesh of
+    /// their own so they can fade in as the tree finishes growing
+    pub fn generate_memorials(&self, root: &BranchNode) -> Mesh {
+        let mut mesh = Mesh::new();
+        if self.params.memorial_rings {
+            for node in root.iter_preorder().filter(|node| node.visual.deceased) {
+                let start = mesh.vertices.len() as u32;
+                self.generate_memorial(node, &mut mesh);
+                self.bake_colors(node, start, &mut mesh);
+            }
         }
+        mesh.calculate_bounds();
+        mesh
     }
 
-    /// Generate a single branch segment with smooth interpolation
-    fn generate_branch_segment(&self, node: &BranchNode, mesh: &mut Mesh) {
-        let params = &self.params;
+    /// A thin torus around the branch where the person's age at death falls
+    /// along it (near the end when unknown)
+    fn generate_memorial(&self, node: &BranchNode, mesh: &mut Mesh) {
         let visual = &node.visual;
+        let t = visual.age.map_or(0.9, |age| (age / MEMORIAL_LIFESPAN).clamp(0.1, 0.95));
+        let center = node.point_along(t);
+        let axis = (node.point_along((t + 0.01).min(1.0)) - node.point_along(t - 0.01)).normalize();
+        let branch_radius = lerp(node.start_radius, node.end_radius, t);
+        let ring_radius = branch_radius * 1.8 + 0.03;
+        let tube_radius = branch_radius * 0.08 + 0.008;
+        let side = axis.perpendicular();
+        let up = axis.cross(&side).normalize();
+
+        let tube_segments = self.params.twig_segments.max(3);
+        let mut prev_ring = None;
+        for i in 0..=MEMORIAL_SEGMENTS {
+            let angle = i as f32 / MEMORIAL_SEGMENTS as f32 * TAU;
+            let (sin, cos) = angle.sin_cos();
+            let ring = create_ring(
+                center + (side.scale(cos) + up.scale(sin)).scale(ring_radius),
+                up.scale(cos) - side.scale(sin),
+                tube_radius,
+                tube_segments,
+                i as f32 / MEMORIAL_SEGMENTS as f32,
+                1.2,
+                0.15,
+                visual.hue_shift,
+            );
+            let ring_start = mesh.add_vertices(ring);
+            if let Some(prev) = prev_ring {
+                connect_rings(mesh, prev, ring_start, tube_segments);
+            }
+            prev_ring = Some(ring_start);
+        }
+    }
 
-        // Generate curve points along the branch
-        let curve_points = generate_branch_curve(
-            node.start,
-            node.end,
-            node.start_direction,
-            node.end_direction,
-            0.4, // Curvature
-            params.length_segments,
-        );
+    /// Whether `node` is old enough, by age or generation, to be weathered
+    fn is_weathered(&self, node: &BranchNode) -> bool {
+        node.generation < self.params.weathering_generations
+            || self.params.weathering_age.zip(node.visual.age).is_some_and(|(threshold, age)| age >= threshold)
+    }
+
+    /// Raise knots and moss on the strand from vertex `start` (V from
+    /// `v_start` over `length`): each patch bulges the vertices near a
+    /// point on the bark, knots dark and lumpy, moss a thin soft-glowing
+    /// greener layer
+    fn weather(&self, node: &BranchNode, start: usize, v_start: f32, length: f32, mesh: &mut Mesh) {
+        let rng = SplitMix::new(self.params.seed);
+        let patches = |rate: f32| (length * rate).round().max(1.0) as usize;
+        let knots = (0..patches(KNOTS_PER_LENGTH)).map(|i| (format!("{}/knot{}", node.person_id, i), true));
+        let moss = (0..patches(MOSS_PER_LENGTH)).map(|i| (format!("{}/moss{}", node.person_id, i), false));
+
+        for (key, knot) in knots.chain(moss) {
+            let t = WEATHER_MARGIN + (1.0 - 2.0 * WEATHER_MARGIN) * rng.unit(&format!("{}t", key));
+            let angle = rng.unit(&format!("{}a", key)) * TAU;
+            let radius = lerp(node.start_radius, node.end_radius, t);
+            let axis = (node.point_along((t + 0.01).min(1.0)) - node.point_along((t - 0.01).max(0.0))).normalize();
+            let side = axis.perpendicular();
+            let outward = side.scale(angle.cos()) + axis.cross(&side).normalize().scale(angle.sin());
+            let center = node.point_along(t) + outward.scale(radius);
+            let reach = radius * if knot { 1.2 } else { 2.0 };
+            // A knot where a child sprouts would open a gap around its base
+            if knot && node.children.iter().any(|c| c.start.distance(&node.point_along(t)) < reach + c.start_radius) {
+                continue;
+            }
+
+            for v in &mut mesh.vertices[start..] {
+                let along = ((v.uv[1] - v_start) * BARK_TEXTURE_LENGTH / length.max(1e-4)).clamp(0.0, 1.0);
+                let margin = smooth_step(along / WEATHER_MARGIN) * smooth_step((1.0 - along) / WEATHER_MARGIN);
+                let w = smooth_step(1.0 - v.position.distance(&center) / reach) * margin;
+                if w <= 0.0 {
+                    continue;
+                }
+                if knot {
+                    v.position = v.position + v.normal.scale(radius * 0.35 * w);
+                    v.glow *= 1.0 - 0.5 * w;
+                    v.luminance *= 1.0 - 0.5 * w;
+                } else {
+                    v.position = v.position + v.normal.scale(radius * 0.1 * w);
+                    v.hue += 120.0 * w;
+                    v.glow *= 1.0 + 0.3 * w;
+                    v.luminance *= 1.0 - 0.2 * w;
+                }
+            }
+        }
+    }
+
+    /// Generate a partner's strand, half a turn out of phase with the branch
+    /// it twines around (`v_start` is the branch's V at its base)
+    fn generate_partner(&self, node: &BranchNode, v_start: f32, mesh: &mut Mesh) {
+        if let Some(partner) = &node.partner {
+            let vertex_start = mesh.vertices.len() as u32;
+            self.generate_strand(partner, Some(PI), None, v_start, mesh);
+            self.generate_event_nodules(partner, mesh);
+            self.generate_partnership_blossom(node, partner, mesh);
+            mesh.fade_from(vertex_start, partner.visual.opacity);
+            self.bake_colors(partner, vertex_start, mesh);
+        }
+    }
+
+    /// Bake colors into the vertices from `start` on when the params ask,
+    /// with the generation in place of height for the warm-to-cool gradient
+    fn bake_colors(&self, node: &BranchNode, start: u32, mesh: &mut Mesh) {
+        if !self.params.vertex_colors {
+            return;
+        }
+        let (gradient, vibrancy) = color_gradient(node);
+        mesh.bake_colors_from(start, gradient, vibrancy, self.params.hue_stops);
+    }
+
+    /// The canonical twig every instance draws
+    pub fn twig_mesh(&self) -> Mesh {
+        twig_mesh(self.params.twig_segments)
+    }
+
+    /// Instances for the branches left out of the mesh as twigs (none unless
+    /// `instanced_twigs` is set)
+    pub fn twig_instances(&self, root: &BranchNode) -> Vec<TwigInstance> {
+        let mut twigs = Vec::new();
+        if self.params.instanced_twigs {
+            self.collect_twigs(root, None, true, &mut twigs);
+        }
+        twigs
+    }
+
+    fn collect_twigs(&self, node: &BranchNode, base: Option<JunctionBase>, crown: bool, twigs: &mut Vec<TwigInstance>) {
+        let v_start = base.map_or(0.0, |base| base.v);
+        if self.params.is_twig(node) {
+            let visual = &node.visual;
+            let axis = node.end - node.start;
+            let (gradient, vibrancy) = color_gradient(node);
+            twigs.push(TwigInstance {
+                base: node.start,
+                axis,
+                side: axis.normalize().perpendicular(),
+                start_radius: node.start_radius,
+                end_radius: node.end_radius,
+                v_start,
+                glow: visual.glow_intensity,
+                luminance: visual.luminance,
+                hue: visual.hue_shift,
+                opacity: visual.opacity,
+                color: if self.params.vertex_colors {
+                    baked_color(visual.hue_shift, visual.luminance, gradient, vibrancy, self.params.hue_stops)
+                } else {
+                    Vec3::ZERO
+                },
+            });
+            return;
+        }
+        let length = arc_lengths(&self.branch_curve(node)).last().copied().unwrap_or(0.0);
+        for (child, base, crown) in branches(node, crown, v_start, length, &self.params) {
+            self.collect_twigs(child, base, crown, twigs);
+        }
+    }
+
+    /// Tube along the branch curve; with a braid `phase`, the tube spirals
+    /// around the curve and closes onto it before the branch end
+    ///
+    /// With a junction `base`, the first rings start out inside the parent,
+    /// heading along it, and turn into the branch's own; a lone child at the
+    /// parent's end starts as its whole cross-section (with its segment
+    /// count), so the fork is one skin.
+    /// V runs on from `v_start` by arc length; returns the curve's length.
+    fn generate_strand(
+        &self,
+        node: &BranchNode,
+        phase: Option<f32>,
+        base: Option<JunctionBase>,
+        v_start: f32,
+        mesh: &mut Mesh,
+    ) -> f32 {
+        let params = &self.params;
+        let visual = &node.visual;
+        let curve_points = self.branch_curve(node);
+        let segments = curve_points.len();
+        let arc = arc_lengths(&curve_points);
+        let radial_segments = params.segments_for_radius(node.start_radius);
+        let ring_segments = |i: usize| match base {
+            Some(base) if i == 0 => base.segments,
+            _ => radial_segments,
+        };
 
         // Calculate directions along the curve
-        let mut directions = Vec::with_capacity(params.length_segments);
-        for i in 0..params.length_segments {
+        let mut directions = Vec::with_capacity(segments);
+        for i in 0..segments {
             let dir = if i == 0 {
                 node.start_direction
-            } else if i == params.length_segments - 1 {
+            } else if i == segments - 1 {
                 node.end_direction
             } else {
                 let prev = curve_points[i - 1];
-                let next = curve_points[(i + 1).min(params.length_segments - 1)];
+                let next = curve_points[(i + 1).min(segments - 1)];
                 (next - prev).normalize()
             };
             directions.push(dir);
         }
 
         // Create rings along the curve
-        let mut ring_starts = Vec::with_capacity(params.length_segments);
+        let mut ring_starts = Vec::with_capacity(segments);
 
-        for i in 0..params.length_segments {
-            let t = i as f32 / (params.length_segments - 1) as f32;
+        for i in 0..segments {
+            let t = i as f32 / (segments - 1) as f32;
 
-            // Interpolate radius
+            // Interpolate radius, growing out of the parent at a fork
             let radius = lerp(node.start_radius, node.end_radius, t);
+            let (direction, radius, offset) = match base {
+                Some(base) if t < JUNCTION_BLEND => {
+                    let w = smooth_step(t / JUNCTION_BLEND);
+                    (base.direction.lerp(&directions[i], w).normalize(), lerp(base.radius, radius, w), base.offset.scale(1.0 - w))
+                }
+                _ => (directions[i], radius, Vec3::ZERO),
+            };
 
-            // Add slight bark displacement
-            let displaced_radius = radius + self.bark_noise(i, params.seed) * params.bark_displacement;
+            // Braided strands orbit the branch curve, merging by its end
+            let center = match phase {
+                Some(phase) => {
+                    let angle = phase + t * BRAID_TURNS * TAU;
+                    let orbit = radius * (1.0 - smooth_step((t / BRAID_MERGE).min(1.0)));
+                    let tangent = directions[i].perpendicular();
+                    let bitangent = directions[i].cross(&tangent).normalize();
+                    curve_points[i] + (tangent.scale(angle.cos()) + bitangent.scale(angle.sin())).scale(orbit) + offset
+                }
+                None => curve_points[i] + offset,
+            };
+
+            // Add slight bark displacement, fading out at the ends so the
+            // rings at a fork line up
+            let displaced_radius = radius
+                + self.bark_noise(i, params.seed) * params.bark_displacement * visual.bark_roughness * (PI * t).sin();
+
+            // Uncertain dates flicker the glow along the branch for a fuzzier look
+            let glow = visual.glow_intensity
+                * (1.0 + self.bark_noise(i + segments, params.seed) * visual.uncertainty * 0.4);
 
             // Create ring
             let ring = create_ring(
-                curve_points[i],
-                directions[i],
+                center,
+                direction,
                 displaced_radius,
-                params.radial_segments,
-                t, // v coordinate
-                visual.glow_intensity,
+                ring_segments(i),
+                v_start + arc[i] / BARK_TEXTURE_LENGTH,
+                glow,
                 visual.luminance,
                 visual.hue_shift,
             );
@@ -121,51 +552,342 @@ impl MeshGenerator {
         }
 
         // Connect consecutive rings
-        for i in 0..(params.length_segments - 1) {
-            connect_rings(mesh, ring_starts[i], ring_starts[i + 1], params.radial_segments);
+        for i in 0..(segments - 1) {
+            connect_rings_adaptive(mesh, ring_starts[i], ring_starts[i + 1], ring_segments(i), ring_segments(i + 1));
+        }
+        arc[segments - 1]
+    }
+
+    /// Points along a branch's centre line, through any waypoints
+    fn branch_curve(&self, node: &BranchNode) -> Vec<Vec3> {
+        let params = &self.params;
+        if node.waypoints.is_empty() {
+            return generate_branch_curve(
+                node.start,
+                node.end,
+                node.start_direction,
+                node.end_direction,
+                0.4, // Curvature
+                params.length_segments,
+            );
+        }
+        let mut points = Vec::with_capacity(node.waypoints.len() + 2);
+        points.push(node.start);
+        points.extend_from_slice(&node.waypoints);
+        points.push(node.end);
+        let segments = params.length_segments.max(points.len() * params.waypoint_rings);
+        CatmullRomSpline::new(points).sample(segments)
+    }
+
+    /// Generate small glowing nodules on the branch surface for life events
+    fn generate_event_nodules(&self, node: &BranchNode, mesh: &mut Mesh) {
+        let visual = &node.visual;
+        let length = node.start.distance(&node.end);
+        let m0 = node.start_direction.scale(length * 0.4);
+        let m1 = node.end_direction.scale(length * 0.4);
+        let golden_angle = std::f32::consts::PI * (3.0 - 5.0f32.sqrt());
+
+        for (i, &t) in node.event_positions.iter().enumerate() {
+            let center = hermite_curve(node.start, node.end, m0, m1, t);
+            let direction = node.start_direction.lerp(&node.end_direction, t).normalize();
+            let branch_radius = lerp(node.start_radius, node.end_radius, t);
+
+            // Walk around the branch so consecutive events don't stack on one side
+            let angle = i as f32 * golden_angle;
+            let tangent = direction.perpendicular();
+            let bitangent = direction.cross(&tangent).normalize();
+            let outward = (tangent.scale(angle.cos()) + bitangent.scale(angle.sin())).normalize();
+
+            let nodule_radius = branch_radius * 0.35 + 0.015;
+            let nodule_center = center + outward.scale(branch_radius);
+            self.generate_nodule(
+                mesh,
+                nodule_center,
+                outward,
+                nodule_radius,
+                (visual.glow_intensity * 1.6).min(1.5),
+                (visual.luminance * 1.4).min(1.2),
+                visual.hue_shift,
+            );
         }
     }
 
-    /// Generate a smooth joint where parent meets children
-    fn generate_joint(&self, parent: &BranchNode, mesh: &mut Mesh) {
-        let children = &parent.children;
-        if children.is_empty() {
+    /// A blossom where a child sprouts from its parent, ringing the base of
+    /// its branch (trunks aren't born from anything)
+    fn generate_birth_blossom(&self, node: &BranchNode, mesh: &mut Mesh) {
+        if self.params.blossom_size <= 0.0 || node.generation == 0 {
             return;
         }
+        let axis = node.start_direction.normalize();
+        self.generate_blossom(
+            mesh,
+            node.start + axis.scale(node.start_radius),
+            axis,
+            node.start_radius + self.params.blossom_size,
+            (node.visual.luminance * 1.3).min(1.2),
+            node.visual.hue_shift,
+        );
+    }
 
-        // For now, we generate a simple dome cap at the end of the parent
-        // A more sophisticated approach would blend smoothly into children
+    /// A blossom where a partner's strand closes onto the branch, in a hue
+    /// between the two
+    fn generate_partnership_blossom(&self, node: &BranchNode, partner: &BranchNode, mesh: &mut Mesh) {
+        if self.params.blossom_size <= 0.0 {
+            return;
+        }
+        let direction = node.start_direction.lerp(&node.end_direction, BRAID_MERGE).normalize();
+        let radius = lerp(node.start_radius, node.end_radius, BRAID_MERGE);
+        self.generate_blossom(
+            mesh,
+            node.point_along(BRAID_MERGE),
+            direction,
+            radius + self.params.blossom_size * 1.5,
+            (node.visual.luminance.max(partner.visual.luminance) * 1.3).min(1.2),
+            (node.visual.hue_shift + partner.visual.hue_shift) / 2.0,
+        );
+    }
+
+    /// Petals fanning out around `axis`, tilted toward it like a cup, with a
+    /// glowing bud at the heart
+    fn generate_blossom(&self, mesh: &mut Mesh, center: Vec3, axis: Vec3, size: f32, luminance: f32, hue: f32) {
+        let glow = self.params.blossom_glow;
+        let tangent = axis.perpendicular();
+        let bitangent = axis.cross(&tangent).normalize();
+
+        for petal in 0..BLOSSOM_PETALS {
+            let angle = petal as f32 / BLOSSOM_PETALS as f32 * TAU;
+            let outward = tangent.scale(angle.cos()) + bitangent.scale(angle.sin());
+            let reach = (outward + axis.scale(0.5)).normalize();
+            let side = axis.cross(&outward).normalize();
+            let normal = side.cross(&reach).normalize();
+
+            // Diamond petal: base, widest point either side, then tip
+            let corners = [
+                (center, 0.5, 0.0),
+                (center + reach.scale(size * 0.45) - side.scale(size * 0.3), 0.0, 0.45),
+                (center + reach.scale(size), 0.5, 1.0),
+                (center + reach.scale(size * 0.45) + side.scale(size * 0.3), 1.0, 0.45),
+            ];
+            let start = mesh.add_vertices(corners.iter().map(|&(position, u, v)| {
+                // Petal tips glow brightest
+                Vertex::new(position, normal)
+                    .with_uv(u, v)
+                    .with_visual(glow * (0.6 + 0.4 * v), luminance, hue)
+            }));
+            mesh.add_quad(start, start + 1, start + 2, start + 3);
+        }
+
+        self.generate_nodule(mesh, center, axis, size * 0.2, glow * 1.25, luminance, hue);
+    }
+
+    /// Flare and surface roots anchoring a trunk in the ground, whether or
+    /// not ancestor roots are grown beneath it
+    fn generate_trunk_base(&self, node: &BranchNode, mesh: &mut Mesh) {
+        if node.generation != 0 {
+            return;
+        }
+        self.generate_root_flare(node, mesh);
+        for i in 0..self.params.surface_roots {
+            self.generate_surface_root(node, i, mesh);
+        }
+    }
+
+    /// The trunk widening into the ground, sunk a little below its base
+    fn generate_root_flare(&self, node: &BranchNode, mesh: &mut Mesh) {
+        if self.params.root_flare <= 1.0 {
+            return;
+        }
+        let visual = &node.visual;
+        let radius = node.start_radius;
+        let segments = self.params.segments_for_radius(radius);
+        // Coarser meshes (fewer length segments) get fewer rings
+        let rings = (self.params.length_segments / 2 + 1).clamp(3, 5);
+
+        // Along the trunk's start, so a leaning trunk flares along its lean
+        let axis = if node.start_direction.length_squared() > 1e-8 { node.start_direction.normalize() } else { Vec3::UP };
+
+        let mut ring_starts = Vec::with_capacity(rings);
+        for i in 0..rings {
+            // From up the trunk (s = 0) down to just below the ground (s = 1)
+            let s = i as f32 / (rings - 1) as f32;
+            let height = radius * (FLARE_HEIGHT * (1.0 - s) - 0.3 * s);
+            let center = node.start + axis.scale(height);
+            let ring = create_ring(
+                center,
+                axis,
+                radius * lerp(1.0, self.params.root_flare, s * s),
+                segments,
+                height / BARK_TEXTURE_LENGTH,
+                visual.glow_intensity,
+                visual.luminance * (1.0 - 0.2 * s),
+                visual.hue_shift,
+            );
+            ring_starts.push(mesh.add_vertices(ring));
+        }
+        for pair in ring_starts.windows(2) {
+            connect_rings(mesh, pair[1], pair[0], segments);
+        }
+    }
+
+    /// A tapering root snaking outward along the ground before diving under
+    fn generate_surface_root(&self, node: &BranchNode, index: usize, mesh: &mut Mesh) {
+        let visual = &node.visual;
+        let params = &self.params;
+        let trunk_radius = node.start_radius;
+        let seed = params.seed.wrapping_add(index as u32 * 7919);
+
+        let angle = (index as f32 + 0.35 * self.bark_noise(1, seed)) / params.surface_roots as f32 * TAU;
+        let outward = Vec3::new(angle.cos(), 0.0, angle.sin());
+        let length = trunk_radius * 6.0 * (1.0 + 0.3 * self.bark_noise(2, seed));
+        let root_radius = trunk_radius * 0.4;
+        let segments = (params.radial_segments / 2).max(3);
+        let rings = (params.length_segments * 3 / 4).max(3);
+
+        // Rises over the flare's skirt, then sinks as it thins
+        let points: Vec<Vec3> = (0..rings)
+            .map(|i| {
+                let t = i as f32 / (rings - 1) as f32;
+                let reach = trunk_radius * params.root_flare.max(1.0) * 0.6 + length * t;
+                let height = root_radius * (0.5 * (1.0 - t) - 1.5 * t * t);
+                node.start + outward.scale(reach) + Vec3::UP.scale(height)
+            })
+            .collect();
+
+        let arc = arc_lengths(&points);
+        let mut ring_starts = Vec::with_capacity(points.len());
+        for (i, &center) in points.iter().enumerate() {
+            let t = i as f32 / (points.len() - 1) as f32;
+            let next = points[(i + 1).min(points.len() - 1)];
+            let prev = points[i.saturating_sub(1)];
+            let ring = create_ring(
+                center,
+                (next - prev).normalize(),
+                (root_radius * (1.0 - t).powf(1.5)).max(0.008),
+                segments,
+                arc[i] / BARK_TEXTURE_LENGTH,
+                visual.glow_intensity * (1.0 - 0.5 * t),
+                visual.luminance * 0.8,
+                visual.hue_shift,
+            );
+            ring_starts.push(mesh.add_vertices(ring));
+        }
+        for pair in ring_starts.windows(2) {
+            connect_rings(mesh, pair[0], pair[1], segments);
+        }
+    }
+
+    /// Bright bulb at the tip of a branch whose descendants are held back,
+    /// larger for bigger hidden lines
+    fn generate_bud(&self, node: &BranchNode, mesh: &mut Mesh) {
+        if node.hidden_descendants == 0 {
+            return;
+        }
+        let visual = &node.visual;
+        let size = 1.0 + (node.hidden_descendants as f32).ln_1p() * 0.25;
+        let radius = (node.end_radius * 1.6 + 0.03) * size;
+        self.generate_nodule(
+            mesh,
+            node.end + node.end_direction.scale(radius * 0.6),
+            node.end_direction,
+            radius,
+            (visual.glow_intensity * 2.0).min(1.5),
+            (visual.luminance * 1.5).min(1.2),
+            visual.hue_shift,
+        );
+    }
+
+    /// Generate a small sphere oriented along `axis`
+    #[allow(clippy::too_many_arguments)]
+    fn generate_nodule(
+        &self,
+        mesh: &mut Mesh,
+        center: Vec3,
+        axis: Vec3,
+        radius: f32,
+        glow: f32,
+        luminance: f32,
+        hue: f32,
+    ) {
+        let segments = self.params.segments_for_radius(radius).max(4);
+        let rings = 3;
+
+        let bottom = Vertex::new(center - axis.scale(radius), -axis)
+            .with_uv(0.5, 0.0)
+            .with_visual(glow, luminance, hue);
+        let bottom_idx = mesh.add_vertices(std::iter::once(bottom));
+
+        let mut ring_starts = Vec::with_capacity(rings);
+        for r in 1..=rings {
+            let phi = r as f32 / (rings + 1) as f32 * std::f32::consts::PI;
+            let ring_center = center - axis.scale(radius * phi.cos());
+            let ring = create_ring(
+                ring_center,
+                axis,
+                radius * phi.sin(),
+                segments,
+                r as f32 / (rings + 1) as f32,
+                glow,
+                luminance,
+                hue,
+            );
+            ring_starts.push(mesh.add_vertices(ring));
+        }
+
+        let top = Vertex::new(center + axis.scale(radius), axis)
+            .with_uv(0.5, 1.0)
+            .with_visual(glow, luminance, hue);
+        let top_idx = mesh.add_vertices(std::iter::once(top));
+
+        for i in 0..segments {
+            let first = ring_starts[0];
+            mesh.add_triangle(bottom_idx, first + i as u32 + 1, first + i as u32);
+        }
+        for pair in ring_starts.windows(2) {
+            connect_rings(mesh, pair[0], pair[1], segments);
+        }
+        let last = ring_starts[rings - 1];
+        for i in 0..segments {
+            mesh.add_triangle(last + i as u32, last + i as u32 + 1, top_idx);
+        }
+    }
+
+    /// Simple deterministic noise for bark texture
+    fn bark_noise(&self, index: usize, seed: u32) -> f32 {
+        let x = (index as u32).wrapping_mul(seed).wrapping_add(12345);
+        let x = x ^ (x >> 16);
+        let x = x.wrapping_mul(0x85ebca6b);
+        let x = x ^ (x >> 13);
+        (x as f32 / u32::MAX as f32) * 2.0 - 1.0
+    }
+
+    /// Generate a smooth joint where the parent's end meets several children
+    fn generate_joint(&self, parent: &BranchNode, v_start: f32, mesh: &mut Mesh) {
+        let visual = &parent.visual;
         let center = parent.end;
         let direction = parent.end_direction;
         let radius = parent.end_radius;
+        let segments = self.params.segments_for_radius(parent.start_radius);
 
-        // Create a small dome at the end
+        // A low dome over the end, for the children to grow out of
         let dome_segments = 3;
         let mut prev_ring_start = None;
-
         for i in 0..=dome_segments {
             let t = i as f32 / dome_segments as f32;
-            let dome_radius = radius * (1.0 - t * 0.5).max(0.1);
-            let offset = direction.scale(radius * t * 0.3);
-            let ring_center = center + offset;
-
+            let rise = radius * t * 0.3;
             let ring = create_ring(
-                ring_center,
+                center + direction.scale(rise),
                 direction,
-                dome_radius,
-                self.params.radial_segments,
-                1.0 + t * 0.1,
-                parent.visual.glow_intensity,
-                parent.visual.luminance,
-                parent.visual.hue_shift,
+                radius * (1.0 - t * 0.5),
+                segments,
+                v_start + rise / BARK_TEXTURE_LENGTH,
+                visual.glow_intensity,
+                visual.luminance,
+                visual.hue_shift,
             );
-
             let ring_start = mesh.add_vertices(ring);
-
             if let Some(prev_start) = prev_ring_start {
-                connect_rings(mesh, prev_start, ring_start, self.params.radial_segments);
+                connect_rings(mesh, prev_start, ring_start, segments);
             }
-
             prev_ring_start = Some(ring_start);
         }
 
@@ -173,41 +895,23 @@ impl MeshGenerator {
         if let Some(last_ring) = prev_ring_start {
             let tip = center + direction.scale(radius * 0.5);
             let tip_vertex = Vertex::new(tip, direction)
-                .with_uv(0.5, 1.0)
-                .with_visual(
-                    parent.visual.glow_intensity,
-                    parent.visual.luminance,
-                    parent.visual.hue_shift,
-                );
+                .with_uv(0.5, v_start + radius * 0.5 / BARK_TEXTURE_LENGTH)
+                .with_visual(visual.glow_intensity, visual.luminance, visual.hue_shift);
             let tip_idx = mesh.add_vertices(std::iter::once(tip_vertex));
-
-            for i in 0..self.params.radial_segments {
-                let next = (i + 1) % self.params.radial_segments;
-                mesh.add_triangle(
-                    last_ring + i as u32,
-                    last_ring + next as u32,
-                    tip_idx,
-                );
+            for i in 0..segments {
+                mesh.add_triangle(last_ring + i as u32, last_ring + i as u32 + 1, tip_idx);
             }
         }
     }
 
-    /// Simple deterministic noise for bark texture
-    fn bark_noise(&self, index: usize, seed: u32) -> f32 {
-        let x = (index as u32).wrapping_mul(seed).wrapping_add(12345);
-        let x = x ^ (x >> 16);
-        let x = x.wrapping_mul(0x85ebca6b);
-        let x = x ^ (x >> 13);
-        (x as f32 / u32::MAX as f32) * 2.0 - 1.0
-    }
-
     /// Generate organic tapered tip for leaf branches (no children)
-    fn generate_organic_tip(&self, node: &BranchNode, mesh: &mut Mesh) {
+    fn generate_organic_tip(&self, node: &BranchNode, v_start: f32, mesh: &mut Mesh) {
         let visual = &node.visual;
         let params = &self.params;
 
         let tip_length = node.end_radius * 1.5;
-        let tip_segments = 4;
+        // Coarser meshes (fewer length segments) get fewer rings
+        let tip_segments = (params.length_segments / 2).clamp(2, 4);
         let direction = node.end_direction;
         let mut prev_ring_start = None;
         let start_pos = node.end;
@@ -223,7 +927,7 @@ impl MeshGenerator {
             let ring_center = start_pos + offset;
 
             // Fewer segments for smaller rings
-            let seg_count = (params.radial_segments as f32 * (0.5 + 0.5 * taper)) as usize;
+            let seg_count = (params.segments_for_radius(node.start_radius) as f32 * (0.5 + 0.5 * taper)) as usize;
             let seg_count = seg_count.max(4);
 
             let ring = create_ring(
@@ -231,7 +935,7 @@ impl MeshGenerator {
                 direction,
                 radius.max(0.01),
                 seg_count,
-                1.0 + t * 0.2,
+                v_start + tip_length * t / BARK_TEXTURE_LENGTH,
                 visual.glow_intensity * (1.0 + 0.5 * t), // Brighter at tip
                 visual.luminance * (1.0 + 0.3 * t),
                 visual.hue_shift,
@@ -251,7 +955,7 @@ impl MeshGenerator {
         if let Some((last_ring, seg_count)) = prev_ring_start {
             let tip = start_pos + direction.scale(tip_length);
             let tip_vertex = Vertex::new(tip, direction)
-                .with_uv(0.5, 1.2)
+                .with_uv(0.5, v_start + tip_length / BARK_TEXTURE_LENGTH)
                 .with_visual(
                     visual.glow_intensity * 1.5, // Extra glow at tip
                     visual.luminance * 1.2,
@@ -260,12 +964,7 @@ impl MeshGenerator {
             let tip_idx = mesh.add_vertices(std::iter::once(tip_vertex));
 
             for i in 0..seg_count {
-                let next = (i + 1) % seg_count;
-                mesh.add_triangle(
-                    last_ring + i as u32,
-                    last_ring + next as u32,
-                    tip_idx,
-                );
+                mesh.add_triangle(last_ring + i as u32, last_ring + i as u32 + 1, tip_idx);
             }
         }
     }
@@ -290,24 +989,16 @@ fn connect_rings_adaptive(
         return;
     }
 
-    // Handle different segment counts by interpolating
+    // Handle different segment counts by interpolating; the seam vertex
+    // closes each ring, so nothing wraps
     let max_seg = seg1.max(seg2);
 
     for i in 0..max_seg {
         // Map indices from larger ring to smaller
-        let i1 = if seg1 >= seg2 {
-            i % seg1
-        } else {
-            (i * seg1 / max_seg) % seg1
-        };
-        let i1_next = (i1 + 1) % seg1;
-
-        let i2 = if seg2 >= seg1 {
-            i % seg2
-        } else {
-            (i * seg2 / max_seg) % seg2
-        };
-        let i2_next = (i2 + 1) % seg2;
+        let i1 = i * seg1 / max_seg;
+        let i1_next = (i + 1) * seg1 / max_seg;
+        let i2 = i * seg2 / max_seg;
+        let i2_next = (i + 1) * seg2 / max_seg;
 
         let a = ring1_start + i1 as u32;
         let b = ring1_start + i1_next as u32;
@@ -324,6 +1015,84 @@ fn connect_rings_adaptive(
     }
 }
 
+/// Children (or, within a root, further roots), the trunk's ancestor roots
+/// and the rest of the grove, in pre-order, each with where it leaves
+/// `node` (branches carrying on from it only) and whether it grows in the
+/// crown; `v_start` and `length` are `node`'s base V and arc length, and
+/// `params` size the junctions
+fn branches<'a>(
+    node: &'a BranchNode,
+    crown: bool,
+    v_start: f32,
+    length: f32,
+    params: &'a MeshParams,
+) -> impl Iterator<Item = (&'a BranchNode, Option<JunctionBase>, bool)> {
+    let trunk_roots: &[BranchNode] = if crown { &node.roots } else { &[] };
+    onward(node, crown)
+        .iter()
+        .map(move |next| (next, Some(JunctionBase::new(node, next, crown, v_start, length, params)), crown))
+        .chain(trunk_roots.iter().map(|root| (root, None, false)))
+        .chain(node.grove.iter().map(move |trunk| (trunk, None, crown)))
+}
+
+/// Branches carrying on from `node`'s end: its children in the crown, and
+/// within a root the ancestor's own parents
+fn onward(node: &BranchNode, crown: bool) -> &[BranchNode] {
+    if crown {
+        &node.children
+    } else {
+        &node.roots
+    }
+}
+
+/// Distance along a polyline to each of its points
+fn arc_lengths(points: &[Vec3]) -> Vec<f32> {
+    let mut total = 0.0;
+    let mut lengths = Vec::with_capacity(points.len());
+    for (i, point) in points.iter().enumerate() {
+        if i > 0 {
+            total += point.distance(&points[i - 1]);
+        }
+        lengths.push(total);
+    }
+    lengths
+}
+
+/// Where `node` sits on the baked colors' warm-to-cool gradient, and how
+/// saturated its colors are
+fn color_gradient(node: &BranchNode) -> (f32, f32) {
+    let gradient = (node.generation as f32 / COLOR_GENERATIONS).min(1.0);
+    (gradient, 0.75 + 0.25 * node.visual.color_vibrancy)
+}
+
+/// Whether `child` grows out of its parent's end ring
+fn sprouts_from_end(parent: &BranchNode, child: &BranchNode) -> bool {
+    child.start.distance(&parent.end) <= parent.end_radius * 0.1
+}
+
+/// The child that carries on from `parent`'s end ring: the only branch
+/// (other than twigs) sprouting from the end
+fn end_heir<'a>(parent: &'a BranchNode, crown: bool, params: &MeshParams) -> Option<&'a BranchNode> {
+    let mut from_end = onward(parent, crown).iter().filter(|child| sprouts_from_end(parent, child) && !params.is_twig(child));
+    match (from_end.next(), from_end.next()) {
+        (Some(child), None) => Some(child),
+        _ => None,
+    }
+}
+
+/// Capsules along a branch's curve, each as thick as the wider end of its
+/// stretch
+fn branch_colliders(node: &BranchNode) -> Vec<Capsule> {
+    (0..COLLIDERS_PER_BRANCH)
+        .map(|i| {
+            let t0 = i as f32 / COLLIDERS_PER_BRANCH as f32;
+            let t1 = (i + 1) as f32 / COLLIDERS_PER_BRANCH as f32;
+            let radius = lerp(node.start_radius, node.end_radius, t0).max(lerp(node.start_radius, node.end_radius, t1));
+            Capsule::new(node.point_along(t0), node.point_along(t1), radius)
+        })
+        .collect()
+}
+
 fn lerp(a: f32, b: f32, t: f32) -> f32 {
     a + (b - a) * t
 }
@@ -338,6 +1107,23 @@ pub struct BranchMeshInfo {
     pub index_count: u32,
     pub bounds_center: Vec3,
     pub bounds_radius: f32,
+    /// Bark V coordinate at the branch's base
+    pub v_start: f32,
+    /// Depth of the branch's person from the trunk (0 = trunk)
+    pub generation: usize,
+    /// Where a label for the person is pinned (the branch's tip)
+    pub anchor: Vec3,
+    /// Capsules hugging the branch's curve, tighter than the bounding
+    /// sphere (for picking and camera collision)
+    pub colliders: Vec<Capsule>,
+}
+
+/// Vertices and indices of a mesh rewritten in place, so only they need
+/// uploading again
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshPatch {
+    pub vertices: Range<usize>,
+    pub indices: Range<usize>,
 }
 
 /// Generate mesh with per-branch tracking for picking
@@ -357,15 +1143,124 @@ impl TrackedMeshGenerator {
         let mut mesh = Mesh::new();
         let mut branch_infos = Vec::new();
 
-        self.generate_branch_tracked(root, &mut mesh, &mut branch_infos);
+        self.generate_branch_tracked(root, None, true, 0..0, &mut mesh, &mut branch_infos);
         mesh.calculate_bounds();
 
         (mesh, branch_infos)
     }
 
+    /// Coarser meshes of the tree for levels 1 to `levels`, most detailed
+    /// first, to draw instead of the tracked mesh from afar
+    pub fn generate_lods(&self, root: &BranchNode, levels: usize) -> Vec<Mesh> {
+        (1..=levels)
+            .map(|level| MeshGenerator::new(self.generator.params.lod(level)).generate_tree(root))
+            .collect()
+    }
+
+    /// Twigs left out of the tracked mesh, see [`MeshGenerator::twig_instances`]
+    pub fn twig_instances(&self, root: &BranchNode) -> Vec<TwigInstance> {
+        self.generator.twig_instances(root)
+    }
+
+    /// The canonical twig the instances draw
+    pub fn twig_mesh(&self) -> Mesh {
+        self.generator.twig_mesh()
+    }
+
+    /// Memorial rings, see [`MeshGenerator::generate_memorials`]
+    pub fn generate_memorials(&self, root: &BranchNode) -> Mesh {
+        self.generator.generate_memorials(root)
+    }
+
+    /// Replace the geometry of a regrown subtree, keeping every other branch
+    ///
+    /// `old_count` is how many branches the subtree had when `mesh` and
+    /// `infos` were generated; tracked branches are laid out in pre-order, so
+    /// a subtree's vertices and indices are contiguous. `parent` is the
+    /// branch the subtree grows out of (None for a trunk).
+    ///
+    /// When the regrown subtree has as many vertices and indices as before,
+    /// it is written over the old one and the changed block is returned;
+    /// otherwise the buffers are resized and nothing is.
+    pub fn regenerate_subtree(
+        &self,
+        mesh: &mut Mesh,
+        infos: &mut Vec<BranchMeshInfo>,
+        old_count: usize,
+        subtree: &BranchNode,
+        parent: Option<&BranchNode>,
+    ) -> Result<Option<MeshPatch>, String> {
+        let first = infos
+            .iter()
+            .position(|info| info.person_id == subtree.person_id)
+            .ok_or_else(|| format!("No geometry for '{}'", subtree.person_id))?;
+        let last = first + old_count.max(1) - 1;
+        if last >= infos.len() {
+            return Err(format!("Subtree of '{}' is larger than the tracked mesh", subtree.person_id));
+        }
+
+        let vertex_start = infos[first].vertex_start;
+        let index_start = infos[first].index_start;
+        let vertex_end = infos[last].vertex_start + infos[last].vertex_count;
+        let index_end = infos[last].index_start + infos[last].index_count;
+
+        // The bark carries on from where the parent's V reached
+        let (base, seam) = match parent {
+            Some(parent) => {
+                let info = infos
+                    .iter()
+                    .find(|info| info.person_id == parent.person_id)
+                    .ok_or_else(|| format!("No geometry for '{}'", parent.person_id))?;
+                let length = *arc_lengths(&self.generator.branch_curve(parent)).last().unwrap_or(&0.0);
+                let seam = info.vertex_start as usize..(info.vertex_start + info.vertex_count) as usize;
+                (Some(JunctionBase::new(parent, subtree, true, info.v_start, length, &self.generator.params)), seam)
+            }
+            None => (None, 0..0),
+        };
+
+        let mut replacement = Mesh::new();
+        let mut new_infos = Vec::new();
+        self.generate_branch_tracked(subtree, base, true, 0..0, &mut replacement, &mut new_infos);
+
+        let vertex_delta = replacement.vertices.len() as i64 - (vertex_end - vertex_start) as i64;
+        let index_delta = replacement.indices.len() as i64 - (index_end - index_start) as i64;
+        let vertices = vertex_start as usize..vertex_end as usize;
+        let indices = index_start as usize..index_end as usize;
+        let in_place = vertex_delta == 0
+            && index_delta == 0
+            && mesh.replace_range(vertices.clone(), indices.clone(), &replacement);
+        if !in_place {
+            mesh.splice(vertices.clone(), indices.clone(), &replacement);
+        }
+
+        for info in &mut new_infos {
+            info.vertex_start += vertex_start;
+            info.index_start += index_start;
+        }
+        for info in &mut infos[last + 1..] {
+            info.vertex_start = (info.vertex_start as i64 + vertex_delta) as u32;
+            info.index_start = (info.index_start as i64 + index_delta) as u32;
+        }
+        // Each regrown branch was smoothed on its own; the first shades on
+        // from its parent, which is left as is
+        if let Some(info) = new_infos.first() {
+            let own = info.vertex_start as usize..(info.vertex_start + info.vertex_count) as usize;
+            mesh.copy_seam_normals(own, seam);
+        }
+        infos.splice(first..=last, new_infos);
+        mesh.calculate_bounds();
+
+        // Branches are smoothed on their own, so nothing outside the
+        // subtree's spans changed
+        Ok(in_place.then_some(MeshPatch { vertices, indices }))
+    }
+
     fn generate_branch_tracked(
         &self,
         node: &BranchNode,
+        base: Option<JunctionBase>,
+        crown: bool,
+        seam: Range<usize>,
         mesh: &mut Mesh,
         infos: &mut Vec<BranchMeshInfo>,
     ) {
@@ -373,7 +1268,21 @@ impl TrackedMeshGenerator {
         let index_start = mesh.indices.len() as u32;
 
         // Generate this branch
-        self.generator.generate_branch_segment(node, mesh);
+        let v_start = base.map_or(0.0, |base| base.v);
+        let length = self.generator.generate_branch_segment(node, base, mesh);
+        self.generator.generate_event_nodules(node, mesh);
+        self.generator.generate_bud(node, mesh);
+        if crown {
+            self.generator.generate_birth_blossom(node, mesh);
+            self.generator.generate_trunk_base(node, mesh);
+        }
+        mesh.fade_from(vertex_start, node.visual.opacity);
+        self.generator.bake_colors(node, vertex_start, mesh);
+        // Smooth the branch's own rings; it takes the parent's normals where
+        // they meet rather than welding to it (or to its siblings)
+        let own = vertex_start as usize..mesh.vertices.len();
+        mesh.recompute_normals_range(own.clone(), index_start as usize..mesh.indices.len());
+        mesh.copy_seam_normals(own.clone(), seam);
 
         let vertex_count = mesh.vertices.len() as u32 - vertex_start;
         let index_count = mesh.indices.len() as u32 - index_start;
@@ -390,11 +1299,38 @@ impl TrackedMeshGenerator {
             index_count,
             bounds_center: center,
             bounds_radius: radius,
+            v_start,
+            generation: node.generation,
+            anchor: node.end,
+            colliders: branch_colliders(node),
         });
 
-        // Generate children
-        for child in &node.children {
-            self.generate_branch_tracked(child, mesh, infos);
+        // The partner's strand is picked as its own person
+        if let Some(partner) = &node.partner {
+            let vertex_start = mesh.vertices.len() as u32;
+            let index_start = mesh.indices.len() as u32;
+            self.generator.generate_partner(node, v_start, mesh);
+            mesh.recompute_normals_range(vertex_start as usize..mesh.vertices.len(), index_start as usize..mesh.indices.len());
+            infos.push(BranchMeshInfo {
+                person_id: partner.person_id.clone(),
+                vertex_start,
+                vertex_count: mesh.vertices.len() as u32 - vertex_start,
+                index_start,
+                index_count: mesh.indices.len() as u32 - index_start,
+                bounds_center: center,
+                bounds_radius: radius,
+                v_start,
+                generation: partner.generation,
+                anchor: partner.end,
+                colliders: branch_colliders(partner),
+            });
+        }
+
+        // Generate children (and, at the trunk, ancestor roots and the rest
+        // of the grove)
+        for (child, base, crown) in branches(node, crown, v_start, length, &self.generator.params) {
+            let seam = if base.is_some() { own.clone() } else { 0..0 };
+            self.generate_branch_tracked(child, base, crown, seam, mesh, infos);
         }
     }
 }
@@ -403,21 +1339,11 @@ impl TrackedMeshGenerator {
 mod tests {
     use super::*;
     use crate::data::{FamilyTree, VisualParams};
+    use crate::mesh::branch::VERTEX_FLOATS;
     use crate::growth::{TreeGrowth, GrowthParams};
 
     fn create_simple_node() -> BranchNode {
-        BranchNode {
-            person_id: "test".to_string(),
-            visual: VisualParams::default(),
-            start: Vec3::ZERO,
-            end: Vec3::new(0.0, 2.0, 0.0),
-            start_direction: Vec3::UP,
-            end_direction: Vec3::UP,
-            start_radius: 0.3,
-            end_radius: 0.2,
-            generation: 0,
-            children: vec![],
-        }
+        BranchNode::straight("test", Vec3::ZERO, Vec3::new(0.0, 2.0, 0.0))
     }
 
     #[test]
@@ -457,7 +1383,21 @@ mod tests {
         let mesh = generator.generate_tree(&node);
 
         let data = mesh.vertex_data();
-        assert_eq!(data.len() % 11, 0); // Each vertex is 11 floats
+        assert_eq!(data.len() % VERTEX_FLOATS, 0);
+    }
+
+    #[test]
+    fn test_event_nodules_add_geometry() {
+        let plain = create_simple_node();
+        let mut eventful = create_simple_node();
+        eventful.event_positions = vec![0.3, 0.7];
+
+        let generator = MeshGenerator::new(MeshParams::default());
+        let plain_mesh = generator.generate_tree(&plain);
+        let eventful_mesh = generator.generate_tree(&eventful);
+
+        assert!(eventful_mesh.vertex_count() > plain_mesh.vertex_count());
+        assert!(eventful_mesh.indices.iter().all(|&i| (i as usize) < eventful_mesh.vertex_count()));
     }
 
     #[test]
@@ -511,6 +1451,29 @@ people:
         assert!(mesh.vertex_count() > 0);
     }
 
+    #[test]
+    fn test_branch_colliders_follow_curve() {
+        let mut node = create_simple_node();
+        node.end_direction = Vec3::new(1.0, 1.0, 0.0).normalize();
+        let generator = TrackedMeshGenerator::new(MeshParams::default());
+        let (_, infos) = generator.generate_tree_tracked(&node);
+
+        let colliders = &infos[0].colliders;
+        assert_eq!(colliders.len(), COLLIDERS_PER_BRANCH);
+        assert_eq!(colliders[0].start, node.start);
+        assert_eq!(colliders[COLLIDERS_PER_BRANCH - 1].end, node.end);
+        for pair in colliders.windows(2) {
+            assert_eq!(pair[0].end, pair[1].start);
+        }
+        // Every point on the centre line is inside, and the capsules are
+        // thinner than the bounding sphere
+        for i in 0..=10 {
+            let p = node.point_along(i as f32 / 10.0);
+            assert!(colliders.iter().any(|c| c.distance(p) < 0.0));
+        }
+        assert!(colliders.iter().all(|c| c.radius <= node.start_radius && c.radius < infos[0].bounds_radius));
+    }
+
     #[test]
     fn test_branch_bounds_calculated() {
         let yaml = r#"
@@ -530,4 +1493,421 @@ people:
 
         assert!(infos[0].bounds_radius > 0.0);
     }
+
+    #[test]
+    fn test_regenerate_subtree_matches_full_mesh() {
+        let yaml = r#"
+family:
+  name: "Edit"
+  root: "root"
+people:
+  - id: "root"
+    name: "Root"
+    children: ["a", "b"]
+  - id: "a"
+    name: "A"
+  - id: "b"
+    name: "B"
+"#;
+        let growth = TreeGrowth::new(GrowthParams::default());
+        let generator = TrackedMeshGenerator::new(MeshParams::default());
+        let mut tree = growth.grow(&FamilyTree::from_yaml(yaml).unwrap()).unwrap();
+        let (mut mesh, mut infos) = generator.generate_tree_tracked(&tree);
+
+        let mut family = FamilyTree::from_yaml(yaml).unwrap();
+        family.add_child("a", crate::data::Person::new("a1", "A1")).unwrap();
+        growth.regrow_subtree(&mut tree, "a", &family).unwrap();
+        let subtree = tree.iter_preorder().find(|n| n.person_id == "a").unwrap();
+        let patch = generator.regenerate_subtree(&mut mesh, &mut infos, 1, subtree, tree.crown_parent("a")).unwrap();
+        // A new child grows the buffers, so they can't be patched in place
+        assert_eq!(patch, None);
+
+        let (full, full_infos) = generator.generate_tree_tracked(&tree);
+        assert_eq!(mesh.indices, full.indices);
+        assert_eq!(mesh.vertex_data(), full.vertex_data());
+        let ranges = |i: &[BranchMeshInfo]| i.iter().map(|b| (b.person_id.clone(), b.vertex_start, b.index_start)).collect::<Vec<_>>();
+        assert_eq!(ranges(&infos), ranges(&full_infos));
+    }
+
+    #[test]
+    fn test_regenerate_subtree_patches_in_place() {
+        let parent = create_simple_node();
+        let child = BranchNode {
+            person_id: "child".to_string(),
+            generation: 1,
+            start: parent.end,
+            end: parent.end + Vec3::new(1.0, 1.0, 0.0),
+            start_direction: Vec3::new(1.0, 1.0, 0.0).normalize(),
+            end_direction: Vec3::new(1.0, 1.0, 0.0).normalize(),
+            ..create_simple_node()
+        };
+        let mut node = BranchNode { children: vec![child], ..create_simple_node() };
+        let generator = TrackedMeshGenerator::new(MeshParams::default());
+        let (mut mesh, mut infos) = generator.generate_tree_tracked(&node);
+        let before = mesh.clone();
+
+        // Bending the child keeps its layout
+        let bent = Vec3::new(-1.0, 1.5, 0.5);
+        node.children[0].end = node.children[0].start + bent;
+        node.children[0].end_direction = bent.normalize();
+        let child = node.children[0].clone();
+        let patch = generator.regenerate_subtree(&mut mesh, &mut infos, 1, &child, Some(&node)).unwrap().unwrap();
+
+        // The patch is just the child's block, and nothing outside it moved
+        let (full, _) = generator.generate_tree_tracked(&node);
+        assert_eq!(mesh.vertex_data(), full.vertex_data());
+        assert_eq!(patch.vertices.start, infos[1].vertex_start as usize);
+        assert_eq!(patch.indices.start, infos[1].index_start as usize);
+        for i in (0..mesh.vertex_count()).filter(|i| !patch.vertices.contains(i)) {
+            assert_eq!(mesh.vertices[i], before.vertices[i]);
+        }
+    }
+
+    #[test]
+    fn test_lods_shrink() {
+        let yaml = r#"
+family:
+  name: "Lod"
+  root: "root"
+people:
+  - id: "root"
+    name: "Root"
+    children: ["a", "b"]
+  - id: "a"
+    name: "A"
+  - id: "b"
+    name: "B"
+"#;
+        let tree = TreeGrowth::new(GrowthParams::default()).grow(&FamilyTree::from_yaml(yaml).unwrap()).unwrap();
+        let generator = TrackedMeshGenerator::new(MeshParams::default());
+        let full = MeshGenerator::new(MeshParams::default()).generate_tree(&tree);
+        let lods = generator.generate_lods(&tree, 2);
+
+        assert_eq!(lods.len(), 2);
+        // The first coarser level is about a quarter of the full mesh
+        assert!(lods[0].vertex_count() * 10 < full.vertex_count() * 4, "{} vs {}", lods[0].vertex_count(), full.vertex_count());
+        assert!(lods[1].vertex_count() < lods[0].vertex_count());
+    }
+
+    #[test]
+    fn test_fork_is_one_skin() {
+        let parent = create_simple_node();
+        let child = BranchNode {
+            person_id: "child".to_string(),
+            generation: 1,
+            start: parent.end,
+            end: parent.end + Vec3::new(1.0, 1.0, 0.0),
+            start_direction: Vec3::new(1.0, 1.0, 0.0).normalize(),
+            end_direction: Vec3::new(1.0, 1.0, 0.0).normalize(),
+            start_radius: 0.15,
+            end_radius: 0.1,
+            ..create_simple_node()
+        };
+        let node = BranchNode { children: vec![child], ..create_simple_node() };
+        let params = MeshParams { blossom_size: 0.0, root_flare: 1.0, surface_roots: 0, ..Default::default() };
+        let (mesh, infos) = TrackedMeshGenerator::new(params).generate_tree_tracked(&node);
+
+        // The child's first ring is the parent's last one, not a cap over it
+        let ring = params.radial_segments + 1;
+        let parent_last = infos[0].vertex_start as usize + (params.length_segments - 1) * ring;
+        let child_first = infos[1].vertex_start as usize;
+        for i in 0..ring {
+            let (a, b) = (&mesh.vertices[parent_last + i], &mesh.vertices[child_first + i]);
+            assert!(a.position.distance(&b.position) < 1e-4, "{:?} vs {:?}", a.position, b.position);
+            // ...and shades as one, with no crease along the seam
+            assert!(a.normal.dot(&b.normal) > 0.9999);
+        }
+    }
+
+    #[test]
+    fn test_sibling_forks_start_apart() {
+        let parent = create_simple_node();
+        let child = |x: f32| BranchNode {
+            person_id: format!("child {}", x),
+            generation: 1,
+            start: parent.end,
+            end: parent.end + Vec3::new(x, 1.0, 0.0),
+            start_direction: Vec3::new(x, 1.0, 0.0).normalize(),
+            end_direction: Vec3::new(x, 1.0, 0.0).normalize(),
+            start_radius: 0.15,
+            end_radius: 0.1,
+            ..create_simple_node()
+        };
+        let node = BranchNode { children: vec![child(1.0), child(-1.0)], ..create_simple_node() };
+        let params = MeshParams { blossom_size: 0.0, root_flare: 1.0, surface_roots: 0, ..Default::default() };
+        let (mesh, infos) = TrackedMeshGenerator::new(params).generate_tree_tracked(&node);
+
+        // Each child's first ring sits inside the parent's end, on the side it
+        // heads out to, so the two don't overlap
+        let ring = params.segments_for_radius(0.15) + 1;
+        for (info, side) in [(&infos[1], 1.0), (&infos[2], -1.0)] {
+            let first = &mesh.vertices[info.vertex_start as usize..info.vertex_start as usize + ring];
+            assert!(first.iter().all(|v| v.position.distance(&parent.end) <= parent.end_radius + 1e-4));
+            let mean_x = first.iter().map(|v| v.position.x).sum::<f32>() / ring as f32;
+            assert!(mean_x * side > 0.01, "{}", mean_x);
+        }
+
+        // ...and the parent's end is domed over for them to grow out of
+        let dome_top = parent.end + Vec3::UP.scale(parent.end_radius * 0.5);
+        let mesh = MeshGenerator::new(params).generate_tree(&node);
+        assert!(mesh.vertices.iter().any(|v| v.position.distance(&dome_top) < 1e-4));
+    }
+
+    #[test]
+    fn test_uvs_follow_arc_length() {
+        let parent = create_simple_node();
+        let child = BranchNode {
+            person_id: "child".to_string(),
+            generation: 1,
+            start: parent.end,
+            end: parent.end + Vec3::new(1.0, 1.0, 0.0),
+            start_direction: Vec3::new(1.0, 1.0, 0.0).normalize(),
+            end_direction: Vec3::new(1.0, 1.0, 0.0).normalize(),
+            ..create_simple_node()
+        };
+        let node = BranchNode { children: vec![child.clone()], ..create_simple_node() };
+        let params = MeshParams { blossom_size: 0.0, root_flare: 1.0, surface_roots: 0, ..Default::default() };
+        let generator = TrackedMeshGenerator::new(params);
+        let (mut mesh, mut infos) = generator.generate_tree_tracked(&node);
+
+        // V climbs by arc length and carries on into the child; U wraps 0 to 1
+        let ring = params.radial_segments + 1;
+        let v = |i: usize| mesh.vertices[i].uv[1];
+        let parent_last = infos[0].vertex_start as usize + (params.length_segments - 1) * ring;
+        assert!((v(parent_last) - 2.0 / BARK_TEXTURE_LENGTH).abs() < 0.05);
+        assert!((1..params.length_segments).all(|r| v(r * ring) > v((r - 1) * ring)));
+        assert!((v(infos[1].vertex_start as usize) - v(parent_last)).abs() < 1e-4);
+        assert!((infos[1].v_start - v(parent_last)).abs() < 1e-4);
+        assert_eq!(mesh.vertices[ring - 1].uv[0], 1.0);
+
+        // A regrown child picks its V up from the tracked parent
+        let before = mesh.vertices[infos[1].vertex_start as usize].uv;
+        generator.regenerate_subtree(&mut mesh, &mut infos, 1, &child, Some(&node)).unwrap();
+        assert_eq!(mesh.vertices[infos[1].vertex_start as usize].uv, before);
+    }
+
+    #[test]
+    fn test_segments_follow_radius() {
+        let params = MeshParams::default();
+        assert_eq!(params.segments_for_radius(0.01), params.twig_segments);
+        assert_eq!(params.segments_for_radius(params.trunk_radius * 2.0), params.radial_segments);
+        let mid = params.segments_for_radius((params.twig_radius + params.trunk_radius) / 2.0);
+        assert!(params.twig_segments < mid && mid < params.radial_segments);
+
+        // A twig is cheaper than a trunk-sized branch of the same length
+        let twig = BranchNode { start_radius: 0.02, end_radius: 0.015, ..create_simple_node() };
+        let generator = MeshGenerator::new(MeshParams { root_flare: 1.0, surface_roots: 0, ..params });
+        assert!(generator.generate_tree(&twig).vertex_count() * 2 < generator.generate_tree(&create_simple_node()).vertex_count());
+    }
+
+    #[test]
+    fn test_baked_vertex_colors() {
+        let trunk = create_simple_node();
+        let plain = MeshGenerator::new(MeshParams::default()).generate_tree(&trunk);
+        assert!(plain.vertices.iter().all(|v| v.color == Vec3::ZERO));
+
+        // Warm at the trunk, cooling towards later generations
+        let generator = MeshGenerator::new(MeshParams { vertex_colors: true, ..MeshParams::default() });
+        let warm = generator.generate_tree(&trunk);
+        assert!(warm.vertices.iter().all(|v| v.color.x > v.color.y && v.color.x <= 1.0));
+        let cool = generator.generate_tree(&BranchNode { generation: 5, ..create_simple_node() });
+        assert!(cool.vertices.iter().all(|v| v.color.y > v.color.x));
+        assert_eq!(warm.vertex_data()[VERTEX_FLOATS - 3], warm.vertices[0].color.x);
+
+        // A blue theme's stops bake the trunk blue
+        let themed = MeshParams { vertex_colors: true, hue_stops: [0.62, 0.78], ..MeshParams::default() };
+        let blue = MeshGenerator::new(themed).generate_tree(&trunk);
+        assert!(blue.vertices.iter().all(|v| v.color.z > v.color.x));
+    }
+
+    #[test]
+    fn test_instanced_twigs() {
+        let mut parent = create_simple_node();
+        parent.children.push(BranchNode {
+            person_id: "twig".to_string(),
+            generation: 1,
+            start: parent.end,
+            end: parent.end + Vec3::new(0.5, 0.5, 0.0),
+            start_radius: 0.03,
+            end_radius: 0.02,
+            ..create_simple_node()
+        });
+        let params = MeshParams { blossom_size: 0.0, ..MeshParams::default() };
+        let meshed = MeshGenerator::new(params);
+        let instanced = MeshGenerator::new(MeshParams { instanced_twigs: true, ..params });
+
+        assert!(meshed.twig_instances(&parent).is_empty());
+        let twigs = instanced.twig_instances(&parent);
+        assert_eq!(twigs.len(), 1);
+        assert_eq!(twigs[0].base, parent.children[0].start);
+        assert_eq!(twigs[0].axis, parent.children[0].end - parent.children[0].start);
+        assert!(twigs[0].side.dot(&twigs[0].axis).abs() < 1e-5);
+        assert!(twigs[0].v_start > 1.9);
+
+        // The twig's tube and tip leave the mesh; the parent's stays, and
+        // gets a tip of its own where the twig no longer closes it
+        let with_twig = meshed.generate_tree(&parent).vertex_count();
+        let without = instanced.generate_tree(&parent).vertex_count();
+        assert!(without < with_twig);
+        assert!(without > instanced.generate_tree(&create_simple_node()).vertex_count() / 2);
+    }
+
+    #[test]
+    fn test_weathering_knots_and_moss() {
+        let young = create_simple_node();
+        let old = BranchNode { visual: VisualParams { age: Some(92.0), ..VisualParams::default() }, ..create_simple_node() };
+        let generator = MeshGenerator::new(MeshParams { weathering_age: Some(80.0), ..MeshParams::default() });
+        let fresh = generator.generate_tree(&young);
+        let weathered = generator.generate_tree(&old);
+
+        // Same vertices, some raised into knots and moss with their own look
+        assert_eq!(weathered.vertex_count(), fresh.vertex_count());
+        let moved: Vec<usize> = (0..fresh.vertex_count())
+            .filter(|&i| weathered.vertices[i].position.distance(&fresh.vertices[i].position) > 1e-3)
+            .collect();
+        assert!(!moved.is_empty() && moved.len() < fresh.vertex_count() / 2);
+        assert!(moved.iter().any(|&i| weathered.vertices[i].luminance < fresh.vertices[i].luminance));
+        assert!(moved.iter().any(|&i| weathered.vertices[i].hue > fresh.vertices[i].hue));
+        // Clear of the base, so forks still line up
+        assert!(moved.iter().all(|&i| fresh.vertices[i].position.y > 0.2));
+
+        // By generation too, and not at all when switched off
+        let by_generation = MeshGenerator::new(MeshParams { weathering_generations: 1, ..MeshParams::default() });
+        assert_eq!(by_generation.generate_tree(&young).vertices, weathered.vertices);
+        let off = MeshGenerator::new(MeshParams::default());
+        assert_eq!(off.generate_tree(&old).vertices, fresh.vertices);
+    }
+
+    #[test]
+    fn test_no_knots_at_child_junctions() {
+        let generator = MeshGenerator::new(MeshParams { weathering_age: Some(80.0), ..MeshParams::default() });
+        let visual = VisualParams { age: Some(92.0), ..VisualParams::default() };
+        let bare = BranchNode { visual, ..create_simple_node() };
+        // Children sprouting all along the branch, as a phyllotaxis spiral does
+        let children = (3..=17)
+            .map(|i| {
+                let start = bare.point_along(i as f32 * 0.05);
+                BranchNode::straight(&format!("c{}", i), start, start + Vec3::new(1.0, 0.5, 0.0))
+            })
+            .collect();
+        let forked = BranchNode { children, ..bare.clone() };
+
+        // Knots push the bark out far further than moss does
+        let largest_bulge = |node: &BranchNode| {
+            let young = BranchNode { visual: VisualParams::default(), ..node.clone() };
+            let fresh = generator.generate_tree(&young);
+            let weathered = generator.generate_tree(node);
+            fresh
+                .vertices
+                .iter()
+                .zip(&weathered.vertices)
+                .map(|(a, b)| a.position.distance(&b.position))
+                .fold(0.0, f32::max)
+        };
+        let knot_depth = bare.end_radius * 0.35 * 0.5;
+        assert!(largest_bulge(&bare) > knot_depth);
+        assert!(largest_bulge(&forked) < knot_depth);
+    }
+
+    #[test]
+    fn test_memorial_rings() {
+        let generator = MeshGenerator::new(MeshParams::default());
+        assert_eq!(generator.generate_memorials(&create_simple_node()).vertex_count(), 0);
+
+        // Halfway up for someone who died at fifty, ringing the branch
+        let visual = VisualParams { deceased: true, age: Some(50.0), ..VisualParams::default() };
+        let node = BranchNode { visual, ..create_simple_node() };
+        let mesh = generator.generate_memorials(&node);
+        assert!(mesh.triangle_count() > 0);
+        let center = node.point_along(0.5);
+        for v in &mesh.vertices {
+            assert!((v.position.y - center.y).abs() < 0.05);
+            let distance = v.position.distance(&center);
+            assert!(distance > node.start_radius && distance < node.start_radius * 3.0);
+        }
+        // Not part of the branch mesh
+        assert_eq!(generator.generate_tree(&node).vertices, generator.generate_tree(&create_simple_node()).vertices);
+
+        let off = MeshGenerator::new(MeshParams { memorial_rings: false, ..MeshParams::default() });
+        assert_eq!(off.generate_memorials(&node).vertex_count(), 0);
+    }
+
+    #[test]
+    fn test_bud_adds_geometry() {
+        let plain = create_simple_node();
+        let bud = BranchNode { hidden_descendants: 5, ..create_simple_node() };
+        let generator = MeshGenerator::new(MeshParams::default());
+
+        assert!(generator.generate_tree(&bud).vertex_count() > generator.generate_tree(&plain).vertex_count());
+    }
+
+    #[test]
+    fn test_waypoints_bend_branch() {
+        let mut node = create_simple_node();
+        node.waypoints = vec![node.start.lerp(&node.end, 0.5) + Vec3::new(0.5, 0.0, 0.0)];
+        let mesh = MeshGenerator::new(MeshParams::default()).generate_tree(&node);
+
+        let max_x = mesh.vertices.iter().map(|v| v.position.x).fold(f32::MIN, f32::max);
+        assert!(max_x > 0.5);
+    }
+
+    #[test]
+    fn test_partner_strand_is_tracked() {
+        let partner = BranchNode { person_id: "partner".to_string(), ..create_simple_node() };
+        let node = BranchNode { partner: Some(Box::new(partner)), ..create_simple_node() };
+        let (mesh, infos) = TrackedMeshGenerator::new(MeshParams::default()).generate_tree_tracked(&node);
+
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[1].person_id, "partner");
+        assert!(infos[1].vertex_count > 0);
+        assert!(mesh.vertex_count() > MeshGenerator::new(MeshParams::default()).generate_tree(&create_simple_node()).vertex_count());
+    }
+
+    #[test]
+    fn test_trunk_base_flares_into_roots() {
+        let bare = MeshParams { root_flare: 1.0, surface_roots: 0, ..Default::default() };
+        let trunk = create_simple_node();
+        let plain = MeshGenerator::new(bare).generate_tree(&trunk);
+        let rooted = MeshGenerator::new(MeshParams::default()).generate_tree(&trunk);
+
+        assert!(rooted.vertex_count() > plain.vertex_count());
+        // Roots sink below the trunk base and spread well past its radius
+        let lowest = rooted.vertices.iter().map(|v| v.position.y).fold(f32::MAX, f32::min);
+        let widest = rooted.vertices.iter().map(|v| v.position.x.hypot(v.position.z)).fold(0.0, f32::max);
+        assert!(lowest < trunk.start.y);
+        assert!(widest > trunk.start_radius * 4.0);
+
+        // A leaning trunk flares along its lean, not straight up
+        let lean = Vec3::new(0.5f32.sin(), 0.5f32.cos(), 0.0);
+        let leaning = BranchNode { start_direction: lean, end: lean.scale(2.0), ..create_simple_node() };
+        let sum_x = |params| MeshGenerator::new(params).generate_tree(&leaning).vertices.iter().map(|v| v.position.x).sum::<f32>();
+        assert!(sum_x(MeshParams { surface_roots: 0, ..Default::default() }) - sum_x(bare) > 1.0);
+
+        // Only trunks get a base
+        let branch = BranchNode { generation: 1, ..create_simple_node() };
+        assert_eq!(
+            MeshGenerator::new(bare).generate_tree(&branch).vertex_count(),
+            MeshGenerator::new(MeshParams::default()).generate_tree(&branch).vertex_count()
+        );
+    }
+
+    #[test]
+    fn test_blossoms_at_births_and_partnerships() {
+        let child = BranchNode { person_id: "child".to_string(), generation: 1, start: Vec3::new(0.0, 2.0, 0.0), end: Vec3::new(0.0, 3.0, 0.0), ..create_simple_node() };
+        let partner = BranchNode { person_id: "partner".to_string(), ..create_simple_node() };
+        let node = BranchNode { children: vec![child], partner: Some(Box::new(partner)), ..create_simple_node() };
+
+        let bare = MeshParams { blossom_size: 0.0, ..Default::default() };
+        let (plain, _) = TrackedMeshGenerator::new(bare).generate_tree_tracked(&node);
+        let (mesh, infos) = TrackedMeshGenerator::new(MeshParams::default()).generate_tree_tracked(&node);
+
+        // One blossom for the child's birth, one where the partner joins
+        let blossom = BLOSSOM_PETALS * 4;
+        assert!(mesh.vertex_count() >= plain.vertex_count() + 2 * blossom);
+        // Each petal tip carries the blossom's own glow
+        let tips = mesh.vertices.iter().filter(|v| v.glow == MeshParams::default().blossom_glow).count();
+        assert_eq!(tips, 2 * BLOSSOM_PETALS);
+        assert_eq!(infos.len(), 3);
+    }
+
 }
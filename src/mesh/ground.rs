@@ -0,0 +1,156 @@
+use std::f32::consts::TAU;
+use crate::growth::{GrowthRng, SplitMix};
+use crate::math::Vec3;
+use super::branch::{Mesh, Vertex};
+
+/// Parameters for the ground disc around the tree
+#[derive(Debug, Clone, Copy)]
+pub struct GroundParams {
+    /// Rings from the centre out to the rim
+    pub rings: usize,
+    /// Vertices around each ring
+    pub sectors: usize,
+    /// Height of the swells (world units)
+    pub undulation: f32,
+    /// Distance between swells (world units)
+    pub wavelength: f32,
+    /// Share of vertices lit as glowing grass speckles
+    pub speckle_density: f32,
+    /// Glow of a speckle
+    pub speckle_glow: f32,
+    /// Hue of the grass (degrees)
+    pub hue: f32,
+    /// Coverage of the ground (below 1.0 lets roots show through)
+    pub opacity: f32,
+    /// Seed for the swells and speckles
+    pub seed: u32,
+}
+
+impl Default for GroundParams {
+    fn default() -> Self {
+        Self {
+            rings: 24,
+            sectors: 48,
+            undulation: 0.15,
+            wavelength: 3.0,
+            speckle_density: 0.12,
+            speckle_glow: 0.9,
+            hue: 140.0,
+            opacity: 1.0,
+            seed: 42,
+        }
+    }
+}
+
+/// Fraction of the radius kept level around the trunk, so it stands flat
+const LEVEL_CENTER: f32 = 0.12;
+/// Fraction of the radius over which the rim fades out
+const RIM_FADE: f32 = 0.2;
+
+/// Radius of ground around `trunk` to put under a tree with the given bounds
+pub fn ground_radius(trunk: Vec3, (min, max): (Vec3, Vec3)) -> f32 {
+    let reach = [min.x - trunk.x, max.x - trunk.x, min.z - trunk.z, max.z - trunk.z]
+        .iter()
+        .fold(0.0f32, |reach, d| reach.max(d.abs()));
+    reach * 1.6 + 2.0
+}
+
+/// Generate a gently undulating disc of ground centred under `center`,
+/// level with it, with glowing speckles of grass
+pub fn generate_ground(center: Vec3, radius: f32, params: &GroundParams) -> Mesh {
+    let mut mesh = Mesh::new();
+    let rng = SplitMix::new(params.seed);
+    let rings = params.rings.max(1);
+    let sectors = params.sectors.max(3);
+
+    // Swells from a few crossing waves, phased by the seed
+    let phases: Vec<f32> = (0..3).map(|i| rng.unit(&format!("phase{}", i)) * TAU).collect();
+    let swell = |x: f32, z: f32| {
+        let k = TAU / params.wavelength.max(0.1);
+        let waves = (x * k + phases[0]).sin()
+            + (z * k * 0.8 + phases[1]).sin()
+            + ((x + z) * k * 0.6 + phases[2]).sin();
+        waves / 3.0 * params.undulation
+    };
+
+    let vertex = |x: f32, z: f32, key: String| {
+        let s = (x * x + z * z).sqrt() / radius.max(1e-3);
+        let level = smooth_step((s / LEVEL_CENTER - 1.0).clamp(0.0, 1.0));
+        let fade = 1.0 - smooth_step(((s - (1.0 - RIM_FADE)) / RIM_FADE).clamp(0.0, 1.0));
+        let speckle = rng.unit(&key) < params.speckle_density;
+        Vertex::new(center + Vec3::new(x, swell(x, z) * level, z), Vec3::UP)
+            .with_uv(x, z)
+            .with_visual(
+                if speckle { params.speckle_glow } else { 0.05 },
+                0.15,
+                params.hue + (rng.unit(&format!("{}h", key)) - 0.5) * 20.0,
+            )
+            .with_opacity(params.opacity * fade)
+    };
+
+    let center_idx = mesh.add_vertices(std::iter::once(vertex(0.0, 0.0, "c".to_string())));
+    let mut ring_starts = Vec::with_capacity(rings);
+    for r in 1..=rings {
+        let distance = radius * r as f32 / rings as f32;
+        let ring = (0..sectors).map(|i| {
+            let angle = i as f32 / sectors as f32 * TAU;
+            vertex(distance * angle.cos(), distance * angle.sin(), format!("{}/{}", r, i))
+        });
+        ring_starts.push(mesh.add_vertices(ring.collect::<Vec<_>>()));
+    }
+
+    // Wound so the faces look up
+    let first = ring_starts[0];
+    for i in 0..sectors {
+        let next = (i + 1) % sectors;
+        mesh.add_triangle(center_idx, first + next as u32, first + i as u32);
+    }
+    for pair in ring_starts.windows(2) {
+        for i in 0..sectors {
+            let next = (i + 1) % sectors;
+            let (a, b) = (pair[0] + i as u32, pair[0] + next as u32);
+            let (c, d) = (pair[1] + next as u32, pair[1] + i as u32);
+            mesh.add_quad(a, b, c, d);
+        }
+    }
+
+    mesh.recompute_normals();
+    mesh.calculate_bounds();
+    mesh
+}
+
+fn smooth_step(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ground_disc() {
+        let params = GroundParams::default();
+        let center = Vec3::new(1.0, 0.5, -2.0);
+        let mesh = generate_ground(center, 10.0, &params);
+
+        assert_eq!(mesh.vertex_count(), 1 + params.rings * params.sectors);
+        assert_eq!(mesh.triangle_count(), params.sectors * (2 * params.rings - 1));
+        for v in &mesh.vertices {
+            let offset = v.position - center;
+            assert!((offset.x * offset.x + offset.z * offset.z).sqrt() <= 10.0 + 1e-3);
+            assert!(offset.y.abs() <= params.undulation + 1e-4);
+            assert!(v.normal.y > 0.9);
+        }
+        // Level under the trunk, faded out at the rim, speckled in between
+        assert_eq!(mesh.vertices[0].position, center);
+        assert!(mesh.vertices[mesh.vertex_count() - 1].opacity < 1e-4);
+        let speckles = mesh.vertices.iter().filter(|v| v.glow == params.speckle_glow).count();
+        assert!(speckles > 0 && speckles < mesh.vertex_count() / 4);
+    }
+
+    #[test]
+    fn test_ground_radius_covers_tree() {
+        let bounds = (Vec3::new(-3.0, 0.0, -1.0), Vec3::new(5.0, 8.0, 1.0));
+        assert!(ground_radius(Vec3::ZERO, bounds) > 5.0 * 1.5);
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use serde::Deserialize;
+use crate::growth::BranchNode;
+use crate::math::Vec3;
+use super::branch::{Mesh, Vertex};
+
+/// Parameters for the name labels at branch tips
+#[derive(Debug, Clone, Copy)]
+pub struct LabelParams {
+    /// Height of one line of text (world units)
+    pub height: f32,
+    /// Gap between a branch tip and its label's baseline (world units)
+    pub lift: f32,
+    /// Glow of the lettering
+    pub glow: f32,
+}
+
+impl Default for LabelParams {
+    fn default() -> Self {
+        Self {
+            height: 0.22,
+            lift: 0.12,
+            glow: 0.6,
+        }
+    }
+}
+
+/// Where a glyph sits in the atlas and on the line, in atlas pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glyph {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// From the pen position to the glyph's left edge
+    pub x_offset: f32,
+    /// From the top of the line to the glyph's top edge
+    pub y_offset: f32,
+    /// How far the pen moves on past the glyph
+    pub advance: f32,
+}
+
+/// A signed-distance-field font: glyph metrics into an atlas texture the
+/// host uploads alongside them
+#[derive(Debug, Clone)]
+pub struct SdfFont {
+    pub line_height: f32,
+    /// From the top of the line down to the baseline
+    pub base: f32,
+    pub atlas_width: f32,
+    pub atlas_height: f32,
+    pub glyphs: HashMap<char, Glyph>,
+}
+
+/// BMFont JSON, as written by msdf-bmfont and similar atlas tools
+#[derive(Deserialize)]
+struct BmFont {
+    common: BmCommon,
+    chars: Vec<BmChar>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BmCommon {
+    line_height: f32,
+    base: f32,
+    scale_w: f32,
+    scale_h: f32,
+}
+
+#[derive(Deserialize)]
+struct BmChar {
+    id: u32,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    xoffset: f32,
+    yoffset: f32,
+    xadvance: f32,
+}
+
+impl SdfFont {
+    /// Parse BMFont JSON metrics (`common` and `chars`; other sections are ignored)
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let font: BmFont = serde_json::from_str(json)
+            .map_err(|e| format!("Invalid font metrics: {}", e))?;
+        if font.common.line_height <= 0.0 || font.common.scale_w <= 0.0 || font.common.scale_h <= 0.0 {
+            return Err("Font metrics need a positive lineHeight, scaleW and scaleH".to_string());
+        }
+        let glyphs = font.chars.iter()
+            .filter_map(|c| {
+                let glyph = Glyph {
+                    x: c.x,
+                    y: c.y,
+                    width: c.width,
+                    height: c.height,
+                    x_offset: c.xoffset,
+                    y_offset: c.yoffset,
+                    advance: c.xadvance,
+                };
+                char::from_u32(c.id).map(|ch| (ch, glyph))
+            })
+            .collect();
+        Ok(Self {
+            line_height: font.common.line_height,
+            base: font.common.base,
+            atlas_width: font.common.scale_w,
+            atlas_height: font.common.scale_h,
+            glyphs,
+        })
+    }
+
+    /// The glyph drawn for `c`: its own, else '?', else nothing
+    pub fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs.get(&c).or_else(|| self.glyphs.get(&'?'))
+    }
+
+    /// Width of `text` on one line (atlas pixels)
+    pub fn measure(&self, text: &str) -> f32 {
+        text.chars().filter_map(|c| self.glyph(c)).map(|g| g.advance).sum()
+    }
+}
+
+/// Builds a person's name into billboard quads at the tip of their branch
+///
+/// Each vertex's position is the label's anchor and its normal holds the
+/// corner's offset (x right, y up, world units) in the camera's plane; the
+/// label shader turns it to face the camera. UVs address the atlas, with V
+/// running down from its first row.
+pub fn generate_labels(
+    root: &BranchNode,
+    font: &SdfFont,
+    params: &LabelParams,
+    name: impl Fn(&str) -> Option<String>,
+) -> Mesh {
+    let mut mesh = Mesh::new();
+    let scale = params.height / font.line_height;
+    for node in root.iter_preorder() {
+        let Some(text) = name(&node.person_id) else {
+            continue;
+        };
+        // A partner's strand ends at the same tip; their name goes underneath
+        let line = if node.partner.is_some() { 1.0 } else { 0.0 };
+        let baseline = params.lift + line * params.height;
+        let mut pen = -font.measure(&text) / 2.0;
+
+        for c in text.chars() {
+            let Some(glyph) = font.glyph(c) else {
+                continue;
+            };
+            if glyph.width > 0.0 && glyph.height > 0.0 {
+                let left = (pen + glyph.x_offset) * scale;
+                let right = left + glyph.width * scale;
+                let top = baseline + (font.base - glyph.y_offset) * scale;
+                let bottom = top - glyph.height * scale;
+                let (u0, v0) = (glyph.x / font.atlas_width, glyph.y / font.atlas_height);
+                let (u1, v1) = ((glyph.x + glyph.width) / font.atlas_width, (glyph.y + glyph.height) / font.atlas_height);
+
+                let corners = [(left, bottom, u0, v1), (right, bottom, u1, v1), (right, top, u1, v0), (left, top, u0, v0)];
+                let start = mesh.add_vertices(corners.iter().map(|&(x, y, u, v)| {
+                    Vertex::new(node.end, Vec3::new(x, y, 0.0))
+                        .with_uv(u, v)
+                        .with_visual(params.glow, node.visual.luminance, node.visual.hue_shift)
+                        .with_opacity(node.visual.opacity)
+                }));
+                mesh.add_quad(start, start + 1, start + 2, start + 3);
+            }
+            pen += glyph.advance;
+        }
+    }
+    mesh.calculate_bounds();
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METRICS: &str = r#"{
+        "info": {"face": "Test", "size": 32},
+        "common": {"lineHeight": 40, "base": 30, "scaleW": 256, "scaleH": 128},
+        "chars": [
+            {"id": 65, "x": 0, "y": 0, "width": 20, "height": 24, "xoffset": 1, "yoffset": 6, "xadvance": 22},
+            {"id": 32, "x": 0, "y": 0, "width": 0, "height": 0, "xoffset": 0, "yoffset": 0, "xadvance": 10},
+            {"id": 63, "x": 20, "y": 0, "width": 16, "height": 24, "xoffset": 2, "yoffset": 6, "xadvance": 18}
+        ]
+    }"#;
+
+    #[test]
+    fn test_font_from_json() {
+        let font = SdfFont::from_json(METRICS).unwrap();
+        assert_eq!(font.glyphs.len(), 3);
+        assert_eq!(font.glyph('A').unwrap().advance, 22.0);
+        // Missing glyphs fall back to '?'
+        assert_eq!(font.glyph('Z').unwrap().x, 20.0);
+        assert_eq!(font.measure("A A"), 54.0);
+
+        assert!(SdfFont::from_json("{}").is_err());
+        assert!(SdfFont::from_json(&METRICS.replace("\"scaleW\": 256", "\"scaleW\": 0")).is_err());
+    }
+
+    #[test]
+    fn test_labels_at_branch_tips() {
+        let font = SdfFont::from_json(METRICS).unwrap();
+        let params = LabelParams::default();
+        let mut root = BranchNode::straight("a", Vec3::ZERO, Vec3::new(0.0, 2.0, 0.0));
+        root.children.push(BranchNode::straight("b", Vec3::ZERO, Vec3::new(1.0, 3.0, 0.0)));
+        root.children.push(BranchNode::straight("hidden", Vec3::ZERO, Vec3::new(-1.0, 3.0, 0.0)));
+        let name = |id: &str| (id != "hidden").then(|| "A A".to_string());
+        let mesh = generate_labels(&root, &font, &params, name);
+
+        // Two visible glyphs per label (the space has no quad)
+        assert_eq!(mesh.vertex_count(), 2 * 2 * 4);
+        assert_eq!(mesh.triangle_count(), 2 * 2 * 2);
+        let (first, second) = mesh.vertices.split_at(8);
+        assert!(first.iter().all(|v| v.position == root.end));
+        assert!(second.iter().all(|v| v.position == root.children[0].end));
+
+        // Centred on the anchor, sitting above it, sampling inside the atlas
+        let (min_x, max_x) = first.iter().fold((f32::MAX, f32::MIN), |(lo, hi), v| (lo.min(v.normal.x), hi.max(v.normal.x)));
+        assert!((min_x + max_x).abs() < 0.05);
+        for v in first {
+            assert!(v.normal.y >= params.lift - 1e-5);
+            assert!(v.normal.y <= params.lift + params.height);
+            assert!(v.uv.iter().all(|&t| (0.0..=1.0).contains(&t)));
+        }
+    }
+
+    #[test]
+    fn test_partner_label_below() {
+        let font = SdfFont::from_json(METRICS).unwrap();
+        let params = LabelParams::default();
+        let mut root = BranchNode::straight("a", Vec3::ZERO, Vec3::new(0.0, 2.0, 0.0));
+        root.partner = Some(Box::new(BranchNode::straight("p", Vec3::ZERO, root.end)));
+        let mesh = generate_labels(&root, &font, &params, |_| Some("A".to_string()));
+
+        let lowest = |vertices: &[Vertex]| vertices.iter().map(|v| v.normal.y).fold(f32::MAX, f32::min);
+        let (own, partner) = mesh.vertices.split_at(4);
+        assert!(lowest(own) > lowest(partner) + params.height * 0.9);
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use crate::growth::{FoliageCluster, GrowthRng, SplitMix};
+use crate::math::Vec3;
+use super::branch::{Mesh, Vertex};
+
+/// Floats per leaf instance: offset(3) + side(3) + up(3) + hue(1) + glow(1)
+pub const LEAF_INSTANCE_FLOATS: usize = 11;
+
+/// Parameters for instanced leaves
+#[derive(Debug, Clone, Copy)]
+pub struct LeafParams {
+    /// Leaves scattered through each foliage cluster
+    pub leaves_per_cluster: usize,
+    /// Blade length (world units)
+    pub length: f32,
+    /// Blade width relative to its length
+    pub width: f32,
+    /// Seed for the scatter, so canopies don't shimmer between rebuilds
+    pub seed: u32,
+}
+
+impl Default for LeafParams {
+    fn default() -> Self {
+        Self {
+            leaves_per_cluster: 24,
+            length: 0.22,
+            width: 0.45,
+            seed: 42,
+        }
+    }
+}
+
+/// One placed leaf: where it sits and how its blade is oriented and sized
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LeafInstance {
+    pub offset: Vec3,
+    /// Across the blade, scaled to its width
+    pub side: Vec3,
+    /// Along the blade from stem to tip, scaled to its length
+    pub up: Vec3,
+    /// Hue of the cluster's branch (degrees)
+    pub hue: f32,
+    pub glow: f32,
+}
+
+impl LeafInstance {
+    pub fn to_array(&self) -> [f32; LEAF_INSTANCE_FLOATS] {
+        [
+            self.offset.x, self.offset.y, self.offset.z,
+            self.side.x, self.side.y, self.side.z,
+            self.up.x, self.up.y, self.up.z,
+            self.hue, self.glow,
+        ]
+    }
+}
+
+/// Scatters leaf blades through foliage clusters for instanced drawing
+pub struct LeafGenerator {
+    params: LeafParams,
+    rng: SplitMix,
+}
+
+impl LeafGenerator {
+    pub fn new(params: LeafParams) -> Self {
+        Self { params, rng: SplitMix::new(params.seed) }
+    }
+
+    /// A single unit blade in leaf space (x across, y from stem to tip),
+    /// shared by every instance
+    pub fn leaf_mesh(&self) -> Mesh {
+        let mut mesh = Mesh::new();
+        let normal = Vec3::new(0.0, 0.0, 1.0);
+        // Diamond blade, widest a third of the way up
+        let outline = [(0.0, 0.0), (-0.5, 0.35), (0.0, 1.0), (0.5, 0.35)];
+        let start = mesh.add_vertices(outline.iter().map(|&(x, y)| {
+            Vertex::new(Vec3::new(x, y, 0.0), normal).with_uv(x + 0.5, y).with_tangent(Vec3::RIGHT)
+        }));
+        mesh.add_quad(start, start + 1, start + 2, start + 3);
+        mesh.bounds_center = Vec3::new(0.0, 0.5, 0.0);
+        mesh.bounds_radius = 0.5;
+        mesh
+    }
+
+    /// Leaves for every cluster, scattered inside its sphere
+    pub fn instances(&self, clusters: &[FoliageCluster]) -> Vec<LeafInstance> {
+        let mut ordinals: HashMap<&str, usize> = HashMap::new();
+        let mut leaves = Vec::with_capacity(clusters.len() * self.params.leaves_per_cluster);
+
+        for cluster in clusters {
+            // Key by the person's own cluster count so edits elsewhere don't reshuffle
+            let ordinal = ordinals.entry(cluster.person_id.as_str()).or_insert(0);
+            let key = format!("{}#{}", cluster.person_id, ordinal);
+            *ordinal += 1;
+
+            for i in 0..self.params.leaves_per_cluster {
+                let unit = |channel: &str| self.rng.unit(&format!("{}/{}{}", key, channel, i));
+
+                // Uniform point in the sphere, biased a little outward so the
+                // canopy reads as a shell rather than a solid ball
+                let azimuth = unit("a") * std::f32::consts::TAU;
+                let height = unit("h") * 2.0 - 1.0;
+                let ring = (1.0 - height * height).sqrt();
+                let outward = Vec3::new(ring * azimuth.cos(), height, ring * azimuth.sin());
+                let distance = cluster.radius * unit("r").cbrt().max(0.35);
+                let offset = cluster.center + outward.scale(distance);
+
+                // Blades point away from the cluster, tipped by a random roll
+                let up = (outward + Vec3::new(0.0, 0.6, 0.0)).normalize();
+                let across = up.perpendicular();
+                let roll = unit("t") * std::f32::consts::TAU;
+                let side = (across.scale(roll.cos()) + up.cross(&across).scale(roll.sin())).normalize();
+
+                let length = self.params.length * (0.75 + 0.5 * unit("s"));
+                leaves.push(LeafInstance {
+                    offset,
+                    side: side.scale(length * self.params.width),
+                    up: up.scale(length),
+                    hue: cluster.hue,
+                    glow: cluster.glow,
+                });
+            }
+        }
+        leaves
+    }
+
+    /// Flat per-instance buffer, `LEAF_INSTANCE_FLOATS` per leaf
+    pub fn instance_data(&self, clusters: &[FoliageCluster]) -> Vec<f32> {
+        self.instances(clusters).iter().flat_map(|leaf| leaf.to_array()).collect()
+    }
+}
+
+impl Default for LeafGenerator {
+    fn default() -> Self {
+        Self::new(LeafParams::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cluster(id: &str, center: Vec3) -> FoliageCluster {
+        FoliageCluster { person_id: id.to_string(), center, radius: 0.5, hue: 120.0, glow: 0.6 }
+    }
+
+    #[test]
+    fn test_leaves_fill_clusters() {
+        let generator = LeafGenerator::default();
+        let clusters = vec![cluster("a", Vec3::new(0.0, 5.0, 0.0)), cluster("b", Vec3::new(3.0, 4.0, 0.0))];
+        let leaves = generator.instances(&clusters);
+
+        assert_eq!(leaves.len(), 2 * LeafParams::default().leaves_per_cluster);
+        for (chunk, owner) in leaves.chunks(LeafParams::default().leaves_per_cluster).zip(&clusters) {
+            for leaf in chunk {
+                assert!(leaf.offset.distance(&owner.center) <= owner.radius + 1e-4);
+                assert!(leaf.side.dot(&leaf.up).abs() < 1e-4);
+                assert_eq!(leaf.hue, owner.hue);
+            }
+        }
+        assert_eq!(generator.instance_data(&clusters).len(), leaves.len() * LEAF_INSTANCE_FLOATS);
+        assert_eq!(generator.leaf_mesh().indices.len(), 6);
+    }
+
+    #[test]
+    fn test_leaves_deterministic() {
+        let generator = LeafGenerator::default();
+        let a = cluster("a", Vec3::new(0.0, 5.0, 0.0));
+        let alone = generator.instances(std::slice::from_ref(&a));
+        // Another person's cluster ahead of it doesn't move its leaves
+        let shared = generator.instances(&[cluster("b", Vec3::ZERO), a]);
+        assert_eq!(alone[..], shared[24..]);
+    }
+}
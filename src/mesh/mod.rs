@@ -1,5 +1,20 @@
 pub mod branch;
+pub mod fruit;
 pub mod generator;
+pub mod ground;
+pub mod labels;
+pub mod leaves;
+pub mod simplify;
+pub mod stats;
+pub mod threads;
+pub mod twigs;
 
-pub use branch::{Mesh, Vertex};
+pub use branch::{IndexData, Mesh, Vertex, VERTEX_FLOATS};
+pub use fruit::{FruitGenerator, FruitInstance, FruitParams};
 pub use generator::MeshGenerator;
+pub use ground::{GroundParams, generate_ground, ground_radius};
+pub use labels::{LabelParams, SdfFont, generate_labels};
+pub use leaves::{LeafGenerator, LeafInstance, LeafParams};
+pub use simplify::simplify;
+pub use stats::MeshStats;
+pub use twigs::{TwigInstance, twig_instance_data};
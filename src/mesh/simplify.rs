@@ -0,0 +1,331 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use crate::math::Vec3;
+use super::branch::{Mesh, Vertex};
+
+/// Weight of the planes pinning open edges (branch ends, petals) in place,
+/// relative to the surface's own planes
+const BOUNDARY_WEIGHT: f64 = 100.0;
+/// Cost of collapsing across a change in glow, luminance, hue or opacity,
+/// in squared bounding radii per unit of difference
+const ATTRIBUTE_WEIGHT: f64 = 0.05;
+/// Collapses that turn a triangle further than this (cosine) are rejected
+const MIN_FLIP_COSINE: f32 = 0.2;
+
+/// Error quadric of a vertex: the symmetric 4x4 matrix, upper triangle
+#[derive(Debug, Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    /// Squared distance to the plane through `point` with unit `normal`
+    fn plane(normal: Vec3, point: Vec3, weight: f64) -> Self {
+        let (a, b, c) = (normal.x as f64, normal.y as f64, normal.z as f64);
+        let d = -(a * point.x as f64 + b * point.y as f64 + c * point.z as f64);
+        Self([a * a, a * b, a * c, a * d, b * b, b * c, b * d, c * c, c * d, d * d].map(|q| q * weight))
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for (q, o) in self.0.iter_mut().zip(other.0) {
+            *q += o;
+        }
+    }
+
+    fn error(&self, p: Vec3) -> f64 {
+        let (x, y, z) = (p.x as f64, p.y as f64, p.z as f64);
+        let q = &self.0;
+        q[0] * x * x + 2.0 * q[1] * x * y + 2.0 * q[2] * x * z + 2.0 * q[3] * x
+            + q[4] * y * y + 2.0 * q[5] * y * z + 2.0 * q[6] * y
+            + q[7] * z * z + 2.0 * q[8] * z
+            + q[9]
+    }
+}
+
+/// Candidate collapse of `from` onto `to`, valid while both are unchanged
+struct Collapse {
+    cost: f64,
+    from: u32,
+    to: u32,
+    stamps: (u32, u32),
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    // Cheapest first out of the max-heap
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+/// Reduce a mesh to about `target_ratio` of its triangles by edge collapse
+///
+/// Each collapse moves one vertex onto a neighbour (never inventing new
+/// vertices), so glow, luminance, hue and opacity are kept exactly; quadric
+/// error keeps the silhouette, and open edges and attribute changes are
+/// costly to collapse across.
+pub fn simplify(mesh: &Mesh, target_ratio: f32) -> Mesh {
+    let target = (mesh.triangle_count() as f32 * target_ratio.clamp(0.0, 1.0)).ceil() as usize;
+    Simplifier::new(mesh).run(target)
+}
+
+struct Simplifier<'a> {
+    vertices: &'a [Vertex],
+    faces: Vec<[u32; 3]>,
+    face_alive: Vec<bool>,
+    vertex_faces: Vec<Vec<usize>>,
+    quadrics: Vec<Quadric>,
+    stamps: Vec<u32>,
+    vertex_alive: Vec<bool>,
+    attribute_scale: f64,
+}
+
+impl<'a> Simplifier<'a> {
+    fn new(mesh: &'a Mesh) -> Self {
+        let vertices = &mesh.vertices[..];
+        let faces: Vec<[u32; 3]> = mesh.indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect();
+        let mut vertex_faces = vec![Vec::new(); vertices.len()];
+        let mut quadrics = vec![Quadric::default(); vertices.len()];
+        let mut edge_uses: HashMap<(u32, u32), (usize, u32)> = HashMap::new();
+
+        for (f, face) in faces.iter().enumerate() {
+            let [a, b, c] = face.map(|i| vertices[i as usize].position);
+            let cross = (b - a).cross(&(c - a));
+            let area = cross.length() as f64 * 0.5;
+            let plane = Quadric::plane(cross.normalize(), a, area);
+            for (k, &v) in face.iter().enumerate() {
+                vertex_faces[v as usize].push(f);
+                quadrics[v as usize].add(&plane);
+                let w = face[(k + 1) % 3];
+                edge_uses.entry((v.min(w), v.max(w))).or_insert((f, 0)).1 += 1;
+            }
+        }
+
+        // Open edges get a plane through them, perpendicular to their face
+        for (&(v, w), &(f, uses)) in &edge_uses {
+            if uses != 1 {
+                continue;
+            }
+            let [a, b, c] = faces[f].map(|i| vertices[i as usize].position);
+            let (p, q) = (vertices[v as usize].position, vertices[w as usize].position);
+            let edge = q - p;
+            let normal = edge.cross(&(b - a).cross(&(c - a))).normalize();
+            let plane = Quadric::plane(normal, p, BOUNDARY_WEIGHT * edge.length_squared() as f64);
+            quadrics[v as usize].add(&plane);
+            quadrics[w as usize].add(&plane);
+        }
+
+        let mut bounded = mesh.clone();
+        bounded.calculate_bounds();
+        let radius = bounded.bounds_radius.max(1e-3) as f64;
+
+        Self {
+            vertices,
+            face_alive: vec![true; faces.len()],
+            faces,
+            vertex_faces,
+            quadrics,
+            stamps: vec![0; vertices.len()],
+            vertex_alive: vec![true; vertices.len()],
+            attribute_scale: ATTRIBUTE_WEIGHT * radius * radius,
+        }
+    }
+
+    fn run(mut self, target: usize) -> Mesh {
+        let mut heap = BinaryHeap::new();
+        for face in &self.faces {
+            for k in 0..3 {
+                let (v, w) = (face[k], face[(k + 1) % 3]);
+                self.push_collapses(&mut heap, v, w);
+            }
+        }
+
+        let mut alive = self.faces.len();
+        while alive > target {
+            let Some(collapse) = heap.pop() else {
+                break;
+            };
+            let (from, to) = (collapse.from as usize, collapse.to as usize);
+            if !self.vertex_alive[from] || !self.vertex_alive[to] || collapse.stamps != (self.stamps[from], self.stamps[to]) {
+                continue;
+            }
+            if self.flips(collapse.from, collapse.to) {
+                continue;
+            }
+            alive -= self.collapse(collapse.from, collapse.to);
+
+            let neighbours: Vec<u32> = self.vertex_faces[to]
+                .iter()
+                .flat_map(|&f| self.faces[f])
+                .filter(|&n| n as usize != to)
+                .collect();
+            for n in neighbours {
+                self.push_collapses(&mut heap, collapse.to, n);
+            }
+        }
+
+        self.into_mesh()
+    }
+
+    fn push_collapses(&self, heap: &mut BinaryHeap<Collapse>, v: u32, w: u32) {
+        for (from, to) in [(v, w), (w, v)] {
+            let (f, t) = (from as usize, to as usize);
+            let mut quadric = self.quadrics[f];
+            quadric.add(&self.quadrics[t]);
+            let cost = quadric.error(self.vertices[t].position) + self.attribute_cost(f, t);
+            heap.push(Collapse { cost, from, to, stamps: (self.stamps[f], self.stamps[t]) });
+        }
+    }
+
+    fn attribute_cost(&self, a: usize, b: usize) -> f64 {
+        let (a, b) = (&self.vertices[a], &self.vertices[b]);
+        // The short way round the hue circle, so 350° and 10° are 20° apart
+        let d = (a.hue - b.hue).abs() % 360.0;
+        let hue = d.min(360.0 - d) / 360.0;
+        let difference = [a.glow - b.glow, a.luminance - b.luminance, hue, a.opacity - b.opacity];
+        difference.iter().map(|d| (d * d) as f64).sum::<f64>() * self.attribute_scale
+    }
+
+    /// Whether moving `from` onto `to` would turn any surviving face over
+    fn flips(&self, from: u32, to: u32) -> bool {
+        let target = self.vertices[to as usize].position;
+        self.vertex_faces[from as usize].iter().any(|&f| {
+            let face = self.faces[f];
+            if !self.face_alive[f] || face.contains(&to) {
+                return false;
+            }
+            let [a, b, c] = face.map(|i| self.vertices[i as usize].position);
+            let before = (b - a).cross(&(c - a));
+            let [a, b, c] = face.map(|i| if i == from { target } else { self.vertices[i as usize].position });
+            let after = (b - a).cross(&(c - a));
+            before.normalize().dot(&after.normalize()) < MIN_FLIP_COSINE
+        })
+    }
+
+    /// Move `from` onto `to`, returning how many faces disappeared
+    fn collapse(&mut self, from: u32, to: u32) -> usize {
+        let (f_idx, t_idx) = (from as usize, to as usize);
+        let mut removed = 0;
+        for f in std::mem::take(&mut self.vertex_faces[f_idx]) {
+            if !self.face_alive[f] {
+                continue;
+            }
+            let face = &mut self.faces[f];
+            if face.contains(&to) {
+                self.face_alive[f] = false;
+                removed += 1;
+                continue;
+            }
+            for i in face.iter_mut() {
+                if *i == from {
+                    *i = to;
+                }
+            }
+            self.vertex_faces[t_idx].push(f);
+        }
+        self.vertex_faces[t_idx].retain(|&f| self.face_alive[f]);
+
+        let quadric = self.quadrics[f_idx];
+        self.quadrics[t_idx].add(&quadric);
+        self.vertex_alive[f_idx] = false;
+        self.stamps[t_idx] += 1;
+        removed
+    }
+
+    /// Surviving faces over the vertices they still use
+    fn into_mesh(self) -> Mesh {
+        let mut mesh = Mesh::new();
+        let mut remap: HashMap<u32, u32> = HashMap::new();
+        for (face, _) in self.faces.iter().zip(&self.face_alive).filter(|(_, &alive)| alive) {
+            for &i in face {
+                let index = *remap
+                    .entry(i)
+                    .or_insert_with(|| mesh.add_vertices(std::iter::once(self.vertices[i as usize])));
+                mesh.indices.push(index);
+            }
+        }
+        mesh.calculate_bounds();
+        mesh
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::FamilyTree;
+    use crate::growth::{GrowthParams, TreeGrowth};
+    use crate::mesh::generator::{MeshGenerator, MeshParams};
+
+    fn tree_mesh() -> Mesh {
+        let yaml = r#"
+family:
+  name: "Simplify"
+  root: "root"
+people:
+  - id: "root"
+    name: "Root"
+    children: ["a", "b"]
+  - id: "a"
+    name: "A"
+  - id: "b"
+    name: "B"
+"#;
+        let tree = TreeGrowth::new(GrowthParams::default()).grow(&FamilyTree::from_yaml(yaml).unwrap()).unwrap();
+        MeshGenerator::new(MeshParams::default()).generate_tree(&tree)
+    }
+
+    #[test]
+    fn test_simplify_meets_budget() {
+        let mesh = tree_mesh();
+        let simplified = simplify(&mesh, 0.5);
+
+        assert!(simplified.triangle_count() <= mesh.triangle_count() / 2 + 1);
+        assert!(simplified.triangle_count() > 0);
+        assert!(simplified.indices.iter().all(|&i| (i as usize) < simplified.vertex_count()));
+        // The crown's silhouette keeps its extent in every direction (thin
+        // surface roots may be whittled away)
+        let extent = |m: &Mesh, axis: fn(&Vertex) -> f32| {
+            let values = m.vertices.iter().filter(|v| v.position.y > 0.5).map(axis);
+            values.clone().fold(f32::MIN, f32::max) - values.fold(f32::MAX, f32::min)
+        };
+        for axis in [|v: &Vertex| v.position.x, |v: &Vertex| v.position.y, |v: &Vertex| v.position.z] {
+            assert!(extent(&simplified, axis) > extent(&mesh, axis) * 0.9);
+        }
+        assert_eq!(simplify(&mesh, 1.0).triangle_count(), mesh.triangle_count());
+    }
+
+    #[test]
+    fn test_simplify_keeps_attributes() {
+        let mesh = tree_mesh();
+        let simplified = simplify(&mesh, 0.3);
+
+        // Every surviving vertex is an original one, glow and all
+        for v in &simplified.vertices {
+            assert!(mesh.vertices.iter().any(|o| o.position == v.position && o.glow == v.glow && o.hue == v.hue));
+        }
+    }
+
+    #[test]
+    fn test_hue_cost_wraps() {
+        let hued = |x: f32, hue: f32| Vertex::new(Vec3::new(x, 0.0, 0.0), Vec3::UP).with_visual(0.5, 0.5, hue);
+        let mut mesh = Mesh::new();
+        mesh.add_vertices([hued(0.0, 350.0), hued(1.0, 10.0), hued(2.0, 330.0), hued(3.0, 170.0)]);
+        let simplifier = Simplifier::new(&mesh);
+
+        // 350° to 10° costs the same as 350° to 330°, far less than the far side
+        let cost = |b| simplifier.attribute_cost(0, b);
+        assert!(cost(1) > 0.0 && (cost(1) - cost(2)).abs() < 1e-6);
+        assert!(cost(3) > cost(1) * 10.0);
+    }
+}
@@ -0,0 +1,138 @@
+use serde::Serialize;
+use super::generator::BranchMeshInfo;
+
+/// Geometry of one person's branch
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BranchStats {
+    #[serde(rename = "id")]
+    pub person_id: String,
+    pub generation: usize,
+    pub vertices: usize,
+    pub triangles: usize,
+}
+
+/// Geometry summed over the branches of one generation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GenerationMeshStats {
+    /// Depth from the trunk (0 = trunk)
+    pub generation: usize,
+    pub branches: usize,
+    pub vertices: usize,
+    pub triangles: usize,
+}
+
+/// Size of a mesh, in elements and in GPU memory, for debug overlays and
+/// budget checks
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MeshStats {
+    pub vertices: usize,
+    pub triangles: usize,
+    /// Bytes of the uploaded vertex buffer
+    pub vertex_bytes: usize,
+    /// Bytes of the uploaded index buffer, at the width it's packed to
+    pub index_bytes: usize,
+    /// Copies drawn of an instanced mesh (0 = not instanced)
+    pub instances: usize,
+    /// Bytes of the per-instance buffer
+    pub instance_bytes: usize,
+    /// Per-branch counts, when the mesh was generated with tracking
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub branches: Vec<BranchStats>,
+}
+
+impl MeshStats {
+    /// Break the counts down by the branches of a tracked mesh
+    pub fn with_branches(mut self, infos: &[BranchMeshInfo]) -> Self {
+        self.branches = infos
+            .iter()
+            .map(|info| BranchStats {
+                person_id: info.person_id.clone(),
+                generation: info.generation,
+                vertices: info.vertex_count as usize,
+                triangles: info.index_count as usize / 3,
+            })
+            .collect();
+        self
+    }
+
+    /// Count `instances` copies drawn of the mesh, each with
+    /// `instance_floats` floats of per-instance data
+    pub fn with_instances(mut self, instances: usize, instance_floats: usize) -> Self {
+        self.instances = instances;
+        self.instance_bytes = instances * instance_floats * 4;
+        self
+    }
+
+    pub fn gpu_bytes(&self) -> usize {
+        self.vertex_bytes + self.index_bytes + self.instance_bytes
+    }
+
+    /// Triangles drawn: the mesh's own, once per instance when instanced
+    pub fn drawn_triangles(&self) -> usize {
+        self.triangles * self.instances.max(1)
+    }
+
+    /// Branch counts summed per generation, shallowest first
+    pub fn generations(&self) -> Vec<GenerationMeshStats> {
+        let mut generations: Vec<GenerationMeshStats> = Vec::new();
+        for branch in &self.branches {
+            if generations.len() <= branch.generation {
+                generations.extend((generations.len()..=branch.generation).map(|generation| GenerationMeshStats {
+                    generation,
+                    branches: 0,
+                    vertices: 0,
+                    triangles: 0,
+                }));
+            }
+            let generation = &mut generations[branch.generation];
+            generation.branches += 1;
+            generation.vertices += branch.vertices;
+            generation.triangles += branch.triangles;
+        }
+        generations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::FamilyTree;
+    use crate::growth::{GrowthParams, TreeGrowth};
+    use crate::mesh::VERTEX_FLOATS;
+    use crate::mesh::generator::{MeshParams, TrackedMeshGenerator};
+
+    #[test]
+    fn test_mesh_stats() {
+        let yaml = r#"
+family:
+  name: "Stats"
+  root: "root"
+people:
+  - id: "root"
+    name: "Root"
+    children: ["a", "b"]
+  - id: "a"
+    name: "A"
+  - id: "b"
+    name: "B"
+"#;
+        let tree = TreeGrowth::new(GrowthParams::default()).grow(&FamilyTree::from_yaml(yaml).unwrap()).unwrap();
+        let (mesh, infos) = TrackedMeshGenerator::new(MeshParams::default()).generate_tree_tracked(&tree);
+        let stats = mesh.stats().with_branches(&infos);
+
+        assert_eq!(stats.vertices, mesh.vertex_count());
+        assert_eq!(stats.triangles, mesh.triangle_count());
+        assert_eq!(stats.vertex_bytes, mesh.vertex_count() * VERTEX_FLOATS * 4);
+        // Small enough for 16-bit indices
+        assert_eq!(stats.index_bytes, mesh.indices.len() * 2);
+
+        let generations = stats.generations();
+        assert_eq!(generations.len(), 2);
+        assert_eq!(generations[1].branches, 2);
+        let branch_vertices: usize = generations.iter().map(|g| g.vertices).sum();
+        assert!(branch_vertices <= stats.vertices);
+
+        let leaves = mesh.stats().with_instances(10, 11);
+        assert_eq!(leaves.drawn_triangles(), 10 * mesh.triangle_count());
+        assert_eq!(leaves.gpu_bytes(), stats.gpu_bytes() + 10 * 11 * 4);
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+use crate::data::PersonLink;
+use crate::growth::BranchNode;
+use crate::math::{Vec3, generate_branch_curve};
+use super::branch::{Mesh, create_ring, connect_rings};
+
+/// Parameters for memory thread geometry
+#[derive(Debug, Clone, Copy)]
+pub struct ThreadParams {
+    /// Tube radius of each thread
+    pub radius: f32,
+    /// Radial segments around the tube
+    pub radial_segments: usize,
+    /// Samples along the arc
+    pub length_segments: usize,
+    /// How high the arc bows above the straight line (fraction of distance)
+    pub arc_height: f32,
+    /// Glow of the thread (kept faint so branches stay dominant)
+    pub glow: f32,
+    /// Luminance of the thread
+    pub luminance: f32,
+    /// Theme base hues at the trunk and the tips, for the baked colors
+    pub hue_stops: [f32; 2],
+}
+
+impl Default for ThreadParams {
+    fn default() -> Self {
+        Self {
+            radius: 0.012,
+            radial_segments: 6,
+            length_segments: 16,
+            arc_height: 0.35,
+            glow: 0.25,
+            luminance: 0.5,
+            hue_stops: [0.02, 0.45],
+        }
+    }
+}
+
+/// Per-thread data for picking
+#[derive(Debug, Clone)]
+pub struct ThreadMeshInfo {
+    /// Index into the family's link list
+    pub link_index: usize,
+    pub from_id: String,
+    pub to_id: String,
+    pub label: String,
+    /// Points sampled along the arc
+    pub points: Vec<Vec3>,
+    pub radius: f32,
+}
+
+/// Generate luminous arcs between linked people's branches
+pub fn generate_threads(
+    root: &BranchNode,
+    links: &[PersonLink],
+    params: &ThreadParams,
+) -> (Mesh, Vec<ThreadMeshInfo>) {
+    let midpoints: HashMap<&str, Vec3> = root
+        .iter_preorder()
+        .map(|node| (node.person_id.as_str(), node.start.lerp(&node.end, 0.5)))
+        .collect();
+
+    let mut mesh = Mesh::new();
+    let mut infos = Vec::new();
+
+    for (link_index, link) in links.iter().enumerate() {
+        let (Some(&start), Some(&end)) = (
+            midpoints.get(link.from.as_str()),
+            midpoints.get(link.to.as_str()),
+        ) else {
+            continue;
+        };
+        if start.distance(&end) < 1e-4 {
+            continue;
+        }
+
+        let points = thread_arc(start, end, params);
+        add_tube(&mut mesh, &points, params, thread_hue(link));
+
+        infos.push(ThreadMeshInfo {
+            link_index,
+            from_id: link.from.clone(),
+            to_id: link.to.clone(),
+            label: link.label.clone(),
+            points,
+            radius: params.radius,
+        });
+    }
+
+    // Threads are mid-tree, so baked colors take the middle of the gradient
+    mesh.bake_colors_from(0, 0.5, 1.0, params.hue_stops);
+    mesh.calculate_bounds();
+    (mesh, infos)
+}
+
+/// Sample an upward-bowing arc between two points
+fn thread_arc(start: Vec3, end: Vec3, params: &ThreadParams) -> Vec<Vec3> {
+    let across = (end - start).normalize();
+    let lift = Vec3::UP.scale(params.arc_height * 2.0);
+    let start_dir = (across + lift).normalize();
+    let end_dir = (across - lift).normalize();
+
+    generate_branch_curve(start, end, start_dir, end_dir, 1.0, params.length_segments.max(2))
+}
+
+/// Build a thin tube along the given points
+fn add_tube(mesh: &mut Mesh, points: &[Vec3], params: &ThreadParams, hue: f32) {
+    let n = points.len();
+    let mut prev_ring = None;
+
+    for (i, &point) in points.iter().enumerate() {
+        let direction = (points[(i + 1).min(n - 1)] - points[i.saturating_sub(1)]).normalize();
+        let t = i as f32 / (n - 1) as f32;
+
+        // Fade the glow toward both ends so threads emerge softly from the bark
+        let fade = (t * (1.0 - t) * 4.0).sqrt();
+
+        let ring = create_ring(
+            point,
+            direction,
+            params.radius,
+            params.radial_segments,
+            t,
+            params.glow * fade,
+            params.luminance * fade,
+            hue,
+        );
+        let ring_start = mesh.add_vertices(ring);
+
+        if let Some(prev) = prev_ring {
+            connect_rings(mesh, prev, ring_start, params.radial_segments);
+        }
+        prev_ring = Some(ring_start);
+    }
+}
+
+/// Stable hue per link label so similar relationships share a color
+fn thread_hue(link: &PersonLink) -> f32 {
+    let hash = link.label.bytes().fold(17u32, |acc, b| acc.wrapping_mul(31).wrapping_add(b as u32));
+    (hash % 360) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::FamilyTree;
+    use crate::growth::{TreeGrowth, GrowthParams};
+
+    const YAML: &str = r#"
+family:
+  name: "Linked"
+  root: "root"
+people:
+  - id: "root"
+    name: "Root"
+    children: ["a", "b"]
+  - id: "a"
+    name: "A"
+  - id: "b"
+    name: "B"
+links:
+  - from: "a"
+    to: "b"
+    label: "business partners"
+"#;
+
+    #[test]
+    fn test_generate_thread() {
+        let family = FamilyTree::from_yaml(YAML).unwrap();
+        let tree = TreeGrowth::new(GrowthParams::default()).grow(&family).unwrap();
+
+        let (mesh, infos) = generate_threads(&tree, &family.links, &ThreadParams::default());
+
+        assert_eq!(infos.len(), 1);
+        assert_eq!(infos[0].label, "business partners");
+        assert!(mesh.triangle_count() > 0);
+    }
+
+    #[test]
+    fn test_thread_arcs_upward() {
+        let params = ThreadParams::default();
+        let start = Vec3::new(-1.0, 2.0, 0.0);
+        let end = Vec3::new(1.0, 2.0, 0.0);
+        let points = thread_arc(start, end, &params);
+
+        let mid = points[points.len() / 2];
+        assert!(mid.y > 2.0);
+        assert!(points[0].distance(&start) < 0.0001);
+        assert!(points[points.len() - 1].distance(&end) < 0.0001);
+    }
+
+    #[test]
+    fn test_missing_endpoint_skipped() {
+        let family = FamilyTree::from_yaml(YAML).unwrap();
+        let tree = TreeGrowth::new(GrowthParams::default()).grow(&family).unwrap();
+        let links = vec![PersonLink {
+            from: "a".to_string(),
+            to: "ghost".to_string(),
+            label: String::new(),
+        }];
+
+        let (mesh, infos) = generate_threads(&tree, &links, &ThreadParams::default());
+        assert!(infos.is_empty());
+        assert_eq!(mesh.vertex_count(), 0);
+    }
+}
@@ -0,0 +1,93 @@
+use crate::math::Vec3;
+use super::branch::{Mesh, Vertex, connect_rings, create_ring};
+
+/// Floats per twig instance: base(3) + axis(3) + side(3) + start radius,
+/// end radius, base V (3) + glow, luminance, hue, opacity (4) + color(3)
+pub const TWIG_INSTANCE_FLOATS: usize = 19;
+
+/// Heights of the canonical twig's rings; the apex closes it at 1.0
+const TWIG_RINGS: [f32; 4] = [0.0, 0.3, 0.6, 0.9];
+
+/// One twig drawn from the canonical mesh: where it starts, which way it
+/// grows and how it tapers and glows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TwigInstance {
+    pub base: Vec3,
+    /// From base to tip, as long as the twig
+    pub axis: Vec3,
+    /// Unit direction across the twig, perpendicular to `axis`
+    pub side: Vec3,
+    pub start_radius: f32,
+    pub end_radius: f32,
+    /// Bark V coordinate at the base
+    pub v_start: f32,
+    pub glow: f32,
+    pub luminance: f32,
+    /// Hue of the person (degrees)
+    pub hue: f32,
+    pub opacity: f32,
+    /// Baked RGB color (black unless the generator bakes colors)
+    pub color: Vec3,
+}
+
+impl TwigInstance {
+    pub fn to_array(&self) -> [f32; TWIG_INSTANCE_FLOATS] {
+        [
+            self.base.x, self.base.y, self.base.z,
+            self.axis.x, self.axis.y, self.axis.z,
+            self.side.x, self.side.y, self.side.z,
+            self.start_radius, self.end_radius, self.v_start,
+            self.glow, self.luminance, self.hue, self.opacity,
+            self.color.x, self.color.y, self.color.z,
+        ]
+    }
+}
+
+/// The canonical twig, shared by every instance: a unit-radius tube up the
+/// Y axis from 0 to 1 closing to a point, V running 0 to 1 along it (the
+/// shader tapers, orients and scales it per instance)
+pub fn twig_mesh(segments: usize) -> Mesh {
+    let mut mesh = Mesh::new();
+    let segments = segments.max(3);
+    let mut prev_ring = None;
+    for &y in &TWIG_RINGS {
+        let ring = create_ring(Vec3::new(0.0, y, 0.0), Vec3::UP, 1.0, segments, y, 0.0, 0.0, 0.0);
+        let ring_start = mesh.add_vertices(ring);
+        if let Some(prev) = prev_ring {
+            connect_rings(&mut mesh, prev, ring_start, segments);
+        }
+        prev_ring = Some(ring_start);
+    }
+
+    let apex = Vertex::new(Vec3::UP, Vec3::UP).with_uv(0.5, 1.0).with_tangent(Vec3::RIGHT);
+    let apex_idx = mesh.add_vertices(std::iter::once(apex));
+    let last_ring = prev_ring.expect("twig has rings");
+    for i in 0..segments as u32 {
+        mesh.add_triangle(last_ring + i, last_ring + i + 1, apex_idx);
+    }
+
+    mesh.calculate_bounds();
+    mesh
+}
+
+/// Flat per-instance buffer, `TWIG_INSTANCE_FLOATS` per twig
+pub fn twig_instance_data(twigs: &[TwigInstance]) -> Vec<f32> {
+    twigs.iter().flat_map(|twig| twig.to_array()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_twig_mesh() {
+        let mesh = twig_mesh(5);
+        assert_eq!(mesh.vertex_count(), TWIG_RINGS.len() * 6 + 1);
+        assert_eq!(mesh.triangle_count(), (TWIG_RINGS.len() - 1) * 5 * 2 + 5);
+        for v in &mesh.vertices {
+            assert!(v.position.y >= 0.0 && v.position.y <= 1.0);
+            assert!((v.position.x * v.position.x + v.position.z * v.position.z).sqrt() <= 1.0 + 1e-5);
+            assert_eq!(v.uv[1], v.position.y);
+        }
+    }
+}
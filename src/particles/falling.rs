@@ -0,0 +1,264 @@
+//! Seasonal particles drifting down through the crown
+//!
+//! Blossom petals, falling leaves or snow, spawned above the tree and
+//! swaying down to the ground.
+
+use crate::math::{hsv_to_rgb, Vec3};
+use crate::growth::BranchNode;
+use super::{PARTICLE_FLOATS, UNTINTED};
+
+/// What drifts down through the crown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FallingKind {
+    Petals,
+    Leaves,
+    Snow,
+}
+
+impl FallingKind {
+    /// Fall speed (units per second) and sway amplitude
+    fn motion(self) -> (f32, f32) {
+        match self {
+            Self::Petals => (0.35, 0.6),
+            Self::Leaves => (0.6, 0.9),
+            Self::Snow => (0.25, 0.3),
+        }
+    }
+
+    fn size(self) -> f32 {
+        match self {
+            Self::Petals => 6.0,
+            Self::Leaves => 8.0,
+            Self::Snow => 4.0,
+        }
+    }
+
+    fn color(self, seed: u32) -> Vec3 {
+        let vary = (seed % 1000) as f32 * 0.001;
+        match self {
+            Self::Petals => hsv_to_rgb(0.92 + vary * 0.05, 0.35, 1.0),
+            Self::Leaves => hsv_to_rgb(0.02 + vary * 0.1, 0.8, 0.9),
+            Self::Snow => Vec3::new(0.9, 0.95, 1.0),
+        }
+    }
+}
+
+/// A single falling particle
+#[derive(Debug, Clone)]
+struct Flake {
+    position: Vec3,
+    kind: FallingKind,
+    phase: f32,        // Phase offset for swaying
+    size: f32,
+    age: f32,
+    color: Vec3,
+}
+
+impl Flake {
+    fn new(position: Vec3, kind: FallingKind, seed: u32) -> Self {
+        let phase = (seed as f32 / u32::MAX as f32) * std::f32::consts::TAU;
+        let size = kind.size() * (0.8 + (seed % 100) as f32 * 0.004);
+
+        Self {
+            position,
+            kind,
+            phase,
+            size,
+            age: 0.0,
+            color: kind.color(seed),
+        }
+    }
+
+    fn alpha(&self, ground: f32) -> f32 {
+        // Fade in after spawning, out just above the ground
+        let fade_in = self.age.min(1.0);
+        let fade_out = (self.position.y - ground).clamp(0.0, 1.0);
+        fade_in * fade_out * 0.7
+    }
+}
+
+/// Spawn sequence start, so a reset system replays the same particles
+const INITIAL_SEED: u32 = 7;
+
+/// System managing seasonal falling particles
+#[derive(Clone)]
+pub struct FallingSystem {
+    flakes: Vec<Flake>,
+    max_flakes: usize,
+    spawn_rate: f32,
+    spawn_accumulator: f32,
+    /// What new particles are (None = nothing falls)
+    kind: Option<FallingKind>,
+    /// Bounds for spawning (derived from tree); particles die at the bottom
+    bounds_min: Vec3,
+    bounds_max: Vec3,
+    seed: u32,
+    /// Activity scale based on tree growth (0.0 = dormant, 1.0 = full activity)
+    activity_scale: f32,
+}
+
+impl FallingSystem {
+    pub fn new(max_flakes: usize) -> Self {
+        Self {
+            flakes: Vec::with_capacity(max_flakes),
+            max_flakes,
+            spawn_rate: 12.0,
+            spawn_accumulator: 0.0,
+            kind: None,
+            bounds_min: Vec3::new(-3.0, 0.0, -3.0),
+            bounds_max: Vec3::new(3.0, 8.0, 3.0),
+            seed: INITIAL_SEED,
+            activity_scale: 1.0,
+        }
+    }
+
+    /// Drop every particle and restart the spawn sequence
+    pub fn reset(&mut self) {
+        self.flakes.clear();
+        self.spawn_accumulator = 0.0;
+        self.seed = INITIAL_SEED;
+    }
+
+    /// Change what falls; particles already in the air keep falling
+    pub fn set_kind(&mut self, kind: Option<FallingKind>) {
+        self.kind = kind;
+    }
+
+    pub fn kind(&self) -> Option<FallingKind> {
+        self.kind
+    }
+
+    /// Set activity scale based on tree growth progress
+    pub fn set_activity_scale(&mut self, scale: f32) {
+        self.activity_scale = scale.clamp(0.0, 1.0);
+    }
+
+    /// Configure bounds from the tree's extent
+    pub fn configure_from_tree(&mut self, root: &BranchNode) {
+        let mut min = Vec3::new(f32::MAX, f32::MAX, f32::MAX);
+        let mut max = Vec3::new(f32::MIN, f32::MIN, f32::MIN);
+
+        for node in root.iter_preorder() {
+            min.x = min.x.min(node.start.x).min(node.end.x);
+            min.y = min.y.min(node.start.y).min(node.end.y);
+            min.z = min.z.min(node.start.z).min(node.end.z);
+            max.x = max.x.max(node.start.x).max(node.end.x);
+            max.y = max.y.max(node.start.y).max(node.end.y);
+            max.z = max.z.max(node.start.z).max(node.end.z);
+        }
+
+        // Fall from a little above the crown down to the trunk's base
+        let margin = Vec3::new(1.5, 1.0, 1.5);
+        self.bounds_min = Vec3::new(min.x - margin.x, root.start.y, min.z - margin.z);
+        self.bounds_max = max + margin;
+    }
+
+    /// Update the particle system
+    pub fn update(&mut self, dt: f32, time: f32) {
+        let effective_max = ((self.max_flakes as f32) * self.activity_scale) as usize;
+
+        if let Some(kind) = self.kind {
+            self.spawn_accumulator += dt * self.spawn_rate * self.activity_scale;
+            while self.spawn_accumulator >= 1.0 && self.flakes.len() < effective_max {
+                self.spawn_flake(kind);
+                self.spawn_accumulator -= 1.0;
+            }
+        } else {
+            self.spawn_accumulator = 0.0;
+        }
+
+        for flake in &mut self.flakes {
+            flake.age += dt;
+            let (speed, sway) = flake.kind.motion();
+            let drift = Vec3::new(
+                (time * 0.9 + flake.phase).sin() * sway,
+                -speed,
+                (time * 0.7 + flake.phase * 1.3).cos() * sway * 0.6,
+            );
+            flake.position = flake.position + drift.scale(dt);
+        }
+
+        // Settled on the ground
+        let ground = self.bounds_min.y;
+        self.flakes.retain(|f| f.position.y > ground);
+    }
+
+    fn spawn_flake(&mut self, kind: FallingKind) {
+        self.seed = self.seed.wrapping_mul(1664525).wrapping_add(1013904223);
+        let t_x = (self.seed % 10000) as f32 / 10000.0;
+        self.seed = self.seed.wrapping_mul(1664525).wrapping_add(1013904223);
+        let t_z = (self.seed % 10000) as f32 / 10000.0;
+
+        let position = Vec3::new(
+            lerp(self.bounds_min.x, self.bounds_max.x, t_x),
+            self.bounds_max.y,
+            lerp(self.bounds_min.z, self.bounds_max.z, t_z),
+        );
+
+        self.flakes.push(Flake::new(position, kind, self.seed));
+    }
+
+    /// Get particle data for GPU upload
+    /// Format: position(3) + size(1) + alpha(1) + color(3) + palette slot(1)
+    /// = `PARTICLE_FLOATS` per particle; the season's colors aren't tinted
+    /// by the theme
+    pub fn get_particle_data(&self) -> Vec<f32> {
+        let mut data = Vec::with_capacity(self.flakes.len() * PARTICLE_FLOATS);
+        let ground = self.bounds_min.y;
+
+        for f in &self.flakes {
+            data.push(f.position.x);
+            data.push(f.position.y);
+            data.push(f.position.z);
+            data.push(f.size);
+            data.push(f.alpha(ground));
+            data.push(f.color.x);
+            data.push(f.color.y);
+            data.push(f.color.z);
+            data.push(UNTINTED);
+        }
+
+        data
+    }
+
+    pub fn count(&self) -> usize {
+        self.flakes.len()
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nothing_falls_without_kind() {
+        let mut system = FallingSystem::new(50);
+        system.update(1.0, 0.0);
+        assert_eq!(system.count(), 0);
+    }
+
+    #[test]
+    fn test_snow_falls_and_settles() {
+        let mut system = FallingSystem::new(50);
+        system.set_kind(Some(FallingKind::Snow));
+        system.update(1.0, 0.0);
+        let count = system.count();
+        assert!(count > 0);
+        assert_eq!(system.get_particle_data().len(), count * PARTICLE_FLOATS);
+
+        let top = system.get_particle_data()[1];
+        system.update(0.5, 1.0);
+        assert!(system.get_particle_data()[1] < top);
+
+        // Once nothing new spawns, everything reaches the ground
+        system.set_kind(None);
+        for i in 0..400 {
+            system.update(0.1, i as f32 * 0.1);
+        }
+        assert_eq!(system.count(), 0);
+    }
+}
@@ -1,5 +1,6 @@
-use crate::math::Vec3;
+use crate::math::{hsv_to_rgb, Vec3};
 use crate::growth::BranchNode;
+use super::PARTICLE_FLOATS;
 
 /// A single firefly particle
 #[derive(Debug, Clone)]
@@ -11,6 +12,8 @@ struct Firefly {
     lifetime: f32,
     max_lifetime: f32,
     color: Vec3,
+    /// Theme palette color it's tinted toward, fixed for its life
+    palette: u32,
 }
 
 impl Firefly {
@@ -31,6 +34,7 @@ impl Firefly {
             lifetime,
             max_lifetime: lifetime,
             color,
+            palette: seed % 3,
         }
     }
 
@@ -43,7 +47,11 @@ impl Firefly {
     }
 }
 
+/// Spawn sequence start, so a reset system replays the same particles
+const INITIAL_SEED: u32 = 42;
+
 /// System managing multiple firefly particles
+#[derive(Clone)]
 pub struct FireflySystem {
     fireflies: Vec<Firefly>,
     max_fireflies: usize,
@@ -69,11 +77,18 @@ impl FireflySystem {
             bounds_min: Vec3::new(-3.0, 0.0, -3.0),
             bounds_max: Vec3::new(3.0, 8.0, 3.0),
             attractors: Vec::new(),
-            seed: 42,
+            seed: INITIAL_SEED,
             activity_scale: 1.0,
         }
     }
 
+    /// Drop every particle and restart the spawn sequence
+    pub fn reset(&mut self) {
+        self.fireflies.clear();
+        self.spawn_accumulator = 0.0;
+        self.seed = INITIAL_SEED;
+    }
+
     /// Set activity scale based on tree growth progress
     /// 0.0 = no fireflies active, 1.0 = full activity
     pub fn set_activity_scale(&mut self, scale: f32) {
@@ -187,9 +202,10 @@ impl FireflySystem {
     }
 
     /// Get particle data for GPU upload
-    /// Format: position(3) + size(1) + alpha(1) + color(3) = 8 floats per particle
+    /// Format: position(3) + size(1) + alpha(1) + color(3) + palette slot(1)
+    /// = `PARTICLE_FLOATS` per particle
     pub fn get_particle_data(&self) -> Vec<f32> {
-        let mut data = Vec::with_capacity(self.fireflies.len() * 8);
+        let mut data = Vec::with_capacity(self.fireflies.len() * PARTICLE_FLOATS);
 
         for f in &self.fireflies {
             data.push(f.position.x);
@@ -200,6 +216,7 @@ impl FireflySystem {
             data.push(f.color.x);
             data.push(f.color.y);
             data.push(f.color.z);
+            data.push(f.palette as f32);
         }
 
         data
@@ -252,25 +269,6 @@ fn hash2d(x: i32, y: i32) -> f32 {
     (n as u32 as f32) / (u32::MAX as f32)
 }
 
-/// HSV to RGB conversion
-fn hsv_to_rgb(h: f32, s: f32, v: f32) -> Vec3 {
-    let h = h * 6.0;
-    let i = h.floor() as i32;
-    let f = h - h.floor();
-    let p = v * (1.0 - s);
-    let q = v * (1.0 - f * s);
-    let t = v * (1.0 - (1.0 - f) * s);
-
-    match i % 6 {
-        0 => Vec3::new(v, t, p),
-        1 => Vec3::new(q, v, p),
-        2 => Vec3::new(p, v, t),
-        3 => Vec3::new(p, q, v),
-        4 => Vec3::new(t, p, v),
-        _ => Vec3::new(v, p, q),
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -294,7 +292,22 @@ mod tests {
         system.update(0.5, 0.0);
 
         let data = system.get_particle_data();
-        assert_eq!(data.len() % 8, 0);
+        assert_eq!(data.len() % PARTICLE_FLOATS, 0);
+    }
+
+    #[test]
+    fn test_reset_replays_spawns() {
+        let mut system = FireflySystem::new(20);
+        let run = |system: &mut FireflySystem| {
+            for i in 0..30 {
+                system.update(1.0 / 3.0, i as f32 / 3.0);
+            }
+            system.get_particle_data()
+        };
+        let first = run(&mut system);
+        system.reset();
+        assert_eq!(system.count(), 0);
+        assert_eq!(run(&mut system), first);
     }
 
     #[test]
@@ -317,20 +330,7 @@ mod tests {
     }
 
     #[test]
-    fn test_hsv_to_rgb() {
-        // Red
-        let red = hsv_to_rgb(0.0, 1.0, 1.0);
-        assert!((red.x - 1.0).abs() < 0.01);
-        assert!(red.y.abs() < 0.01);
-        assert!(red.z.abs() < 0.01);
-
-        // Green
-        let green = hsv_to_rgb(1.0 / 3.0, 1.0, 1.0);
-        assert!(green.x.abs() < 0.01);
-        assert!((green.y - 1.0).abs() < 0.01);
-    }
-
-    #[test]
+    #[allow(clippy::manual_range_contains)]
     fn test_noise_range() {
         for i in 0..100 {
             let x = i as f32 * 0.1;
@@ -1,5 +1,13 @@
+pub mod falling;
 pub mod fireflies;
 pub mod orbs;
 
+/// Floats per particle: position(3) + size(1) + alpha(1) + color(3) +
+/// palette slot(1)
+pub const PARTICLE_FLOATS: usize = 9;
+/// Palette slot of particles that keep their own color under every theme
+pub const UNTINTED: f32 = -1.0;
+
+pub use falling::{FallingKind, FallingSystem};
 pub use fireflies::FireflySystem;
 pub use orbs::OrbSystem;
@@ -3,8 +3,9 @@
 //! Creates ethereal, bioluminescent orbs that float around the tree,
 //! attracted to branches with high luminance (long biographies).
 
-use crate::math::Vec3;
+use crate::math::{hsv_to_rgb, Vec3};
 use crate::growth::BranchNode;
+use super::PARTICLE_FLOATS;
 
 /// A single glowing orb particle
 #[derive(Debug, Clone)]
@@ -19,6 +20,8 @@ struct Orb {
     orbit_center: Vec3, // Point to orbit around
     orbit_radius: f32,
     orbit_speed: f32,
+    /// Theme palette color it's tinted toward, fixed for its life
+    palette: u32,
 }
 
 impl Orb {
@@ -44,6 +47,7 @@ impl Orb {
             orbit_center,
             orbit_radius,
             orbit_speed,
+            palette: seed % 3,
         }
     }
 
@@ -56,7 +60,11 @@ impl Orb {
     }
 }
 
+/// Spawn sequence start, so a reset system replays the same particles
+const INITIAL_SEED: u32 = 12345;
+
 /// System managing ethereal glowing orbs
+#[derive(Clone)]
 pub struct OrbSystem {
     orbs: Vec<Orb>,
     max_orbs: usize,
@@ -85,11 +93,18 @@ impl OrbSystem {
             spawn_rate: 3.0, // Slower spawn than fireflies
             spawn_accumulator: 0.0,
             attractors: Vec::new(),
-            seed: 12345,
+            seed: INITIAL_SEED,
             activity_scale: 1.0,
         }
     }
 
+    /// Drop every particle and restart the spawn sequence
+    pub fn reset(&mut self) {
+        self.orbs.clear();
+        self.spawn_accumulator = 0.0;
+        self.seed = INITIAL_SEED;
+    }
+
     /// Configure attractors from tree
     pub fn configure_from_tree(&mut self, root: &BranchNode) {
         self.attractors.clear();
@@ -194,9 +209,10 @@ impl OrbSystem {
     }
 
     /// Get particle data for GPU upload
-    /// Format: position(3) + size(1) + alpha(1) + color(3) = 8 floats per orb
+    /// Format: position(3) + size(1) + alpha(1) + color(3) + palette slot(1)
+    /// = `PARTICLE_FLOATS` per orb
     pub fn get_particle_data(&self) -> Vec<f32> {
-        let mut data = Vec::with_capacity(self.orbs.len() * 8);
+        let mut data = Vec::with_capacity(self.orbs.len() * PARTICLE_FLOATS);
 
         for orb in &self.orbs {
             // Pulsing size effect
@@ -211,6 +227,7 @@ impl OrbSystem {
             data.push(orb.base_color.x);
             data.push(orb.base_color.y);
             data.push(orb.base_color.z);
+            data.push(orb.palette as f32);
         }
 
         data
@@ -221,25 +238,6 @@ impl OrbSystem {
     }
 }
 
-/// HSV to RGB conversion
-fn hsv_to_rgb(h: f32, s: f32, v: f32) -> Vec3 {
-    let h = h * 6.0;
-    let i = h.floor() as i32;
-    let f = h - h.floor();
-    let p = v * (1.0 - s);
-    let q = v * (1.0 - f * s);
-    let t = v * (1.0 - (1.0 - f) * s);
-
-    match i % 6 {
-        0 => Vec3::new(v, t, p),
-        1 => Vec3::new(q, v, p),
-        2 => Vec3::new(p, v, t),
-        3 => Vec3::new(p, q, v),
-        4 => Vec3::new(t, p, v),
-        _ => Vec3::new(v, p, q),
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -272,7 +270,7 @@ mod tests {
         system.update(1.0, 0.0);
 
         let data = system.get_particle_data();
-        assert_eq!(data.len() % 8, 0);
+        assert_eq!(data.len() % PARTICLE_FLOATS, 0);
     }
 
     #[test]
@@ -1,6 +1,10 @@
 pub mod webgl;
 pub mod shaders;
 pub mod pipeline;
+pub mod season;
+pub mod theme;
 
 pub use webgl::WebGLContext;
-pub use pipeline::RenderPipeline;
+pub use pipeline::{Antialiasing, RenderPipeline, RenderStats, ToneMapping};
+pub use season::{Season, SeasonLook};
+pub use theme::Theme;
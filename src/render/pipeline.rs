@@ -1,28 +1,237 @@
+use std::ops::Range;
 use web_sys::{
     WebGl2RenderingContext, WebGlBuffer, WebGlProgram, WebGlVertexArrayObject,
-    WebGlTexture, WebGlFramebuffer, WebGlUniformLocation,
+    WebGlTexture, WebGlFramebuffer, WebGlRenderbuffer, WebGlUniformLocation,
 };
 use crate::math::{Vec3, Mat4};
-use crate::mesh::Mesh;
+use crate::mesh::{IndexData, Mesh, MeshStats, VERTEX_FLOATS};
+use crate::mesh::fruit::FRUIT_INSTANCE_FLOATS;
+use crate::mesh::leaves::LEAF_INSTANCE_FLOATS;
+use crate::mesh::twigs::TWIG_INSTANCE_FLOATS;
+use crate::particles::PARTICLE_FLOATS;
 use super::webgl::WebGLContext;
 use super::shaders::*;
+use super::season::SeasonLook;
+use super::theme::Theme;
+
+/// Near clipping plane distance
+const NEAR_PLANE: f32 = 0.1;
+/// Far clipping plane distance
+const FAR_PLANE: f32 = 100.0;
+/// Smallest internal resolution (longest side) tried before giving up
+const MIN_RENDER_SIZE: i32 = 256;
+/// Camera distances beyond which the first and second coarser tree LODs are drawn
+const DEFAULT_LOD_DISTANCES: [f32; 2] = [16.0, 24.0];
+/// Clip height that keeps everything
+const NO_CLIP_HEIGHT: f32 = -1.0e9;
+/// Where the moon hangs in the sky (toward it, unnormalized)
+const MOON_DIRECTION: Vec3 = Vec3::new(-0.5, 0.45, -0.75);
+/// Most samples per pixel asked of a multisampled scene target
+const MAX_MSAA_SAMPLES: i32 = 4;
+/// How far light shafts reach toward the light (fraction of the way)
+const GOD_RAY_DENSITY: f32 = 0.85;
+/// Falloff per god-ray sample, so shafts fade away from their source
+const GOD_RAY_DECAY: f32 = 0.96;
+/// Largest poster rendered, in pixels (an 8K square, 256 MB of RGBA)
+pub const MAX_POSTER_PIXELS: usize = 8192 * 8192;
+/// Extra pixels rendered around each poster tile and thrown away, so bloom
+/// and light shafts near a tile's edge see the scene beyond it
+const POSTER_TILE_OVERLAP: i32 = 64;
+/// Uniform block binding point of the shared camera block
+const CAMERA_BINDING: u32 = 0;
+/// Floats in the std140 camera block: view, projection, then the eye
+/// with the time packed into its fourth component
+const CAMERA_BLOCK_FLOATS: usize = 36;
+
+/// How edges are antialiased
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Antialiasing {
+    /// No antialiasing
+    Off,
+    /// A post pass over the final frame; cheap, softens thin twigs
+    #[default]
+    Fxaa,
+    /// A multisampled scene target, resolved before post-processing
+    Msaa,
+}
+
+impl Antialiasing {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "off" | "none" => Ok(Self::Off),
+            "fxaa" => Ok(Self::Fxaa),
+            "msaa" => Ok(Self::Msaa),
+            other => Err(format!("Unknown antialiasing mode '{}'", other)),
+        }
+    }
+}
+
+/// How the tree's programs get their vertices
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TreeVertices {
+    /// A mesh in the tree vertex layout
+    Mesh,
+    /// The canonical twig, shaped per twig instance
+    Twigs,
+}
+
+/// Curve mapping the scene's linear HDR color to the display
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ToneMapping {
+    /// Clipped at 1.0
+    None,
+    /// ACES filmic approximation
+    #[default]
+    Aces,
+    Reinhard,
+    /// Hejl-Burgess-Dawson film curve
+    Filmic,
+}
+
+impl ToneMapping {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "none" | "off" => Ok(Self::None),
+            "aces" => Ok(Self::Aces),
+            "reinhard" => Ok(Self::Reinhard),
+            "filmic" => Ok(Self::Filmic),
+            other => Err(format!("Unknown tone mapping '{}'", other)),
+        }
+    }
+
+    /// The composite shader's `u_tone_mapping` value
+    fn shader_index(self) -> i32 {
+        match self {
+            Self::None => 0,
+            Self::Aces => 1,
+            Self::Reinhard => 2,
+            Self::Filmic => 3,
+        }
+    }
+}
 
 /// Cached uniform locations for tree shader
 struct TreeUniforms {
     model: Option<WebGlUniformLocation>,
-    view: Option<WebGlUniformLocation>,
-    projection: Option<WebGlUniformLocation>,
-    time: Option<WebGlUniformLocation>,
-    camera_pos: Option<WebGlUniformLocation>,
     base_color: Option<WebGlUniformLocation>,
     ambient_strength: Option<WebGlUniformLocation>,
+    tree_origin: Option<WebGlUniformLocation>,
+    fade: Option<WebGlUniformLocation>,
+    fog_color: Option<WebGlUniformLocation>,
+    fog_density: Option<WebGlUniformLocation>,
+    fog_height_falloff: Option<WebGlUniformLocation>,
+    hue_stops: Option<WebGlUniformLocation>,
+    clip_height: Option<WebGlUniformLocation>,
+}
+
+impl TreeUniforms {
+    fn locate(ctx: &WebGLContext, program: &WebGlProgram) -> Self {
+        Self {
+            model: ctx.get_uniform_location(program, "u_model"),
+            base_color: ctx.get_uniform_location(program, "u_base_color"),
+            ambient_strength: ctx.get_uniform_location(program, "u_ambient_strength"),
+            tree_origin: ctx.get_uniform_location(program, "u_tree_origin"),
+            fade: ctx.get_uniform_location(program, "u_fade"),
+            fog_color: ctx.get_uniform_location(program, "u_fog_color"),
+            fog_density: ctx.get_uniform_location(program, "u_fog_density"),
+            fog_height_falloff: ctx.get_uniform_location(program, "u_fog_height_falloff"),
+            hue_stops: ctx.get_uniform_location(program, "u_hue_stops"),
+            clip_height: ctx.get_uniform_location(program, "u_clip_height"),
+        }
+    }
+}
+
+/// GPU buffers of one tree mesh: a coarser LOD, or the previous tree kept
+/// alive while it fades out
+struct TreeBuffers {
+    vao: WebGlVertexArrayObject,
+    vertex_buffer: Option<WebGlBuffer>,
+    index_buffer: Option<WebGlBuffer>,
+    index_count: i32,
+    /// `UNSIGNED_SHORT` or `UNSIGNED_INT`
+    index_type: u32,
+    stats: MeshStats,
 }
 
 /// Cached uniform locations for particle shader
 struct ParticleUniforms {
+    palette: [Option<WebGlUniformLocation>; 3],
+    tree_origin: Option<WebGlUniformLocation>,
+    fog_color: Option<WebGlUniformLocation>,
+    fog_density: Option<WebGlUniformLocation>,
+    fog_height_falloff: Option<WebGlUniformLocation>,
+}
+
+/// Cached uniform locations for leaf shader
+struct LeafUniforms {
     view: Option<WebGlUniformLocation>,
     projection: Option<WebGlUniformLocation>,
     time: Option<WebGlUniformLocation>,
+    camera_pos: Option<WebGlUniformLocation>,
+    tree_origin: Option<WebGlUniformLocation>,
+    fade: Option<WebGlUniformLocation>,
+    leaf_hues: Option<WebGlUniformLocation>,
+    leaf_saturation: Option<WebGlUniformLocation>,
+}
+
+/// Cached uniform locations for fruit shader
+struct FruitUniforms {
+    view: Option<WebGlUniformLocation>,
+    projection: Option<WebGlUniformLocation>,
+    time: Option<WebGlUniformLocation>,
+    camera_pos: Option<WebGlUniformLocation>,
+    fade: Option<WebGlUniformLocation>,
+}
+
+/// GPU buffers of an instanced mesh: one shape (a leaf blade, a twig)
+/// drawn once per instance
+struct InstancedBuffers {
+    vao: WebGlVertexArrayObject,
+    vertex_buffer: WebGlBuffer,
+    index_buffer: WebGlBuffer,
+    instance_buffer: WebGlBuffer,
+    index_count: i32,
+    index_type: u32,
+    instance_count: i32,
+    stats: MeshStats,
+}
+
+/// Cached uniform locations for label shader
+struct LabelUniforms {
+    atlas: Option<WebGlUniformLocation>,
+    fade: Option<WebGlUniformLocation>,
+}
+
+/// Cached uniform locations for ground shader
+struct GroundUniforms {
+    view: Option<WebGlUniformLocation>,
+    projection: Option<WebGlUniformLocation>,
+    time: Option<WebGlUniformLocation>,
+    camera_pos: Option<WebGlUniformLocation>,
+    tree_origin: Option<WebGlUniformLocation>,
+    fog_color: Option<WebGlUniformLocation>,
+    fog_density: Option<WebGlUniformLocation>,
+    reflection: Option<WebGlUniformLocation>,
+    reflection_strength: Option<WebGlUniformLocation>,
+    screen_size: Option<WebGlUniformLocation>,
+}
+
+/// Cached uniform locations for the sky pass
+struct SkyUniforms {
+    view: Option<WebGlUniformLocation>,
+    projection: Option<WebGlUniformLocation>,
+    time: Option<WebGlUniformLocation>,
+    background: Option<WebGlUniformLocation>,
+    horizon: Option<WebGlUniformLocation>,
+    moon_direction: Option<WebGlUniformLocation>,
+}
+
+/// Cached uniform locations for the ambient occlusion pass
+struct SsaoUniforms {
+    depth: Option<WebGlUniformLocation>,
+    proj_params: Option<WebGlUniformLocation>,
+    depth_params: Option<WebGlUniformLocation>,
+    radius: Option<WebGlUniformLocation>,
 }
 
 /// Cached uniform locations for post-processing
@@ -33,7 +242,22 @@ struct PostUniforms {
     scene: Option<WebGlUniformLocation>,
     bloom: Option<WebGlUniformLocation>,
     bloom_strength: Option<WebGlUniformLocation>,
+    ao: Option<WebGlUniformLocation>,
+    ao_strength: Option<WebGlUniformLocation>,
     vignette_strength: Option<WebGlUniformLocation>,
+    viewport_rect: Option<WebGlUniformLocation>,
+    exposure: Option<WebGlUniformLocation>,
+    tone_mapping: Option<WebGlUniformLocation>,
+    fxaa_texture: Option<WebGlUniformLocation>,
+    rays: Option<WebGlUniformLocation>,
+    ray_strength: Option<WebGlUniformLocation>,
+    ray_texture: Option<WebGlUniformLocation>,
+    light_pos: Option<WebGlUniformLocation>,
+    ray_density: Option<WebGlUniformLocation>,
+    ray_decay: Option<WebGlUniformLocation>,
+    outline_mask: Option<WebGlUniformLocation>,
+    outline_color: Option<WebGlUniformLocation>,
+    outline_width: Option<WebGlUniformLocation>,
 }
 
 /// Complete render pipeline for the tree visualization
@@ -42,21 +266,78 @@ pub struct RenderPipeline {
 
     // Shaders
     tree_program: WebGlProgram,
+    /// Tree shading over baked vertex colors, for low-end GPUs
+    baked_tree_program: WebGlProgram,
+    /// Instanced twigs, with either tree fragment shader
+    twig_program: WebGlProgram,
+    baked_twig_program: WebGlProgram,
     particle_program: WebGlProgram,
+    leaf_program: WebGlProgram,
+    fruit_program: WebGlProgram,
+    ground_program: WebGlProgram,
+    label_program: WebGlProgram,
+    sky_program: WebGlProgram,
+    ssao_program: WebGlProgram,
     bloom_extract_program: WebGlProgram,
     blur_program: WebGlProgram,
+    god_ray_program: WebGlProgram,
     composite_program: WebGlProgram,
+    fxaa_program: WebGlProgram,
+    /// Selected branch drawn flat white, and the rim traced around it
+    outline_mask_program: WebGlProgram,
+    outline_program: WebGlProgram,
 
     // Uniform locations
     tree_uniforms: TreeUniforms,
+    baked_tree_uniforms: TreeUniforms,
+    twig_uniforms: TreeUniforms,
+    baked_twig_uniforms: TreeUniforms,
+    outline_mask_uniforms: TreeUniforms,
     particle_uniforms: ParticleUniforms,
+    leaf_uniforms: LeafUniforms,
+    fruit_uniforms: FruitUniforms,
+    ground_uniforms: GroundUniforms,
+    label_uniforms: LabelUniforms,
+    sky_uniforms: SkyUniforms,
+    ssao_uniforms: SsaoUniforms,
     post_uniforms: PostUniforms,
+    /// View, projection, eye and time, bound to `CAMERA_BINDING`
+    camera_buffer: Option<WebGlBuffer>,
 
     // Tree mesh data
     tree_vao: Option<WebGlVertexArrayObject>,
     tree_vertex_buffer: Option<WebGlBuffer>,
+    tree_vertex_count: usize,
     tree_index_buffer: Option<WebGlBuffer>,
     tree_index_count: i32,
+    tree_index_type: u32,
+    tree_stats: MeshStats,
+    /// Previous tree mesh during a cross-fade
+    previous_tree: Option<TreeBuffers>,
+    /// Cross-fade progress from the previous to the current tree (1.0 = done)
+    crossfade: f32,
+    /// The next tree upload fades in over the current tree
+    crossfade_next: bool,
+    /// Coarser versions of the current tree, most detailed first
+    tree_lods: Vec<TreeBuffers>,
+    /// Camera distances at which each coarser LOD takes over
+    pub lod_distances: [f32; 2],
+
+    // Leaf data
+    leaves: Option<InstancedBuffers>,
+    /// Thin childless branches, drawn as instances of one twig
+    twigs: Option<InstancedBuffers>,
+    /// Fruit hung along branches, one per child
+    fruit: Option<InstancedBuffers>,
+
+    /// Ground disc under the tree, in the tree vertex layout
+    ground: Option<TreeBuffers>,
+    /// Memorial rings of the deceased, glowing over the tree once it's grown
+    memorials: Option<TreeBuffers>,
+    /// Name labels at branch tips, in the tree vertex layout
+    labels: Option<TreeBuffers>,
+    /// Signed distance field font atlas the labels sample
+    label_atlas: Option<WebGlTexture>,
 
     // Particle data
     particle_vao: Option<WebGlVertexArrayObject>,
@@ -64,20 +345,106 @@ pub struct RenderPipeline {
     particle_count: i32,
 
     // Framebuffers for post-processing
+    /// Scene color, linear HDR; alpha is the bloom mask (0 = excluded from bloom)
     scene_texture: Option<WebGlTexture>,
+    /// Scene depth, read back by the ambient occlusion pass
+    scene_depth: Option<WebGlTexture>,
     scene_fbo: Option<WebGlFramebuffer>,
+    /// Ambient occlusion at half resolution (red: 1 = open)
+    ssao_texture: Option<WebGlTexture>,
+    ssao_fbo: Option<WebGlFramebuffer>,
     bloom_textures: [Option<WebGlTexture>; 2],
     bloom_fbos: [Option<WebGlFramebuffer>; 2],
+    /// The tree mirrored in the ground plane, at half resolution
+    reflection_texture: Option<WebGlTexture>,
+    reflection_depth: Option<WebGlTexture>,
+    reflection_fbo: Option<WebGlFramebuffer>,
+    /// Composited frame at internal resolution, for the FXAA pass to read
+    post_texture: Option<WebGlTexture>,
+    post_fbo: Option<WebGlFramebuffer>,
+    /// Selected branch coverage at internal resolution, for the outline pass
+    outline_texture: Option<WebGlTexture>,
+    outline_fbo: Option<WebGlFramebuffer>,
+    /// Index ranges of the selected branch within the full-detail tree mesh
+    /// (empty = nothing outlined)
+    outline_ranges: Vec<Range<u32>>,
+    /// Multisampled scene target (color, depth), resolved into `scene_fbo`
+    msaa_fbo: Option<WebGlFramebuffer>,
+    msaa_renderbuffers: Vec<WebGlRenderbuffer>,
+    antialiasing: Antialiasing,
 
     // Dimensions
     width: i32,
     height: i32,
+    /// Offscreen resolution; below the canvas size when capped, then upscaled
+    render_width: i32,
+    render_height: i32,
+    max_texture_size: i32,
+    /// Scene and bloom targets are half-float, so glow isn't clipped at 1.0
+    /// before bloom extraction (false when unsupported or allocation failed,
+    /// and they're 8-bit sRGB)
+    hdr: bool,
+    /// Set by `dispose`; uploads fail and frames draw nothing afterwards
+    disposed: bool,
 
     // Camera state
     pub camera_position: Vec3,
     pub camera_target: Vec3,
     pub fov: f32,
 
+    /// Trunk base position (height gradient and fog are relative to it)
+    pub tree_origin: Vec3,
+
+    /// Shade the tree from its baked vertex colors with the simplified shader
+    pub baked_colors: bool,
+
+    /// Background, fog, tree hues and particle colors
+    pub theme: Theme,
+    /// Draw a starry sky with a moon behind the scene (else the flat
+    /// theme background)
+    pub sky: bool,
+    /// Leaf colors and fog tint of the season (mid-blend while changing)
+    pub season: SeasonLook,
+    /// Fog amount over the tree, ground and particles (1 = default haze,
+    /// 0 = none)
+    pub fog_density: f32,
+    /// How quickly the fog thins with height above the trunk base
+    pub fog_height_falloff: f32,
+    /// Fog color in place of the theme's (still tinted by the season)
+    pub fog_override: Option<Vec3>,
+
+    /// How much ambient occlusion darkens junctions and the inner canopy
+    /// (0 = pass skipped)
+    pub ssao_strength: f32,
+    /// Reach of the occlusion sampling (world units)
+    pub ssao_radius: f32,
+
+    /// How brightly the ground mirrors the tree (0 = pass skipped)
+    pub reflection_strength: f32,
+
+    /// Scene brightness above which bloom picks up glow
+    pub bloom_threshold: f32,
+    /// How strongly the blurred glow is added back over the scene
+    pub bloom_strength: f32,
+    /// Horizontal-then-vertical blur passes; more spreads the glow wider
+    pub bloom_passes: u32,
+
+    /// How brightly light shafts stream from `light_position` (0 = pass
+    /// skipped)
+    pub light_shafts: f32,
+    /// Where the light shafts radiate from (world space)
+    pub light_position: Vec3,
+
+    /// Display color of the rim around the selected branch
+    pub outline_color: Vec3,
+    /// Rim width in pixels at internal resolution
+    pub outline_width: f32,
+
+    /// Curve from linear HDR to the display, applied in the composite
+    pub tone_mapping: ToneMapping,
+    /// Exposure in stops (0 = unchanged)
+    pub exposure: f32,
+
     // Animation state
     growth_progress: f32,
 }
@@ -88,26 +455,90 @@ impl RenderPipeline {
 
         // Compile shaders
         let tree_program = ctx.create_program(TREE_VERTEX_SHADER, TREE_FRAGMENT_SHADER)?;
+        let baked_tree_program = ctx.create_program(TREE_VERTEX_SHADER, TREE_BAKED_FRAGMENT_SHADER)?;
+        let twig_program = ctx.create_program(TWIG_VERTEX_SHADER, TREE_FRAGMENT_SHADER)?;
+        let baked_twig_program = ctx.create_program(TWIG_VERTEX_SHADER, TREE_BAKED_FRAGMENT_SHADER)?;
         let particle_program = ctx.create_program(PARTICLE_VERTEX_SHADER, PARTICLE_FRAGMENT_SHADER)?;
+        let leaf_program = ctx.create_program(LEAF_VERTEX_SHADER, LEAF_FRAGMENT_SHADER)?;
+        let fruit_program = ctx.create_program(FRUIT_VERTEX_SHADER, FRUIT_FRAGMENT_SHADER)?;
+        let ground_program = ctx.create_program(GROUND_VERTEX_SHADER, GROUND_FRAGMENT_SHADER)?;
+        let label_program = ctx.create_program(LABEL_VERTEX_SHADER, LABEL_FRAGMENT_SHADER)?;
+        let sky_program = ctx.create_program(SKY_VERTEX_SHADER, SKY_FRAGMENT_SHADER)?;
+        let ssao_program = ctx.create_program(FULLSCREEN_VERTEX_SHADER, SSAO_SHADER)?;
         let bloom_extract_program = ctx.create_program(FULLSCREEN_VERTEX_SHADER, BLOOM_EXTRACT_SHADER)?;
         let blur_program = ctx.create_program(FULLSCREEN_VERTEX_SHADER, BLUR_SHADER)?;
+        let god_ray_program = ctx.create_program(FULLSCREEN_VERTEX_SHADER, GOD_RAY_SHADER)?;
         let composite_program = ctx.create_program(FULLSCREEN_VERTEX_SHADER, COMPOSITE_SHADER)?;
+        let fxaa_program = ctx.create_program(FULLSCREEN_VERTEX_SHADER, FXAA_SHADER)?;
+        let outline_mask_program = ctx.create_program(TREE_VERTEX_SHADER, OUTLINE_MASK_FRAGMENT_SHADER)?;
+        let outline_program = ctx.create_program(FULLSCREEN_VERTEX_SHADER, OUTLINE_SHADER)?;
 
         // Get uniform locations
-        let tree_uniforms = TreeUniforms {
-            model: ctx.get_uniform_location(&tree_program, "u_model"),
-            view: ctx.get_uniform_location(&tree_program, "u_view"),
-            projection: ctx.get_uniform_location(&tree_program, "u_projection"),
-            time: ctx.get_uniform_location(&tree_program, "u_time"),
-            camera_pos: ctx.get_uniform_location(&tree_program, "u_camera_pos"),
-            base_color: ctx.get_uniform_location(&tree_program, "u_base_color"),
-            ambient_strength: ctx.get_uniform_location(&tree_program, "u_ambient_strength"),
-        };
+        let tree_uniforms = TreeUniforms::locate(&ctx, &tree_program);
+        let baked_tree_uniforms = TreeUniforms::locate(&ctx, &baked_tree_program);
+        let twig_uniforms = TreeUniforms::locate(&ctx, &twig_program);
+        let baked_twig_uniforms = TreeUniforms::locate(&ctx, &baked_twig_program);
+        let outline_mask_uniforms = TreeUniforms::locate(&ctx, &outline_mask_program);
 
         let particle_uniforms = ParticleUniforms {
-            view: ctx.get_uniform_location(&particle_program, "u_view"),
-            projection: ctx.get_uniform_location(&particle_program, "u_projection"),
-            time: ctx.get_uniform_location(&particle_program, "u_time"),
+            palette: [0, 1, 2].map(|i| ctx.get_uniform_location(&particle_program, &format!("u_particle_palette[{}]", i))),
+            tree_origin: ctx.get_uniform_location(&particle_program, "u_tree_origin"),
+            fog_color: ctx.get_uniform_location(&particle_program, "u_fog_color"),
+            fog_density: ctx.get_uniform_location(&particle_program, "u_fog_density"),
+            fog_height_falloff: ctx.get_uniform_location(&particle_program, "u_fog_height_falloff"),
+        };
+
+        let leaf_uniforms = LeafUniforms {
+            view: ctx.get_uniform_location(&leaf_program, "u_view"),
+            projection: ctx.get_uniform_location(&leaf_program, "u_projection"),
+            time: ctx.get_uniform_location(&leaf_program, "u_time"),
+            camera_pos: ctx.get_uniform_location(&leaf_program, "u_camera_pos"),
+            tree_origin: ctx.get_uniform_location(&leaf_program, "u_tree_origin"),
+            fade: ctx.get_uniform_location(&leaf_program, "u_fade"),
+            leaf_hues: ctx.get_uniform_location(&leaf_program, "u_leaf_hues"),
+            leaf_saturation: ctx.get_uniform_location(&leaf_program, "u_leaf_saturation"),
+        };
+
+        let fruit_uniforms = FruitUniforms {
+            view: ctx.get_uniform_location(&fruit_program, "u_view"),
+            projection: ctx.get_uniform_location(&fruit_program, "u_projection"),
+            time: ctx.get_uniform_location(&fruit_program, "u_time"),
+            camera_pos: ctx.get_uniform_location(&fruit_program, "u_camera_pos"),
+            fade: ctx.get_uniform_location(&fruit_program, "u_fade"),
+        };
+
+        let ground_uniforms = GroundUniforms {
+            view: ctx.get_uniform_location(&ground_program, "u_view"),
+            projection: ctx.get_uniform_location(&ground_program, "u_projection"),
+            time: ctx.get_uniform_location(&ground_program, "u_time"),
+            camera_pos: ctx.get_uniform_location(&ground_program, "u_camera_pos"),
+            tree_origin: ctx.get_uniform_location(&ground_program, "u_tree_origin"),
+            fog_color: ctx.get_uniform_location(&ground_program, "u_fog_color"),
+            fog_density: ctx.get_uniform_location(&ground_program, "u_fog_density"),
+            reflection: ctx.get_uniform_location(&ground_program, "u_reflection"),
+            reflection_strength: ctx.get_uniform_location(&ground_program, "u_reflection_strength"),
+            screen_size: ctx.get_uniform_location(&ground_program, "u_screen_size"),
+        };
+
+        let label_uniforms = LabelUniforms {
+            atlas: ctx.get_uniform_location(&label_program, "u_atlas"),
+            fade: ctx.get_uniform_location(&label_program, "u_fade"),
+        };
+
+        let sky_uniforms = SkyUniforms {
+            view: ctx.get_uniform_location(&sky_program, "u_view"),
+            projection: ctx.get_uniform_location(&sky_program, "u_projection"),
+            time: ctx.get_uniform_location(&sky_program, "u_time"),
+            background: ctx.get_uniform_location(&sky_program, "u_background"),
+            horizon: ctx.get_uniform_location(&sky_program, "u_horizon"),
+            moon_direction: ctx.get_uniform_location(&sky_program, "u_moon_direction"),
+        };
+
+        let ssao_uniforms = SsaoUniforms {
+            depth: ctx.get_uniform_location(&ssao_program, "u_depth"),
+            proj_params: ctx.get_uniform_location(&ssao_program, "u_proj_params"),
+            depth_params: ctx.get_uniform_location(&ssao_program, "u_depth_params"),
+            radius: ctx.get_uniform_location(&ssao_program, "u_radius"),
         };
 
         let post_uniforms = PostUniforms {
@@ -117,66 +548,558 @@ impl RenderPipeline {
             scene: ctx.get_uniform_location(&composite_program, "u_scene"),
             bloom: ctx.get_uniform_location(&composite_program, "u_bloom"),
             bloom_strength: ctx.get_uniform_location(&composite_program, "u_bloom_strength"),
+            ao: ctx.get_uniform_location(&composite_program, "u_ao"),
+            ao_strength: ctx.get_uniform_location(&composite_program, "u_ao_strength"),
             vignette_strength: ctx.get_uniform_location(&composite_program, "u_vignette_strength"),
+            viewport_rect: ctx.get_uniform_location(&composite_program, "u_viewport_rect"),
+            exposure: ctx.get_uniform_location(&composite_program, "u_exposure"),
+            tone_mapping: ctx.get_uniform_location(&composite_program, "u_tone_mapping"),
+            fxaa_texture: ctx.get_uniform_location(&fxaa_program, "u_texture"),
+            rays: ctx.get_uniform_location(&composite_program, "u_rays"),
+            ray_strength: ctx.get_uniform_location(&composite_program, "u_ray_strength"),
+            ray_texture: ctx.get_uniform_location(&god_ray_program, "u_texture"),
+            light_pos: ctx.get_uniform_location(&god_ray_program, "u_light_pos"),
+            ray_density: ctx.get_uniform_location(&god_ray_program, "u_density"),
+            ray_decay: ctx.get_uniform_location(&god_ray_program, "u_decay"),
+            outline_mask: ctx.get_uniform_location(&outline_program, "u_mask"),
+            outline_color: ctx.get_uniform_location(&outline_program, "u_color"),
+            outline_width: ctx.get_uniform_location(&outline_program, "u_width"),
         };
 
         let mut pipeline = Self {
             ctx,
             tree_program,
+            baked_tree_program,
+            twig_program,
+            baked_twig_program,
             particle_program,
+            leaf_program,
+            fruit_program,
+            ground_program,
+            label_program,
+            sky_program,
+            ssao_program,
             bloom_extract_program,
             blur_program,
+            god_ray_program,
             composite_program,
+            fxaa_program,
+            outline_mask_program,
+            outline_program,
             tree_uniforms,
+            baked_tree_uniforms,
+            twig_uniforms,
+            baked_twig_uniforms,
+            outline_mask_uniforms,
             particle_uniforms,
+            leaf_uniforms,
+            fruit_uniforms,
+            ground_uniforms,
+            label_uniforms,
+            sky_uniforms,
+            ssao_uniforms,
             post_uniforms,
+            camera_buffer: None,
             tree_vao: None,
             tree_vertex_buffer: None,
+            tree_vertex_count: 0,
             tree_index_buffer: None,
             tree_index_count: 0,
+            tree_index_type: WebGl2RenderingContext::UNSIGNED_SHORT,
+            tree_stats: MeshStats::default(),
+            previous_tree: None,
+            crossfade: 1.0,
+            crossfade_next: false,
+            tree_lods: Vec::new(),
+            lod_distances: DEFAULT_LOD_DISTANCES,
+            leaves: None,
+            twigs: None,
+            fruit: None,
+            ground: None,
+            memorials: None,
+            labels: None,
+            label_atlas: None,
             particle_vao: None,
             particle_buffer: None,
             particle_count: 0,
             scene_texture: None,
+            scene_depth: None,
             scene_fbo: None,
+            ssao_texture: None,
+            ssao_fbo: None,
             bloom_textures: [None, None],
             bloom_fbos: [None, None],
+            reflection_texture: None,
+            reflection_depth: None,
+            reflection_fbo: None,
+            post_texture: None,
+            post_fbo: None,
+            outline_texture: None,
+            outline_fbo: None,
+            outline_ranges: Vec::new(),
+            msaa_fbo: None,
+            msaa_renderbuffers: Vec::new(),
+            antialiasing: Antialiasing::default(),
             width,
             height,
+            render_width: width,
+            render_height: height,
+            max_texture_size: 0,
+            hdr: false,
+            disposed: false,
             camera_position: Vec3::new(0.0, 4.0, 10.0),
             camera_target: Vec3::new(0.0, 3.0, 0.0),
             fov: std::f32::consts::FRAC_PI_4,
+            tree_origin: Vec3::ZERO,
+            baked_colors: false,
+            theme: Theme::default(),
+            sky: true,
+            season: SeasonLook::default(),
+            fog_density: 1.0,
+            fog_height_falloff: 0.15,
+            fog_override: None,
+            ssao_strength: 0.6,
+            ssao_radius: 0.35,
+            reflection_strength: 0.5,
+            bloom_threshold: 0.5,
+            bloom_strength: 0.8,
+            bloom_passes: 1,
+            light_shafts: 0.0,
+            light_position: Vec3::new(0.0, 8.0, 0.0),
+            outline_color: Vec3::new(1.0, 0.85, 0.4),
+            outline_width: 3.0,
+            tone_mapping: ToneMapping::default(),
+            exposure: 0.0,
             growth_progress: 1.0, // Start fully grown by default
         };
 
+        // One camera block for every program that draws from the camera
+        pipeline.camera_buffer = Some(pipeline.ctx.create_uniform_buffer(CAMERA_BLOCK_FLOATS, CAMERA_BINDING)?);
+        let camera_programs = [
+            &pipeline.tree_program,
+            &pipeline.baked_tree_program,
+            &pipeline.twig_program,
+            &pipeline.baked_twig_program,
+            &pipeline.outline_mask_program,
+            &pipeline.particle_program,
+            &pipeline.label_program,
+        ];
+        for program in camera_programs {
+            pipeline.ctx.bind_uniform_block(program, "Camera", CAMERA_BINDING);
+        }
+
+        pipeline.max_texture_size = pipeline.ctx.max_texture_size();
+        pipeline.hdr = pipeline.ctx.enable_color_buffer_float();
         pipeline.create_framebuffers()?;
 
         Ok(pipeline)
     }
 
+    /// Allocate the offscreen targets, falling back from half-float to 8-bit sRGB
+    /// color and then lowering the internal resolution when the canvas
+    /// exceeds `MAX_TEXTURE_SIZE` or allocation fails
     fn create_framebuffers(&mut self) -> Result<(), String> {
-        // Scene framebuffer
-        let scene_tex = self.ctx.create_texture(self.width, self.height, WebGl2RenderingContext::RGBA)?;
-        let scene_fbo = self.ctx.create_framebuffer(&scene_tex)?;
-        self.scene_texture = Some(scene_tex);
-        self.scene_fbo = Some(scene_fbo);
+        self.release_framebuffers();
+        let mut limit = self.max_texture_size;
+        loop {
+            let (width, height) = capped_resolution(self.width, self.height, limit);
+            match self.allocate_framebuffers(width, height) {
+                Ok(()) => {
+                    self.render_width = width;
+                    self.render_height = height;
+                    return self.allocate_msaa();
+                }
+                Err(_) if self.hdr => self.hdr = false,
+                Err(e) => {
+                    let longest = width.max(height);
+                    if longest / 2 < MIN_RENDER_SIZE {
+                        return Err(e);
+                    }
+                    limit = longest / 2;
+                }
+            }
+        }
+    }
+
+    fn allocate_framebuffers(&mut self, width: i32, height: i32) -> Result<(), String> {
+        // Free whatever was allocated before a failure
+        fn discard(gl: &WebGl2RenderingContext, textures: &[WebGlTexture], fbos: &[WebGlFramebuffer]) {
+            for tex in textures {
+                gl.delete_texture(Some(tex));
+            }
+            for fbo in fbos {
+                gl.delete_framebuffer(Some(fbo));
+            }
+        }
+
+        let mut textures = Vec::with_capacity(7);
+        let mut fbos = Vec::with_capacity(7);
+
+        // Scene, composited frame and selection mask at full internal
+        // resolution; bloom, ambient occlusion and the ground reflection at
+        // half. Scene, bloom and reflection carry HDR color
+        let full = (width, height);
+        let half = (width / 2, height / 2);
+        let color = self.color_format();
+        let ldr = WebGl2RenderingContext::RGBA;
+        let targets = [
+            (full, color),
+            (half, color),
+            (half, color),
+            (half, ldr),
+            (full, ldr),
+            (half, color),
+            (full, ldr),
+        ];
+        for ((w, h), format) in targets {
+            let result = self.ctx
+                .create_texture(w.max(1), h.max(1), format)
+                .and_then(|tex| {
+                    let fbo = self.ctx.create_framebuffer(&tex);
+                    textures.push(tex);
+                    fbo
+                });
+            match result {
+                Ok(fbo) => fbos.push(fbo),
+                Err(e) => {
+                    discard(&self.ctx.gl, &textures, &fbos);
+                    return Err(e);
+                }
+            }
+        }
+
+        // The scene pass depth-tests into a texture the occlusion pass reads;
+        // the reflection pass needs depth of its own
+        let mut depths = Vec::with_capacity(2);
+        for (fbo, (w, h)) in [(0, full), (5, half)] {
+            let depth = self.ctx
+                .create_depth_texture(w.max(1), h.max(1))
+                .and_then(|depth| match self.ctx.attach_depth_texture(&fbos[fbo], &depth) {
+                    Ok(()) => Ok(depth),
+                    Err(e) => {
+                        self.ctx.gl.delete_texture(Some(&depth));
+                        Err(e)
+                    }
+                });
+            match depth {
+                Ok(depth) => depths.push(depth),
+                Err(e) => {
+                    textures.append(&mut depths);
+                    discard(&self.ctx.gl, &textures, &fbos);
+                    return Err(e);
+                }
+            }
+        }
+
+        // The selection mask is depth-tested (read only) against the scene,
+        // so branches in front of the selected one hide its outline
+        if let Err(e) = self.ctx.attach_depth_texture(&fbos[6], &depths[0]) {
+            textures.append(&mut depths);
+            discard(&self.ctx.gl, &textures, &fbos);
+            return Err(e);
+        }
+
+        let mut depths = depths.into_iter();
+        let mut textures = textures.into_iter();
+        let mut fbos = fbos.into_iter();
+        self.scene_texture = textures.next();
+        self.scene_depth = depths.next();
+        self.scene_fbo = fbos.next();
+        self.bloom_textures = [textures.next(), textures.next()];
+        self.bloom_fbos = [fbos.next(), fbos.next()];
+        self.ssao_texture = textures.next();
+        self.ssao_fbo = fbos.next();
+        self.post_texture = textures.next();
+        self.post_fbo = fbos.next();
+        self.reflection_texture = textures.next();
+        self.reflection_fbo = fbos.next();
+        self.reflection_depth = depths.next();
+        self.outline_texture = textures.next();
+        self.outline_fbo = fbos.next();
+        Ok(())
+    }
+
+    /// Delete every offscreen target, multisampled one included
+    fn release_framebuffers(&mut self) {
+        let gl = &self.ctx.gl;
+        let textures = [
+            self.scene_texture.take(),
+            self.scene_depth.take(),
+            self.ssao_texture.take(),
+            self.bloom_textures[0].take(),
+            self.bloom_textures[1].take(),
+            self.reflection_texture.take(),
+            self.reflection_depth.take(),
+            self.post_texture.take(),
+            self.outline_texture.take(),
+        ];
+        for texture in textures.iter().flatten() {
+            gl.delete_texture(Some(texture));
+        }
+        let fbos = [
+            self.scene_fbo.take(),
+            self.ssao_fbo.take(),
+            self.bloom_fbos[0].take(),
+            self.bloom_fbos[1].take(),
+            self.reflection_fbo.take(),
+            self.post_fbo.take(),
+            self.outline_fbo.take(),
+            self.msaa_fbo.take(),
+        ];
+        for fbo in fbos.iter().flatten() {
+            gl.delete_framebuffer(Some(fbo));
+        }
+        for buffer in self.msaa_renderbuffers.drain(..) {
+            gl.delete_renderbuffer(Some(&buffer));
+        }
+    }
 
-        // Bloom framebuffers (at half resolution)
-        let bloom_width = self.width / 2;
-        let bloom_height = self.height / 2;
+    /// Internal format of the scene and bloom targets
+    ///
+    /// The 8-bit fallback is sRGB-encoded: the scene is linear until the
+    /// composite tone maps it, and linear RGBA8 would band dark gradients
+    /// and round the near-black theme backgrounds to 0. The GPU encodes on
+    /// write, blends in linear and decodes on sampling, so no shader changes.
+    fn color_format(&self) -> u32 {
+        if self.hdr {
+            WebGl2RenderingContext::RGBA16F
+        } else {
+            WebGl2RenderingContext::SRGB8_ALPHA8
+        }
+    }
+
+    /// (Re)allocate the multisampled scene target at the internal resolution
+    /// when MSAA is on, freeing the previous one
+    fn allocate_msaa(&mut self) -> Result<(), String> {
+        let gl = &self.ctx.gl;
+        if let Some(fbo) = self.msaa_fbo.take() {
+            gl.delete_framebuffer(Some(&fbo));
+        }
+        for buffer in self.msaa_renderbuffers.drain(..) {
+            gl.delete_renderbuffer(Some(&buffer));
+        }
+        if self.antialiasing != Antialiasing::Msaa {
+            return Ok(());
+        }
 
-        for i in 0..2 {
-            let tex = self.ctx.create_texture(bloom_width, bloom_height, WebGl2RenderingContext::RGBA)?;
-            let fbo = self.ctx.create_framebuffer(&tex)?;
-            self.bloom_textures[i] = Some(tex);
-            self.bloom_fbos[i] = Some(fbo);
+        let samples = self.ctx.max_samples().min(MAX_MSAA_SAMPLES);
+        if samples < 2 {
+            return Err("Multisampling is not supported".to_string());
         }
+        let (fbo, buffers) = self.ctx.create_multisample_framebuffer(
+            self.render_width,
+            self.render_height,
+            samples,
+            self.color_format(),
+        )?;
+        self.msaa_fbo = Some(fbo);
+        self.msaa_renderbuffers = buffers.into();
+        Ok(())
+    }
 
+    /// Error for any upload or readback after `dispose`
+    fn check_live(&self) -> Result<(), String> {
+        if self.disposed {
+            return Err("Render pipeline has been disposed".to_string());
+        }
         Ok(())
     }
 
+    /// Switch edge antialiasing; falls back to no antialiasing when MSAA
+    /// can't be allocated
+    pub fn set_antialiasing(&mut self, mode: Antialiasing) -> Result<(), String> {
+        self.check_live()?;
+        self.antialiasing = mode;
+        let result = self.allocate_msaa();
+        if result.is_err() {
+            self.antialiasing = Antialiasing::Off;
+        }
+        result
+    }
+
+    /// Current edge antialiasing
+    pub fn antialiasing(&self) -> Antialiasing {
+        self.antialiasing
+    }
+
+    /// Canvas and internal resolution, for diagnosing capped rendering
+    pub fn render_stats(&self) -> RenderStats {
+        RenderStats {
+            canvas_width: self.width,
+            canvas_height: self.height,
+            render_width: self.render_width,
+            render_height: self.render_height,
+            max_texture_size: self.max_texture_size,
+            hdr: self.hdr,
+        }
+    }
+
     /// Upload tree mesh to GPU
     pub fn upload_tree_mesh(&mut self, mesh: &Mesh) -> Result<(), String> {
+        self.check_live()?;
+        let buffers = self.create_tree_buffers(mesh)?;
+        if std::mem::take(&mut self.crossfade_next) && self.tree_vao.is_some() {
+            self.keep_fading_tree();
+        } else {
+            self.release_tree();
+        }
+
+        self.tree_vao = Some(buffers.vao);
+        self.tree_vertex_buffer = buffers.vertex_buffer;
+        self.tree_vertex_count = mesh.vertex_count();
+        self.tree_index_buffer = buffers.index_buffer;
+        self.tree_index_count = buffers.index_count;
+        self.tree_index_type = buffers.index_type;
+        self.tree_stats = buffers.stats;
+
+        Ok(())
+    }
+
+    /// Re-upload only the given vertices and indices of the current tree mesh
+    ///
+    /// `mesh` must have the uploaded mesh's layout (as after
+    /// `Mesh::replace_range`); returns false, changing nothing, when its
+    /// sizes differ or no tree is uploaded, so the caller can upload it whole.
+    pub fn patch_tree_mesh(&mut self, mesh: &Mesh, vertices: Range<usize>, indices: Range<usize>) -> bool {
+        let (Some(vertex_buffer), Some(index_buffer)) = (&self.tree_vertex_buffer, &self.tree_index_buffer) else {
+            return false;
+        };
+        if mesh.vertex_count() != self.tree_vertex_count
+            || mesh.indices.len() != self.tree_index_count as usize
+            || vertices.end > mesh.vertex_count()
+            || indices.end > mesh.indices.len()
+        {
+            return false;
+        }
+
+        let vertex_data: Vec<f32> = mesh.vertices[vertices.clone()].iter().flat_map(|v| v.to_array()).collect();
+        self.ctx.update_buffer_f32(vertex_buffer, vertices.start * VERTEX_FLOATS, &vertex_data);
+
+        let index_data = match self.tree_index_type {
+            WebGl2RenderingContext::UNSIGNED_SHORT => {
+                IndexData::U16(mesh.indices[indices.clone()].iter().map(|&i| i as u16).collect())
+            }
+            _ => IndexData::U32(&mesh.indices[indices.clone()]),
+        };
+        self.ctx.update_index_buffer(index_buffer, indices.start, &index_data);
+        true
+    }
+
+    /// Outline the triangles at these index ranges of the uploaded tree mesh
+    /// (a selected person's `BranchMeshInfo` ranges); empty clears it
+    pub fn set_outline(&mut self, index_ranges: Vec<Range<u32>>) {
+        self.outline_ranges = index_ranges;
+    }
+
+    /// Size of every mesh on the GPU, by part: the tree (with its memory
+    /// threads), its coarser LODs, the tree fading out, twigs, leaves, fruit,
+    /// ground, memorial rings and labels
+    pub fn uploaded_stats(&self) -> Vec<(String, MeshStats)> {
+        let mut parts = Vec::new();
+        if self.tree_vao.is_some() {
+            parts.push(("tree".to_string(), self.tree_stats.clone()));
+        }
+        for (i, lod) in self.tree_lods.iter().enumerate() {
+            parts.push((format!("tree_lod{}", i + 1), lod.stats.clone()));
+        }
+        let meshes = [
+            ("previous_tree", &self.previous_tree),
+            ("ground", &self.ground),
+            ("memorials", &self.memorials),
+            ("labels", &self.labels),
+        ];
+        for (name, buffers) in meshes {
+            if let Some(buffers) = buffers {
+                parts.push((name.to_string(), buffers.stats.clone()));
+            }
+        }
+        for (name, buffers) in [("twigs", &self.twigs), ("leaves", &self.leaves), ("fruit", &self.fruit)] {
+            if let Some(buffers) = buffers {
+                parts.push((name.to_string(), buffers.stats.clone()));
+            }
+        }
+        parts
+    }
+
+    /// Upload coarser versions of the current tree, most detailed first,
+    /// drawn instead of it as the camera backs away (see `lod_distances`)
+    pub fn upload_tree_lods(&mut self, lods: &[Mesh]) -> Result<(), String> {
+        self.check_live()?;
+        self.release_tree_lods();
+        for mesh in lods {
+            let buffers = self.create_tree_buffers(mesh)?;
+            self.tree_lods.push(buffers);
+        }
+        Ok(())
+    }
+
+    fn release_tree(&mut self) {
+        let gl = &self.ctx.gl;
+        if let Some(vao) = self.tree_vao.take() {
+            gl.delete_vertex_array(Some(&vao));
+        }
+        gl.delete_buffer(self.tree_vertex_buffer.take().as_ref());
+        gl.delete_buffer(self.tree_index_buffer.take().as_ref());
+        self.tree_vertex_count = 0;
+        self.tree_index_count = 0;
+        self.tree_stats = MeshStats::default();
+    }
+
+    fn release_tree_lods(&mut self) {
+        let gl = &self.ctx.gl;
+        for lod in self.tree_lods.drain(..) {
+            gl.delete_vertex_array(Some(&lod.vao));
+            gl.delete_buffer(lod.vertex_buffer.as_ref());
+            gl.delete_buffer(lod.index_buffer.as_ref());
+        }
+    }
+
+    /// Program and uniforms tree-shaded geometry is drawn with
+    fn tree_shading(&self, vertices: TreeVertices) -> (&WebGlProgram, &TreeUniforms) {
+        match (vertices, self.baked_colors) {
+            (TreeVertices::Mesh, false) => (&self.tree_program, &self.tree_uniforms),
+            (TreeVertices::Mesh, true) => (&self.baked_tree_program, &self.baked_tree_uniforms),
+            (TreeVertices::Twigs, false) => (&self.twig_program, &self.twig_uniforms),
+            (TreeVertices::Twigs, true) => (&self.baked_twig_program, &self.baked_twig_uniforms),
+        }
+    }
+
+    /// Fill the camera block read by the tree, particle and label programs
+    fn upload_camera(&self, view: &Mat4, projection: &Mat4, camera: Vec3, time: f32) {
+        if let Some(buffer) = &self.camera_buffer {
+            self.ctx.update_uniform_buffer(buffer, &camera_block(view, projection, camera, time));
+        }
+    }
+
+    /// Set the uniforms shared by the tree's programs; the program must be in
+    /// use. Camera and time come from the camera block (see `upload_camera`)
+    fn set_tree_uniforms(&self, uniforms: &TreeUniforms, model: &Mat4) {
+        self.ctx.uniform_matrix4fv(uniforms.model.as_ref(), model.as_slice());
+        self.ctx.uniform_3f(uniforms.base_color.as_ref(), 0.2, 0.8, 0.6);
+        self.ctx.uniform_1f(uniforms.ambient_strength.as_ref(), 0.3);
+        self.ctx.uniform_3f(
+            uniforms.tree_origin.as_ref(),
+            self.tree_origin.x,
+            self.tree_origin.y,
+            self.tree_origin.z,
+        );
+        self.ctx.uniform_1f(uniforms.fade.as_ref(), self.crossfade);
+        let fog = self.fog_color();
+        self.ctx.uniform_3f(uniforms.fog_color.as_ref(), fog.x, fog.y, fog.z);
+        self.ctx.uniform_1f(uniforms.fog_density.as_ref(), self.fog_density);
+        self.ctx.uniform_1f(uniforms.fog_height_falloff.as_ref(), self.fog_height_falloff);
+        let [trunk_hue, tip_hue] = self.theme.hue_stops;
+        self.ctx.uniform_2f(uniforms.hue_stops.as_ref(), trunk_hue, tip_hue);
+        self.ctx.uniform_1f(uniforms.clip_height.as_ref(), NO_CLIP_HEIGHT);
+    }
+
+    /// The fog (the theme's unless overridden), tinted by the season
+    fn fog_color(&self) -> Vec3 {
+        let (fog, tint) = (self.fog_override.unwrap_or(self.theme.fog), self.season.fog_tint);
+        Vec3::new(fog.x * tint.x, fog.y * tint.y, fog.z * tint.z)
+    }
+
+    fn create_tree_buffers(&self, mesh: &Mesh) -> Result<TreeBuffers, String> {
         let gl = &self.ctx.gl;
 
         // Create VAO
@@ -187,13 +1110,14 @@ impl RenderPipeline {
         let vertex_data = mesh.vertex_data();
         let vertex_buffer = self.ctx.create_buffer_f32(&vertex_data, WebGl2RenderingContext::STATIC_DRAW)?;
 
-        // Upload index data
-        let index_data = mesh.index_data();
-        let index_buffer = self.ctx.create_index_buffer(index_data, WebGl2RenderingContext::STATIC_DRAW)?;
+        // Upload index data, 16-bit when the mesh is small enough
+        let index_data = mesh.packed_indices();
+        let (index_buffer, index_type) = self.ctx.create_index_buffer(&index_data, WebGl2RenderingContext::STATIC_DRAW)?;
 
         // Set up vertex attributes
-        // Layout: position(3) + normal(3) + uv(2) + glow(1) + luminance(1) + hue(1) = 11 floats
-        let stride = 11 * 4; // 11 floats * 4 bytes
+        // Layout: position(3) + normal(3) + uv(2) + glow(1) + luminance(1) + hue(1) + opacity(1)
+        // + tangent(3) + color(3) = 18 floats
+        let stride = (VERTEX_FLOATS * 4) as i32;
 
         gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(&vertex_buffer));
         gl.bind_buffer(WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER, Some(&index_buffer));
@@ -222,19 +1146,272 @@ impl RenderPipeline {
         gl.enable_vertex_attrib_array(5);
         gl.vertex_attrib_pointer_with_i32(5, 1, WebGl2RenderingContext::FLOAT, false, stride, 40);
 
+        // Opacity (location 6)
+        gl.enable_vertex_attrib_array(6);
+        gl.vertex_attrib_pointer_with_i32(6, 1, WebGl2RenderingContext::FLOAT, false, stride, 44);
+
+        // Tangent (location 7)
+        gl.enable_vertex_attrib_array(7);
+        gl.vertex_attrib_pointer_with_i32(7, 3, WebGl2RenderingContext::FLOAT, false, stride, 48);
+
+        // Baked color (location 8)
+        gl.enable_vertex_attrib_array(8);
+        gl.vertex_attrib_pointer_with_i32(8, 3, WebGl2RenderingContext::FLOAT, false, stride, 60);
+
         gl.bind_vertex_array(None);
 
-        self.tree_vao = Some(vao);
-        self.tree_vertex_buffer = Some(vertex_buffer);
-        self.tree_index_buffer = Some(index_buffer);
-        self.tree_index_count = index_data.len() as i32;
+        Ok(TreeBuffers {
+            vao,
+            vertex_buffer: Some(vertex_buffer),
+            index_buffer: Some(index_buffer),
+            index_count: index_data.len() as i32,
+            index_type,
+            stats: mesh.stats(),
+        })
+    }
+
+    /// Fade the next uploaded tree mesh in over the current one (advance
+    /// with `set_crossfade`); the current tree is kept as is until that
+    /// upload succeeds
+    pub fn begin_crossfade(&mut self) {
+        self.crossfade_next = true;
+    }
+
+    /// Drop a cross-fade still waiting for its upload; returns whether one
+    /// was (false once the upload has started it)
+    pub fn cancel_crossfade(&mut self) -> bool {
+        std::mem::take(&mut self.crossfade_next)
+    }
+
+    /// Move the current tree mesh into the fading slot, to fade out as the
+    /// newly uploaded one fades in
+    fn keep_fading_tree(&mut self) {
+        self.release_previous_tree();
+        self.release_tree_lods();
+        if let Some(vao) = self.tree_vao.take() {
+            self.previous_tree = Some(TreeBuffers {
+                vao,
+                vertex_buffer: self.tree_vertex_buffer.take(),
+                index_buffer: self.tree_index_buffer.take(),
+                index_count: self.tree_index_count,
+                index_type: self.tree_index_type,
+                stats: std::mem::take(&mut self.tree_stats),
+            });
+            self.crossfade = 0.0;
+        }
+    }
+
+    /// Set cross-fade progress (0.0 = previous tree, 1.0 = current tree)
+    pub fn set_crossfade(&mut self, progress: f32) {
+        self.crossfade = progress.clamp(0.0, 1.0);
+        if self.crossfade >= 1.0 {
+            self.release_previous_tree();
+        }
+    }
+
+    /// Whether a cross-fade is in progress
+    pub fn is_crossfading(&self) -> bool {
+        self.previous_tree.is_some()
+    }
+
+    fn release_previous_tree(&mut self) {
+        if let Some(previous) = self.previous_tree.take() {
+            let gl = &self.ctx.gl;
+            gl.delete_vertex_array(Some(&previous.vao));
+            gl.delete_buffer(previous.vertex_buffer.as_ref());
+            gl.delete_buffer(previous.index_buffer.as_ref());
+        }
+        self.crossfade = 1.0;
+    }
+
+    /// Upload instanced leaves: the shared blade `mesh` and per-leaf data
+    /// (`LEAF_INSTANCE_FLOATS` per leaf, see `LeafInstance::to_array`)
+    pub fn upload_leaves(&mut self, mesh: &Mesh, instances: &[f32]) -> Result<(), String> {
+        self.check_live()?;
+        let leaves = self.leaves.take();
+        self.release_instanced(leaves);
+        if instances.is_empty() {
+            return Ok(());
+        }
+        // Blade shape, from the tree vertex layout: position and UV; per
+        // instance: offset(3) + side(3) + up(3) + hue(1) + glow(1)
+        self.leaves = Some(self.create_instanced_buffers(
+            mesh,
+            &[(0, 3, 0), (2, 2, 24)],
+            instances,
+            LEAF_INSTANCE_FLOATS,
+            &[(7, 3, 0), (8, 3, 12), (9, 3, 24), (10, 1, 36), (11, 1, 40)],
+        )?);
+        Ok(())
+    }
+
+    /// Upload instanced twigs: the canonical twig `mesh` and per-twig data
+    /// (`TWIG_INSTANCE_FLOATS` per twig, see `TwigInstance::to_array`)
+    pub fn upload_twigs(&mut self, mesh: &Mesh, instances: &[f32]) -> Result<(), String> {
+        self.check_live()?;
+        let twigs = self.twigs.take();
+        self.release_instanced(twigs);
+        if instances.is_empty() {
+            return Ok(());
+        }
+        // Twig shape: position, normal, UV and tangent; per instance:
+        // base(3) + axis(3) + side(3) + shape(3) + visual(4) + color(3)
+        self.twigs = Some(self.create_instanced_buffers(
+            mesh,
+            &[(0, 3, 0), (1, 3, 12), (2, 2, 24), (3, 3, 48)],
+            instances,
+            TWIG_INSTANCE_FLOATS,
+            &[(4, 3, 0), (5, 3, 12), (6, 3, 24), (7, 3, 36), (8, 4, 48), (9, 3, 64)],
+        )?);
+        Ok(())
+    }
+
+    /// Upload instanced fruit: the shared sphere `mesh` and per-fruit data
+    /// (`FRUIT_INSTANCE_FLOATS` per fruit, see `FruitInstance::to_array`)
+    pub fn upload_fruit(&mut self, mesh: &Mesh, instances: &[f32]) -> Result<(), String> {
+        self.check_live()?;
+        let fruit = self.fruit.take();
+        self.release_instanced(fruit);
+        if instances.is_empty() {
+            return Ok(());
+        }
+        // Sphere: position and normal; per instance: center(3) + radius(1)
+        // + hue(1) + glow(1)
+        self.fruit = Some(self.create_instanced_buffers(
+            mesh,
+            &[(0, 3, 0), (1, 3, 12)],
+            instances,
+            FRUIT_INSTANCE_FLOATS,
+            &[(7, 3, 0), (8, 1, 12), (9, 1, 16), (10, 1, 20)],
+        )?);
+        Ok(())
+    }
+
+    /// Buffers for `mesh` drawn once per instance; attributes are
+    /// `(location, size, byte offset)` into the tree vertex layout and into
+    /// each instance's `instance_floats`
+    fn create_instanced_buffers(
+        &self,
+        mesh: &Mesh,
+        mesh_attributes: &[(u32, i32, i32)],
+        instances: &[f32],
+        instance_floats: usize,
+        instance_attributes: &[(u32, i32, i32)],
+    ) -> Result<InstancedBuffers, String> {
+        let gl = &self.ctx.gl;
+
+        let vao = self.ctx.create_vao()?;
+        gl.bind_vertex_array(Some(&vao));
+
+        let vertex_buffer = self.ctx.create_buffer_f32(&mesh.vertex_data(), WebGl2RenderingContext::STATIC_DRAW)?;
+        let (index_buffer, index_type) =
+            self.ctx.create_index_buffer(&mesh.packed_indices(), WebGl2RenderingContext::STATIC_DRAW)?;
+        let instance_buffer = self.ctx.create_buffer_f32(instances, WebGl2RenderingContext::STATIC_DRAW)?;
+
+        let stride = (VERTEX_FLOATS * 4) as i32;
+        gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(&vertex_buffer));
+        gl.bind_buffer(WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER, Some(&index_buffer));
+        for &(location, size, offset) in mesh_attributes {
+            gl.enable_vertex_attrib_array(location);
+            gl.vertex_attrib_pointer_with_i32(location, size, WebGl2RenderingContext::FLOAT, false, stride, offset);
+        }
+
+        // Advancing once per instance
+        let stride = (instance_floats * 4) as i32;
+        gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(&instance_buffer));
+        for &(location, size, offset) in instance_attributes {
+            gl.enable_vertex_attrib_array(location);
+            gl.vertex_attrib_pointer_with_i32(location, size, WebGl2RenderingContext::FLOAT, false, stride, offset);
+            gl.vertex_attrib_divisor(location, 1);
+        }
+
+        gl.bind_vertex_array(None);
+
+        Ok(InstancedBuffers {
+            vao,
+            vertex_buffer,
+            index_buffer,
+            instance_buffer,
+            index_count: mesh.indices.len() as i32,
+            index_type,
+            instance_count: (instances.len() / instance_floats) as i32,
+            stats: mesh.stats().with_instances(instances.len() / instance_floats, instance_floats),
+        })
+    }
+
+    fn release_instanced(&self, buffers: Option<InstancedBuffers>) {
+        if let Some(buffers) = buffers {
+            let gl = &self.ctx.gl;
+            gl.delete_vertex_array(Some(&buffers.vao));
+            gl.delete_buffer(Some(&buffers.vertex_buffer));
+            gl.delete_buffer(Some(&buffers.index_buffer));
+            gl.delete_buffer(Some(&buffers.instance_buffer));
+        }
+    }
+
+    /// Upload the ground disc, drawn in its own pass under the tree
+    pub fn upload_ground(&mut self, mesh: &Mesh) -> Result<(), String> {
+        self.check_live()?;
+        self.release_ground();
+        self.ground = Some(self.create_tree_buffers(mesh)?);
+        Ok(())
+    }
+
+    fn release_ground(&mut self) {
+        if let Some(ground) = self.ground.take() {
+            let gl = &self.ctx.gl;
+            gl.delete_vertex_array(Some(&ground.vao));
+            gl.delete_buffer(ground.vertex_buffer.as_ref());
+            gl.delete_buffer(ground.index_buffer.as_ref());
+        }
+    }
+
+    /// Upload the memorial rings, drawn additively after the tree
+    pub fn upload_memorials(&mut self, mesh: &Mesh) -> Result<(), String> {
+        self.check_live()?;
+        if let Some(memorials) = self.memorials.take() {
+            let gl = &self.ctx.gl;
+            gl.delete_vertex_array(Some(&memorials.vao));
+            gl.delete_buffer(memorials.vertex_buffer.as_ref());
+            gl.delete_buffer(memorials.index_buffer.as_ref());
+        }
+        if !mesh.indices.is_empty() {
+            self.memorials = Some(self.create_tree_buffers(mesh)?);
+        }
+        Ok(())
+    }
+
+    /// Upload the signed distance field atlas the labels sample, one byte
+    /// per pixel, rows top first
+    pub fn upload_label_atlas(&mut self, pixels: &[u8], width: i32, height: i32) -> Result<(), String> {
+        self.check_live()?;
+        let atlas = self.ctx.create_texture_r8(width, height, pixels)?;
+        if let Some(previous) = self.label_atlas.replace(atlas) {
+            self.ctx.gl.delete_texture(Some(&previous));
+        }
+        Ok(())
+    }
 
+    /// Upload the name labels, drawn over the tree once an atlas is uploaded
+    pub fn upload_labels(&mut self, mesh: &Mesh) -> Result<(), String> {
+        self.check_live()?;
+        if let Some(labels) = self.labels.take() {
+            let gl = &self.ctx.gl;
+            gl.delete_vertex_array(Some(&labels.vao));
+            gl.delete_buffer(labels.vertex_buffer.as_ref());
+            gl.delete_buffer(labels.index_buffer.as_ref());
+        }
+        if !mesh.indices.is_empty() {
+            self.labels = Some(self.create_tree_buffers(mesh)?);
+        }
         Ok(())
     }
 
     /// Upload particle data to GPU
-    /// Format: position(3) + size(1) + alpha(1) + color(3) = 8 floats per particle
+    /// Format: position(3) + size(1) + alpha(1) + color(3) + palette slot(1)
+    /// = `PARTICLE_FLOATS` per particle
     pub fn upload_particles(&mut self, data: &[f32]) -> Result<(), String> {
+        self.check_live()?;
         let gl = &self.ctx.gl;
 
         let vao = self.ctx.create_vao()?;
@@ -242,7 +1419,7 @@ impl RenderPipeline {
 
         let buffer = self.ctx.create_buffer_f32(data, WebGl2RenderingContext::DYNAMIC_DRAW)?;
 
-        let stride = 8 * 4;
+        let stride = (PARTICLE_FLOATS * 4) as i32;
         gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(&buffer));
 
         // Position
@@ -261,11 +1438,15 @@ impl RenderPipeline {
         gl.enable_vertex_attrib_array(3);
         gl.vertex_attrib_pointer_with_i32(3, 3, WebGl2RenderingContext::FLOAT, false, stride, 20);
 
+        // Palette slot
+        gl.enable_vertex_attrib_array(4);
+        gl.vertex_attrib_pointer_with_i32(4, 1, WebGl2RenderingContext::FLOAT, false, stride, 32);
+
         gl.bind_vertex_array(None);
 
         self.particle_vao = Some(vao);
         self.particle_buffer = Some(buffer);
-        self.particle_count = (data.len() / 8) as i32;
+        self.particle_count = (data.len() / PARTICLE_FLOATS) as i32;
 
         Ok(())
     }
@@ -284,98 +1465,403 @@ impl RenderPipeline {
                 );
             }
             gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, None);
-            self.particle_count = (data.len() / 8) as i32;
+            self.particle_count = (data.len() / PARTICLE_FLOATS) as i32;
         }
     }
 
     /// Render a frame
     pub fn render(&self, time: f32) {
+        if self.disposed {
+            return;
+        }
+        let aspect = self.width as f32 / self.height as f32;
+        let projection = Mat4::perspective(self.fov, aspect, NEAR_PLANE, FAR_PLANE);
+        self.render_with_projection(time, &projection, [0.0, 0.0, 1.0, 1.0]);
+    }
+
+    /// Render a frame with an explicit projection
+    ///
+    /// `viewport_rect` is the region (x, y, width, height in 0..1, y up) of the
+    /// full image this frame covers, so screen-space effects line up across tiles.
+    fn render_with_projection(&self, time: f32, projection: &Mat4, viewport_rect: [f32; 4]) {
         let gl = &self.ctx.gl;
 
         // Calculate matrices
-        let aspect = self.width as f32 / self.height as f32;
-        let projection = Mat4::perspective(self.fov, aspect, 0.1, 100.0);
         let view = Mat4::look_at(self.camera_position, self.camera_target, Vec3::UP);
         let model = Mat4::identity();
 
-        // === Pass 1: Render scene to framebuffer ===
-        gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, self.scene_fbo.as_ref());
-        self.ctx.viewport(0, 0, self.width, self.height);
-        self.ctx.clear(0.02, 0.03, 0.05, 1.0);
+        // The tree mirrored in the ground, for the ground pass to sample
+        if self.reflection_strength > 0.0 && self.ground.is_some() {
+            self.render_reflection(&view, projection, time);
+        }
+
+        self.upload_camera(&view, projection, self.camera_position, time);
+
+        // === Pass 1: Render scene to framebuffer (multisampled when MSAA is on) ===
+        gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, self.msaa_fbo.as_ref().or(self.scene_fbo.as_ref()));
+        self.ctx.viewport(0, 0, self.render_width, self.render_height);
+        let background = self.theme.background;
+        self.ctx.clear(background.x, background.y, background.z, 1.0);
+
+        // Sky behind everything, before depth testing starts
+        if self.sky {
+            gl.use_program(Some(&self.sky_program));
+            self.ctx.uniform_matrix4fv(self.sky_uniforms.view.as_ref(), view.as_slice());
+            self.ctx.uniform_matrix4fv(self.sky_uniforms.projection.as_ref(), projection.as_slice());
+            self.ctx.uniform_1f(self.sky_uniforms.time.as_ref(), time);
+            self.ctx.uniform_3f(self.sky_uniforms.background.as_ref(), background.x, background.y, background.z);
+            let horizon = self.fog_color().scale(0.3);
+            self.ctx.uniform_3f(self.sky_uniforms.horizon.as_ref(), horizon.x, horizon.y, horizon.z);
+            self.ctx.uniform_3f(
+                self.sky_uniforms.moon_direction.as_ref(),
+                MOON_DIRECTION.x,
+                MOON_DIRECTION.y,
+                MOON_DIRECTION.z,
+            );
+
+            gl.draw_arrays(WebGl2RenderingContext::TRIANGLES, 0, 3);
+        }
+
         self.ctx.enable_depth_test();
 
-        // Render tree
-        if self.tree_vao.is_some() {
-            gl.use_program(Some(&self.tree_program));
+        // Ground first, in its own pass; it never cross-fades with the tree
+        if let Some(ground) = &self.ground {
+            gl.use_program(Some(&self.ground_program));
 
-            self.ctx.uniform_matrix4fv(self.tree_uniforms.model.as_ref(), model.as_slice());
-            self.ctx.uniform_matrix4fv(self.tree_uniforms.view.as_ref(), view.as_slice());
-            self.ctx.uniform_matrix4fv(self.tree_uniforms.projection.as_ref(), projection.as_slice());
-            self.ctx.uniform_1f(self.tree_uniforms.time.as_ref(), time);
+            self.ctx.uniform_matrix4fv(self.ground_uniforms.view.as_ref(), view.as_slice());
+            self.ctx.uniform_matrix4fv(self.ground_uniforms.projection.as_ref(), projection.as_slice());
+            self.ctx.uniform_1f(self.ground_uniforms.time.as_ref(), time);
             self.ctx.uniform_3f(
-                self.tree_uniforms.camera_pos.as_ref(),
+                self.ground_uniforms.camera_pos.as_ref(),
                 self.camera_position.x,
                 self.camera_position.y,
                 self.camera_position.z,
             );
-            self.ctx.uniform_3f(self.tree_uniforms.base_color.as_ref(), 0.2, 0.8, 0.6);
-            self.ctx.uniform_1f(self.tree_uniforms.ambient_strength.as_ref(), 0.3);
+            self.ctx.uniform_3f(
+                self.ground_uniforms.tree_origin.as_ref(),
+                self.tree_origin.x,
+                self.tree_origin.y,
+                self.tree_origin.z,
+            );
+            let fog = self.fog_color();
+            self.ctx.uniform_3f(self.ground_uniforms.fog_color.as_ref(), fog.x, fog.y, fog.z);
+            self.ctx.uniform_1f(self.ground_uniforms.fog_density.as_ref(), self.fog_density);
+            gl.active_texture(WebGl2RenderingContext::TEXTURE0);
+            gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, self.reflection_texture.as_ref());
+            self.ctx.uniform_1i(self.ground_uniforms.reflection.as_ref(), 0);
+            self.ctx.uniform_1f(self.ground_uniforms.reflection_strength.as_ref(), self.reflection_strength);
+            self.ctx.uniform_2f(
+                self.ground_uniforms.screen_size.as_ref(),
+                self.render_width as f32,
+                self.render_height as f32,
+            );
 
-            gl.bind_vertex_array(self.tree_vao.as_ref());
+            gl.bind_vertex_array(Some(&ground.vao));
+            gl.draw_elements_with_i32(
+                WebGl2RenderingContext::TRIANGLES,
+                ground.index_count,
+                ground.index_type,
+                0,
+            );
+        }
+
+        // Render tree, coarser the further the camera backs away
+        let (tree_vao, tree_index_count, tree_index_type) = self.tree_lod();
+        let (tree_program, tree_uniforms) = self.tree_shading(TreeVertices::Mesh);
+        if tree_vao.is_some() {
+            gl.use_program(Some(tree_program));
+            self.set_tree_uniforms(tree_uniforms, &model);
+
+            gl.bind_vertex_array(tree_vao);
             gl.draw_elements_with_i32(
                 WebGl2RenderingContext::TRIANGLES,
-                self.tree_index_count,
-                WebGl2RenderingContext::UNSIGNED_INT,
+                tree_index_count,
+                tree_index_type,
                 0,
             );
         }
 
+        // Twigs, shaded like the tree, at every level of detail
+        if let Some(twigs) = &self.twigs {
+            let (program, uniforms) = self.tree_shading(TreeVertices::Twigs);
+            gl.use_program(Some(program));
+            self.set_tree_uniforms(uniforms, &model);
+
+            gl.bind_vertex_array(Some(&twigs.vao));
+            gl.draw_elements_instanced_with_i32(
+                WebGl2RenderingContext::TRIANGLES,
+                twigs.index_count,
+                twigs.index_type,
+                0,
+                twigs.instance_count,
+            );
+        }
+
+        // Leaves share the tree's depth pass; the shader lights both faces
+        if let Some(leaves) = &self.leaves {
+            gl.use_program(Some(&self.leaf_program));
+
+            self.ctx.uniform_matrix4fv(self.leaf_uniforms.view.as_ref(), view.as_slice());
+            self.ctx.uniform_matrix4fv(self.leaf_uniforms.projection.as_ref(), projection.as_slice());
+            self.ctx.uniform_1f(self.leaf_uniforms.time.as_ref(), time);
+            self.ctx.uniform_3f(
+                self.leaf_uniforms.camera_pos.as_ref(),
+                self.camera_position.x,
+                self.camera_position.y,
+                self.camera_position.z,
+            );
+            self.ctx.uniform_3f(
+                self.leaf_uniforms.tree_origin.as_ref(),
+                self.tree_origin.x,
+                self.tree_origin.y,
+                self.tree_origin.z,
+            );
+            self.ctx.uniform_1f(self.leaf_uniforms.fade.as_ref(), self.crossfade);
+            let [low_hue, high_hue] = self.season.leaf_hues;
+            self.ctx.uniform_2f(self.leaf_uniforms.leaf_hues.as_ref(), low_hue, high_hue);
+            self.ctx.uniform_1f(self.leaf_uniforms.leaf_saturation.as_ref(), self.season.leaf_saturation);
+
+            gl.bind_vertex_array(Some(&leaves.vao));
+            gl.draw_elements_instanced_with_i32(
+                WebGl2RenderingContext::TRIANGLES,
+                leaves.index_count,
+                leaves.index_type,
+                0,
+                leaves.instance_count,
+            );
+        }
+
+        // Fruit hangs among the leaves
+        if let Some(fruit) = &self.fruit {
+            gl.use_program(Some(&self.fruit_program));
+
+            self.ctx.uniform_matrix4fv(self.fruit_uniforms.view.as_ref(), view.as_slice());
+            self.ctx.uniform_matrix4fv(self.fruit_uniforms.projection.as_ref(), projection.as_slice());
+            self.ctx.uniform_1f(self.fruit_uniforms.time.as_ref(), time);
+            self.ctx.uniform_3f(
+                self.fruit_uniforms.camera_pos.as_ref(),
+                self.camera_position.x,
+                self.camera_position.y,
+                self.camera_position.z,
+            );
+            self.ctx.uniform_1f(self.fruit_uniforms.fade.as_ref(), self.crossfade);
+
+            gl.bind_vertex_array(Some(&fruit.vao));
+            gl.draw_elements_instanced_with_i32(
+                WebGl2RenderingContext::TRIANGLES,
+                fruit.index_count,
+                fruit.index_type,
+                0,
+                fruit.instance_count,
+            );
+        }
+
+        // Memorial rings glow in as the growth animation finishes
+        let fade = memorial_fade(self.growth_progress);
+        if let (Some(memorials), true) = (&self.memorials, fade > 0.0) {
+            let (program, uniforms) = self.tree_shading(TreeVertices::Mesh);
+            gl.use_program(Some(program));
+            self.set_tree_uniforms(uniforms, &model);
+            self.ctx.uniform_1f(uniforms.fade.as_ref(), self.crossfade * fade);
+            gl.depth_mask(false);
+            self.ctx.enable_additive_blending();
+
+            gl.bind_vertex_array(Some(&memorials.vao));
+            gl.draw_elements_with_i32(
+                WebGl2RenderingContext::TRIANGLES,
+                memorials.index_count,
+                memorials.index_type,
+                0,
+            );
+
+            gl.depth_mask(true);
+            gl.disable(WebGl2RenderingContext::BLEND);
+        }
+
+        // Outgoing tree fades out on top, without occluding the incoming one
+        if let Some(previous) = &self.previous_tree {
+            gl.use_program(Some(tree_program));
+            self.ctx.uniform_1f(tree_uniforms.fade.as_ref(), 1.0 - self.crossfade);
+            gl.depth_mask(false);
+            self.ctx.enable_additive_blending();
+
+            gl.bind_vertex_array(Some(&previous.vao));
+            gl.draw_elements_with_i32(
+                WebGl2RenderingContext::TRIANGLES,
+                previous.index_count,
+                previous.index_type,
+                0,
+            );
+
+            gl.depth_mask(true);
+            gl.disable(WebGl2RenderingContext::BLEND);
+        }
+
+        // Names appear with the memorial rings, behind the branches in front
+        // of them, and kept out of the bloom
+        if let (Some(labels), Some(atlas), true) = (&self.labels, &self.label_atlas, fade > 0.0) {
+            gl.use_program(Some(&self.label_program));
+            self.ctx.uniform_1f(self.label_uniforms.fade.as_ref(), self.crossfade * fade);
+            gl.active_texture(WebGl2RenderingContext::TEXTURE0);
+            gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(atlas));
+            self.ctx.uniform_1i(self.label_uniforms.atlas.as_ref(), 0);
+            gl.depth_mask(false);
+            self.ctx.enable_unbloomed_blending();
+
+            gl.bind_vertex_array(Some(&labels.vao));
+            gl.draw_elements_with_i32(
+                WebGl2RenderingContext::TRIANGLES,
+                labels.index_count,
+                labels.index_type,
+                0,
+            );
+
+            gl.depth_mask(true);
+            gl.disable(WebGl2RenderingContext::BLEND);
+        }
+
         // Render particles
         if self.particle_vao.is_some() && self.particle_count > 0 {
             gl.use_program(Some(&self.particle_program));
             gl.disable(WebGl2RenderingContext::DEPTH_TEST);
             self.ctx.enable_additive_blending();
 
-            self.ctx.uniform_matrix4fv(self.particle_uniforms.view.as_ref(), view.as_slice());
-            self.ctx.uniform_matrix4fv(self.particle_uniforms.projection.as_ref(), projection.as_slice());
-            self.ctx.uniform_1f(self.particle_uniforms.time.as_ref(), time);
+            for (location, color) in self.particle_uniforms.palette.iter().zip(self.theme.particle_palette) {
+                self.ctx.uniform_3f(location.as_ref(), color.x, color.y, color.z);
+            }
+            self.ctx.uniform_3f(
+                self.particle_uniforms.tree_origin.as_ref(),
+                self.tree_origin.x,
+                self.tree_origin.y,
+                self.tree_origin.z,
+            );
+            let fog = self.fog_color();
+            self.ctx.uniform_3f(self.particle_uniforms.fog_color.as_ref(), fog.x, fog.y, fog.z);
+            self.ctx.uniform_1f(self.particle_uniforms.fog_density.as_ref(), self.fog_density);
+            self.ctx.uniform_1f(self.particle_uniforms.fog_height_falloff.as_ref(), self.fog_height_falloff);
 
             gl.bind_vertex_array(self.particle_vao.as_ref());
             gl.draw_arrays(WebGl2RenderingContext::POINTS, 0, self.particle_count);
         }
 
-        // === Pass 2: Extract bloom ===
-        gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, self.bloom_fbos[0].as_ref());
-        self.ctx.viewport(0, 0, self.width / 2, self.height / 2);
         gl.disable(WebGl2RenderingContext::DEPTH_TEST);
         gl.disable(WebGl2RenderingContext::BLEND);
 
+        // Resolve the multisampled scene, depth included for the occlusion pass
+        if let Some(msaa) = &self.msaa_fbo {
+            gl.bind_framebuffer(WebGl2RenderingContext::READ_FRAMEBUFFER, Some(msaa));
+            gl.bind_framebuffer(WebGl2RenderingContext::DRAW_FRAMEBUFFER, self.scene_fbo.as_ref());
+            gl.blit_framebuffer(
+                0, 0, self.render_width, self.render_height,
+                0, 0, self.render_width, self.render_height,
+                WebGl2RenderingContext::COLOR_BUFFER_BIT | WebGl2RenderingContext::DEPTH_BUFFER_BIT,
+                WebGl2RenderingContext::NEAREST,
+            );
+            gl.bind_framebuffer(WebGl2RenderingContext::READ_FRAMEBUFFER, None);
+        }
+
+        // === Selection mask: the selected branch's full-detail triangles ===
+        let outlined = self.tree_vao.is_some() && !self.outline_ranges.is_empty();
+        if outlined {
+            gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, self.outline_fbo.as_ref());
+            // Keep the scene depth it shares: clear color only and test
+            // against it without writing
+            gl.clear_color(0.0, 0.0, 0.0, 0.0);
+            gl.clear(WebGl2RenderingContext::COLOR_BUFFER_BIT);
+            gl.enable(WebGl2RenderingContext::DEPTH_TEST);
+            gl.depth_func(WebGl2RenderingContext::LEQUAL);
+            gl.depth_mask(false);
+            gl.use_program(Some(&self.outline_mask_program));
+            self.set_tree_uniforms(&self.outline_mask_uniforms, &model);
+
+            gl.bind_vertex_array(self.tree_vao.as_ref());
+            let index_size = if self.tree_index_type == WebGl2RenderingContext::UNSIGNED_INT { 4 } else { 2 };
+            for range in &self.outline_ranges {
+                gl.draw_elements_with_i32(
+                    WebGl2RenderingContext::TRIANGLES,
+                    range.len() as i32,
+                    self.tree_index_type,
+                    range.start as i32 * index_size,
+                );
+            }
+            gl.depth_mask(true);
+            gl.depth_func(WebGl2RenderingContext::LESS);
+            gl.disable(WebGl2RenderingContext::DEPTH_TEST);
+        }
+
+        self.ctx.viewport(0, 0, self.render_width / 2, self.render_height / 2);
+
+        // === Ambient occlusion from the scene depth ===
+        if self.ssao_strength > 0.0 {
+            gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, self.ssao_fbo.as_ref());
+            gl.use_program(Some(&self.ssao_program));
+            gl.active_texture(WebGl2RenderingContext::TEXTURE0);
+            gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, self.scene_depth.as_ref());
+            self.ctx.uniform_1i(self.ssao_uniforms.depth.as_ref(), 0);
+            let m = projection.as_slice();
+            self.ctx.uniform_4f(self.ssao_uniforms.proj_params.as_ref(), m[0], m[5], m[8], m[9]);
+            self.ctx.uniform_2f(self.ssao_uniforms.depth_params.as_ref(), m[10], m[14]);
+            self.ctx.uniform_1f(self.ssao_uniforms.radius.as_ref(), self.ssao_radius);
+
+            gl.draw_arrays(WebGl2RenderingContext::TRIANGLES, 0, 3);
+        }
+
+        // === Pass 2: Extract bloom ===
+        gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, self.bloom_fbos[0].as_ref());
+
         gl.use_program(Some(&self.bloom_extract_program));
         gl.active_texture(WebGl2RenderingContext::TEXTURE0);
         gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, self.scene_texture.as_ref());
         self.ctx.uniform_1i(self.post_uniforms.texture.as_ref(), 0);
-        self.ctx.uniform_1f(self.post_uniforms.threshold.as_ref(), 0.5);
+        self.ctx.uniform_1f(self.post_uniforms.threshold.as_ref(), self.bloom_threshold);
 
         gl.draw_arrays(WebGl2RenderingContext::TRIANGLES, 0, 3);
 
-        // === Pass 3: Blur horizontally ===
-        gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, self.bloom_fbos[1].as_ref());
+        // === Passes 3 and 4: Blur horizontally, then vertically, repeatedly ===
         gl.use_program(Some(&self.blur_program));
-        gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, self.bloom_textures[0].as_ref());
-        self.ctx.uniform_2f(self.post_uniforms.direction.as_ref(), 1.0, 0.0);
+        for _ in 0..self.bloom_passes {
+            gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, self.bloom_fbos[1].as_ref());
+            gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, self.bloom_textures[0].as_ref());
+            self.ctx.uniform_2f(self.post_uniforms.direction.as_ref(), 1.0, 0.0);
 
-        gl.draw_arrays(WebGl2RenderingContext::TRIANGLES, 0, 3);
+            gl.draw_arrays(WebGl2RenderingContext::TRIANGLES, 0, 3);
 
-        // === Pass 4: Blur vertically ===
-        gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, self.bloom_fbos[0].as_ref());
-        gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, self.bloom_textures[1].as_ref());
-        self.ctx.uniform_2f(self.post_uniforms.direction.as_ref(), 0.0, 1.0);
+            gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, self.bloom_fbos[0].as_ref());
+            gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, self.bloom_textures[1].as_ref());
+            self.ctx.uniform_2f(self.post_uniforms.direction.as_ref(), 0.0, 1.0);
 
-        gl.draw_arrays(WebGl2RenderingContext::TRIANGLES, 0, 3);
+            gl.draw_arrays(WebGl2RenderingContext::TRIANGLES, 0, 3);
+        }
 
-        // === Pass 5: Composite ===
-        gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, None);
-        self.ctx.viewport(0, 0, self.width, self.height);
+        // === Light shafts: the blurred glow smeared out from the light ===
+        let light_uv = projection.mul(&view).project_point(self.light_position)
+            .map(|ndc| (ndc.x * 0.5 + 0.5, ndc.y * 0.5 + 0.5));
+        let ray_strength = match light_uv {
+            Some((light_u, light_v)) if self.light_shafts > 0.0 => {
+                gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, self.bloom_fbos[1].as_ref());
+                gl.use_program(Some(&self.god_ray_program));
+                gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, self.bloom_textures[0].as_ref());
+                self.ctx.uniform_1i(self.post_uniforms.ray_texture.as_ref(), 0);
+                self.ctx.uniform_2f(self.post_uniforms.light_pos.as_ref(), light_u, light_v);
+                self.ctx.uniform_1f(self.post_uniforms.ray_density.as_ref(), GOD_RAY_DENSITY);
+                self.ctx.uniform_1f(self.post_uniforms.ray_decay.as_ref(), GOD_RAY_DECAY);
+
+                gl.draw_arrays(WebGl2RenderingContext::TRIANGLES, 0, 3);
+                self.light_shafts
+            }
+            // Light behind the camera: no shafts to see
+            _ => 0.0,
+        };
+
+        // === Pass 5: Composite (into the FXAA input when FXAA is on) ===
+        let fxaa = self.antialiasing == Antialiasing::Fxaa;
+        if fxaa {
+            gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, self.post_fbo.as_ref());
+            self.ctx.viewport(0, 0, self.render_width, self.render_height);
+        } else {
+            gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, None);
+            self.ctx.viewport(0, 0, self.width, self.height);
+        }
 
         gl.use_program(Some(&self.composite_program));
 
@@ -387,19 +1873,285 @@ impl RenderPipeline {
         gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, self.bloom_textures[0].as_ref());
         self.ctx.uniform_1i(self.post_uniforms.bloom.as_ref(), 1);
 
-        self.ctx.uniform_1f(self.post_uniforms.bloom_strength.as_ref(), 0.8);
+        gl.active_texture(WebGl2RenderingContext::TEXTURE2);
+        gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, self.ssao_texture.as_ref());
+        self.ctx.uniform_1i(self.post_uniforms.ao.as_ref(), 2);
+        self.ctx.uniform_1f(self.post_uniforms.ao_strength.as_ref(), self.ssao_strength);
+
+        gl.active_texture(WebGl2RenderingContext::TEXTURE3);
+        gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, self.bloom_textures[1].as_ref());
+        self.ctx.uniform_1i(self.post_uniforms.rays.as_ref(), 3);
+        self.ctx.uniform_1f(self.post_uniforms.ray_strength.as_ref(), ray_strength);
+
+        self.ctx.uniform_1f(self.post_uniforms.bloom_strength.as_ref(), self.bloom_strength);
         self.ctx.uniform_1f(self.post_uniforms.vignette_strength.as_ref(), 0.4);
+        let [rect_x, rect_y, rect_w, rect_h] = viewport_rect;
+        self.ctx.uniform_4f(self.post_uniforms.viewport_rect.as_ref(), rect_x, rect_y, rect_w, rect_h);
+        self.ctx.uniform_1f(self.post_uniforms.exposure.as_ref(), self.exposure);
+        self.ctx.uniform_1i(self.post_uniforms.tone_mapping.as_ref(), self.tone_mapping.shader_index());
 
         gl.draw_arrays(WebGl2RenderingContext::TRIANGLES, 0, 3);
+
+        // === Selection outline over the composited frame ===
+        if outlined {
+            gl.use_program(Some(&self.outline_program));
+            self.ctx.enable_blending();
+            gl.active_texture(WebGl2RenderingContext::TEXTURE0);
+            gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, self.outline_texture.as_ref());
+            self.ctx.uniform_1i(self.post_uniforms.outline_mask.as_ref(), 0);
+            let color = self.outline_color;
+            self.ctx.uniform_3f(self.post_uniforms.outline_color.as_ref(), color.x, color.y, color.z);
+            self.ctx.uniform_1f(self.post_uniforms.outline_width.as_ref(), self.outline_width);
+
+            gl.draw_arrays(WebGl2RenderingContext::TRIANGLES, 0, 3);
+            gl.disable(WebGl2RenderingContext::BLEND);
+        }
+
+        // === Pass 6: FXAA to the canvas ===
+        if fxaa {
+            gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, None);
+            self.ctx.viewport(0, 0, self.width, self.height);
+
+            gl.use_program(Some(&self.fxaa_program));
+            gl.active_texture(WebGl2RenderingContext::TEXTURE0);
+            gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, self.post_texture.as_ref());
+            self.ctx.uniform_1i(self.post_uniforms.fxaa_texture.as_ref(), 0);
+
+            gl.draw_arrays(WebGl2RenderingContext::TRIANGLES, 0, 3);
+        }
+    }
+
+    /// Tree mesh for the camera's distance: its VAO, index count and type
+    fn tree_lod(&self) -> (Option<&WebGlVertexArrayObject>, i32, u32) {
+        let distance = self.camera_position.distance(&self.camera_target);
+        match lod_level(distance, self.lod_distances, self.tree_lods.len()) {
+            0 => (self.tree_vao.as_ref(), self.tree_index_count, self.tree_index_type),
+            level => {
+                let lod = &self.tree_lods[level - 1];
+                (Some(&lod.vao), lod.index_count, lod.index_type)
+            }
+        }
+    }
+
+    /// Draw the tree, twigs and leaves mirrored in the ground plane into the
+    /// reflection target, clipped where they'd show from below the ground
+    fn render_reflection(&self, view: &Mat4, projection: &Mat4, time: f32) {
+        let gl = &self.ctx.gl;
+        let ground_height = self.tree_origin.y;
+        let mirrored_view = view.mul(&Mat4::reflection_y(ground_height));
+        let mirrored_camera = Mat4::reflection_y(ground_height).transform_point(self.camera_position);
+        let model = Mat4::identity();
+
+        gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, self.reflection_fbo.as_ref());
+        self.ctx.viewport(0, 0, self.render_width / 2, self.render_height / 2);
+        self.ctx.clear(0.0, 0.0, 0.0, 0.0);
+        self.ctx.enable_depth_test();
+        self.upload_camera(&mirrored_view, projection, mirrored_camera, time);
+
+        // Tree and twigs, lit from the mirrored eye
+        let mirror_tree_uniforms = |uniforms: &TreeUniforms| {
+            self.set_tree_uniforms(uniforms, &model);
+            self.ctx.uniform_1f(uniforms.clip_height.as_ref(), ground_height);
+        };
+
+        let (tree_vao, tree_index_count, tree_index_type) = self.tree_lod();
+        if tree_vao.is_some() {
+            let (program, uniforms) = self.tree_shading(TreeVertices::Mesh);
+            gl.use_program(Some(program));
+            mirror_tree_uniforms(uniforms);
+
+            gl.bind_vertex_array(tree_vao);
+            gl.draw_elements_with_i32(WebGl2RenderingContext::TRIANGLES, tree_index_count, tree_index_type, 0);
+        }
+
+        if let Some(twigs) = &self.twigs {
+            let (program, uniforms) = self.tree_shading(TreeVertices::Twigs);
+            gl.use_program(Some(program));
+            mirror_tree_uniforms(uniforms);
+
+            gl.bind_vertex_array(Some(&twigs.vao));
+            gl.draw_elements_instanced_with_i32(
+                WebGl2RenderingContext::TRIANGLES,
+                twigs.index_count,
+                twigs.index_type,
+                0,
+                twigs.instance_count,
+            );
+        }
+
+        if let Some(leaves) = &self.leaves {
+            gl.use_program(Some(&self.leaf_program));
+            self.ctx.uniform_matrix4fv(self.leaf_uniforms.view.as_ref(), mirrored_view.as_slice());
+            self.ctx.uniform_matrix4fv(self.leaf_uniforms.projection.as_ref(), projection.as_slice());
+            self.ctx.uniform_1f(self.leaf_uniforms.time.as_ref(), time);
+            self.ctx.uniform_3f(self.leaf_uniforms.camera_pos.as_ref(), mirrored_camera.x, mirrored_camera.y, mirrored_camera.z);
+            self.ctx.uniform_3f(
+                self.leaf_uniforms.tree_origin.as_ref(),
+                self.tree_origin.x,
+                self.tree_origin.y,
+                self.tree_origin.z,
+            );
+            self.ctx.uniform_1f(self.leaf_uniforms.fade.as_ref(), self.crossfade);
+            let [low_hue, high_hue] = self.season.leaf_hues;
+            self.ctx.uniform_2f(self.leaf_uniforms.leaf_hues.as_ref(), low_hue, high_hue);
+            self.ctx.uniform_1f(self.leaf_uniforms.leaf_saturation.as_ref(), self.season.leaf_saturation);
+
+            gl.bind_vertex_array(Some(&leaves.vao));
+            gl.draw_elements_instanced_with_i32(
+                WebGl2RenderingContext::TRIANGLES,
+                leaves.index_count,
+                leaves.index_type,
+                0,
+                leaves.instance_count,
+            );
+        }
+
+        gl.disable(WebGl2RenderingContext::DEPTH_TEST);
     }
 
     /// Resize the render pipeline
     pub fn resize(&mut self, width: i32, height: i32) -> Result<(), String> {
+        self.check_live()?;
         self.width = width;
         self.height = height;
         self.create_framebuffers()
     }
 
+    /// Free every GPU resource the pipeline holds: meshes, particles,
+    /// textures, framebuffers and shader programs
+    ///
+    /// Nothing is drawn afterwards and uploads fail; the WebGL context
+    /// itself is left to its owner. Disposing twice does nothing.
+    pub fn dispose(&mut self) {
+        if std::mem::replace(&mut self.disposed, true) {
+            return;
+        }
+        self.release_tree();
+        self.release_previous_tree();
+        self.release_tree_lods();
+        for buffers in [self.leaves.take(), self.twigs.take(), self.fruit.take()] {
+            self.release_instanced(buffers);
+        }
+        self.release_ground();
+        self.release_framebuffers();
+
+        let gl = &self.ctx.gl;
+        for buffers in [self.memorials.take(), self.labels.take()].into_iter().flatten() {
+            gl.delete_vertex_array(Some(&buffers.vao));
+            gl.delete_buffer(buffers.vertex_buffer.as_ref());
+            gl.delete_buffer(buffers.index_buffer.as_ref());
+        }
+        if let Some(atlas) = self.label_atlas.take() {
+            gl.delete_texture(Some(&atlas));
+        }
+        if let Some(vao) = self.particle_vao.take() {
+            gl.delete_vertex_array(Some(&vao));
+        }
+        gl.delete_buffer(self.particle_buffer.take().as_ref());
+        gl.delete_buffer(self.camera_buffer.take().as_ref());
+        self.particle_count = 0;
+        self.outline_ranges.clear();
+
+        let programs = [
+            &self.tree_program,
+            &self.baked_tree_program,
+            &self.twig_program,
+            &self.baked_twig_program,
+            &self.particle_program,
+            &self.leaf_program,
+            &self.fruit_program,
+            &self.ground_program,
+            &self.label_program,
+            &self.sky_program,
+            &self.ssao_program,
+            &self.bloom_extract_program,
+            &self.blur_program,
+            &self.god_ray_program,
+            &self.composite_program,
+            &self.fxaa_program,
+            &self.outline_mask_program,
+            &self.outline_program,
+        ];
+        for program in programs {
+            gl.delete_program(Some(program));
+        }
+    }
+
+    /// Read back the displayed frame as RGBA bytes, top row first
+    pub fn read_pixels(&self) -> Result<Vec<u8>, String> {
+        self.check_live()?;
+        let gl = &self.ctx.gl;
+        let mut pixels = vec![0u8; (self.width * self.height * 4) as usize];
+
+        gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, None);
+        gl.read_pixels_with_opt_u8_array(
+            0,
+            0,
+            self.width,
+            self.height,
+            WebGl2RenderingContext::RGBA,
+            WebGl2RenderingContext::UNSIGNED_BYTE,
+            Some(&mut pixels),
+        ).map_err(|e| format!("Failed to read pixels: {:?}", e))?;
+
+        Ok(flip_rows(&pixels, self.width as usize, self.height as usize))
+    }
+
+    /// Render a single frame of arbitrary size by stitching canvas-sized tiles
+    ///
+    /// Each tile is rendered with an off-center sub-frustum of the full view and
+    /// read back, so the result can exceed the maximum canvas size. Tiles
+    /// overlap (see [`poster_tiles`]) so post effects have no seams.
+    /// Returns RGBA bytes, top row first; posters over [`MAX_POSTER_PIXELS`]
+    /// are refused.
+    pub fn render_poster(&self, time: f32, width: i32, height: i32) -> Result<Vec<u8>, String> {
+        self.check_live()?;
+        if width <= 0 || height <= 0 {
+            return Err(format!("Invalid poster size {}x{}", width, height));
+        }
+        let pixels = (width as usize)
+            .checked_mul(height as usize)
+            .filter(|&pixels| pixels <= MAX_POSTER_PIXELS)
+            .ok_or_else(|| format!("Poster {}x{} is over the {} pixel limit", width, height, MAX_POSTER_PIXELS))?;
+
+        let (tile_w, tile_h) = (self.width, self.height);
+        let top = NEAR_PLANE * (self.fov / 2.0).tan();
+        let right = top * width as f32 / height as f32;
+
+        let mut poster = vec![0u8; pixels * 4];
+
+        for tile in poster_tiles(width, height, tile_w, tile_h) {
+            // Frustum for this tile's window (pixel y grows downward, frustum y upward)
+            let (x, y) = tile.window;
+            let left = -right + 2.0 * right * x as f32 / width as f32;
+            let tile_right = -right + 2.0 * right * (x + tile_w) as f32 / width as f32;
+            let tile_top = top - 2.0 * top * y as f32 / height as f32;
+            let bottom = top - 2.0 * top * (y + tile_h) as f32 / height as f32;
+            let projection = Mat4::frustum(left, tile_right, bottom, tile_top, NEAR_PLANE, FAR_PLANE);
+
+            let rect = [
+                x as f32 / width as f32,
+                1.0 - (y + tile_h) as f32 / height as f32,
+                tile_w as f32 / width as f32,
+                tile_h as f32 / height as f32,
+            ];
+
+            self.render_with_projection(time, &projection, rect);
+            let pixels = self.read_pixels()?;
+
+            // Copy the tile's own block, without its margins, into the poster
+            let (copy_w, copy_h) = (tile.size.0 as usize, tile.size.1 as usize);
+            let (src_x, src_y) = ((tile.at.0 - x) as usize, (tile.at.1 - y) as usize);
+            for row in 0..copy_h {
+                let src = ((src_y + row) * tile_w as usize + src_x) * 4;
+                let dst = ((tile.at.1 as usize + row) * width as usize + tile.at.0 as usize) * 4;
+                poster[dst..dst + copy_w * 4].copy_from_slice(&pixels[src..src + copy_w * 4]);
+            }
+        }
+
+        Ok(poster)
+    }
+
     /// Set growth animation progress (0.0 to 1.0)
     /// This affects shader-based visual effects like glow intensity
     pub fn set_growth_progress(&mut self, progress: f32) {
@@ -411,3 +2163,219 @@ impl RenderPipeline {
         self.growth_progress
     }
 }
+
+/// Canvas size versus the resolution actually rendered
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderStats {
+    pub canvas_width: i32,
+    pub canvas_height: i32,
+    pub render_width: i32,
+    pub render_height: i32,
+    pub max_texture_size: i32,
+    /// Scene and bloom targets are half-float
+    pub hdr: bool,
+}
+
+impl RenderStats {
+    /// Whether the scene is rendered below canvas resolution and upscaled
+    pub fn is_capped(&self) -> bool {
+        self.render_width < self.canvas_width || self.render_height < self.canvas_height
+    }
+
+    /// Serialize to a JSON object
+    pub fn to_json(&self) -> String {
+        format!(
+            r#"{{"canvas_width":{},"canvas_height":{},"render_width":{},"render_height":{},"max_texture_size":{},"hdr":{},"capped":{}}}"#,
+            self.canvas_width,
+            self.canvas_height,
+            self.render_width,
+            self.render_height,
+            self.max_texture_size,
+            self.hdr,
+            self.is_capped()
+        )
+    }
+}
+
+/// One tile of a poster, in poster pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PosterTile {
+    /// Top-left of the canvas-sized window rendered (it may hang off the
+    /// poster's edges)
+    pub window: (i32, i32),
+    /// Top-left of the block of the window copied into the poster
+    pub at: (i32, i32),
+    /// Size of that block
+    pub size: (i32, i32),
+}
+
+/// Cover a `width`x`height` poster with `tile_w`x`tile_h` windows, each
+/// block copied out of its window with a margin of up to
+/// [`POSTER_TILE_OVERLAP`] (a quarter of the tile at most) all round
+pub fn poster_tiles(width: i32, height: i32, tile_w: i32, tile_h: i32) -> Vec<PosterTile> {
+    let margin_x = POSTER_TILE_OVERLAP.min(tile_w / 4);
+    let margin_y = POSTER_TILE_OVERLAP.min(tile_h / 4);
+    let (step_x, step_y) = ((tile_w - 2 * margin_x).max(1), (tile_h - 2 * margin_y).max(1));
+    let mut tiles = Vec::new();
+    for y in (0..height).step_by(step_y as usize) {
+        for x in (0..width).step_by(step_x as usize) {
+            tiles.push(PosterTile {
+                window: (x - margin_x, y - margin_y),
+                at: (x, y),
+                size: (step_x.min(width - x), step_y.min(height - y)),
+            });
+        }
+    }
+    tiles
+}
+
+/// Scale `width`x`height` down, keeping the aspect ratio, so neither side
+/// exceeds `max_size` (non-positive means no limit)
+pub fn capped_resolution(width: i32, height: i32, max_size: i32) -> (i32, i32) {
+    let longest = width.max(height);
+    if max_size <= 0 || longest <= max_size {
+        return (width, height);
+    }
+    let scale = max_size as f32 / longest as f32;
+    (
+        ((width as f32 * scale) as i32).clamp(1, max_size),
+        ((height as f32 * scale) as i32).clamp(1, max_size),
+    )
+}
+
+/// Memorial ring visibility over the growth animation: they fade in over
+/// its last stretch, once the branches they ring have grown
+pub fn memorial_fade(growth_progress: f32) -> f32 {
+    let t = ((growth_progress - 0.8) / 0.2).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Tree detail level for a camera `distance`: 0 is the full mesh, then one
+/// level per threshold passed, up to the `available` coarser LODs
+pub fn lod_level(distance: f32, thresholds: [f32; 2], available: usize) -> usize {
+    thresholds.iter().take_while(|&&t| distance >= t).count().min(available)
+}
+
+/// Flip RGBA rows vertically (WebGL reads bottom row first)
+pub fn flip_rows(pixels: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let row_len = width * 4;
+    let mut flipped = Vec::with_capacity(pixels.len());
+    for row in (0..height).rev() {
+        flipped.extend_from_slice(&pixels[row * row_len..(row + 1) * row_len]);
+    }
+    flipped
+}
+
+/// Camera block contents in std140 layout: view and projection (column
+/// major), then the eye position with the time in its fourth float
+pub fn camera_block(view: &Mat4, projection: &Mat4, camera: Vec3, time: f32) -> [f32; CAMERA_BLOCK_FLOATS] {
+    let mut data = [0.0; CAMERA_BLOCK_FLOATS];
+    data[..16].copy_from_slice(view.as_slice());
+    data[16..32].copy_from_slice(projection.as_slice());
+    data[32..].copy_from_slice(&[camera.x, camera.y, camera.z, time]);
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_camera_block() {
+        let view = Mat4::translation(1.0, 2.0, 3.0);
+        let projection = Mat4::perspective(1.0, 1.5, 0.1, 100.0);
+        let data = camera_block(&view, &projection, Vec3::new(4.0, 5.0, 6.0), 7.5);
+        assert_eq!(&data[12..15], &[1.0, 2.0, 3.0]);
+        assert_eq!(&data[16..32], projection.as_slice());
+        // vec3 eye, then the time packed into the same 16-byte slot
+        assert_eq!(&data[32..], &[4.0, 5.0, 6.0, 7.5]);
+    }
+
+    #[test]
+    fn test_antialiasing_parse() {
+        assert_eq!(Antialiasing::parse(" FXAA ").unwrap(), Antialiasing::Fxaa);
+        assert_eq!(Antialiasing::parse("msaa").unwrap(), Antialiasing::Msaa);
+        assert_eq!(Antialiasing::parse("none").unwrap(), Antialiasing::Off);
+        assert!(Antialiasing::parse("ssaa").is_err());
+    }
+
+    #[test]
+    fn test_tone_mapping_parse() {
+        assert_eq!(ToneMapping::parse("ACES").unwrap(), ToneMapping::Aces);
+        assert_eq!(ToneMapping::parse(" reinhard").unwrap(), ToneMapping::Reinhard);
+        assert_eq!(ToneMapping::parse("filmic").unwrap().shader_index(), 3);
+        assert_eq!(ToneMapping::parse("none").unwrap().shader_index(), 0);
+        assert!(ToneMapping::parse("agx").is_err());
+    }
+
+    #[test]
+    fn test_memorial_fade() {
+        assert_eq!(memorial_fade(0.5), 0.0);
+        assert!(memorial_fade(0.9) > 0.0 && memorial_fade(0.9) < 1.0);
+        assert_eq!(memorial_fade(1.0), 1.0);
+    }
+
+    #[test]
+    fn test_flip_rows() {
+        // 1x2 image: bottom row red, top row blue
+        let pixels = [255, 0, 0, 255, 0, 0, 255, 255];
+        let flipped = flip_rows(&pixels, 1, 2);
+        assert_eq!(flipped, vec![0, 0, 255, 255, 255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_lod_level() {
+        assert_eq!(lod_level(10.0, [16.0, 24.0], 2), 0);
+        assert_eq!(lod_level(16.0, [16.0, 24.0], 2), 1);
+        assert_eq!(lod_level(30.0, [16.0, 24.0], 2), 2);
+        // Without coarser meshes uploaded the full one is kept
+        assert_eq!(lod_level(30.0, [16.0, 24.0], 0), 0);
+        assert_eq!(lod_level(30.0, [16.0, 24.0], 1), 1);
+    }
+
+    #[test]
+    fn test_poster_tiles_overlap() {
+        let (width, height, tile_w, tile_h) = (1000, 700, 400, 300);
+        let tiles = poster_tiles(width, height, tile_w, tile_h);
+
+        // Every poster pixel is copied exactly once...
+        let mut covered = vec![0; (width * height) as usize];
+        for tile in &tiles {
+            for y in tile.at.1..tile.at.1 + tile.size.1 {
+                for x in tile.at.0..tile.at.0 + tile.size.0 {
+                    covered[(y * width + x) as usize] += 1;
+                }
+            }
+        }
+        assert!(covered.iter().all(|&n| n == 1));
+        // ...from well inside the window rendered for it
+        for tile in &tiles {
+            assert_eq!(tile.at.0 - tile.window.0, POSTER_TILE_OVERLAP);
+            assert_eq!(tile.at.1 - tile.window.1, POSTER_TILE_OVERLAP);
+            assert!(tile.window.0 + tile_w - (tile.at.0 + tile.size.0) >= POSTER_TILE_OVERLAP);
+            assert!(tile.window.1 + tile_h - (tile.at.1 + tile.size.1) >= POSTER_TILE_OVERLAP);
+        }
+    }
+
+    #[test]
+    fn test_capped_resolution() {
+        assert_eq!(capped_resolution(1920, 1080, 4096), (1920, 1080));
+        assert_eq!(capped_resolution(7680, 4320, 4096), (4096, 2304));
+        assert_eq!(capped_resolution(4320, 7680, 4096), (2304, 4096));
+        assert_eq!(capped_resolution(800, 600, 0), (800, 600));
+    }
+
+    #[test]
+    fn test_render_stats_json() {
+        let stats = RenderStats {
+            canvas_width: 7680,
+            canvas_height: 4320,
+            render_width: 4096,
+            render_height: 2304,
+            max_texture_size: 4096,
+            hdr: true,
+        };
+        assert!(stats.is_capped());
+        assert!(stats.to_json().ends_with(r#""max_texture_size":4096,"hdr":true,"capped":true}"#));
+    }
+}
@@ -0,0 +1,125 @@
+use crate::math::Vec3;
+use crate::particles::FallingKind;
+
+/// Time of year the scene is dressed for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Season {
+    /// Pale new leaves and drifting blossom
+    Spring,
+    /// Deep green canopy; only the fireflies and orbs fly (the default)
+    #[default]
+    Summer,
+    /// Red and amber leaves falling through a warm haze
+    Autumn,
+    /// Frosted leaves, snow and a cold fog
+    Winter,
+}
+
+impl Season {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "spring" => Ok(Self::Spring),
+            "summer" => Ok(Self::Summer),
+            "autumn" | "fall" => Ok(Self::Autumn),
+            "winter" => Ok(Self::Winter),
+            other => Err(format!("Unknown season '{}'", other)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Spring => "spring",
+            Self::Summer => "summer",
+            Self::Autumn => "autumn",
+            Self::Winter => "winter",
+        }
+    }
+
+    /// What falls through the crown, if anything
+    pub fn particles(self) -> Option<FallingKind> {
+        match self {
+            Self::Spring => Some(FallingKind::Petals),
+            Self::Summer => None,
+            Self::Autumn => Some(FallingKind::Leaves),
+            Self::Winter => Some(FallingKind::Snow),
+        }
+    }
+
+    pub fn look(self) -> SeasonLook {
+        match self {
+            Self::Spring => SeasonLook {
+                leaf_hues: [0.22, 0.3],
+                leaf_saturation: 0.6,
+                fog_tint: Vec3::new(1.0, 0.95, 1.05),
+            },
+            Self::Summer => SeasonLook::default(),
+            Self::Autumn => SeasonLook {
+                leaf_hues: [0.02, 0.12],
+                leaf_saturation: 0.85,
+                fog_tint: Vec3::new(1.15, 1.0, 0.85),
+            },
+            Self::Winter => SeasonLook {
+                leaf_hues: [0.5, 0.55],
+                leaf_saturation: 0.15,
+                fog_tint: Vec3::new(0.9, 1.0, 1.2),
+            },
+        }
+    }
+}
+
+/// Season-dependent shading, passed to the shaders as uniforms
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeasonLook {
+    /// Leaf hue (0 to 1) low in the canopy and at the top
+    pub leaf_hues: [f32; 2],
+    pub leaf_saturation: f32,
+    /// Multiplies the theme's fog color
+    pub fog_tint: Vec3,
+}
+
+impl SeasonLook {
+    /// Blend toward `other` (`t` 0 to 1), for animated season changes
+    pub fn lerp(&self, other: &SeasonLook, t: f32) -> SeasonLook {
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+        SeasonLook {
+            leaf_hues: [mix(self.leaf_hues[0], other.leaf_hues[0]), mix(self.leaf_hues[1], other.leaf_hues[1])],
+            leaf_saturation: mix(self.leaf_saturation, other.leaf_saturation),
+            fog_tint: self.fog_tint.lerp(&other.fog_tint, t),
+        }
+    }
+}
+
+impl Default for SeasonLook {
+    fn default() -> Self {
+        Self {
+            leaf_hues: [0.28, 0.42],
+            leaf_saturation: 0.75,
+            fog_tint: Vec3::new(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_season_parse() {
+        assert_eq!(Season::parse(" Winter").unwrap(), Season::Winter);
+        assert_eq!(Season::parse("fall").unwrap(), Season::Autumn);
+        assert_eq!(Season::default().particles(), None);
+        assert!(Season::parse("monsoon").is_err());
+    }
+
+    #[test]
+    fn test_look_lerp() {
+        let summer = Season::Summer.look();
+        let winter = Season::Winter.look();
+        assert_eq!(summer.lerp(&winter, 0.0), summer);
+        let end = summer.lerp(&winter, 1.0);
+        assert!((end.leaf_hues[1] - winter.leaf_hues[1]).abs() < 1e-5);
+        assert!(end.fog_tint.distance(&winter.fog_tint) < 1e-5);
+        let halfway = summer.lerp(&winter, 0.5);
+        assert!((halfway.leaf_saturation - 0.45).abs() < 1e-5);
+    }
+}
@@ -8,11 +8,18 @@ layout(location = 2) in vec2 a_uv;
 layout(location = 3) in float a_glow;
 layout(location = 4) in float a_luminance;
 layout(location = 5) in float a_hue;
+layout(location = 6) in float a_opacity;
+layout(location = 7) in vec3 a_tangent;
+layout(location = 8) in vec3 a_color;
 
 uniform mat4 u_model;
-uniform mat4 u_view;
-uniform mat4 u_projection;
-uniform float u_time;
+// View, projection, eye and clock, shared with the other camera programs
+layout(std140) uniform Camera {
+    mat4 u_view;
+    mat4 u_projection;
+    vec3 u_camera_pos;
+    float u_time;
+};
 
 out vec3 v_position;
 out vec3 v_normal;
@@ -21,6 +28,9 @@ out vec2 v_uv;
 out float v_glow;
 out float v_luminance;
 out float v_hue;
+out float v_opacity;
+out vec3 v_tangent;
+out vec3 v_color;
 
 void main() {
     vec4 world_pos = u_model * vec4(a_position, 1.0);
@@ -32,10 +42,83 @@ void main() {
     v_world_position = world_pos.xyz;
     v_position = a_position;
     v_normal = mat3(u_model) * a_normal;
+    v_tangent = mat3(u_model) * a_tangent;
     v_uv = a_uv;
     v_glow = a_glow;
     v_luminance = a_luminance;
     v_hue = a_hue;
+    v_opacity = a_opacity;
+    v_color = a_color;
+
+    gl_Position = u_projection * u_view * world_pos;
+}
+"#;
+
+/// Vertex shader for instanced twigs: the canonical twig (unit radius, up Y
+/// from 0 to 1) tapered, oriented and placed per instance; its outputs match
+/// the tree vertex shader so either tree fragment shader can shade it
+pub const TWIG_VERTEX_SHADER: &str = r#"#version 300 es
+precision highp float;
+
+layout(location = 0) in vec3 a_position;
+layout(location = 1) in vec3 a_normal;
+layout(location = 2) in vec2 a_uv;
+layout(location = 3) in vec3 a_tangent;
+
+// Per instance
+layout(location = 4) in vec3 a_base;
+layout(location = 5) in vec3 a_axis;
+layout(location = 6) in vec3 a_side;
+layout(location = 7) in vec3 a_shape;  // start radius, end radius, base V
+layout(location = 8) in vec4 a_visual; // glow, luminance, hue, opacity
+layout(location = 9) in vec3 a_color;
+
+uniform mat4 u_model;
+// View, projection, eye and clock, shared with the other camera programs
+layout(std140) uniform Camera {
+    mat4 u_view;
+    mat4 u_projection;
+    vec3 u_camera_pos;
+    float u_time;
+};
+
+out vec3 v_position;
+out vec3 v_normal;
+out vec3 v_world_position;
+out vec2 v_uv;
+out float v_glow;
+out float v_luminance;
+out float v_hue;
+out float v_opacity;
+out vec3 v_tangent;
+out vec3 v_color;
+
+void main() {
+    float length_along = length(a_axis);
+    vec3 up = a_axis / max(length_along, 1e-5);
+    vec3 front = cross(a_side, up);
+    mat3 frame = mat3(a_side, up, front);
+
+    float radius = mix(a_shape.x, a_shape.y, a_position.y);
+    vec3 local = frame * vec3(a_position.x * radius, a_position.y * length_along, a_position.z * radius);
+    vec3 position = a_base + local;
+    vec3 normal = frame * a_normal;
+    vec4 world_pos = u_model * vec4(position, 1.0);
+
+    // Same breathing as the tree
+    float breath = sin(u_time * 0.5 + position.y * 0.5) * 0.02 * a_visual.y;
+    world_pos.xyz += normal * breath;
+
+    v_world_position = world_pos.xyz;
+    v_position = position;
+    v_normal = mat3(u_model) * normal;
+    v_tangent = mat3(u_model) * (frame * a_tangent);
+    v_uv = vec2(a_uv.x, a_shape.z + a_uv.y * length_along);
+    v_glow = a_visual.x;
+    v_luminance = a_visual.y;
+    v_hue = a_visual.z;
+    v_opacity = a_visual.w;
+    v_color = a_color;
 
     gl_Position = u_projection * u_view * world_pos;
 }
@@ -52,11 +135,28 @@ in vec2 v_uv;
 in float v_glow;
 in float v_luminance;
 in float v_hue;
-
-uniform vec3 u_camera_pos;
-uniform float u_time;
+in float v_opacity;
+in vec3 v_tangent;
+
+// View, projection, eye and clock, shared with the other camera programs
+layout(std140) uniform Camera {
+    mat4 u_view;
+    mat4 u_projection;
+    vec3 u_camera_pos;
+    float u_time;
+};
 uniform vec3 u_base_color;
 uniform float u_ambient_strength;
+uniform vec3 u_tree_origin;
+uniform float u_fade;
+// Theme: atmosphere color and base hue at the trunk (x) and tips (y)
+uniform vec3 u_fog_color;
+uniform vec2 u_hue_stops;
+// Fog amount (1 = default haze) and how fast it thins with height
+uniform float u_fog_density;
+uniform float u_fog_height_falloff;
+// Nothing below this height is drawn (the ground's mirror image clips here)
+uniform float u_clip_height;
 
 out vec4 fragColor;
 
@@ -100,15 +200,53 @@ float fbm(vec3 p) {
     return value;
 }
 
+// Bark relief as a height field over UV: furrows running along the branch,
+// eight whole waves around (16 pi) and noise sampled on a cylinder so
+// nothing breaks at the U seam
+float bark_height(vec2 uv) {
+    float angle = uv.x * 6.2831853;
+    float wobble = noise(vec3(cos(angle), sin(angle), uv.y) * 3.0);
+    return sin(uv.x * 50.265482 + wobble * 3.0) * 0.5 + 0.5;
+}
+
+// Tilt the surface normal by the bark relief, in the tangent frame; vertices
+// without a tangent (anything but bark rings) keep their normal
+vec3 bark_normal(vec3 normal, vec3 tangent, vec2 uv) {
+    vec3 along = tangent - normal * dot(normal, tangent);
+    if (dot(along, along) < 1e-8) {
+        return normal;
+    }
+    vec3 t = normalize(along);
+    vec3 b = cross(normal, t);
+    const float eps = 0.002;
+    float h = bark_height(uv);
+    float du = (bark_height(uv + vec2(eps, 0.0)) - h) / eps;
+    float dv = (bark_height(uv + vec2(0.0, eps)) - h) / eps;
+    return normalize(normal - (t * du + b * dv) * 0.01);
+}
+
+// Interleaved gradient noise: a per-pixel threshold that tiles without visible patterns
+float dither_threshold(vec2 frag_coord) {
+    return fract(52.9829189 * fract(dot(frag_coord, vec2(0.06711056, 0.00583715))));
+}
+
 void main() {
-    vec3 normal = normalize(v_normal);
-    vec3 view_dir = normalize(u_camera_pos - v_world_position);
+    // Dithered transparency for ghost branches: drop a share of fragments instead
+    // of blending, so they stay in the depth-tested pass without sorting
+    if (v_opacity < 1.0 && v_opacity <= dither_threshold(gl_FragCoord.xy)) {
+        discard;
+    }
+    if (v_world_position.y < u_clip_height) {
+        discard;
+    }
 
-    // Height-based gradient: warm at base (red/orange), cool at tips (cyan/green)
-    float height_factor = clamp(v_world_position.y / 10.0, 0.0, 1.0);
+    vec3 normal = bark_normal(normalize(v_normal), v_tangent, v_uv);
+    vec3 view_dir = normalize(u_camera_pos - v_world_position);
+    vec3 tree_position = v_world_position - u_tree_origin;
 
-    // Base hue transitions: red (0.0) -> orange (0.08) -> yellow (0.15) -> green (0.33) -> cyan (0.5)
-    float base_hue = mix(0.02, 0.45, height_factor); // Red to cyan gradient
+    // Height-based gradient between the theme's trunk and tip hues
+    float height_factor = clamp(tree_position.y / 10.0, 0.0, 1.0);
+    float base_hue = mix(u_hue_stops.x, u_hue_stops.y, height_factor);
     float personal_hue = (v_hue / 360.0) * 0.2; // Person's hue contributes 20%
     float hue = fract(base_hue + personal_hue);
 
@@ -125,9 +263,10 @@ void main() {
     vec3 glow_color = hsv2rgb(vec3(fract(hue + 0.08), 0.9, 1.0));
     vec3 edge_glow = fresnel * glow_color * v_glow * 3.0;
 
-    // Energy veins - pulsing patterns that flow upward
+    // Energy veins - pulsing patterns that flow upward; three whole waves
+    // around (6 pi) so they meet themselves at the seam
     float vein_flow = u_time * 1.5 - v_world_position.y * 0.8;
-    float vein_pattern = sin(vein_flow + v_uv.x * 20.0) * 0.5 + 0.5;
+    float vein_pattern = sin(vein_flow + v_uv.x * 18.849556) * 0.5 + 0.5;
     vein_pattern *= sin(vein_flow * 0.7 + v_uv.y * 15.0) * 0.5 + 0.5;
     float veins = pow(vein_pattern, 3.0) * v_luminance;
     vec3 vein_color = hsv2rgb(vec3(fract(hue + 0.15), 0.95, 1.0));
@@ -161,23 +300,70 @@ void main() {
     final_color *= (1.0 + bark + bark_detail);
 
     // Ethereal atmosphere with height-based fog
-    float atmosphere = exp(-length(v_world_position) * 0.08) * 0.15;
-    float height_fog = exp(-v_world_position.y * 0.15) * 0.1;
-    vec3 fog_color = hsv2rgb(vec3(0.55, 0.3, 0.2)); // Soft teal fog
-    final_color += fog_color * (atmosphere + height_fog);
+    float atmosphere = exp(-length(tree_position) * 0.08) * 0.15;
+    float height_fog = exp(-tree_position.y * u_fog_height_falloff) * 0.1;
+    final_color += u_fog_color * (atmosphere + height_fog) * u_fog_density;
 
     // Magical sparkle effect on high-luminance areas
     float sparkle = noise(v_position * 50.0 + u_time * 5.0);
     sparkle = pow(sparkle, 20.0) * v_luminance * 2.0;
     final_color += vec3(1.0) * sparkle;
 
-    // HDR tone mapping (ACES approximation)
-    final_color = final_color * (2.51 * final_color + 0.03) / (final_color * (2.43 * final_color + 0.59) + 0.14);
+    // Cross-fade between tree geometries (1.0 = fully shown); alpha 1.0 lets it bloom.
+    // Linear HDR out: the composite pass tone maps and gamma corrects
+    fragColor = vec4(final_color * u_fade, 1.0);
+}
+"#;
+
+/// Simplified tree fragment shader for low-end GPUs: the theme comes baked
+/// into the vertex colors, lit by a fresnel rim and a slow pulse
+pub const TREE_BAKED_FRAGMENT_SHADER: &str = r#"#version 300 es
+// highp to match the camera block in the shared vertex shader
+precision highp float;
+
+in vec3 v_normal;
+in vec3 v_world_position;
+in float v_glow;
+in float v_luminance;
+in float v_opacity;
+in vec3 v_color;
+
+// View, projection, eye and clock, shared with the other camera programs
+layout(std140) uniform Camera {
+    mat4 u_view;
+    mat4 u_projection;
+    vec3 u_camera_pos;
+    float u_time;
+};
+uniform float u_ambient_strength;
+uniform float u_fade;
+uniform float u_clip_height;
 
-    // Gamma correction
-    final_color = pow(final_color, vec3(1.0 / 2.2));
+out vec4 fragColor;
 
-    fragColor = vec4(final_color, 1.0);
+float dither_threshold(vec2 frag_coord) {
+    return fract(52.9829189 * fract(dot(frag_coord, vec2(0.06711056, 0.00583715))));
+}
+
+void main() {
+    if (v_opacity < 1.0 && v_opacity <= dither_threshold(gl_FragCoord.xy)) {
+        discard;
+    }
+    if (v_world_position.y < u_clip_height) {
+        discard;
+    }
+
+    vec3 normal = normalize(v_normal);
+    vec3 view_dir = normalize(u_camera_pos - v_world_position);
+
+    float fresnel = pow(1.0 - max(dot(normal, view_dir), 0.0), 4.0);
+    float pulse = sin(u_time * 2.0 + v_world_position.y * 2.0) * 0.5 + 0.5;
+    vec3 glow_color = mix(v_color, vec3(1.0), 0.3);
+
+    vec3 final_color = v_color * (u_ambient_strength + v_luminance * (0.6 + pulse * 0.4))
+        + glow_color * fresnel * v_glow * 2.0;
+
+    fragColor = vec4(final_color * u_fade, 1.0);
 }
 "#;
 
@@ -189,10 +375,23 @@ layout(location = 0) in vec3 a_position;
 layout(location = 1) in float a_size;
 layout(location = 2) in float a_alpha;
 layout(location = 3) in vec3 a_color;
-
-uniform mat4 u_view;
-uniform mat4 u_projection;
-uniform float u_time;
+// Theme palette color to tint toward, or negative to keep a_color
+layout(location = 4) in float a_palette;
+
+// View, projection, eye and clock, shared with the other camera programs
+layout(std140) uniform Camera {
+    mat4 u_view;
+    mat4 u_projection;
+    vec3 u_camera_pos;
+    float u_time;
+};
+// Theme colors particles are tinted toward
+uniform vec3 u_particle_palette[3];
+// Same haze as the tree, so particles sit in it rather than on top of it
+uniform vec3 u_tree_origin;
+uniform vec3 u_fog_color;
+uniform float u_fog_density;
+uniform float u_fog_height_falloff;
 
 out float v_alpha;
 out vec3 v_color;
@@ -201,7 +400,13 @@ void main() {
     // Flicker effect
     float flicker = sin(u_time * 10.0 + a_position.x * 100.0) * 0.3 + 0.7;
     v_alpha = a_alpha * flicker;
-    v_color = a_color;
+    // Tinted toward the theme, keeping a little of each particle's own color
+    v_color = a_palette < 0.0 ? a_color : mix(a_color, u_particle_palette[int(a_palette)], 0.75);
+
+    vec3 tree_position = a_position - u_tree_origin;
+    float atmosphere = exp(-length(tree_position) * 0.08) * 0.15;
+    float height_fog = exp(-tree_position.y * u_fog_height_falloff) * 0.1;
+    v_color += u_fog_color * (atmosphere + height_fog) * u_fog_density;
 
     vec4 view_pos = u_view * vec4(a_position, 1.0);
     gl_Position = u_projection * view_pos;
@@ -238,6 +443,352 @@ void main() {
 }
 "#;
 
+/// Vertex shader for instanced leaves
+///
+/// Per-vertex blade shape (locations 0 and 2) is placed by per-instance
+/// offset and side/up axes (locations 7-9).
+pub const LEAF_VERTEX_SHADER: &str = r#"#version 300 es
+precision highp float;
+
+layout(location = 0) in vec3 a_position;
+layout(location = 2) in vec2 a_uv;
+layout(location = 7) in vec3 a_offset;
+layout(location = 8) in vec3 a_side;
+layout(location = 9) in vec3 a_up;
+layout(location = 10) in float a_hue;
+layout(location = 11) in float a_glow;
+
+uniform mat4 u_view;
+uniform mat4 u_projection;
+uniform float u_time;
+
+out vec3 v_normal;
+out vec3 v_world_position;
+out vec2 v_uv;
+out float v_hue;
+out float v_glow;
+
+void main() {
+    // Flutter the tip more than the stem
+    float flutter = sin(u_time * 2.3 + a_offset.x * 3.1 + a_offset.z * 2.7) * 0.15 * a_position.y;
+    vec3 world = a_offset + a_side * a_position.x + a_up * a_position.y
+               + normalize(cross(a_side, a_up)) * flutter * length(a_up);
+
+    v_normal = normalize(cross(a_side, a_up));
+    v_world_position = world;
+    v_uv = a_uv;
+    v_hue = a_hue;
+    v_glow = a_glow;
+
+    gl_Position = u_projection * u_view * vec4(world, 1.0);
+}
+"#;
+
+/// Fragment shader for instanced leaves
+pub const LEAF_FRAGMENT_SHADER: &str = r#"#version 300 es
+precision highp float;
+
+in vec3 v_normal;
+in vec3 v_world_position;
+in vec2 v_uv;
+in float v_hue;
+in float v_glow;
+
+uniform vec3 u_camera_pos;
+uniform vec3 u_tree_origin;
+uniform float u_fade;
+// Season: canopy hue low and high (x, y), and saturation
+uniform vec2 u_leaf_hues;
+uniform float u_leaf_saturation;
+
+out vec4 fragColor;
+
+vec3 hsv2rgb(vec3 c) {
+    vec4 K = vec4(1.0, 2.0 / 3.0, 1.0 / 3.0, 3.0);
+    vec3 p = abs(fract(c.xxx + K.xyz) * 6.0 - K.www);
+    return c.z * mix(K.xxx, clamp(p - K.xxx, 0.0, 1.0), c.y);
+}
+
+void main() {
+    vec3 view_dir = normalize(u_camera_pos - v_world_position);
+    // Leaves are two-sided: light whichever face looks at the camera
+    vec3 normal = normalize(v_normal);
+    if (dot(normal, view_dir) < 0.0) {
+        normal = -normal;
+    }
+
+    // Seasonal canopy colors drifting toward the branch's own hue
+    float height_factor = clamp((v_world_position.y - u_tree_origin.y) / 10.0, 0.0, 1.0);
+    float hue = fract(mix(u_leaf_hues.x, u_leaf_hues.y, height_factor) + (v_hue / 360.0) * 0.2);
+    vec3 base = hsv2rgb(vec3(hue, u_leaf_saturation, 0.35 + 0.4 * v_glow));
+
+    // Bright midrib and glowing, backlit edges
+    float midrib = 1.0 - smoothstep(0.0, 0.06, abs(v_uv.x - 0.5));
+    float rim = pow(1.0 - max(dot(normal, view_dir), 0.0), 3.0);
+    vec3 color = base * (0.45 + 0.55 * max(dot(normal, normalize(vec3(0.3, 1.0, 0.2))), 0.0));
+    color += hsv2rgb(vec3(fract(hue + 0.05), 0.8, 1.0)) * (midrib * 0.3 + rim * v_glow);
+
+    // Leaves only bloom through their glow, unlike the branches
+    fragColor = vec4(color * u_fade, v_glow * 0.5);
+}
+"#;
+
+/// Vertex shader for instanced fruit: the unit sphere scaled and placed
+/// per instance, swaying gently on its stalk
+pub const FRUIT_VERTEX_SHADER: &str = r#"#version 300 es
+precision highp float;
+
+layout(location = 0) in vec3 a_position;
+layout(location = 1) in vec3 a_normal;
+layout(location = 7) in vec3 a_center;
+layout(location = 8) in float a_radius;
+layout(location = 9) in float a_hue;
+layout(location = 10) in float a_glow;
+
+uniform mat4 u_view;
+uniform mat4 u_projection;
+uniform float u_time;
+
+out vec3 v_normal;
+out vec3 v_world_position;
+out float v_hue;
+out float v_glow;
+
+void main() {
+    float phase = a_center.x * 2.1 + a_center.z * 1.7;
+    vec3 sway = vec3(sin(u_time * 1.3 + phase), 0.0, cos(u_time * 1.1 + phase)) * a_radius * 0.15;
+    vec3 world = a_center + sway + a_position * a_radius;
+
+    v_normal = a_normal;
+    v_world_position = world;
+    v_hue = a_hue;
+    v_glow = a_glow;
+
+    gl_Position = u_projection * u_view * vec4(world, 1.0);
+}
+"#;
+
+/// Fragment shader for fruit: a glowing core under a bright rim, in the
+/// hue of the child each one stands for
+pub const FRUIT_FRAGMENT_SHADER: &str = r#"#version 300 es
+precision highp float;
+
+in vec3 v_normal;
+in vec3 v_world_position;
+in float v_hue;
+in float v_glow;
+
+uniform vec3 u_camera_pos;
+uniform float u_time;
+uniform float u_fade;
+
+out vec4 fragColor;
+
+vec3 hsv2rgb(vec3 c) {
+    vec4 K = vec4(1.0, 2.0 / 3.0, 1.0 / 3.0, 3.0);
+    vec3 p = abs(fract(c.xxx + K.xyz) * 6.0 - K.www);
+    return c.z * mix(K.xxx, clamp(p - K.xxx, 0.0, 1.0), c.y);
+}
+
+void main() {
+    vec3 normal = normalize(v_normal);
+    vec3 view_dir = normalize(u_camera_pos - v_world_position);
+    float facing = max(dot(normal, view_dir), 0.0);
+
+    // Warm fruit tones nudged toward the child's hue
+    float hue = fract(0.08 + (v_hue / 360.0) * 0.3);
+    float pulse = 0.85 + 0.15 * sin(u_time * 1.7 + v_world_position.y * 3.0);
+    vec3 core = hsv2rgb(vec3(hue, 0.7, 0.5 + 0.5 * v_glow)) * (0.4 + 0.6 * facing) * pulse;
+    vec3 rim = hsv2rgb(vec3(fract(hue + 0.05), 0.5, 1.0)) * pow(1.0 - facing, 2.5);
+    vec3 color = core + rim * (0.5 + v_glow);
+
+    fragColor = vec4(color * u_fade, 0.5 + 0.5 * v_glow);
+}
+"#;
+
+/// Vertex shader for the ground disc (tree vertex layout, locations 0-6)
+pub const GROUND_VERTEX_SHADER: &str = r#"#version 300 es
+precision highp float;
+
+layout(location = 0) in vec3 a_position;
+layout(location = 1) in vec3 a_normal;
+layout(location = 3) in float a_glow;
+layout(location = 5) in float a_hue;
+layout(location = 6) in float a_opacity;
+
+uniform mat4 u_view;
+uniform mat4 u_projection;
+
+out vec3 v_normal;
+out vec3 v_world_position;
+out float v_glow;
+out float v_hue;
+out float v_opacity;
+
+void main() {
+    v_normal = a_normal;
+    v_world_position = a_position;
+    v_glow = a_glow;
+    v_hue = a_hue;
+    v_opacity = a_opacity;
+    gl_Position = u_projection * u_view * vec4(a_position, 1.0);
+}
+"#;
+
+/// Fragment shader for the ground: dark moss lit from above, with grass
+/// speckles twinkling where the vertex glow is high
+pub const GROUND_FRAGMENT_SHADER: &str = r#"#version 300 es
+precision highp float;
+
+in vec3 v_normal;
+in vec3 v_world_position;
+in float v_glow;
+in float v_hue;
+in float v_opacity;
+
+uniform vec3 u_camera_pos;
+uniform vec3 u_tree_origin;
+uniform float u_time;
+uniform vec3 u_fog_color;
+uniform float u_fog_density;
+// The tree mirrored in the ground, over the scene's pixels
+uniform sampler2D u_reflection;
+uniform float u_reflection_strength;
+uniform vec2 u_screen_size;
+
+out vec4 fragColor;
+
+vec3 hsv2rgb(vec3 c) {
+    vec4 K = vec4(1.0, 2.0 / 3.0, 1.0 / 3.0, 3.0);
+    vec3 p = abs(fract(c.xxx + K.xyz) * 6.0 - K.www);
+    return c.z * mix(K.xxx, clamp(p - K.xxx, 0.0, 1.0), c.y);
+}
+
+float hash(vec2 p) {
+    return fract(sin(dot(p, vec2(127.1, 311.7))) * 43758.5453);
+}
+
+// Interleaved gradient noise, as in the tree shader
+float dither_threshold(vec2 frag_coord) {
+    return fract(52.9829189 * fract(dot(frag_coord, vec2(0.06711056, 0.00583715))));
+}
+
+void main() {
+    // The rim dithers away into the fog rather than ending in an edge
+    if (v_opacity < 1.0 && v_opacity <= dither_threshold(gl_FragCoord.xy)) {
+        discard;
+    }
+
+    vec3 normal = normalize(v_normal);
+    float hue = v_hue / 360.0;
+    vec3 base = hsv2rgb(vec3(hue, 0.6, 0.06));
+    vec3 color = base * (0.5 + 0.5 * max(dot(normal, normalize(vec3(0.3, 1.0, 0.2))), 0.0));
+
+    // Speckles: small cells of grass, lit near glowing vertices, twinkling
+    vec2 cell = floor(v_world_position.xz * 12.0);
+    float seed = hash(cell);
+    float blade = step(0.9, seed) * smoothstep(0.2, 0.9, v_glow);
+    float twinkle = 0.6 + 0.4 * sin(u_time * (1.0 + seed * 2.0) + seed * 6.2831853);
+    color += hsv2rgb(vec3(fract(hue + 0.05), 0.8, 1.0)) * blade * twinkle * 0.6;
+
+    // Glossy mirror image, rippled by the ground's undulation and strongest
+    // at grazing angles
+    if (u_reflection_strength > 0.0) {
+        vec2 screen_uv = gl_FragCoord.xy / u_screen_size + normal.xz * 0.03;
+        vec3 view_dir = normalize(u_camera_pos - v_world_position);
+        float fresnel = 0.3 + 0.7 * pow(1.0 - max(dot(normal, view_dir), 0.0), 3.0);
+        color += texture(u_reflection, screen_uv).rgb * u_reflection_strength * fresnel;
+    }
+
+    // Fade toward the fog with distance from the trunk, like the tree
+    float reach = length(v_world_position - u_tree_origin);
+    color = mix(color, u_fog_color * 0.25, clamp(reach * 0.04 * u_fog_density, 0.0, 0.6));
+
+    // Only the speckles bloom
+    fragColor = vec4(color, blade * twinkle);
+}
+"#;
+
+/// Vertex shader for name labels (tree vertex layout): the anchor at
+/// location 0 and each corner's offset, in the camera's plane, at location 1
+pub const LABEL_VERTEX_SHADER: &str = r#"#version 300 es
+precision highp float;
+
+layout(location = 0) in vec3 a_position;
+layout(location = 1) in vec3 a_normal;
+layout(location = 2) in vec2 a_uv;
+layout(location = 3) in float a_glow;
+layout(location = 5) in float a_hue;
+layout(location = 6) in float a_opacity;
+
+// View, projection, eye and clock, shared with the other camera programs
+layout(std140) uniform Camera {
+    mat4 u_view;
+    mat4 u_projection;
+    vec3 u_camera_pos;
+    float u_time;
+};
+
+out vec2 v_uv;
+out float v_glow;
+out float v_hue;
+out float v_opacity;
+
+void main() {
+    // Billboard: the camera's right and up are the view matrix's first two rows
+    vec3 right = vec3(u_view[0][0], u_view[1][0], u_view[2][0]);
+    vec3 up = vec3(u_view[0][1], u_view[1][1], u_view[2][1]);
+    vec3 world = a_position + right * a_normal.x + up * a_normal.y;
+
+    v_uv = a_uv;
+    v_glow = a_glow;
+    v_hue = a_hue;
+    v_opacity = a_opacity;
+    gl_Position = u_projection * u_view * vec4(world, 1.0);
+}
+"#;
+
+/// Fragment shader for name labels: lettering cut from the signed distance
+/// field atlas (0.5 = glyph edge), antialiased over one screen pixel
+pub const LABEL_FRAGMENT_SHADER: &str = r#"#version 300 es
+precision highp float;
+
+in vec2 v_uv;
+in float v_glow;
+in float v_hue;
+in float v_opacity;
+
+uniform sampler2D u_atlas;
+uniform float u_fade;
+
+out vec4 fragColor;
+
+vec3 hsv2rgb(vec3 c) {
+    vec4 K = vec4(1.0, 2.0 / 3.0, 1.0 / 3.0, 3.0);
+    vec3 p = abs(fract(c.xxx + K.xyz) * 6.0 - K.www);
+    return c.z * mix(K.xxx, clamp(p - K.xxx, 0.0, 1.0), c.y);
+}
+
+void main() {
+    float distance = texture(u_atlas, v_uv).r;
+    float width = max(fwidth(distance), 1e-4);
+    float coverage = smoothstep(0.5 - width, 0.5 + width, distance);
+    // A soft halo just outside the letters, tinted by the person's hue
+    float halo = smoothstep(0.2, 0.5, distance) * (1.0 - coverage) * v_glow;
+
+    float hue = fract(0.45 + v_hue / 360.0);
+    vec3 ink = mix(vec3(0.92, 0.96, 1.0), hsv2rgb(vec3(hue, 0.5, 1.0)), 0.25);
+    vec3 color = mix(hsv2rgb(vec3(hue, 0.8, 0.8)), ink, coverage);
+    float alpha = max(coverage, halo * 0.5) * v_opacity * u_fade;
+    if (alpha < 0.01) {
+        discard;
+    }
+
+    // Alpha is the blend weight here; labels are kept out of the bloom
+    fragColor = vec4(color, alpha);
+}
+"#;
+
 /// Fullscreen quad vertex shader for post-processing
 pub const FULLSCREEN_VERTEX_SHADER: &str = r#"#version 300 es
 precision highp float;
@@ -253,7 +804,165 @@ void main() {
 }
 "#;
 
+/// Vertex shader for the sky: a fullscreen triangle carrying each corner's
+/// world-space view ray
+pub const SKY_VERTEX_SHADER: &str = r#"#version 300 es
+precision highp float;
+
+uniform mat4 u_view;
+uniform mat4 u_projection;
+
+out vec3 v_ray;
+
+void main() {
+    float x = float((gl_VertexID & 1) << 2) - 1.0;
+    float y = float((gl_VertexID & 2) << 1) - 1.0;
+    // Back through the projection to view space, then rotate into the world
+    vec4 view_ray = inverse(u_projection) * vec4(x, y, 1.0, 1.0);
+    v_ray = transpose(mat3(u_view)) * (view_ray.xyz / view_ray.w);
+    gl_Position = vec4(x, y, 0.0, 1.0);
+}
+"#;
+
+/// Procedural night sky: a horizon gradient, faint nebula, twinkling stars
+/// and a moon; stars and nebula fade out on light backgrounds
+pub const SKY_FRAGMENT_SHADER: &str = r#"#version 300 es
+precision highp float;
+
+in vec3 v_ray;
+
+uniform float u_time;
+uniform vec3 u_background;
+uniform vec3 u_horizon;
+uniform vec3 u_moon_direction;
+
+out vec4 fragColor;
+
+float hash(vec3 p) {
+    return fract(sin(dot(p, vec3(127.1, 311.7, 74.7))) * 43758.5453);
+}
+
+float noise(vec3 p) {
+    vec3 i = floor(p);
+    vec3 f = fract(p);
+    f = f * f * (3.0 - 2.0 * f);
+    return mix(mix(mix(hash(i), hash(i + vec3(1.0, 0.0, 0.0)), f.x),
+                   mix(hash(i + vec3(0.0, 1.0, 0.0)), hash(i + vec3(1.0, 1.0, 0.0)), f.x), f.y),
+               mix(mix(hash(i + vec3(0.0, 0.0, 1.0)), hash(i + vec3(1.0, 0.0, 1.0)), f.x),
+                   mix(hash(i + vec3(0.0, 1.0, 1.0)), hash(i + vec3(1.0, 1.0, 1.0)), f.x), f.y), f.z);
+}
+
+void main() {
+    vec3 dir = normalize(v_ray);
+    float altitude = dir.y;
+
+    // Background overhead, brightening toward the horizon
+    vec3 color = mix(u_horizon, u_background, smoothstep(-0.05, 0.5, altitude));
+    float night = 1.0 - smoothstep(0.0, 0.1, dot(u_background, vec3(0.2126, 0.7152, 0.0722)));
+    float sky = smoothstep(-0.02, 0.15, altitude) * night;
+
+    // Nebula: two drifting bands of color
+    float cloud = noise(dir * 3.0) * 0.6 + noise(dir * 7.0) * 0.4;
+    cloud = smoothstep(0.55, 0.9, cloud);
+    vec3 nebula = mix(vec3(0.05, 0.02, 0.1), vec3(0.01, 0.06, 0.08), noise(dir * 1.5));
+    color += nebula * cloud * sky;
+
+    // Stars: one per lit cell of a grid wrapped around the view direction
+    vec3 cell = floor(dir * 180.0);
+    float star = step(0.997, hash(cell));
+    float twinkle = 0.6 + 0.4 * sin(u_time * (1.0 + hash(cell + 1.0) * 3.0) + hash(cell + 2.0) * 6.2831853);
+    float point = 1.0 - smoothstep(0.1, 0.5, length(fract(dir * 180.0) - 0.5));
+    color += vec3(1.0, 0.95, 0.9) * star * twinkle * point * 2.0 * sky;
+
+    // Moon: a crisp disc with a soft halo
+    float facing = dot(dir, normalize(u_moon_direction));
+    float disc = smoothstep(0.9994, 0.9997, facing);
+    float halo = pow(max(facing, 0.0), 200.0) * 0.3;
+    color += vec3(1.0, 0.97, 0.9) * (disc * 3.0 + halo) * smoothstep(-0.02, 0.05, altitude);
+
+    fragColor = vec4(color, 1.0);
+}
+"#;
+
+/// Screen-space ambient occlusion from the scene's depth: samples a
+/// hemisphere around each surface point (normal rebuilt from depth) and
+/// darkens where nearby geometry covers it. Writes AO (1 = open) to red.
+pub const SSAO_SHADER: &str = r#"#version 300 es
+precision highp float;
+
+in vec2 v_uv;
+
+uniform sampler2D u_depth;
+// Projection terms: x/y scale (m[0], m[5]) and off-centre shift (m[8], m[9])
+uniform vec4 u_proj_params;
+// Depth terms (m[10], m[14])
+uniform vec2 u_depth_params;
+// Sampling radius (world units)
+uniform float u_radius;
+
+out vec4 fragColor;
+
+const int SAMPLES = 12;
+
+vec3 view_position(vec2 uv, float depth) {
+    float z = -u_depth_params.y / (depth * 2.0 - 1.0 + u_depth_params.x);
+    vec2 ndc = uv * 2.0 - 1.0;
+    return vec3(-z * (ndc + u_proj_params.zw) / u_proj_params.xy, z);
+}
+
+vec2 project(vec3 p) {
+    vec2 ndc = (u_proj_params.xy * p.xy + u_proj_params.zw * p.z) / -p.z;
+    return ndc * 0.5 + 0.5;
+}
+
+// Interleaved gradient noise, as in the tree shader
+float dither_threshold(vec2 frag_coord) {
+    return fract(52.9829189 * fract(dot(frag_coord, vec2(0.06711056, 0.00583715))));
+}
+
+void main() {
+    float depth = texture(u_depth, v_uv).r;
+    if (depth >= 1.0) {
+        // Sky: nothing to occlude
+        fragColor = vec4(1.0);
+        return;
+    }
+
+    vec3 p = view_position(v_uv, depth);
+    vec3 normal = normalize(cross(dFdx(p), dFdy(p)));
+    if (dot(normal, p) > 0.0) {
+        normal = -normal;
+    }
+    vec3 tangent = normalize(cross(normal, abs(normal.y) < 0.99 ? vec3(0.0, 1.0, 0.0) : vec3(1.0, 0.0, 0.0)));
+    vec3 bitangent = cross(normal, tangent);
+
+    // Spiral through the hemisphere, rotated per pixel; the composite's
+    // filtering smooths the noise
+    float noise = dither_threshold(gl_FragCoord.xy);
+    float occlusion = 0.0;
+    for (int i = 0; i < SAMPLES; i++) {
+        float t = (float(i) + 0.5) / float(SAMPLES);
+        float angle = float(i) * 2.3999632 + noise * 6.2831853;
+        float spread = sqrt(1.0 - t * t);
+        vec3 dir = (tangent * cos(angle) + bitangent * sin(angle)) * spread + normal * t;
+        float reach = u_radius * mix(0.2, 1.0, fract(t * 7.31 + noise));
+        vec3 sample_point = p + dir * reach;
+
+        vec2 uv = project(sample_point);
+        float scene_z = view_position(uv, texture(u_depth, uv).r).z;
+        // Only geometry near the point counts, not the far background
+        float range = smoothstep(0.0, 1.0, u_radius / abs(p.z - scene_z));
+        occlusion += step(sample_point.z + 0.02, scene_z) * range;
+    }
+
+    fragColor = vec4(vec3(1.0 - occlusion / float(SAMPLES)), 1.0);
+}
+"#;
+
 /// Bloom extraction shader
+///
+/// The scene's alpha channel is a bloom mask: geometry written with alpha 0
+/// (labels, photo frames, UI) stays crisp, alpha 1 blooms normally.
 pub const BLOOM_EXTRACT_SHADER: &str = r#"#version 300 es
 precision highp float;
 
@@ -265,11 +974,13 @@ uniform float u_threshold;
 out vec4 fragColor;
 
 void main() {
-    vec3 color = texture(u_texture, v_uv).rgb;
+    vec4 scene = texture(u_texture, v_uv);
+    vec3 color = scene.rgb;
+    float bloom_mask = scene.a;
     float brightness = dot(color, vec3(0.2126, 0.7152, 0.0722));
 
     if (brightness > u_threshold) {
-        fragColor = vec4(color * (brightness - u_threshold), 1.0);
+        fragColor = vec4(color * (brightness - u_threshold) * bloom_mask, 1.0);
     } else {
         fragColor = vec4(0.0, 0.0, 0.0, 1.0);
     }
@@ -306,6 +1017,91 @@ void main() {
 }
 "#;
 
+/// God rays: radial blur of the bloom toward the light's screen position,
+/// so bright canopy light streams out through the scene
+pub const GOD_RAY_SHADER: &str = r#"#version 300 es
+precision highp float;
+
+in vec2 v_uv;
+
+uniform sampler2D u_texture;
+// Light position in texture coordinates (may lie off screen)
+uniform vec2 u_light_pos;
+// How far toward the light each ray reaches (0 to 1)
+uniform float u_density;
+// Falloff per sample along the ray
+uniform float u_decay;
+
+out vec4 fragColor;
+
+const int SAMPLES = 48;
+
+void main() {
+    vec2 step = (v_uv - u_light_pos) * u_density / float(SAMPLES);
+    vec2 uv = v_uv;
+    float weight = 1.0;
+    vec3 result = vec3(0.0);
+
+    for (int i = 0; i < SAMPLES; i++) {
+        uv -= step;
+        result += texture(u_texture, clamp(uv, 0.0, 1.0)).rgb * weight;
+        weight *= u_decay;
+    }
+
+    fragColor = vec4(result / float(SAMPLES), 1.0);
+}
+"#;
+
+/// Selection mask: the selected branch's triangles, drawn in white over
+/// black with the tree vertex shader
+pub const OUTLINE_MASK_FRAGMENT_SHADER: &str = r#"#version 300 es
+precision mediump float;
+
+out vec4 fragColor;
+
+void main() {
+    fragColor = vec4(1.0);
+}
+"#;
+
+/// Selection outline: a solid rim just outside the selection mask, blended
+/// over the composited frame
+pub const OUTLINE_SHADER: &str = r#"#version 300 es
+precision highp float;
+
+in vec2 v_uv;
+
+uniform sampler2D u_mask;
+// Display color of the rim
+uniform vec3 u_color;
+// Rim width in mask texels
+uniform float u_width;
+
+out vec4 fragColor;
+
+void main() {
+    // The branch itself stays as it is
+    if (texture(u_mask, v_uv).r > 0.5) {
+        discard;
+    }
+
+    // Strongest mask coverage around this pixel, over two rings
+    vec2 texel = 1.0 / vec2(textureSize(u_mask, 0));
+    float edge = 0.0;
+    for (int i = 0; i < 12; i++) {
+        float angle = float(i) * 0.5235988;
+        vec2 dir = vec2(cos(angle), sin(angle)) * texel;
+        edge = max(edge, texture(u_mask, v_uv + dir * u_width).r);
+        edge = max(edge, texture(u_mask, v_uv + dir * u_width * 0.5).r);
+    }
+    if (edge < 0.5) {
+        discard;
+    }
+
+    fragColor = vec4(u_color, 1.0);
+}
+"#;
+
 /// Final composite shader
 pub const COMPOSITE_SHADER: &str = r#"#version 300 es
 precision highp float;
@@ -314,23 +1110,77 @@ in vec2 v_uv;
 
 uniform sampler2D u_scene;
 uniform sampler2D u_bloom;
+uniform sampler2D u_ao;
+uniform sampler2D u_rays;
 uniform float u_bloom_strength;
+// Light shafts added on top of the bloom (0 = off)
+uniform float u_ray_strength;
+// How much ambient occlusion darkens the scene (0 = off)
+uniform float u_ao_strength;
 uniform float u_vignette_strength;
+// Region of the full image covered by this pass (x, y, width, height), for tiled rendering
+uniform vec4 u_viewport_rect;
+// Exposure in stops
+uniform float u_exposure;
+// 0 = none, 1 = ACES, 2 = Reinhard, 3 = filmic (Hejl-Burgess-Dawson)
+uniform int u_tone_mapping;
 
 out vec4 fragColor;
 
+vec3 aces(vec3 c) {
+    return c * (2.51 * c + 0.03) / (c * (2.43 * c + 0.59) + 0.14);
+}
+
+vec3 reinhard(vec3 c) {
+    return c / (1.0 + c);
+}
+
+// Fits a film curve with gamma built in
+vec3 filmic(vec3 c) {
+    vec3 x = max(vec3(0.0), c - 0.004);
+    return x * (6.2 * x + 0.5) / (x * (6.2 * x + 1.7) + 0.06);
+}
+
 void main() {
     vec3 scene = texture(u_scene, v_uv).rgb;
     vec3 bloom = texture(u_bloom, v_uv).rgb;
 
-    // Add bloom
-    vec3 color = scene + bloom * u_bloom_strength;
+    // Contact darkening, averaged over a few texels to hide the SSAO noise
+    if (u_ao_strength > 0.0) {
+        vec2 texel = 1.0 / vec2(textureSize(u_ao, 0));
+        float ao = 0.0;
+        for (int x = -1; x <= 1; x++) {
+            for (int y = -1; y <= 1; y++) {
+                ao += texture(u_ao, v_uv + vec2(x, y) * texel).r;
+            }
+        }
+        scene *= mix(1.0, ao / 9.0, u_ao_strength);
+    }
 
-    // Vignette
-    vec2 uv = v_uv - 0.5;
+    // Add bloom and light shafts
+    vec3 glow = bloom * u_bloom_strength;
+    if (u_ray_strength > 0.0) {
+        glow += texture(u_rays, v_uv).rgb * u_ray_strength;
+    }
+    vec3 color = (scene + glow) * exp2(u_exposure);
+
+    // Vignette (in full-image coordinates so tiles stitch seamlessly)
+    vec2 uv = u_viewport_rect.xy + v_uv * u_viewport_rect.zw - 0.5;
     float vignette = 1.0 - dot(uv, uv) * u_vignette_strength;
     color *= vignette;
 
+    // HDR tone mapping, then gamma correction
+    if (u_tone_mapping == 3) {
+        color = filmic(color);
+    } else {
+        if (u_tone_mapping == 1) {
+            color = aces(color);
+        } else if (u_tone_mapping == 2) {
+            color = reinhard(color);
+        }
+        color = pow(clamp(color, 0.0, 1.0), vec3(1.0 / 2.2));
+    }
+
     // Color grading - slightly teal shadows, warm highlights
     vec3 shadows = vec3(0.0, 0.05, 0.1);
     vec3 highlights = vec3(0.05, 0.0, 0.0);
@@ -341,6 +1191,52 @@ void main() {
 }
 "#;
 
+/// FXAA: smooths edges along their direction, found from the luma of the
+/// four diagonal neighbours (run on the composited frame)
+pub const FXAA_SHADER: &str = r#"#version 300 es
+precision highp float;
+
+in vec2 v_uv;
+
+uniform sampler2D u_texture;
+
+out vec4 fragColor;
+
+const float SPAN_MAX = 8.0;
+const float REDUCE_MUL = 1.0 / 8.0;
+const float REDUCE_MIN = 1.0 / 128.0;
+
+float luma(vec3 c) {
+    return dot(c, vec3(0.299, 0.587, 0.114));
+}
+
+void main() {
+    vec2 texel = 1.0 / vec2(textureSize(u_texture, 0));
+    vec3 center = texture(u_texture, v_uv).rgb;
+    float luma_nw = luma(texture(u_texture, v_uv + vec2(-1.0, 1.0) * texel).rgb);
+    float luma_ne = luma(texture(u_texture, v_uv + vec2(1.0, 1.0) * texel).rgb);
+    float luma_sw = luma(texture(u_texture, v_uv + vec2(-1.0, -1.0) * texel).rgb);
+    float luma_se = luma(texture(u_texture, v_uv + vec2(1.0, -1.0) * texel).rgb);
+    float luma_m = luma(center);
+    float luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+    float luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+    // Blur across the edge's gradient, i.e. along the edge
+    vec2 dir = vec2(-((luma_nw + luma_ne) - (luma_sw + luma_se)), (luma_nw + luma_sw) - (luma_ne + luma_se));
+    float reduce = max((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * REDUCE_MUL, REDUCE_MIN);
+    float scale = 1.0 / (min(abs(dir.x), abs(dir.y)) + reduce);
+    dir = clamp(dir * scale, vec2(-SPAN_MAX), vec2(SPAN_MAX)) * texel;
+
+    vec3 near = 0.5 * (texture(u_texture, v_uv + dir * (1.0 / 3.0 - 0.5)).rgb
+                     + texture(u_texture, v_uv + dir * (2.0 / 3.0 - 0.5)).rgb);
+    vec3 far = near * 0.5 + 0.25 * (texture(u_texture, v_uv - dir * 0.5).rgb
+                                  + texture(u_texture, v_uv + dir * 0.5).rgb);
+    // The wider blur overshoots across other edges; fall back to the narrow one
+    float luma_far = luma(far);
+    fragColor = vec4(luma_far < luma_min || luma_far > luma_max ? near : far, 1.0);
+}
+"#;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -353,6 +1249,191 @@ mod tests {
         assert!(!PARTICLE_FRAGMENT_SHADER.is_empty());
     }
 
+    #[test]
+    fn test_camera_block_shared() {
+        let block = |shader: &str| {
+            let start = shader.find("layout(std140) uniform Camera {").expect("camera block");
+            shader[start..].split("};").next().unwrap().to_string()
+        };
+        let shaders = [
+            TREE_VERTEX_SHADER,
+            TWIG_VERTEX_SHADER,
+            TREE_FRAGMENT_SHADER,
+            TREE_BAKED_FRAGMENT_SHADER,
+            PARTICLE_VERTEX_SHADER,
+            LABEL_VERTEX_SHADER,
+        ];
+        // Blocks linked across stages must match member for member
+        let expected = block(TREE_VERTEX_SHADER);
+        for shader in shaders {
+            assert_eq!(block(shader), expected);
+            assert!(!shader.contains("uniform mat4 u_view;") && !shader.contains("uniform float u_time;"));
+        }
+    }
+
+    #[test]
+    fn test_ssao_darkens_composite() {
+        assert!(SSAO_SHADER.contains("uniform sampler2D u_depth"));
+        assert!(COMPOSITE_SHADER.contains("scene *= mix(1.0, ao / 9.0, u_ao_strength)"));
+    }
+
+    #[test]
+    fn test_tone_mapping_only_in_composite() {
+        for shader in [TREE_FRAGMENT_SHADER, TREE_BAKED_FRAGMENT_SHADER, LEAF_FRAGMENT_SHADER, FRUIT_FRAGMENT_SHADER, GROUND_FRAGMENT_SHADER] {
+            assert!(!shader.contains("2.51") && !shader.contains("1.0 / 2.2"));
+        }
+        assert!(COMPOSITE_SHADER.contains("uniform int u_tone_mapping"));
+        assert!(COMPOSITE_SHADER.contains("exp2(u_exposure)"));
+    }
+
+    #[test]
+    fn test_god_rays_march_toward_light() {
+        assert!(GOD_RAY_SHADER.contains("(v_uv - u_light_pos) * u_density"));
+        assert!(GOD_RAY_SHADER.contains("weight *= u_decay"));
+        assert!(COMPOSITE_SHADER.contains("texture(u_rays, v_uv).rgb * u_ray_strength"));
+    }
+
+    #[test]
+    fn test_theme_uniforms() {
+        assert!(TREE_FRAGMENT_SHADER.contains("mix(u_hue_stops.x, u_hue_stops.y, height_factor)"));
+        assert!(TREE_FRAGMENT_SHADER.contains("uniform vec3 u_fog_color"));
+        assert!(GROUND_FRAGMENT_SHADER.contains("uniform vec3 u_fog_color"));
+        assert!(PARTICLE_VERTEX_SHADER.contains("uniform vec3 u_particle_palette[3]"));
+        assert!(PARTICLE_VERTEX_SHADER.contains("u_particle_palette[int(a_palette)]"));
+    }
+
+    #[test]
+    fn test_outline_rims_mask() {
+        assert!(OUTLINE_MASK_FRAGMENT_SHADER.contains("fragColor = vec4(1.0)"));
+        assert!(OUTLINE_SHADER.contains("texture(u_mask, v_uv + dir * u_width).r"));
+    }
+
+    #[test]
+    fn test_fog_uniforms() {
+        for shader in [TREE_FRAGMENT_SHADER, PARTICLE_VERTEX_SHADER] {
+            assert!(shader.contains("exp(-tree_position.y * u_fog_height_falloff)"));
+            assert!(shader.contains("(atmosphere + height_fog) * u_fog_density"));
+        }
+        assert!(GROUND_FRAGMENT_SHADER.contains("reach * 0.04 * u_fog_density"));
+    }
+
+    #[test]
+    fn test_leaf_shader_seasonal() {
+        assert!(LEAF_FRAGMENT_SHADER.contains("mix(u_leaf_hues.x, u_leaf_hues.y, height_factor)"));
+        assert!(LEAF_FRAGMENT_SHADER.contains("uniform float u_leaf_saturation"));
+    }
+
+    #[test]
+    fn test_sky_follows_camera() {
+        assert!(SKY_VERTEX_SHADER.contains("transpose(mat3(u_view))"));
+        assert!(SKY_FRAGMENT_SHADER.contains("uniform vec3 u_moon_direction"));
+    }
+
+    #[test]
+    fn test_ground_reflects_clipped_tree() {
+        assert!(GROUND_FRAGMENT_SHADER.contains("uniform sampler2D u_reflection"));
+        assert!(TREE_FRAGMENT_SHADER.contains("v_world_position.y < u_clip_height"));
+        assert!(TREE_BAKED_FRAGMENT_SHADER.contains("v_world_position.y < u_clip_height"));
+    }
+
+    #[test]
+    fn test_fxaa_samples_diagonals() {
+        assert!(FXAA_SHADER.contains("uniform sampler2D u_texture"));
+        assert!(FXAA_SHADER.contains("luma_nw") && FXAA_SHADER.contains("luma_se"));
+    }
+
+    #[test]
+    fn test_bloom_extract_respects_mask() {
+        // How the mask gets cleared is covered by the blend tests in `webgl`;
+        // here, the extracted glow must scale with it
+        assert!(BLOOM_EXTRACT_SHADER.contains("float bloom_mask = scene.a;"));
+        assert!(BLOOM_EXTRACT_SHADER.contains("color * (brightness - u_threshold) * bloom_mask"));
+    }
+
+    /// The `dither_threshold` noise of a shader, evaluated on the CPU with
+    /// the constants read from its source
+    fn dither_threshold(shader: &str) -> impl Fn(f32, f32) -> f32 {
+        let start = shader.find("float dither_threshold").expect("shader dithers");
+        let body = &shader[start..start + shader[start..].find('}').unwrap()];
+        let numbers: Vec<f32> = body
+            .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .filter(|s| s.contains('.'))
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let [scale, kx, ky] = numbers[..] else {
+            panic!("unexpected dither_threshold: {}", body)
+        };
+        move |x, y| {
+            let v = (x * kx + y * ky).fract();
+            (scale * v).fract()
+        }
+    }
+
+    #[test]
+    fn test_tree_shader_dithers_opacity() {
+        assert!(TREE_VERTEX_SHADER.contains("layout(location = 6) in float a_opacity"));
+        assert!(TREE_FRAGMENT_SHADER.contains("v_opacity <= dither_threshold(gl_FragCoord.xy)"));
+
+        // Over a block of pixels, the share of fragments kept tracks opacity
+        let threshold = dither_threshold(TREE_FRAGMENT_SHADER);
+        for opacity in [0.1, 0.35, 0.55, 0.9] {
+            let mut kept = 0;
+            for y in 0..64 {
+                for x in 0..64 {
+                    let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                    if opacity > threshold(px, py) {
+                        kept += 1;
+                    }
+                }
+            }
+            let share = kept as f32 / (64.0 * 64.0);
+            assert!((share - opacity).abs() < 0.05, "opacity {} kept {}", opacity, share);
+        }
+    }
+
+    #[test]
+    fn test_tree_shader_maps_bark_normals() {
+        assert!(TREE_VERTEX_SHADER.contains("layout(location = 7) in vec3 a_tangent"));
+        assert!(TREE_FRAGMENT_SHADER.contains("bark_normal(normalize(v_normal), v_tangent, v_uv)"));
+        // Untangented vertices skip the relief instead of normalizing zero
+        assert!(TREE_FRAGMENT_SHADER.contains("if (dot(along, along) < 1e-8)"));
+    }
+
+    #[test]
+    fn test_baked_shader_reads_vertex_colors() {
+        assert!(TREE_VERTEX_SHADER.contains("layout(location = 8) in vec3 a_color"));
+        assert!(TREE_BAKED_FRAGMENT_SHADER.contains("in vec3 v_color"));
+        assert!(TREE_BAKED_FRAGMENT_SHADER.contains("dither_threshold"));
+    }
+
+    #[test]
+    fn test_twig_shader_matches_tree_outputs() {
+        for output in ["v_uv", "v_glow", "v_opacity", "v_tangent", "v_color"] {
+            assert!(TWIG_VERTEX_SHADER.contains(&format!(" {};", output)));
+        }
+        assert!(TWIG_VERTEX_SHADER.contains("layout(location = 9) in vec3 a_color"));
+    }
+
+    #[test]
+    fn test_ground_shader_uses_tree_layout() {
+        assert!(GROUND_VERTEX_SHADER.contains("layout(location = 6) in float a_opacity"));
+        assert!(GROUND_FRAGMENT_SHADER.contains("dither_threshold"));
+    }
+
+    #[test]
+    fn test_fruit_shader_reads_instances() {
+        assert!(FRUIT_VERTEX_SHADER.contains("layout(location = 7) in vec3 a_center"));
+        assert!(FRUIT_VERTEX_SHADER.contains("layout(location = 10) in float a_glow"));
+        assert!(FRUIT_FRAGMENT_SHADER.contains("uniform float u_fade"));
+    }
+
+    #[test]
+    fn test_label_shader_billboards_sdf() {
+        assert!(LABEL_VERTEX_SHADER.contains("layout(location = 1) in vec3 a_normal"));
+        assert!(LABEL_FRAGMENT_SHADER.contains("uniform sampler2D u_atlas"));
+        assert!(LABEL_FRAGMENT_SHADER.contains("smoothstep(0.5 - width, 0.5 + width, distance)"));
+    }
+
     #[test]
     fn test_shader_version() {
         assert!(TREE_VERTEX_SHADER.contains("#version 300 es"));
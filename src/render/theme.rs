@@ -0,0 +1,107 @@
+use crate::math::Vec3;
+
+/// A color palette for the whole scene, passed to the shaders as uniforms
+///
+/// Colors are linear; the composite pass tone maps them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub name: &'static str,
+    /// Clear color behind everything
+    pub background: Vec3,
+    /// Atmosphere the tree and ground fade into
+    pub fog: Vec3,
+    /// Base hue (0 to 1) at the trunk and at the tips; the tree blends
+    /// between them by height
+    pub hue_stops: [f32; 2],
+    /// Colors fireflies and orbs are tinted toward, each keeping one for its
+    /// life (seasonal particles keep their own colors)
+    pub particle_palette: [Vec3; 3],
+}
+
+impl Theme {
+    /// Glowing red-to-cyan branches in a deep blue night (the default)
+    pub const BIOLUMINESCENT: Theme = Theme {
+        name: "bioluminescent",
+        background: Vec3::new(0.001, 0.002, 0.004),
+        fog: Vec3::new(0.14, 0.18, 0.2),
+        hue_stops: [0.02, 0.45],
+        particle_palette: [
+            Vec3::new(0.4, 1.0, 0.5),
+            Vec3::new(0.4, 0.9, 1.0),
+            Vec3::new(1.0, 0.75, 0.4),
+        ],
+    };
+
+    /// Red to amber foliage in a warm haze
+    pub const AUTUMN: Theme = Theme {
+        name: "autumn",
+        background: Vec3::new(0.004, 0.002, 0.001),
+        fog: Vec3::new(0.2, 0.14, 0.1),
+        hue_stops: [0.0, 0.12],
+        particle_palette: [
+            Vec3::new(1.0, 0.55, 0.2),
+            Vec3::new(1.0, 0.8, 0.3),
+            Vec3::new(0.9, 0.3, 0.2),
+        ],
+    };
+
+    /// Blue to violet under a near-black sky
+    pub const MIDNIGHT: Theme = Theme {
+        name: "midnight",
+        background: Vec3::new(0.0005, 0.0005, 0.002),
+        fog: Vec3::new(0.06, 0.07, 0.14),
+        hue_stops: [0.62, 0.78],
+        particle_palette: [
+            Vec3::new(0.6, 0.7, 1.0),
+            Vec3::new(0.8, 0.6, 1.0),
+            Vec3::new(0.9, 0.9, 1.0),
+        ],
+    };
+
+    /// Ink-like branches on parchment, kept dim enough not to bloom
+    pub const PAPER: Theme = Theme {
+        name: "paper",
+        background: Vec3::new(0.46, 0.42, 0.36),
+        fog: Vec3::new(0.4, 0.37, 0.32),
+        hue_stops: [0.05, 0.3],
+        particle_palette: [
+            Vec3::new(0.3, 0.25, 0.2),
+            Vec3::new(0.5, 0.3, 0.2),
+            Vec3::new(0.2, 0.3, 0.35),
+        ],
+    };
+
+    /// Every built-in theme
+    pub const BUILT_IN: [Theme; 4] = [Self::BIOLUMINESCENT, Self::AUTUMN, Self::MIDNIGHT, Self::PAPER];
+
+    /// A built-in theme by name
+    pub fn named(name: &str) -> Result<Self, String> {
+        let name = name.trim().to_ascii_lowercase();
+        Self::BUILT_IN
+            .into_iter()
+            .find(|theme| theme.name == name)
+            .ok_or_else(|| format!("Unknown theme '{}'", name))
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::BIOLUMINESCENT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_named_themes() {
+        for theme in Theme::BUILT_IN {
+            assert_eq!(Theme::named(theme.name).unwrap(), theme);
+            assert!(theme.hue_stops.iter().all(|h| (0.0..=1.0).contains(h)));
+        }
+        assert_eq!(Theme::named(" Autumn ").unwrap(), Theme::AUTUMN);
+        assert_eq!(Theme::default(), Theme::BIOLUMINESCENT);
+        assert!(Theme::named("neon").is_err());
+    }
+}
@@ -1,7 +1,27 @@
 use web_sys::{
     WebGl2RenderingContext, WebGlBuffer, WebGlProgram, WebGlShader,
-    WebGlUniformLocation, WebGlVertexArrayObject, WebGlTexture, WebGlFramebuffer,
+    WebGlUniformLocation, WebGlVertexArrayObject, WebGlTexture, WebGlFramebuffer, WebGlRenderbuffer,
 };
+use crate::mesh::IndexData;
+
+/// Blend factors (source color, destination color, source alpha, destination
+/// alpha) for additive passes; destination alpha is left untouched so the
+/// scene's bloom mask survives
+const ADDITIVE_BLEND: [u32; 4] = [
+    WebGl2RenderingContext::SRC_ALPHA,
+    WebGl2RenderingContext::ONE,
+    WebGl2RenderingContext::ZERO,
+    WebGl2RenderingContext::ONE,
+];
+
+/// Blend factors for crisp overlays: color blends normally, while alpha is
+/// scaled down under what's drawn, clearing the bloom mask there
+const UNBLOOMED_BLEND: [u32; 4] = [
+    WebGl2RenderingContext::SRC_ALPHA,
+    WebGl2RenderingContext::ONE_MINUS_SRC_ALPHA,
+    WebGl2RenderingContext::ZERO,
+    WebGl2RenderingContext::ONE_MINUS_SRC_ALPHA,
+];
 
 /// Wrapper around WebGL2 context with helper methods
 pub struct WebGLContext {
@@ -85,26 +105,126 @@ impl WebGLContext {
         Ok(buffer)
     }
 
-    /// Create an index buffer
-    pub fn create_index_buffer(&self, data: &[u32], usage: u32) -> Result<WebGlBuffer, String> {
+    /// Create an index buffer, returning it with the index type to draw it with
+    pub fn create_index_buffer(&self, data: &IndexData, usage: u32) -> Result<(WebGlBuffer, u32), String> {
         let gl = &self.gl;
 
         let buffer = gl.create_buffer().ok_or("Failed to create index buffer")?;
         gl.bind_buffer(WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER, Some(&buffer));
 
+        let index_type = match data {
+            IndexData::U16(indices) => {
+                unsafe {
+                    let array = js_sys::Uint16Array::view(indices);
+                    gl.buffer_data_with_array_buffer_view(
+                        WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER,
+                        &array,
+                        usage,
+                    );
+                }
+                WebGl2RenderingContext::UNSIGNED_SHORT
+            }
+            IndexData::U32(indices) => {
+                unsafe {
+                    let array = js_sys::Uint32Array::view(indices);
+                    gl.buffer_data_with_array_buffer_view(
+                        WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER,
+                        &array,
+                        usage,
+                    );
+                }
+                WebGl2RenderingContext::UNSIGNED_INT
+            }
+        };
+
+        gl.bind_buffer(WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER, None);
+        Ok((buffer, index_type))
+    }
+
+    /// Overwrite part of a float buffer, starting `offset` floats in
+    pub fn update_buffer_f32(&self, buffer: &WebGlBuffer, offset: usize, data: &[f32]) {
+        let gl = &self.gl;
+        gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(buffer));
+
         unsafe {
-            let array = js_sys::Uint32Array::view(data);
-            gl.buffer_data_with_array_buffer_view(
-                WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER,
+            let array = js_sys::Float32Array::view(data);
+            gl.buffer_sub_data_with_i32_and_array_buffer_view(
+                WebGl2RenderingContext::ARRAY_BUFFER,
+                (offset * 4) as i32,
                 &array,
-                usage,
             );
         }
 
+        gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, None);
+    }
+
+    /// Overwrite part of an index buffer, starting `offset` indices in; the
+    /// data must be the width the buffer was created with
+    pub fn update_index_buffer(&self, buffer: &WebGlBuffer, offset: usize, data: &IndexData) {
+        let gl = &self.gl;
+        gl.bind_buffer(WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER, Some(buffer));
+
+        unsafe {
+            match data {
+                IndexData::U16(indices) => gl.buffer_sub_data_with_i32_and_array_buffer_view(
+                    WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER,
+                    (offset * 2) as i32,
+                    &js_sys::Uint16Array::view(indices),
+                ),
+                IndexData::U32(indices) => gl.buffer_sub_data_with_i32_and_array_buffer_view(
+                    WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER,
+                    (offset * 4) as i32,
+                    &js_sys::Uint32Array::view(indices),
+                ),
+            }
+        }
+
         gl.bind_buffer(WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER, None);
+    }
+
+    /// Create a uniform buffer of `floats` zeroed floats and attach it to
+    /// uniform block binding point `binding`
+    pub fn create_uniform_buffer(&self, floats: usize, binding: u32) -> Result<WebGlBuffer, String> {
+        let gl = &self.gl;
+
+        let buffer = gl.create_buffer().ok_or("Failed to create uniform buffer")?;
+        gl.bind_buffer(WebGl2RenderingContext::UNIFORM_BUFFER, Some(&buffer));
+        gl.buffer_data_with_i32(
+            WebGl2RenderingContext::UNIFORM_BUFFER,
+            (floats * 4) as i32,
+            WebGl2RenderingContext::DYNAMIC_DRAW,
+        );
+        gl.bind_buffer(WebGl2RenderingContext::UNIFORM_BUFFER, None);
+        gl.bind_buffer_base(WebGl2RenderingContext::UNIFORM_BUFFER, binding, Some(&buffer));
         Ok(buffer)
     }
 
+    /// Overwrite the start of a uniform buffer
+    pub fn update_uniform_buffer(&self, buffer: &WebGlBuffer, data: &[f32]) {
+        let gl = &self.gl;
+        gl.bind_buffer(WebGl2RenderingContext::UNIFORM_BUFFER, Some(buffer));
+
+        unsafe {
+            let array = js_sys::Float32Array::view(data);
+            gl.buffer_sub_data_with_i32_and_array_buffer_view(
+                WebGl2RenderingContext::UNIFORM_BUFFER,
+                0,
+                &array,
+            );
+        }
+
+        gl.bind_buffer(WebGl2RenderingContext::UNIFORM_BUFFER, None);
+    }
+
+    /// Point a program's uniform block at a binding point; programs without
+    /// the block are left alone
+    pub fn bind_uniform_block(&self, program: &WebGlProgram, name: &str, binding: u32) {
+        let index = self.gl.get_uniform_block_index(program, name);
+        if index != WebGl2RenderingContext::INVALID_INDEX {
+            self.gl.uniform_block_binding(program, index, binding);
+        }
+    }
+
     /// Create a Vertex Array Object
     pub fn create_vao(&self) -> Result<WebGlVertexArrayObject, String> {
         self.gl.create_vertex_array().ok_or("Failed to create VAO".to_string())
@@ -116,6 +236,7 @@ impl WebGLContext {
 
         let texture = gl.create_texture().ok_or("Failed to create texture")?;
         gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(&texture));
+        self.clear_errors();
 
         gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
             WebGl2RenderingContext::TEXTURE_2D,
@@ -125,10 +246,18 @@ impl WebGLContext {
             height,
             0,
             WebGl2RenderingContext::RGBA,
-            WebGl2RenderingContext::UNSIGNED_BYTE,
+            texel_type(internal_format),
             None,
         ).map_err(|e| format!("Failed to create texture: {:?}", e))?;
 
+        // Allocation failures (e.g. out of memory) only show up as GL errors
+        let error = gl.get_error();
+        if error != WebGl2RenderingContext::NO_ERROR {
+            gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, None);
+            gl.delete_texture(Some(&texture));
+            return Err(format!("Failed to allocate {}x{} texture: GL error {}", width, height, error));
+        }
+
         gl.tex_parameteri(
             WebGl2RenderingContext::TEXTURE_2D,
             WebGl2RenderingContext::TEXTURE_MIN_FILTER,
@@ -154,6 +283,117 @@ impl WebGLContext {
         Ok(texture)
     }
 
+    /// Create a single-channel texture from 8-bit pixels (rows top first),
+    /// linearly filtered as signed distance fields need
+    pub fn create_texture_r8(&self, width: i32, height: i32, pixels: &[u8]) -> Result<WebGlTexture, String> {
+        let gl = &self.gl;
+        let expected = (width.max(0) as usize).checked_mul(height.max(0) as usize);
+        if width <= 0 || height <= 0 || expected != Some(pixels.len()) {
+            return Err(format!("Expected {}x{} pixels, got {}", width, height, pixels.len()));
+        }
+
+        let texture = gl.create_texture().ok_or("Failed to create texture")?;
+        gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(&texture));
+        // Rows are tightly packed, whatever the width
+        gl.pixel_storei(WebGl2RenderingContext::UNPACK_ALIGNMENT, 1);
+        let result = gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+            WebGl2RenderingContext::TEXTURE_2D,
+            0,
+            WebGl2RenderingContext::R8 as i32,
+            width,
+            height,
+            0,
+            WebGl2RenderingContext::RED,
+            WebGl2RenderingContext::UNSIGNED_BYTE,
+            Some(pixels),
+        );
+        gl.pixel_storei(WebGl2RenderingContext::UNPACK_ALIGNMENT, 4);
+        if let Err(e) = result {
+            gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, None);
+            gl.delete_texture(Some(&texture));
+            return Err(format!("Failed to upload texture: {:?}", e));
+        }
+
+        for (param, value) in [
+            (WebGl2RenderingContext::TEXTURE_MIN_FILTER, WebGl2RenderingContext::LINEAR),
+            (WebGl2RenderingContext::TEXTURE_MAG_FILTER, WebGl2RenderingContext::LINEAR),
+            (WebGl2RenderingContext::TEXTURE_WRAP_S, WebGl2RenderingContext::CLAMP_TO_EDGE),
+            (WebGl2RenderingContext::TEXTURE_WRAP_T, WebGl2RenderingContext::CLAMP_TO_EDGE),
+        ] {
+            gl.tex_parameteri(WebGl2RenderingContext::TEXTURE_2D, param, value as i32);
+        }
+
+        gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, None);
+        Ok(texture)
+    }
+
+    /// Create a 24-bit depth texture for a framebuffer's depth attachment,
+    /// readable by later passes (nearest filtered; depth can't be blended)
+    pub fn create_depth_texture(&self, width: i32, height: i32) -> Result<WebGlTexture, String> {
+        let gl = &self.gl;
+
+        let texture = gl.create_texture().ok_or("Failed to create depth texture")?;
+        gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(&texture));
+        self.clear_errors();
+
+        gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+            WebGl2RenderingContext::TEXTURE_2D,
+            0,
+            WebGl2RenderingContext::DEPTH_COMPONENT24 as i32,
+            width,
+            height,
+            0,
+            WebGl2RenderingContext::DEPTH_COMPONENT,
+            WebGl2RenderingContext::UNSIGNED_INT,
+            None,
+        ).map_err(|e| format!("Failed to create depth texture: {:?}", e))?;
+
+        let error = gl.get_error();
+        if error != WebGl2RenderingContext::NO_ERROR {
+            gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, None);
+            gl.delete_texture(Some(&texture));
+            return Err(format!("Failed to allocate {}x{} depth texture: GL error {}", width, height, error));
+        }
+
+        for (param, value) in [
+            (WebGl2RenderingContext::TEXTURE_MIN_FILTER, WebGl2RenderingContext::NEAREST),
+            (WebGl2RenderingContext::TEXTURE_MAG_FILTER, WebGl2RenderingContext::NEAREST),
+            (WebGl2RenderingContext::TEXTURE_WRAP_S, WebGl2RenderingContext::CLAMP_TO_EDGE),
+            (WebGl2RenderingContext::TEXTURE_WRAP_T, WebGl2RenderingContext::CLAMP_TO_EDGE),
+        ] {
+            gl.tex_parameteri(WebGl2RenderingContext::TEXTURE_2D, param, value as i32);
+        }
+
+        gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, None);
+        Ok(texture)
+    }
+
+    /// Largest texture width/height the GPU supports
+    pub fn max_texture_size(&self) -> i32 {
+        self.gl
+            .get_parameter(WebGl2RenderingContext::MAX_TEXTURE_SIZE)
+            .ok()
+            .and_then(|v| v.as_f64())
+            .map(|v| v as i32)
+            .unwrap_or(4096)
+    }
+
+    /// Enable rendering to half-float color targets, reporting whether the
+    /// GPU supports it (`EXT_color_buffer_float`)
+    pub fn enable_color_buffer_float(&self) -> bool {
+        matches!(self.gl.get_extension("EXT_color_buffer_float"), Ok(Some(_)))
+    }
+
+    /// Discard pending GL errors so the next check only sees new ones
+    fn clear_errors(&self) {
+        // Bounded: a lost context keeps reporting errors
+        for _ in 0..8 {
+            if self.gl.get_error() == WebGl2RenderingContext::NO_ERROR {
+                break;
+            }
+        }
+    }
+
     /// Create a framebuffer with a texture attachment
     pub fn create_framebuffer(&self, texture: &WebGlTexture) -> Result<WebGlFramebuffer, String> {
         let gl = &self.gl;
@@ -178,6 +418,82 @@ impl WebGLContext {
         Ok(fbo)
     }
 
+    /// Attach a depth texture to a framebuffer
+    pub fn attach_depth_texture(&self, fbo: &WebGlFramebuffer, depth: &WebGlTexture) -> Result<(), String> {
+        let gl = &self.gl;
+        gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, Some(fbo));
+        gl.framebuffer_texture_2d(
+            WebGl2RenderingContext::FRAMEBUFFER,
+            WebGl2RenderingContext::DEPTH_ATTACHMENT,
+            WebGl2RenderingContext::TEXTURE_2D,
+            Some(depth),
+            0,
+        );
+
+        let status = gl.check_framebuffer_status(WebGl2RenderingContext::FRAMEBUFFER);
+        gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, None);
+        if status != WebGl2RenderingContext::FRAMEBUFFER_COMPLETE {
+            return Err(format!("Framebuffer incomplete with depth: {}", status));
+        }
+        Ok(())
+    }
+
+    /// Create a multisampled framebuffer (`color_format` color, 24-bit depth)
+    /// to render into and resolve with `blit_framebuffer`
+    pub fn create_multisample_framebuffer(
+        &self,
+        width: i32,
+        height: i32,
+        samples: i32,
+        color_format: u32,
+    ) -> Result<(WebGlFramebuffer, [WebGlRenderbuffer; 2]), String> {
+        let gl = &self.gl;
+        let storage = |format: u32| -> Result<WebGlRenderbuffer, String> {
+            let buffer = gl.create_renderbuffer().ok_or("Failed to create renderbuffer")?;
+            gl.bind_renderbuffer(WebGl2RenderingContext::RENDERBUFFER, Some(&buffer));
+            gl.renderbuffer_storage_multisample(WebGl2RenderingContext::RENDERBUFFER, samples, format, width, height);
+            Ok(buffer)
+        };
+        let color = storage(color_format)?;
+        let depth = storage(WebGl2RenderingContext::DEPTH_COMPONENT24)?;
+        gl.bind_renderbuffer(WebGl2RenderingContext::RENDERBUFFER, None);
+
+        let fbo = gl.create_framebuffer().ok_or("Failed to create framebuffer")?;
+        gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, Some(&fbo));
+        gl.framebuffer_renderbuffer(
+            WebGl2RenderingContext::FRAMEBUFFER,
+            WebGl2RenderingContext::COLOR_ATTACHMENT0,
+            WebGl2RenderingContext::RENDERBUFFER,
+            Some(&color),
+        );
+        gl.framebuffer_renderbuffer(
+            WebGl2RenderingContext::FRAMEBUFFER,
+            WebGl2RenderingContext::DEPTH_ATTACHMENT,
+            WebGl2RenderingContext::RENDERBUFFER,
+            Some(&depth),
+        );
+
+        let status = gl.check_framebuffer_status(WebGl2RenderingContext::FRAMEBUFFER);
+        gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, None);
+        if status != WebGl2RenderingContext::FRAMEBUFFER_COMPLETE {
+            gl.delete_framebuffer(Some(&fbo));
+            gl.delete_renderbuffer(Some(&color));
+            gl.delete_renderbuffer(Some(&depth));
+            return Err(format!("Multisample framebuffer incomplete: {}", status));
+        }
+        Ok((fbo, [color, depth]))
+    }
+
+    /// Most samples a multisampled renderbuffer can have
+    pub fn max_samples(&self) -> i32 {
+        self.gl
+            .get_parameter(WebGl2RenderingContext::MAX_SAMPLES)
+            .ok()
+            .and_then(|v| v.as_f64())
+            .map(|v| v as i32)
+            .unwrap_or(1)
+    }
+
     /// Get uniform location
     pub fn get_uniform_location(&self, program: &WebGlProgram, name: &str) -> Option<WebGlUniformLocation> {
         self.gl.get_uniform_location(program, name)
@@ -198,6 +514,11 @@ impl WebGLContext {
         self.gl.uniform3f(location, x, y, z);
     }
 
+    /// Set vec4 uniform
+    pub fn uniform_4f(&self, location: Option<&WebGlUniformLocation>, x: f32, y: f32, z: f32, w: f32) {
+        self.gl.uniform4f(location, x, y, z, w);
+    }
+
     /// Set mat4 uniform
     pub fn uniform_matrix4fv(&self, location: Option<&WebGlUniformLocation>, data: &[f32; 16]) {
         self.gl.uniform_matrix4fv_with_f32_array(location, false, data);
@@ -229,12 +550,20 @@ impl WebGLContext {
     }
 
     /// Enable additive blending (for particles/glow)
+    ///
+    /// Destination alpha is left untouched so the scene's bloom mask survives.
     pub fn enable_additive_blending(&self) {
+        let [src_rgb, dst_rgb, src_alpha, dst_alpha] = ADDITIVE_BLEND;
         self.gl.enable(WebGl2RenderingContext::BLEND);
-        self.gl.blend_func(
-            WebGl2RenderingContext::SRC_ALPHA,
-            WebGl2RenderingContext::ONE,
-        );
+        self.gl.blend_func_separate(src_rgb, dst_rgb, src_alpha, dst_alpha);
+    }
+
+    /// Enable alpha blending that also clears the bloom mask under what's
+    /// drawn (for crisp overlays such as labels)
+    pub fn enable_unbloomed_blending(&self) {
+        let [src_rgb, dst_rgb, src_alpha, dst_alpha] = UNBLOOMED_BLEND;
+        self.gl.enable(WebGl2RenderingContext::BLEND);
+        self.gl.blend_func_separate(src_rgb, dst_rgb, src_alpha, dst_alpha);
     }
 
     /// Set viewport
@@ -242,3 +571,53 @@ impl WebGLContext {
         self.gl.viewport(x, y, width, height);
     }
 }
+
+/// Pixel data type to allocate a color texture of `internal_format` with
+fn texel_type(internal_format: u32) -> u32 {
+    match internal_format {
+        WebGl2RenderingContext::RGBA16F => WebGl2RenderingContext::HALF_FLOAT,
+        WebGl2RenderingContext::RGBA32F => WebGl2RenderingContext::FLOAT,
+        _ => WebGl2RenderingContext::UNSIGNED_BYTE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The GL blend equation (FUNC_ADD) for the factors used here
+    fn blend(factors: [u32; 4], src: [f32; 4], dst: [f32; 4]) -> [f32; 4] {
+        let factor = |f: u32| match f {
+            WebGl2RenderingContext::ZERO => 0.0,
+            WebGl2RenderingContext::ONE => 1.0,
+            WebGl2RenderingContext::SRC_ALPHA => src[3],
+            WebGl2RenderingContext::ONE_MINUS_SRC_ALPHA => 1.0 - src[3],
+            other => panic!("unmodelled blend factor {:#x}", other),
+        };
+        let [src_rgb, dst_rgb, src_alpha, dst_alpha] = factors.map(factor);
+        [
+            src[0] * src_rgb + dst[0] * dst_rgb,
+            src[1] * src_rgb + dst[1] * dst_rgb,
+            src[2] * src_rgb + dst[2] * dst_rgb,
+            src[3] * src_alpha + dst[3] * dst_alpha,
+        ]
+    }
+
+    #[test]
+    fn test_blending_keeps_or_clears_bloom_mask() {
+        // A bright branch, which blooms (mask 1)
+        let scene = [0.9, 0.8, 0.6, 1.0];
+
+        // Glow adds light but leaves the mask alone
+        let glowing = blend(ADDITIVE_BLEND, [0.5, 0.5, 0.2, 0.5], scene);
+        assert_eq!(glowing[3], 1.0);
+        assert!(glowing[0] > scene[0]);
+
+        // An opaque label clears the mask under it; a faded one only partly
+        let labelled = blend(UNBLOOMED_BLEND, [1.0, 1.0, 1.0, 1.0], scene);
+        assert_eq!(labelled[3], 0.0);
+        assert_eq!(labelled[0], 1.0);
+        let faded = blend(UNBLOOMED_BLEND, [1.0, 1.0, 1.0, 0.25], scene);
+        assert!((faded[3] - 0.75).abs() < 1e-6);
+    }
+}
@@ -111,6 +111,7 @@ Hue Variance:  {:.4}
 }
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
     use super::*;
     use super::super::metrics::analyze_pixels;
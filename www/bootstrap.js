@@ -321,14 +321,12 @@ class Application {
             return;
         }
 
-        const infoJson = this.engine.get_person_info(personId);
-        if (!infoJson) {
-            panel.classList.add('hidden');
-            return;
-        }
-
         try {
-            const info = JSON.parse(infoJson);
+            const info = this.engine.get_person_info(personId);
+            if (!info) {
+                panel.classList.add('hidden');
+                return;
+            }
 
             document.getElementById('person-name').textContent = info.name;
             document.getElementById('person-lifespan').textContent = info.lifespan;
@@ -336,7 +334,7 @@ class Application {
 
             panel.classList.remove('hidden');
         } catch (e) {
-            console.error('Failed to parse person info:', e);
+            console.error('Failed to read person info:', e);
         }
     }
 